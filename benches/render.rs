@@ -0,0 +1,363 @@
+//! Micro-benchmarks of the render pipeline, run them with `cargo bench --features bench --bench render`
+//!
+//! With `EGUI_SW_BENCH_BASELINE=save`, the means of the benchmarks run are written to `benches/baseline/`, one CSV
+//! per group. With `EGUI_SW_BENCH_BASELINE=check` the run fails if a benchmark is more than 20% slower than its
+//! baseline, the benchmarks without a baseline are only reported.
+
+use std::collections::BTreeMap;
+use std::hint::black_box;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput};
+use egui::{Color32, ColorImage, Pos2, Rect, TexturesDelta, epaint::Vertex, pos2};
+use egui_software_backend::{
+    BufferMutRef, ColorFieldOrder, EguiSoftwareRender, SoftwareRenderCaching,
+    bench::{MeshRasterizer, SimdImpl},
+};
+
+/// A benchmark fails the check when its mean is above `MAX_REGRESSION` times its baseline
+const MAX_REGRESSION: f64 = 1.2;
+
+const BASELINE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/benches/baseline");
+
+/// Premultiplied pixels of every alpha
+fn pixels(len: usize) -> Vec<[u8; 4]> {
+    let mut state = 0x2545_f491_u32;
+    (0..len)
+        .map(|_| {
+            // xorshift32
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let [r, g, b, a] = state.to_le_bytes();
+            let [r, g, b] = [r, g, b].map(|c| (c as u16 * a as u16 / 255) as u8);
+            [r, g, b, a]
+        })
+        .collect()
+}
+
+fn blend(c: &mut Criterion) {
+    let mut group = c.benchmark_group("egui_blend_u8_slice");
+    for simd_impl in SimdImpl::supported() {
+        for len in [64, 512, 4096] {
+            let src = pixels(len);
+            let mut dst = pixels(len);
+            dst.reverse();
+            group.throughput(Throughput::Elements(len as u64));
+            group.bench_function(BenchmarkId::new(simd_impl.name(), len), |b| {
+                b.iter(|| simd_impl.egui_blend_u8_slice(black_box(&src), black_box(&mut dst)));
+            });
+        }
+    }
+    group.finish();
+}
+
+const DRAW_SIZE: u32 = 256;
+
+/// Gradient texture, white at the `egui::epaint::WHITE_UV` texel like the font atlas
+fn draw_texture() -> ColorImage {
+    let size = DRAW_SIZE as usize;
+    let mut pixels: Vec<Color32> = (0..size * size)
+        .map(|i| Color32::from_rgba_premultiplied((i % size) as u8, (i / size) as u8, 128, 255))
+        .collect();
+    pixels[0] = Color32::WHITE;
+    ColorImage::new([size, size], pixels)
+}
+
+fn draw_bench(c: &mut Criterion, group_name: &str, meshes: [(&str, egui::Mesh); 3]) {
+    let rasterizer = MeshRasterizer::new(&draw_texture());
+    let mut data = vec![[0u8; 4]; (DRAW_SIZE * DRAW_SIZE) as usize];
+    let mut group = c.benchmark_group(group_name);
+    for (name, mesh) in meshes {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut buffer = BufferMutRef::new(&mut data, DRAW_SIZE, DRAW_SIZE);
+                rasterizer.draw(&mut buffer, black_box(&mesh));
+            });
+        });
+    }
+    group.finish();
+}
+
+fn draw_rect(c: &mut Criterion) {
+    let rect_mesh = |uv: Rect, color: Color32| {
+        let mut mesh = egui::Mesh::default();
+        mesh.add_rect_with_uv(
+            Rect::from_min_max(pos2(8.0, 8.0), pos2(248.0, 248.0)),
+            uv,
+            color,
+        );
+        // The last triangle pair of a mesh is drawn as triangles, end with a rect outside of the buffer
+        let outside = Rect::from_min_size(pos2(1000.0, 1000.0), egui::vec2(1.0, 1.0));
+        mesh.add_rect_with_uv(outside, uv, color);
+        mesh
+    };
+    let white_uv = Rect::from_min_max(egui::epaint::WHITE_UV, egui::epaint::WHITE_UV);
+    draw_bench(
+        c,
+        "draw_rect",
+        [
+            (
+                "const_color",
+                rect_mesh(white_uv, Color32::from_rgba_premultiplied(0, 100, 0, 200)),
+            ),
+            // One texel per pixel, blended tinted without sampling
+            (
+                "tinted",
+                rect_mesh(
+                    Rect::from_min_max(pos2(8.0, 8.0), pos2(248.0, 248.0)) / DRAW_SIZE as f32,
+                    Color32::from_rgba_premultiplied(200, 150, 100, 200),
+                ),
+            ),
+            // Scaled, sampled bilinearly
+            (
+                "uv_varying",
+                rect_mesh(
+                    Rect::from_min_max(Pos2::ZERO, pos2(0.5, 0.5)),
+                    Color32::WHITE,
+                ),
+            ),
+        ],
+    );
+}
+
+fn draw_tri(c: &mut Criterion) {
+    let tri = |colors: [Color32; 3], uvs: [Pos2; 3]| {
+        let mut mesh = egui::Mesh::default();
+        for ((pos, color), uv) in [pos2(8.0, 8.0), pos2(248.0, 40.0), pos2(60.0, 248.0)]
+            .into_iter()
+            .zip(colors)
+            .zip(uvs)
+        {
+            mesh.vertices.push(Vertex { pos, uv, color });
+        }
+        mesh.indices.extend([0, 1, 2]);
+        mesh
+    };
+    let translucent = Color32::from_rgba_premultiplied(0, 100, 0, 200);
+    let white_uvs = [egui::epaint::WHITE_UV; 3];
+    draw_bench(
+        c,
+        "draw_tri",
+        [
+            ("const", tri([translucent; 3], white_uvs)),
+            (
+                "color_varying",
+                tri(
+                    [
+                        translucent,
+                        Color32::from_rgba_premultiplied(100, 0, 0, 100),
+                        Color32::BLUE,
+                    ],
+                    white_uvs,
+                ),
+            ),
+            (
+                "uv_varying",
+                tri(
+                    [Color32::WHITE; 3],
+                    [pos2(0.0, 0.0), pos2(1.0, 0.1), pos2(0.2, 1.0)],
+                ),
+            ),
+        ],
+    );
+}
+
+fn render(c: &mut Criterion) {
+    const WIDTH: u32 = 1280;
+    const HEIGHT: u32 = 720;
+
+    let ctx = egui::Context::default();
+    let mut demo = egui_demo_lib::DemoWindows::default();
+    let mut textures_delta = TexturesDelta::default();
+    let mut paint_jobs = Vec::new();
+    // The first frame only sizes the windows
+    for _ in 0..2 {
+        let input = egui::RawInput {
+            screen_rect: Some(Rect::from_min_size(
+                Pos2::ZERO,
+                egui::vec2(WIDTH as f32, HEIGHT as f32),
+            )),
+            ..Default::default()
+        };
+        let output = ctx.run(input, |ctx| demo.ui(ctx));
+        textures_delta.append(output.textures_delta);
+        paint_jobs = ctx.tessellate(output.shapes, output.pixels_per_point);
+    }
+
+    let mut data = vec![[0u8; 4]; (WIDTH * HEIGHT) as usize];
+    let mut group = c.benchmark_group("render");
+    group.sample_size(20);
+    for mode in [
+        SoftwareRenderCaching::Direct,
+        SoftwareRenderCaching::Mesh,
+        SoftwareRenderCaching::MeshTiled,
+        SoftwareRenderCaching::BlendTiled,
+    ] {
+        let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_mode(mode);
+        let mut buffer = BufferMutRef::new(&mut data, WIDTH, HEIGHT);
+        renderer.render(&mut buffer, true, paint_jobs.clone(), &textures_delta, 1.0);
+        // Every frame is redrawn from scratch, nothing is reused from the cache
+        group.bench_function(format!("{mode:?}"), |b| {
+            b.iter_batched(
+                || paint_jobs.clone(),
+                |paint_jobs| {
+                    renderer.render(
+                        &mut buffer,
+                        true,
+                        paint_jobs,
+                        &TexturesDelta::default(),
+                        1.0,
+                    )
+                },
+                BatchSize::LargeInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+/// Where criterion stores its results, see `Criterion::output_directory()`
+fn criterion_dir() -> PathBuf {
+    std::env::var_os("CARGO_TARGET_DIR")
+        .map_or_else(
+            || Path::new(env!("CARGO_MANIFEST_DIR")).join("target"),
+            PathBuf::from,
+        )
+        .join("criterion")
+}
+
+/// Text of `json` after `"key":`, up to the next `,` or `}`, or the closing quote of a string
+fn json_value<'a>(json: &'a str, key: &str) -> Option<&'a str> {
+    let start = json.find(&format!("\"{key}\":"))? + key.len() + 3;
+    let value = &json[start..];
+    match value.strip_prefix('"') {
+        Some(string) => string.split('"').next(),
+        None => value.split([',', '}']).next(),
+    }
+}
+
+/// Mean time in nanoseconds of each benchmark measured since `since`, by its full id `group/function[/param]`
+fn collect_means(dir: &Path, since: SystemTime, means: &mut BTreeMap<String, f64>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let estimates = path.join("new/estimates.json");
+        let fresh = std::fs::metadata(&estimates)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified >= since);
+        if fresh
+            && let (Ok(benchmark), Ok(estimates)) = (
+                std::fs::read_to_string(path.join("new/benchmark.json")),
+                std::fs::read_to_string(&estimates),
+            )
+            && let Some(full_id) = json_value(&benchmark, "full_id")
+            && let Some(mean) = estimates
+                .split_once("\"mean\":")
+                .and_then(|(_, mean)| json_value(mean, "point_estimate"))
+                .and_then(|mean| mean.parse().ok())
+        {
+            means.insert(full_id.to_string(), mean);
+        }
+        collect_means(&path, since, means);
+    }
+}
+
+fn read_baseline(group: &str) -> BTreeMap<String, f64> {
+    let csv = std::fs::read_to_string(Path::new(BASELINE_DIR).join(format!("{group}.csv")))
+        .unwrap_or_default();
+    csv.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let (id, mean) = line.split_once(',')?;
+            Some((id.to_string(), mean.parse().ok()?))
+        })
+        .collect()
+}
+
+/// Merge `means` into the baselines of their groups
+fn save_baselines(means: &BTreeMap<String, f64>) {
+    let mut groups = BTreeMap::<&str, BTreeMap<String, f64>>::new();
+    for (id, &mean) in means {
+        let group = id.split('/').next().unwrap_or(id);
+        groups
+            .entry(group)
+            .or_insert_with(|| read_baseline(group))
+            .insert(id.clone(), mean);
+    }
+    std::fs::create_dir_all(BASELINE_DIR).unwrap();
+    for (group, baseline) in groups {
+        let mut csv = String::from("benchmark,mean_ns\n");
+        for (id, mean) in baseline {
+            csv += &format!("{id},{mean:.1}\n");
+        }
+        std::fs::write(Path::new(BASELINE_DIR).join(format!("{group}.csv")), csv).unwrap();
+    }
+}
+
+/// Benchmarks more than `MAX_REGRESSION` times slower than their baseline
+fn check_baselines(means: &BTreeMap<String, f64>) -> Vec<String> {
+    let mut baselines = BTreeMap::<&str, BTreeMap<String, f64>>::new();
+    let mut regressions = Vec::new();
+    for (id, &mean) in means {
+        let group = id.split('/').next().unwrap_or(id);
+        let baseline = baselines
+            .entry(group)
+            .or_insert_with(|| read_baseline(group));
+        match baseline.get(id) {
+            Some(&baseline) => {
+                let ratio = mean / baseline;
+                println!(
+                    "{id}: {mean:.1} ns, {:+.1}% from the baseline",
+                    (ratio - 1.0) * 100.0
+                );
+                if ratio > MAX_REGRESSION {
+                    regressions.push(id.clone());
+                }
+            }
+            None => println!("{id}: {mean:.1} ns, no baseline"),
+        }
+    }
+    regressions
+}
+
+fn main() {
+    let start = SystemTime::now();
+    let mut c = Criterion::default()
+        .configure_from_args()
+        .output_directory(&criterion_dir());
+    blend(&mut c);
+    draw_rect(&mut c);
+    draw_tri(&mut c);
+    render(&mut c);
+    c.final_summary();
+
+    let mode = std::env::var("EGUI_SW_BENCH_BASELINE").unwrap_or_default();
+    if mode.is_empty() {
+        return;
+    }
+    let mut means = BTreeMap::new();
+    collect_means(&criterion_dir(), start, &mut means);
+    match mode.as_str() {
+        "save" => save_baselines(&means),
+        "check" => {
+            let regressions = check_baselines(&means);
+            if !regressions.is_empty() {
+                eprintln!(
+                    "{} benchmarks regressed by more than {:.0}%: {}",
+                    regressions.len(),
+                    (MAX_REGRESSION - 1.0) * 100.0,
+                    regressions.join(", ")
+                );
+                std::process::exit(1);
+            }
+        }
+        _ => panic!("EGUI_SW_BENCH_BASELINE is `save` or `check`, not `{mode}`"),
+    }
+}
@@ -137,7 +137,7 @@ fn inner(item: ItemFn) -> Result<TokenStream> {
                 };
                 Some(quote! { #id })
             })
-            .chain(added_const_args.into_iter());
+            .chain(added_const_args);
         let generic_appl = quote! { ::<#(#all_generic_args),*> };
 
         let fn_ident = &sig.ident;
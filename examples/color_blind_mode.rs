@@ -0,0 +1,43 @@
+use egui::Vec2;
+use egui_software_backend::{ColorBlindMode, SoftwareBackend, SoftwareBackendAppConfiguration};
+
+struct EguiApp {
+    color_test: egui_demo_lib::ColorTest,
+}
+
+impl EguiApp {
+    fn new(_context: egui::Context) -> Self {
+        EguiApp {
+            color_test: egui_demo_lib::ColorTest::default(),
+        }
+    }
+}
+
+impl egui_software_backend::App for EguiApp {
+    fn update(&mut self, ctx: &egui::Context, backend: &mut SoftwareBackend) {
+        egui::TopBottomPanel::top("color_blind_mode").show(ctx, |ui| {
+            let mut mode = backend.color_blind_mode();
+            egui::ComboBox::from_label("Color blind mode")
+                .selected_text(format!("{mode:?}"))
+                .show_ui(ui, |ui| {
+                    for option in ColorBlindMode::ALL {
+                        ui.selectable_value(&mut mode, option, format!("{option:?}"));
+                    }
+                });
+            backend.set_color_blind_mode(mode);
+        });
+        egui::CentralPanel::default().show(ctx, |ui| {
+            egui::ScrollArea::both().show(ui, |ui| self.color_test.ui(ui));
+        });
+    }
+}
+
+fn main() {
+    let settings = SoftwareBackendAppConfiguration::new()
+        .inner_size(Some(Vec2::new(900f32, 700f32)))
+        .title(Some("Color blind mode".to_string()));
+
+    egui_software_backend::run_app_with_software_backend(settings, EguiApp::new)
+        //Can fail if winit fails to create the window
+        .expect("Failed to run app")
+}
@@ -0,0 +1,33 @@
+use egui_software_backend::{SoftwareBackend, SoftwareBackendAppConfiguration};
+
+struct EguiApp {}
+
+impl EguiApp {
+    fn new(_context: egui::Context) -> Self {
+        EguiApp {}
+    }
+}
+
+impl egui_software_backend::App for EguiApp {
+    fn update(&mut self, ctx: &egui::Context, backend: &mut SoftwareBackend) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let last_frame_time = backend.last_frame_time().unwrap_or_default();
+
+            ui.label("Hello DRM!");
+            ui.label(format!("Frame Time {}ms", last_frame_time.as_millis()));
+            ui.label(format!("Time {:.1}s", ui.input(|i| i.time)));
+        });
+        // No input, repaint to keep the time label updating
+        ctx.request_repaint_after(std::time::Duration::from_millis(100));
+    }
+}
+
+fn main() {
+    // Run from a virtual terminal, with no display server holding the DRM master
+    let settings =
+        SoftwareBackendAppConfiguration::new().drm_device(std::env::args().nth(1).map(Into::into));
+
+    egui_software_backend::run_app_with_drm_backend(settings, EguiApp::new)
+        //Can fail if the DRM device can't be opened, or no display is connected
+        .expect("Failed to run app")
+}
@@ -0,0 +1,44 @@
+use egui_software_backend::{SoftwareBackend, SoftwareBackendAppConfiguration};
+use std::path::PathBuf;
+
+struct EguiApp {
+    name: String,
+}
+
+impl EguiApp {
+    fn new(_context: egui::Context) -> Self {
+        EguiApp {
+            name: String::new(),
+        }
+    }
+}
+
+impl egui_software_backend::App for EguiApp {
+    fn update(&mut self, ctx: &egui::Context, backend: &mut SoftwareBackend) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let last_frame_time = backend.last_frame_time().unwrap_or_default();
+
+            ui.label("Hello framebuffer!");
+            ui.label(format!("Frame Time {}ms", last_frame_time.as_millis()));
+            ui.text_edit_singleline(&mut self.name);
+            if ui.button("Quit").clicked() {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+        });
+    }
+}
+
+fn main() {
+    // Run from a virtual terminal, with read access to /dev/input/event* for the mouse, touchscreen and keyboard
+    let fb_path = std::env::args()
+        .nth(1)
+        .map_or_else(|| PathBuf::from("/dev/fb0"), PathBuf::from);
+
+    egui_software_backend::run_app_with_framebuffer(
+        &fb_path,
+        SoftwareBackendAppConfiguration::new(),
+        EguiApp::new,
+    )
+    //Can fail if the framebuffer can't be opened, or has an unsupported format
+    .expect("Failed to run app")
+}
@@ -0,0 +1,48 @@
+use egui_software_backend::{SoftwareBackend, SoftwareBackendAppConfiguration};
+
+struct EguiApp {
+    clicks: u32,
+    volume: f32,
+}
+
+impl EguiApp {
+    fn new(_context: egui::Context) -> Self {
+        EguiApp {
+            clicks: 0,
+            volume: 0.5,
+        }
+    }
+}
+
+impl egui_software_backend::App for EguiApp {
+    fn update(&mut self, ctx: &egui::Context, _backend: &mut SoftwareBackend) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.label("Tab to the next widget with the right bumper, press it with A/Cross");
+            ui.label("The D-pad moves the focus, or changes the focused slider");
+            if ui
+                .button(format!("Clicked {} times", self.clicks))
+                .clicked()
+            {
+                self.clicks += 1;
+            }
+            ui.add(egui::Slider::new(&mut self.volume, 0.0..=1.0).text("Volume"));
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.label("Scrolled with the triggers");
+                for row in 0..50 {
+                    ui.label(format!("Row {row}"));
+                }
+            });
+        });
+    }
+}
+
+fn main() {
+    let settings = SoftwareBackendAppConfiguration::new()
+        .inner_size(Some(egui::Vec2::new(500f32, 300f32)))
+        .title(Some("Gamepad example".to_string()))
+        .gamepad_navigation(true);
+
+    egui_software_backend::run_app_with_software_backend(settings, EguiApp::new)
+        //Can fail if winit fails to create the window
+        .expect("Failed to run app")
+}
@@ -0,0 +1,106 @@
+//! Renders a few frames with the pixel buffers allocated from a slab allocator.
+//!
+//! Run with `cargo +nightly run --example pixel_allocator --features allocator_api`
+#![feature(allocator_api)]
+
+use std::alloc::{AllocError, Allocator, Layout, System};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use egui::{Vec2, mutex::Mutex};
+use egui_software_backend::{BufferMutRef, ColorFieldOrder, EguiSoftwareRender};
+
+/// Size of the cached `BlendTiled` tiles, 64x64 pixels
+const SLAB_SIZE: usize = 64 * 64 * 4;
+const SLAB_ALIGN: usize = 4096;
+
+/// Serves every tile sized allocation from a free list of page aligned slabs, and the rest from `System`
+struct SlabAllocator {
+    free: Mutex<Vec<NonNull<u8>>>,
+    slabs: AtomicUsize,
+    other_bytes: AtomicUsize,
+}
+
+// SAFETY: the free list only holds slabs owned by the allocator
+unsafe impl Send for SlabAllocator {}
+unsafe impl Sync for SlabAllocator {}
+
+impl SlabAllocator {
+    fn new() -> Self {
+        Self {
+            free: Mutex::new(Vec::new()),
+            slabs: AtomicUsize::new(0),
+            other_bytes: AtomicUsize::new(0),
+        }
+    }
+
+    fn is_slab(layout: Layout) -> bool {
+        layout.size() == SLAB_SIZE && layout.align() <= SLAB_ALIGN
+    }
+
+    fn slab_layout() -> Layout {
+        Layout::from_size_align(SLAB_SIZE, SLAB_ALIGN).unwrap()
+    }
+}
+
+// SAFETY: slabs are only handed out once until they are deallocated, everything else is forwarded to `System`
+unsafe impl Allocator for SlabAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if !Self::is_slab(layout) {
+            self.other_bytes.fetch_add(layout.size(), Ordering::Relaxed);
+            return System.allocate(layout);
+        }
+        let slab = match self.free.lock().pop() {
+            Some(slab) => slab,
+            None => {
+                self.slabs.fetch_add(1, Ordering::Relaxed);
+                System.allocate(Self::slab_layout())?.cast()
+            }
+        };
+        Ok(NonNull::slice_from_raw_parts(slab, SLAB_SIZE))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if Self::is_slab(layout) {
+            self.free.lock().push(ptr);
+        } else {
+            self.other_bytes.fetch_sub(layout.size(), Ordering::Relaxed);
+            // SAFETY: allocated by `System` with the same layout
+            unsafe { System.deallocate(ptr, layout) };
+        }
+    }
+}
+
+fn main() {
+    const SIZE: Vec2 = Vec2::new(800.0, 600.0);
+    let slab_allocator: &'static SlabAllocator = Box::leak(Box::new(SlabAllocator::new()));
+    let ctx = egui::Context::default();
+    let mut demo = egui_demo_lib::DemoWindows::default();
+    let mut renderer =
+        EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_pixel_allocator(slab_allocator);
+    let mut buffer = vec![[0u8; 4]; (SIZE.x * SIZE.y) as usize];
+
+    for frame in 0..10 {
+        let input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, SIZE)),
+            ..Default::default()
+        };
+        let output = ctx.run(input, |ctx| demo.ui(ctx));
+        let paint_jobs = ctx.tessellate(output.shapes, output.pixels_per_point);
+        let mut buffer_ref = BufferMutRef::new(&mut buffer, SIZE.x as u32, SIZE.y as u32);
+        renderer.render(
+            &mut buffer_ref,
+            frame == 0,
+            paint_jobs,
+            &output.textures_delta,
+            output.pixels_per_point,
+        );
+
+        println!(
+            "frame {frame}: {} slabs ({} free), {} KiB of other pixel buffers",
+            slab_allocator.slabs.load(Ordering::Relaxed),
+            slab_allocator.free.lock().len(),
+            slab_allocator.other_bytes.load(Ordering::Relaxed) / 1024,
+        );
+    }
+}
@@ -0,0 +1,297 @@
+//! egui demo painted by `EguiSoftwareRender` on a 2D HTML canvas, build it with `make` (see the `Makefile`)
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use egui::{Event, Modifiers, PointerButton, Pos2, Rect, ViewportId, ViewportInfo, vec2};
+use egui_software_backend::{BufferMutRef, ColorFieldOrder, DirtyRect, EguiSoftwareRender};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::{Clamped, JsCast};
+use web_sys::{
+    CanvasRenderingContext2d, HtmlCanvasElement, ImageData, KeyboardEvent, MouseEvent, WheelEvent,
+};
+
+struct App {
+    canvas: HtmlCanvasElement,
+    context_2d: CanvasRenderingContext2d,
+    egui_ctx: egui::Context,
+    demo: egui_demo_lib::DemoWindows,
+    renderer: EguiSoftwareRender,
+    /// Rendered frame, kept between frames so only the dirty rect is rendered and put again
+    pixels: Vec<[u8; 4]>,
+    /// Canvas size in pixels
+    size: [u32; 2],
+    events: Vec<Event>,
+    modifiers: Modifiers,
+    /// `now_secs()` of the next repaint requested by egui
+    next_repaint: f64,
+}
+
+fn now_secs() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map_or(0.0, |performance| performance.now() / 1000.0)
+}
+
+fn modifiers(ctrl: bool, shift: bool, alt: bool, meta: bool) -> Modifiers {
+    Modifiers {
+        alt,
+        ctrl,
+        shift,
+        mac_cmd: meta,
+        command: ctrl || meta,
+    }
+}
+
+fn pointer_button(button: i16) -> Option<PointerButton> {
+    match button {
+        0 => Some(PointerButton::Primary),
+        1 => Some(PointerButton::Middle),
+        2 => Some(PointerButton::Secondary),
+        _ => None,
+    }
+}
+
+impl App {
+    fn frame(&mut self) {
+        let Some(window) = web_sys::window() else {
+            return;
+        };
+        // CSS pixels are egui points
+        let pixels_per_point = window.device_pixel_ratio() as f32;
+        let screen_size = vec2(
+            self.canvas.client_width() as f32,
+            self.canvas.client_height() as f32,
+        );
+        let size = [
+            ((screen_size.x * pixels_per_point) as u32).max(1),
+            ((screen_size.y * pixels_per_point) as u32).max(1),
+        ];
+        let resized = size != self.size;
+        if resized {
+            self.size = size;
+            self.canvas.set_width(size[0]);
+            self.canvas.set_height(size[1]);
+            self.pixels.clear();
+            self.pixels.resize((size[0] * size[1]) as usize, [0; 4]);
+        }
+
+        let now = now_secs();
+        if !resized && self.events.is_empty() && now < self.next_repaint {
+            return;
+        }
+
+        let mut raw_input = egui::RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, screen_size)),
+            time: Some(now),
+            modifiers: self.modifiers,
+            events: std::mem::take(&mut self.events),
+            ..Default::default()
+        };
+        raw_input.viewports.insert(
+            ViewportId::ROOT,
+            ViewportInfo {
+                native_pixels_per_point: Some(pixels_per_point),
+                ..Default::default()
+            },
+        );
+        let full_output = self.egui_ctx.run(raw_input, |ctx| self.demo.ui(ctx));
+        self.next_repaint = full_output
+            .viewport_output
+            .get(&ViewportId::ROOT)
+            .map_or(f64::INFINITY, |output| {
+                now + output.repaint_delay.as_secs_f64()
+            });
+
+        let clipped_primitives = self
+            .egui_ctx
+            .tessellate(full_output.shapes, full_output.pixels_per_point);
+        let [width, height] = self.size;
+        let dirty_rect = self.renderer.render(
+            &mut BufferMutRef::new(&mut self.pixels, width, height),
+            resized,
+            clipped_primitives,
+            &full_output.textures_delta,
+            full_output.pixels_per_point,
+        );
+        if let Err(error) = self.put_dirty_rect(dirty_rect) {
+            web_sys::console::error_1(&error);
+        }
+    }
+
+    /// Copy `dirty_rect` of the rendered frame to the canvas
+    fn put_dirty_rect(&self, dirty_rect: DirtyRect) -> Result<(), JsValue> {
+        if dirty_rect.is_empty() {
+            return Ok(());
+        }
+        // `ImageData` is straight alpha, the premultiplied output is made opaque, i.e. blended over black
+        let mut rgba = Vec::with_capacity(dirty_rect.area() as usize * 4);
+        for y in dirty_rect.min_y..dirty_rect.max_y {
+            let row = (y * self.size[0]) as usize;
+            for &[r, g, b, _] in
+                &self.pixels[row + dirty_rect.min_x as usize..row + dirty_rect.max_x as usize]
+            {
+                rgba.extend_from_slice(&[r, g, b, 255]);
+            }
+        }
+        let image_data = ImageData::new_with_u8_clamped_array_and_sh(
+            Clamped(&rgba),
+            dirty_rect.width(),
+            dirty_rect.height(),
+        )?;
+        self.context_2d.put_image_data(
+            &image_data,
+            dirty_rect.min_x as f64,
+            dirty_rect.min_y as f64,
+        )
+    }
+}
+
+/// Call `f` with each `name` event of `target`, for the lifetime of the page
+fn listen<E: JsCast>(
+    target: &web_sys::EventTarget,
+    name: &str,
+    app: &Rc<RefCell<App>>,
+    f: impl Fn(&mut App, E) + 'static,
+) -> Result<(), JsValue> {
+    let app = app.clone();
+    let closure = Closure::<dyn FnMut(web_sys::Event)>::new(move |event: web_sys::Event| {
+        f(&mut app.borrow_mut(), event.unchecked_into());
+    });
+    target.add_event_listener_with_callback(name, closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    Ok(())
+}
+
+fn add_input_listeners(app: &Rc<RefCell<App>>) -> Result<(), JsValue> {
+    let canvas: web_sys::EventTarget = app.borrow().canvas.clone().into();
+    listen(&canvas, "mousemove", app, |app, event: MouseEvent| {
+        let pos = Pos2::new(event.offset_x() as f32, event.offset_y() as f32);
+        app.events.push(Event::PointerMoved(pos));
+    })?;
+    for (name, pressed) in [("mousedown", true), ("mouseup", false)] {
+        listen(&canvas, name, app, move |app, event: MouseEvent| {
+            let Some(button) = pointer_button(event.button()) else {
+                return;
+            };
+            app.modifiers = modifiers(
+                event.ctrl_key(),
+                event.shift_key(),
+                event.alt_key(),
+                event.meta_key(),
+            );
+            app.events.push(Event::PointerButton {
+                pos: Pos2::new(event.offset_x() as f32, event.offset_y() as f32),
+                button,
+                pressed,
+                modifiers: app.modifiers,
+            });
+        })?;
+    }
+    listen(&canvas, "mouseleave", app, |app, _: MouseEvent| {
+        app.events.push(Event::PointerGone);
+    })?;
+    // No browser menu over the secondary button clicks
+    listen(&canvas, "contextmenu", app, |_, event: MouseEvent| {
+        event.prevent_default();
+    })?;
+    listen(&canvas, "wheel", app, |app, event: WheelEvent| {
+        event.prevent_default();
+        let unit = match event.delta_mode() {
+            WheelEvent::DOM_DELTA_LINE => egui::MouseWheelUnit::Line,
+            WheelEvent::DOM_DELTA_PAGE => egui::MouseWheelUnit::Page,
+            _ => egui::MouseWheelUnit::Point,
+        };
+        app.events.push(Event::MouseWheel {
+            unit,
+            delta: vec2(-event.delta_x() as f32, -event.delta_y() as f32),
+            modifiers: app.modifiers,
+        });
+    })?;
+
+    let document: web_sys::EventTarget = app
+        .borrow()
+        .canvas
+        .owner_document()
+        .ok_or("no document")?
+        .into();
+    for (name, pressed) in [("keydown", true), ("keyup", false)] {
+        listen(&document, name, app, move |app, event: KeyboardEvent| {
+            app.modifiers = modifiers(
+                event.ctrl_key(),
+                event.shift_key(),
+                event.alt_key(),
+                event.meta_key(),
+            );
+            let name = event.key();
+            if let Some(key) = egui::Key::from_name(&name) {
+                app.events.push(Event::Key {
+                    key,
+                    physical_key: None,
+                    pressed,
+                    repeat: event.repeat(),
+                    modifiers: app.modifiers,
+                });
+            }
+            // Printable keys are named after their text
+            if pressed && name.chars().count() == 1 && !app.modifiers.command {
+                app.events.push(Event::Text(name));
+            }
+            if app.egui_ctx.wants_keyboard_input() {
+                event.prevent_default();
+            }
+        })?;
+    }
+    Ok(())
+}
+
+/// Run `App::frame()` on each `requestAnimationFrame`
+fn request_animation_frames(app: Rc<RefCell<App>>) {
+    let callback: Rc<RefCell<Option<Closure<dyn FnMut()>>>> = Rc::new(RefCell::new(None));
+    let next_callback = callback.clone();
+    *callback.borrow_mut() = Some(Closure::new(move || {
+        app.borrow_mut().frame();
+        request_animation_frame(next_callback.borrow().as_ref().expect("set below"));
+    }));
+    request_animation_frame(callback.borrow().as_ref().expect("set above"));
+}
+
+fn request_animation_frame(callback: &Closure<dyn FnMut()>) {
+    if let Some(window) = web_sys::window() {
+        _ = window.request_animation_frame(callback.as_ref().unchecked_ref());
+    }
+}
+
+#[wasm_bindgen(start)]
+pub fn start() -> Result<(), JsValue> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("no document")?;
+    let canvas: HtmlCanvasElement = document.create_element("canvas")?.dyn_into()?;
+    let style = canvas.style();
+    style.set_property("display", "block")?;
+    style.set_property("width", "100vw")?;
+    style.set_property("height", "100vh")?;
+    document.body().ok_or("no body")?.append_child(&canvas)?;
+    let context_2d: CanvasRenderingContext2d = canvas
+        .get_context("2d")?
+        .ok_or("no 2d context")?
+        .dyn_into()?;
+
+    let app = Rc::new(RefCell::new(App {
+        canvas,
+        context_2d,
+        egui_ctx: egui::Context::default(),
+        demo: egui_demo_lib::DemoWindows::default(),
+        // `ImageData` is R, G, B, A
+        renderer: EguiSoftwareRender::new(ColorFieldOrder::Rgba),
+        pixels: Vec::new(),
+        size: [0, 0],
+        events: Vec::new(),
+        modifiers: Modifiers::NONE,
+        next_repaint: 0.0,
+    }));
+    add_input_listeners(&app)?;
+    request_animation_frames(app);
+    Ok(())
+}
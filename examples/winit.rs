@@ -3,27 +3,44 @@ use egui::Vec2;
 use egui::ViewportCommand;
 use egui_demo_lib::ColorTest;
 use egui_demo_lib::DemoWindows;
-use egui_software_backend::SoftwareRenderCaching;
+use egui_software_backend::{
+    DebugOverlay, GradientDirection, SoftwareRenderCaching, create_linear_gradient_texture,
+};
 use egui_software_backend::{SoftwareBackend, SoftwareBackendAppConfiguration};
 
 struct EguiApp {
     demo: DemoWindows,
     color_test: ColorTest,
     frame_times: Vec<f32>,
+    background: egui::TextureHandle,
 }
 
 impl EguiApp {
     fn new(context: egui::Context) -> Self {
         egui_extras::install_image_loaders(&context);
+        let gradient = create_linear_gradient_texture(
+            egui::Color32::from_rgb(20, 30, 60),
+            egui::Color32::from_rgb(60, 20, 40),
+            256,
+            GradientDirection::Vertical,
+        );
         EguiApp {
             demo: DemoWindows::default(),
             color_test: ColorTest::default(),
             frame_times: Vec::new(),
+            background: context.load_texture("background", gradient, egui::TextureOptions::LINEAR),
         }
     }
 
     fn ui(&mut self, ctx: &egui::Context) {
-        egui::CentralPanel::default().show(ctx, |_ui| {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // Custom gradient background, stretched from a 1 x 256 texture
+            ui.painter().image(
+                self.background.id(),
+                ui.max_rect(),
+                egui::Rect::from_min_max(egui::Pos2::ZERO, egui::Pos2::new(1.0, 1.0)),
+                egui::Color32::WHITE,
+            );
             self.demo.ui(ctx);
 
             egui::Window::new("Color Test").show(ctx, |ui| {
@@ -53,10 +70,58 @@ fn software_backend_ui(backend: &mut SoftwareBackend, ui: &mut Ui) {
             ui.selectable_value(&mut new, SoftwareRenderCaching::MeshTiled, "MeshTiled");
             ui.selectable_value(&mut new, SoftwareRenderCaching::Mesh, "Mesh");
             ui.selectable_value(&mut new, SoftwareRenderCaching::Direct, "Direct");
+            ui.selectable_value(&mut new, SoftwareRenderCaching::Auto, "Auto");
         });
     if new != old {
         backend.set_caching(new);
     }
+
+    let mut convert_tris_to_rects = backend.convert_tris_to_rects();
+    if ui
+        .checkbox(&mut convert_tris_to_rects, "Convert tris to rects")
+        .changed()
+    {
+        backend.set_convert_tris_to_rects(convert_tris_to_rects);
+    }
+    let mut allow_raster_opt = backend.allow_raster_opt();
+    if ui
+        .checkbox(&mut allow_raster_opt, "Allow raster optimizations")
+        .changed()
+    {
+        backend.set_allow_raster_opt(allow_raster_opt);
+    }
+
+    let mut debug_overlay = backend.debug_overlay();
+    for (flag, label) in [
+        (DebugOverlay::TILE_GRID, "Tile grid"),
+        (DebugOverlay::DIRTY_RECTS, "Dirty rects"),
+        (DebugOverlay::PRIMITIVE_BBOXES, "Primitive bboxes"),
+        (DebugOverlay::CACHE_OCCUPANCY, "Cache occupancy"),
+    ] {
+        let mut enabled = debug_overlay.contains(flag);
+        if ui.checkbox(&mut enabled, label).changed() {
+            debug_overlay.set(flag, enabled);
+        }
+    }
+    if debug_overlay != backend.debug_overlay() {
+        backend.set_debug_overlay(debug_overlay);
+    }
+
+    let mut grayscale = backend.grayscale();
+    if ui.checkbox(&mut grayscale, "Grayscale").changed() {
+        backend.set_grayscale(grayscale);
+    }
+
+    if let Some((tris, rects)) = backend.primitives_this_frame() {
+        ui.label(format!("Drawn: {tris} tris, {rects} rects"));
+    }
+    if let Some(hit_rate) = backend.cache_hit_rate() {
+        ui.label(format!("Cache hit rate: {:.1}%", hit_rate * 100.0));
+    }
+
+    if ui.button("Screenshot").clicked() {
+        backend.request_screenshot();
+    }
 }
 
 impl egui_software_backend::App for EguiApp {
@@ -82,6 +147,13 @@ impl egui_software_backend::App for EguiApp {
             }
         });
     }
+
+    fn on_screenshot(&mut self, image: image::RgbaImage) {
+        match image.save("screenshot.png") {
+            Ok(()) => eprintln!("Saved screenshot.png"),
+            Err(err) => eprintln!("Failed to save screenshot.png: {err}"),
+        }
+    }
 }
 
 fn main() {
@@ -90,8 +162,10 @@ fn main() {
     if std::env::var("USE_EFRAME").unwrap_or_default() == "true" {
         eprintln!("WILL RUN USING EFRAME");
         //eframe for reference.
-        let mut native_options = eframe::NativeOptions::default();
-        native_options.run_and_return = true;
+        let mut native_options = eframe::NativeOptions {
+            run_and_return: true,
+            ..Default::default()
+        };
         native_options.viewport.resizable = Some(false);
         native_options.viewport.title = Some("Viewport Command Tester".to_string());
         native_options.viewport.inner_size = Some(inner_size);
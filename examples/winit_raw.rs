@@ -37,6 +37,23 @@ struct AppState {
     surface: softbuffer::Surface<OwnedDisplayHandle, Rc<Window>>,
     egui_ctx: egui::Context,
     egui_winit: egui_winit::State,
+    /// Presents through MIT-SHM instead of `surface` on X11
+    #[cfg(feature = "x11_shm")]
+    x11_shm: Option<egui_software_backend::X11ShmSurface>,
+}
+
+/// `None` when the window isn't an X11 window, or the X server doesn't support MIT-SHM
+#[cfg(feature = "x11_shm")]
+fn x11_shm_surface(window: &Window) -> Option<egui_software_backend::X11ShmSurface> {
+    use winit::raw_window_handle::{HasWindowHandle, RawWindowHandle};
+    let window_id = match window.window_handle().ok()?.as_raw() {
+        RawWindowHandle::Xlib(handle) => handle.window as u32,
+        RawWindowHandle::Xcb(handle) => handle.window.get(),
+        _ => return None,
+    };
+    egui_software_backend::X11ShmSurface::new(window_id)
+        .inspect_err(|err| println!("MIT-SHM unavailable, using softbuffer: {err}"))
+        .ok()
 }
 
 fn main() {
@@ -85,6 +102,8 @@ fn main() {
                 surface,
                 egui_ctx,
                 egui_winit,
+                #[cfg(feature = "x11_shm")]
+                x11_shm: x11_shm_surface(window),
             }
         },
         |window: &mut Rc<Window>,
@@ -151,6 +170,23 @@ fn main() {
                         .egui_ctx
                         .tessellate(full_output.shapes, full_output.pixels_per_point);
 
+                    #[cfg(feature = "x11_shm")]
+                    if let Some(x11_shm) = &mut app.x11_shm {
+                        let buffer_ref = &mut x11_shm.buffer_mut(width, height).unwrap();
+                        let redraw_everything_this_frame = egui_software_render.cached_size()
+                            != (buffer_ref.width, buffer_ref.height);
+                        let dirty_rect = egui_software_render.render(
+                            buffer_ref,
+                            redraw_everything_this_frame,
+                            clipped_primitives,
+                            &full_output.textures_delta,
+                            full_output.pixels_per_point,
+                        );
+                        x11_shm.present(dirty_rect).unwrap();
+                        // The window title frame times are only measured with softbuffer
+                        return;
+                    }
+
                     let mut buffer = app.surface.buffer_mut().unwrap();
 
                     let buffer_ref = &mut BufferMutRef::new(
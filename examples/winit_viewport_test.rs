@@ -7,6 +7,17 @@ use egui_software_backend::{SoftwareBackend, SoftwareBackendAppConfiguration};
 use std::thread;
 use std::time::Duration;
 
+fn send_viewport_cmd_delayed(ctx: &Context, delay: Duration, command: ViewportCommand) {
+    let ctx = ctx.clone();
+    thread::Builder::new()
+        .name("delayed_viewport_cmd".to_owned())
+        .spawn(move || {
+            thread::sleep(delay);
+            ctx.send_viewport_cmd(command);
+        })
+        .expect("Failed to spawn thread");
+}
+
 #[derive(Default)]
 struct EguiApp {
     title_box: String,
@@ -27,12 +38,12 @@ impl EguiApp {
                     .button("Visible(false) -> Wait 5s -> Visible(true)")
                     .clicked()
                 {
-                    let ctx = ctx.clone();
                     ctx.send_viewport_cmd(ViewportCommand::Visible(false));
-                    thread::spawn(move || {
-                        thread::sleep(Duration::from_secs(5));
-                        ctx.send_viewport_cmd(ViewportCommand::Visible(true));
-                    });
+                    send_viewport_cmd_delayed(
+                        ctx,
+                        Duration::from_secs(5),
+                        ViewportCommand::Visible(true),
+                    );
                 }
 
                 if ui.button("Visible(false) + Close").clicked() {
@@ -57,11 +68,11 @@ impl EguiApp {
                 {
                     ctx.send_viewport_cmd(ViewportCommand::Visible(false));
                     ctx.send_viewport_cmd(ViewportCommand::Fullscreen(true));
-                    let ctx = ctx.clone();
-                    thread::spawn(move || {
-                        thread::sleep(Duration::from_secs(5));
-                        ctx.send_viewport_cmd(ViewportCommand::Visible(true));
-                    });
+                    send_viewport_cmd_delayed(
+                        ctx,
+                        Duration::from_secs(5),
+                        ViewportCommand::Visible(true),
+                    );
                 }
 
                 if ui.button("Resizable(false)").clicked() {
@@ -138,20 +149,18 @@ impl EguiApp {
                     ctx.send_viewport_cmd(ViewportCommand::Minimized(false));
                 }
                 if ui.button("Minimized(true) 3s delay").clicked() {
-                    let ctx = ctx.clone();
-
-                    thread::spawn(move || {
-                        thread::sleep(Duration::from_secs(3));
-                        ctx.send_viewport_cmd(ViewportCommand::Minimized(true));
-                    });
+                    send_viewport_cmd_delayed(
+                        ctx,
+                        Duration::from_secs(3),
+                        ViewportCommand::Minimized(true),
+                    );
                 }
                 if ui.button("Minimized(false) 3s delay").clicked() {
-                    let ctx = ctx.clone();
-
-                    thread::spawn(move || {
-                        thread::sleep(Duration::from_secs(3));
-                        ctx.send_viewport_cmd(ViewportCommand::Minimized(false));
-                    });
+                    send_viewport_cmd_delayed(
+                        ctx,
+                        Duration::from_secs(3),
+                        ViewportCommand::Minimized(false),
+                    );
                 }
 
                 if ui.button("Maximized(true)").clicked() {
@@ -161,20 +170,18 @@ impl EguiApp {
                     ctx.send_viewport_cmd(ViewportCommand::Maximized(false));
                 }
                 if ui.button("Maximized(true) 3s delay").clicked() {
-                    let ctx = ctx.clone();
-
-                    thread::spawn(move || {
-                        thread::sleep(Duration::from_secs(3));
-                        ctx.send_viewport_cmd(ViewportCommand::Maximized(true));
-                    });
+                    send_viewport_cmd_delayed(
+                        ctx,
+                        Duration::from_secs(3),
+                        ViewportCommand::Maximized(true),
+                    );
                 }
                 if ui.button("Maximized(false) 3s delay").clicked() {
-                    let ctx = ctx.clone();
-
-                    thread::spawn(move || {
-                        thread::sleep(Duration::from_secs(3));
-                        ctx.send_viewport_cmd(ViewportCommand::Maximized(false));
-                    });
+                    send_viewport_cmd_delayed(
+                        ctx,
+                        Duration::from_secs(3),
+                        ViewportCommand::Maximized(false),
+                    );
                 }
 
                 if ui.button("SetTheme(Dark)").clicked() {
@@ -187,12 +194,7 @@ impl EguiApp {
                     ctx.send_viewport_cmd(ViewportCommand::SetTheme(SystemTheme::SystemDefault));
                 }
                 if ui.button("Focus 3s delay").clicked() {
-                    let ctx = ctx.clone();
-
-                    thread::spawn(move || {
-                        thread::sleep(Duration::from_secs(3));
-                        ctx.send_viewport_cmd(ViewportCommand::Focus);
-                    });
+                    send_viewport_cmd_delayed(ctx, Duration::from_secs(3), ViewportCommand::Focus);
                 }
             });
         });
@@ -220,8 +222,10 @@ fn main() {
     if std::env::var("USE_EFRAME").unwrap_or_default() == "true" {
         eprintln!("WILL RUN USING EFRAME");
         //eframe for reference.
-        let mut native_options = eframe::NativeOptions::default();
-        native_options.run_and_return = true;
+        let mut native_options = eframe::NativeOptions {
+            run_and_return: true,
+            ..Default::default()
+        };
         native_options.viewport.resizable = Some(false);
         native_options.viewport.title = Some("Viewport Command Tester".to_string());
         native_options.viewport.inner_size = Some(Vec2::new(300f32, 300f32));
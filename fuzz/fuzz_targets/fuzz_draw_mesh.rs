@@ -0,0 +1,112 @@
+#![no_main]
+
+use egui::{
+    Color32, ColorImage, ImageData, Pos2, Rect, TextureId, TextureOptions, TexturesDelta,
+    epaint::{ClippedPrimitive, ImageDelta, Mesh, Primitive, Vertex},
+};
+use egui_software_backend::{BufferMutRef, ColorFieldOrder, EguiSoftwareRender, SoftwareRenderCaching};
+use libfuzzer_sys::fuzz_target;
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 48;
+
+struct Bytes<'a>(&'a [u8]);
+
+impl Bytes<'_> {
+    fn u8(&mut self) -> u8 {
+        let Some((&first, rest)) = self.0.split_first() else {
+            return 0;
+        };
+        self.0 = rest;
+        first
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_le_bytes([self.u8(), self.u8(), self.u8(), self.u8()])
+    }
+
+    /// Mostly small coordinates around the buffer, sometimes any f32 (NaN, Inf, huge, ...)
+    fn f32(&mut self) -> f32 {
+        match self.u8() % 4 {
+            0 => f32::from_bits(self.u32()),
+            _ => (self.u8() as i8) as f32 + self.u8() as f32 / 256.0,
+        }
+    }
+
+    fn pos2(&mut self) -> Pos2 {
+        Pos2::new(self.f32(), self.f32())
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut bytes = Bytes(data);
+
+    let mode = match bytes.u8() % 4 {
+        0 => SoftwareRenderCaching::BlendTiled,
+        1 => SoftwareRenderCaching::MeshTiled,
+        2 => SoftwareRenderCaching::Mesh,
+        _ => SoftwareRenderCaching::Direct,
+    };
+    let flags = bytes.u8();
+    let options = match bytes.u8() % 4 {
+        0 => TextureOptions::LINEAR,
+        1 => TextureOptions::NEAREST,
+        2 => TextureOptions::LINEAR_REPEAT,
+        _ => TextureOptions::NEAREST_MIRRORED_REPEAT,
+    };
+
+    // Minimal texture
+    let tex_size = [1 + bytes.u8() as usize % 4, 1 + bytes.u8() as usize % 4];
+    let pixels = (0..tex_size[0] * tex_size[1])
+        .map(|_| Color32::from_rgba_premultiplied(bytes.u8(), bytes.u8(), bytes.u8(), bytes.u8()))
+        .collect();
+    let image = ImageData::Color(ColorImage::new(tex_size, pixels).into());
+    let textures_delta = TexturesDelta {
+        set: vec![(TextureId::default(), ImageDelta::full(image, options))],
+        free: vec![],
+    };
+
+    let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+        .with_mode(mode)
+        .with_allow_raster_opt(flags & 1 != 0)
+        .with_convert_tris_to_rects(flags & 2 != 0);
+    let pixels_per_point = [1.0, 1.5, 2.0, 0.5][(flags as usize >> 2) % 4];
+
+    let mut paint_jobs = Vec::new();
+    for _ in 0..1 + bytes.u8() % 4 {
+        let clip_rect = Rect::from_min_max(bytes.pos2(), bytes.pos2());
+        let vertex_count = bytes.u8() as usize % 16;
+        let vertices = (0..vertex_count)
+            .map(|_| Vertex {
+                pos: bytes.pos2(),
+                uv: bytes.pos2(),
+                color: Color32::from_rgba_premultiplied(bytes.u8(), bytes.u8(), bytes.u8(), bytes.u8()),
+            })
+            .collect();
+        // Indices may be out of range, and their count not a multiple of 3
+        let index_count = bytes.u8() as usize % 24;
+        let indices = (0..index_count).map(|_| bytes.u8() as u32 % 20).collect();
+        let texture_id = if bytes.u8() % 8 == 0 {
+            TextureId::User(0)
+        } else {
+            TextureId::default()
+        };
+        paint_jobs.push(ClippedPrimitive {
+            clip_rect,
+            primitive: Primitive::Mesh(Mesh {
+                indices,
+                vertices,
+                texture_id,
+            }),
+        });
+    }
+
+    let mut data = vec![[0u8; 4]; (WIDTH * HEIGHT) as usize];
+    let mut buffer = BufferMutRef::new(&mut data, WIDTH, HEIGHT);
+    renderer.render(&mut buffer, true, paint_jobs.clone(), &textures_delta, pixels_per_point);
+    // Second frame goes through the cache
+    renderer.render(&mut buffer, false, paint_jobs, &TexturesDelta::default(), pixels_per_point);
+
+    let mut canvas_renderer = renderer.with_canvas();
+    canvas_renderer.render(&mut buffer, Vec::new(), &TexturesDelta::default(), pixels_per_point);
+});
@@ -0,0 +1,124 @@
+#![no_main]
+
+use egui::{
+    Color32, ColorImage, ImageData, Pos2, Rect, TextureId, TextureOptions, TexturesDelta,
+    epaint::{ClippedPrimitive, ImageDelta, Mesh, Primitive, Vertex},
+};
+use egui_software_backend::{BufferMutRef, ColorFieldOrder, EguiSoftwareRender, SoftwareRenderCaching};
+use libfuzzer_sys::fuzz_target;
+
+const WIDTH: u32 = 256;
+const HEIGHT: u32 = 256;
+
+struct Bytes<'a>(&'a [u8]);
+
+impl Bytes<'_> {
+    fn u8(&mut self) -> u8 {
+        let Some((&first, rest)) = self.0.split_first() else {
+            return 0;
+        };
+        self.0 = rest;
+        first
+    }
+
+    /// Coordinates around the buffer, snapped to 1/16 px
+    fn coord(&mut self) -> f32 {
+        (u16::from_le_bytes([self.u8(), self.u8()]) % (320 * 16)) as f32 / 16.0 - 32.0
+    }
+
+    fn pos2(&mut self) -> Pos2 {
+        Pos2::new(self.coord(), self.coord())
+    }
+
+    fn uv(&mut self) -> Pos2 {
+        Pos2::new(self.u8() as f32 / 255.0, self.u8() as f32 / 255.0)
+    }
+
+    fn color(&mut self) -> Color32 {
+        let a = self.u8();
+        // Premultiplied, so no channel can exceed alpha
+        let mut c = || (self.u8() as u16 * a as u16 / 255) as u8;
+        Color32::from_rgba_premultiplied(c(), c(), c(), a)
+    }
+}
+
+/// Thinner than a pixel, the edge coverage is computed with floats relative to the buffer origin so
+/// pixels along the edges can flip between modes
+fn is_sliver([a, b, c]: [Pos2; 3]) -> bool {
+    let double_area = (b - a).x * (c - a).y - (b - a).y * (c - a).x;
+    let longest_edge = (b - a).length().max((c - b).length()).max((a - c).length());
+    double_area.abs() < longest_edge
+}
+
+fn render(
+    mode: SoftwareRenderCaching,
+    paint_jobs: &[ClippedPrimitive],
+    textures_delta: &TexturesDelta,
+) -> Vec<[u8; 4]> {
+    let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_mode(mode);
+    let mut data = vec![[0u8; 4]; (WIDTH * HEIGHT) as usize];
+    let mut buffer = BufferMutRef::new(&mut data, WIDTH, HEIGHT);
+    renderer.render(&mut buffer, true, paint_jobs.to_vec(), textures_delta, 1.0);
+    data
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut bytes = Bytes(data);
+
+    let tex_size = [1 + bytes.u8() as usize % 8, 1 + bytes.u8() as usize % 8];
+    let pixels = (0..tex_size[0] * tex_size[1])
+        .map(|_| bytes.color())
+        .collect();
+    let image = ImageData::Color(ColorImage::new(tex_size, pixels).into());
+    // Nearest sampling can pick the neighbour texel when the uv is off by a rounding error
+    let textures_delta = TexturesDelta {
+        set: vec![(TextureId::default(), ImageDelta::full(image, TextureOptions::LINEAR))],
+        free: vec![],
+    };
+
+    let mut paint_jobs = Vec::new();
+    for _ in 0..1 + bytes.u8() % 8 {
+        let clip_rect = Rect::from_two_pos(bytes.pos2(), bytes.pos2());
+        let vertices: Vec<Vertex> = (0..3 + bytes.u8() as usize % 6)
+            .map(|_| Vertex {
+                pos: bytes.pos2(),
+                uv: bytes.uv(),
+                color: bytes.color(),
+            })
+            .collect();
+        let mut indices = Vec::new();
+        for _ in 0..1 + bytes.u8() as usize % 4 {
+            let tri = [(); 3].map(|_| bytes.u8() as u32 % vertices.len() as u32);
+            if !is_sliver(tri.map(|i| vertices[i as usize].pos)) {
+                indices.extend(tri);
+            }
+        }
+        paint_jobs.push(ClippedPrimitive {
+            clip_rect,
+            primitive: Primitive::Mesh(Mesh {
+                indices,
+                vertices,
+                texture_id: TextureId::default(),
+            }),
+        });
+    }
+
+    let direct = render(SoftwareRenderCaching::Direct, &paint_jobs, &textures_delta);
+    let blend_tiled = render(SoftwareRenderCaching::BlendTiled, &paint_jobs, &textures_delta);
+    // BlendTiled blends each primitive over transparent before blending it over the canvas, and
+    // interpolates vertex attributes from a different origin, so both round a bit differently
+    let tolerance = 2 * (1 + paint_jobs.len() as u8);
+    let mismatches: Vec<_> = direct
+        .iter()
+        .zip(&blend_tiled)
+        .enumerate()
+        .filter(|(_, (a, b))| (0..4).any(|c| a[c].abs_diff(b[c]) > tolerance))
+        .map(|(i, (a, b))| (i as u32 % WIDTH, i as u32 / WIDTH, *a, *b))
+        .collect();
+    assert!(
+        mismatches.is_empty(),
+        "Direct and BlendTiled differ at {} pixels (x, y, direct, blend_tiled): {:?}",
+        mismatches.len(),
+        &mismatches[..mismatches.len().min(8)]
+    );
+});
@@ -0,0 +1,96 @@
+//! `GlobalAllocStats`, counting the allocations reported by `RenderStats::allocs_this_frame`
+#![allow(unsafe_code)]
+
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+};
+use std::alloc::System;
+
+/// Allocations of every thread since the start of the process, wrapping on overflow
+static ALLOC_COUNT: AtomicUsize = AtomicUsize::new(0);
+static ALLOC_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Global allocator counting the allocations of the inner allocator, `System` by default
+///
+/// Installed by the application, the `RenderStats` allocations stay 0 otherwise:
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: egui_software_backend::GlobalAllocStats = egui_software_backend::GlobalAllocStats::new();
+/// ```
+/// The count is process wide, allocations of other threads during a render are included.
+pub struct GlobalAllocStats<A = System> {
+    inner: A,
+}
+
+impl GlobalAllocStats {
+    pub const fn new() -> Self {
+        Self { inner: System }
+    }
+}
+
+impl Default for GlobalAllocStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A> GlobalAllocStats<A> {
+    /// Counts the allocations of `inner`, e.g. jemalloc
+    pub const fn wrap(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+fn count(bytes: usize) {
+    ALLOC_COUNT.fetch_add(1, Relaxed);
+    ALLOC_BYTES.fetch_add(bytes, Relaxed);
+}
+
+// SAFETY: forwards every call to `inner` unchanged
+unsafe impl<A: GlobalAlloc> GlobalAlloc for GlobalAllocStats<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count(layout.size());
+        unsafe { self.inner.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count(layout.size());
+        unsafe { self.inner.alloc_zeroed(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { self.inner.dealloc(ptr, layout) }
+    }
+
+    /// Counted as an allocation of `new_size`
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count(new_size);
+        unsafe { self.inner.realloc(ptr, layout, new_size) }
+    }
+}
+
+/// Allocations since `AllocSnapshot::now()`
+#[derive(Clone, Copy)]
+pub(crate) struct AllocSnapshot {
+    count: usize,
+    bytes: usize,
+}
+
+impl AllocSnapshot {
+    pub fn now() -> Self {
+        Self {
+            count: ALLOC_COUNT.load(Relaxed),
+            bytes: ALLOC_BYTES.load(Relaxed),
+        }
+    }
+
+    /// Allocations and bytes allocated since `self`
+    pub fn elapsed(self) -> (u64, u64) {
+        let now = Self::now();
+        (
+            now.count.wrapping_sub(self.count) as u64,
+            now.bytes.wrapping_sub(self.bytes) as u64,
+        )
+    }
+}
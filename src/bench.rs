@@ -0,0 +1,128 @@
+//! Internals exposed to `benches/render.rs`, not a stable API
+
+use alloc::vec::Vec;
+use egui::{Vec2, ahash::HashMap};
+
+use crate::{
+    BufferMutRef, ColorFieldOrder, EguiTexture, SelectedImpl,
+    color::{GenericImpl, SelectedInstr},
+    pixel_alloc::PixelAlloc,
+    render::draw_egui_mesh,
+};
+
+/// A color implementation supported by the running CPU
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimdImpl(SelectedInstr);
+
+impl SimdImpl {
+    /// Every implementation the running CPU supports, the scalar one first
+    pub fn supported() -> Vec<SimdImpl> {
+        #[allow(unused_mut)]
+        let mut supported = Vec::from([SimdImpl(SelectedInstr::Generic)]);
+        #[cfg(all(target_arch = "x86_64", not(any(miri, feature = "miri"))))]
+        {
+            supported.push(SimdImpl(SelectedInstr::Sse2));
+            if std::arch::is_x86_feature_detected!("sse4.1") {
+                supported.push(SimdImpl(SelectedInstr::Sse41));
+            }
+            if std::arch::is_x86_feature_detected!("avx2") {
+                supported.push(SimdImpl(SelectedInstr::Avx2));
+            }
+        }
+        #[cfg(all(target_arch = "aarch64", not(any(miri, feature = "miri"))))]
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            supported.push(SimdImpl(SelectedInstr::Neon));
+        }
+        #[cfg(feature = "portable_simd")]
+        supported.push(SimdImpl(SelectedInstr::PortableSimd));
+        supported
+    }
+
+    pub fn name(self) -> &'static str {
+        match self.0 {
+            SelectedInstr::Generic => "generic",
+            #[cfg(all(target_arch = "x86_64", not(any(miri, feature = "miri"))))]
+            SelectedInstr::Sse2 => "sse2",
+            #[cfg(all(target_arch = "x86_64", not(any(miri, feature = "miri"))))]
+            SelectedInstr::Sse41 => "sse41",
+            #[cfg(all(target_arch = "x86_64", not(any(miri, feature = "miri"))))]
+            SelectedInstr::Avx2 => "avx2",
+            #[cfg(all(target_arch = "aarch64", not(any(miri, feature = "miri"))))]
+            SelectedInstr::Neon => "neon",
+            #[cfg(feature = "portable_simd")]
+            SelectedInstr::PortableSimd => "portable_simd",
+        }
+    }
+
+    /// Blend the premultiplied `src` over `dst`
+    pub fn egui_blend_u8_slice(self, src: &[[u8; 4]], dst: &mut [[u8; 4]]) {
+        // SAFETY: only the implementations detected by `SimdImpl::supported()` are constructed
+        #[allow(unsafe_code)]
+        match self.0 {
+            SelectedInstr::Generic => GenericImpl.egui_blend_u8_slice(src, dst),
+            #[cfg(all(target_arch = "x86_64", not(any(miri, feature = "miri"))))]
+            SelectedInstr::Sse2 => {
+                unsafe { crate::color::sse2::Sse2Impl::new() }.egui_blend_u8_slice(src, dst)
+            }
+            #[cfg(all(target_arch = "x86_64", not(any(miri, feature = "miri"))))]
+            SelectedInstr::Sse41 => {
+                unsafe { crate::color::sse41::Sse41Impl::new() }.egui_blend_u8_slice(src, dst)
+            }
+            #[cfg(all(target_arch = "x86_64", not(any(miri, feature = "miri"))))]
+            SelectedInstr::Avx2 => {
+                unsafe { crate::color::avx2::Avx2Impl::new() }.egui_blend_u8_slice(src, dst)
+            }
+            #[cfg(all(target_arch = "aarch64", not(any(miri, feature = "miri"))))]
+            SelectedInstr::Neon => {
+                unsafe { crate::color::neon::NeonImpl::new() }.egui_blend_u8_slice(src, dst)
+            }
+            #[cfg(feature = "portable_simd")]
+            SelectedInstr::PortableSimd => {
+                crate::color::portable_simd::PortableSimdImpl.egui_blend_u8_slice(src, dst)
+            }
+        }
+    }
+}
+
+/// Rasterizes meshes with the `draw_rect()` and `draw_tri()` paths of the renderer
+pub struct MeshRasterizer {
+    textures: HashMap<egui::TextureId, EguiTexture>,
+    #[cfg(all(feature = "raster_stats", not(feature = "rayon")))]
+    stats: crate::stats::RenderStats,
+}
+
+impl MeshRasterizer {
+    /// `image` is the texture of `egui::TextureId::default()`
+    pub fn new(image: &egui::ColorImage) -> Self {
+        let texture = EguiTexture::new(
+            PixelAlloc::default(),
+            ColorFieldOrder::Rgba,
+            egui::TextureOptions::LINEAR,
+            image.size,
+            &image.pixels,
+        );
+        let mut textures = HashMap::default();
+        textures.insert(egui::TextureId::default(), texture);
+        Self {
+            textures,
+            #[cfg(all(feature = "raster_stats", not(feature = "rayon")))]
+            stats: Default::default(),
+        }
+    }
+
+    /// Draw `mesh` over `buffer`, unclipped, with the raster optimizations and the triangle pairs converted to rects
+    pub fn draw(&self, buffer: &mut BufferMutRef, mesh: &egui::Mesh) {
+        draw_egui_mesh::<8>(
+            &self.textures,
+            buffer,
+            &egui::Rect::EVERYTHING,
+            mesh,
+            Vec2::ZERO,
+            true,
+            true,
+            1.0,
+            #[cfg(all(feature = "raster_stats", not(feature = "rayon")))]
+            &self.stats,
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use crate::{TILE_SIZE, as_usize, color::SelectedImpl};
+
+/// Precision of the canvas the cached primitives are blended into, see `EguiSoftwareRender::with_blend_precision()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendPrecision {
+    /// 8 bits per channel, like the output buffer
+    #[default]
+    Bits8,
+    /// 16 bits per channel, rounded with dithering when blitted to the output buffer.
+    /// Avoids the banding of many overlapping semi transparent layers, for twice the canvas memory.
+    Bits16,
+}
+
+/// Pixel of the `EguiSoftwareRenderCanvas` canvas
+pub(crate) trait CanvasPixel: Copy + Send + Sync + 'static {
+    const TRANSPARENT: Self;
+
+    /// dst[i] = blend(src[i], dst[i])
+    fn blend_slice(simd_impl: impl SelectedImpl, src: &[[u8; 4]], dst: &mut [Self]);
+
+    /// dst[i] = blend(src[i], dst[i]), `src` starts at canvas pixel (`x`, `y`)
+    fn blit_slice(simd_impl: impl SelectedImpl, src: &[Self], dst: &mut [[u8; 4]], x: u32, y: u32);
+}
+
+impl CanvasPixel for [u8; 4] {
+    const TRANSPARENT: Self = [0; 4];
+
+    #[inline(always)]
+    fn blend_slice(simd_impl: impl SelectedImpl, src: &[[u8; 4]], dst: &mut [Self]) {
+        simd_impl.egui_blend_u8_slice(src, dst);
+    }
+
+    #[inline(always)]
+    fn blit_slice(
+        simd_impl: impl SelectedImpl,
+        src: &[Self],
+        dst: &mut [[u8; 4]],
+        _x: u32,
+        _y: u32,
+    ) {
+        simd_impl.egui_blend_u8_slice(src, dst);
+    }
+}
+
+/// 4x4 ordered dither offsets, added before dividing by 257, centered in `0..257`
+const DITHER: [[u32; 4]; 4] = {
+    const BAYER: [[u32; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+    let mut dither = [[0; 4]; 4];
+    let mut i = 0;
+    while i < 16 {
+        dither[i / 4][i % 4] = (2 * BAYER[i / 4][i % 4] + 1) * 257 / 32;
+        i += 1;
+    }
+    dither
+};
+
+impl CanvasPixel for [u16; 4] {
+    const TRANSPARENT: Self = [0; 4];
+
+    #[inline(always)]
+    fn blend_slice(simd_impl: impl SelectedImpl, src: &[[u8; 4]], dst: &mut [Self]) {
+        simd_impl.egui_blend_u16_slice(src, dst);
+    }
+
+    fn blit_slice(simd_impl: impl SelectedImpl, src: &[Self], dst: &mut [[u8; 4]], x: u32, y: u32) {
+        let dither = &DITHER[as_usize(y % 4)];
+        let mut src8 = [[0u8; 4]; TILE_SIZE as usize];
+        for (chunk, (src, dst)) in src
+            .chunks(src8.len())
+            .zip(dst.chunks_mut(src8.len()))
+            .enumerate()
+        {
+            let x = as_usize(x) + chunk * src8.len();
+            for (i, (pixel, src)) in src8.iter_mut().zip(src).enumerate() {
+                // Same offset for every channel, so colors stay below alpha
+                let offset = dither[(x + i) % 4];
+                *pixel = src.map(|c| ((c as u32 + offset) / 257) as u8);
+            }
+            simd_impl.egui_blend_u8_slice(&src8[..src.len()], dst);
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Cache effectiveness counters, reset at the start of every render
+#[derive(Default)]
+pub struct CacheStats {
+    /// Primitives reused from the cache
+    pub cache_hits: AtomicU32,
+    /// Primitives not found in the cache, that had to be prepared
+    pub cache_misses: AtomicU32,
+    /// Cached primitives removed since they were not seen this frame
+    pub cache_evictions: AtomicU32,
+}
+
+impl CacheStats {
+    pub(crate) fn add_hit(&self) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_miss(&self) {
+        self.cache_misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_evictions(&self, count: u32) {
+        self.cache_evictions.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn cache_hits(&self) -> u32 {
+        self.cache_hits.load(Ordering::Relaxed)
+    }
+
+    pub fn cache_misses(&self) -> u32 {
+        self.cache_misses.load(Ordering::Relaxed)
+    }
+
+    pub fn cache_evictions(&self) -> u32 {
+        self.cache_evictions.load(Ordering::Relaxed)
+    }
+
+    /// Ratio of primitives reused from the cache, 0.0 if nothing was rendered
+    pub fn cache_hit_rate(&self) -> f32 {
+        let hits = self.cache_hits();
+        let total = hits + self.cache_misses();
+        if total == 0 {
+            0.0
+        } else {
+            hits as f32 / total as f32
+        }
+    }
+}
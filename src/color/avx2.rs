@@ -34,31 +34,41 @@ impl Avx2Impl {
         assert_eq!(src.len(), dst.len());
 
         let n = dst.len();
-        if n == 0 {
+        if n < 4 {
+            for (pixel, src) in dst.iter_mut().zip(src) {
+                *pixel = self.egui_blend_u8(*src, *pixel);
+            }
             return;
         }
 
-        let mut i = 0;
-        while i + 3 < n {
+        let src_ptr = src.as_ptr();
+        let dst_ptr = dst.as_mut_ptr();
+        // Blend the 4 pixels at `i`, without writing them
+        let blend_4 = |i: usize| {
             // Load 4 src pixels
-            let src_ptr = unsafe { src.as_ptr().add(i) }.cast::<__m128i>();
-            let src8: U8x4x4 = unsafe { read_unaligned(src_ptr) };
+            let src8: U8x4x4 = unsafe { read_unaligned(src_ptr.add(i).cast::<__m128i>()) };
             let src16: U16x4x4 = x8_zeroextend16(src8);
 
             // Load 4 dst pixels
-            let dst_ptr = unsafe { dst.as_mut_ptr().add(i) }.cast::<__m128i>();
-            let dst8: U8x4x4 = unsafe { read_unaligned(dst_ptr) };
+            let dst8: U8x4x4 = unsafe { read_unaligned(dst_ptr.add(i).cast::<__m128i>()) };
             let dst16: U16x4x4 = x8_zeroextend16(dst8);
 
-            let dst8: U8x4x4 = egui_blend_4_u16x4(src8, src16, dst16);
+            egui_blend_4_u16x4(src8, src16, dst16)
+        };
 
-            unsafe { write_unaligned(dst_ptr, dst8) };
+        // The last 4 pixels overlap the loop when `n` isn't a multiple of 4: blend them before the loop writes
+        // the shared pixels, instead of finishing one pixel at a time
+        let tail = (n % 4 != 0).then(|| blend_4(n - 4));
+
+        let mut i = 0;
+        while i + 3 < n {
+            let dst8 = blend_4(i);
+            unsafe { write_unaligned(dst_ptr.add(i).cast::<__m128i>(), dst8) };
             i += 4;
         }
 
-        while i < n {
-            dst[i] = self.egui_blend_u8(src[i], dst[i]);
-            i += 1;
+        if let Some(tail) = tail {
+            unsafe { write_unaligned(dst_ptr.add(n - 4).cast::<__m128i>(), tail) };
         }
     }
 
@@ -152,6 +162,170 @@ impl Avx2Impl {
         }
     }
 
+    /// pixels[i] = swizzle_rgba_bgra(pixels[i])
+    #[target_feature(enable = "avx2")]
+    fn swizzle_rgba_bgra_slice_avx2(self, pixels: &mut [[u8; 4]]) {
+        // Shuffles within each 128 bit lane
+        let shuffle = _mm256_setr_epi8(
+            2, 1, 0, 3, 6, 5, 4, 7, 10, 9, 8, 11, 14, 13, 12, 15, 2, 1, 0, 3, 6, 5, 4, 7, 10, 9, 8,
+            11, 14, 13, 12, 15,
+        );
+
+        let mut chunks = pixels.chunks_exact_mut(8);
+        for chunk in &mut chunks {
+            let p = chunk.as_mut_ptr().cast::<__m256i>();
+            let px = unsafe { read_unaligned(p) };
+            unsafe { write_unaligned(p, _mm256_shuffle_epi8(px, shuffle)) };
+        }
+
+        self.sse41.swizzle_rgba_bgra_slice(chunks.into_remainder());
+    }
+
+    /// pixels[i] = grayscale(pixels[i], weights)
+    #[target_feature(enable = "avx2")]
+    fn grayscale_slice_avx2(self, pixels: &mut [[u8; 4]], weights: [u16; 3]) {
+        let even_bytes = _mm256_set1_epi32(0x00FF00FF);
+        let alpha_mask = _mm256_set1_epi32(0xFF000000u32 as i32);
+        // Weights of the channels 0 and 2, then 1 and 3, in the low and high 16 bits of each pixel
+        let weights02 = _mm256_set1_epi32(((weights[2] as i32) << 16) | weights[0] as i32);
+        let weights13 = _mm256_set1_epi32(weights[1] as i32);
+
+        let mut chunks = pixels.chunks_exact_mut(8);
+        for chunk in &mut chunks {
+            let p = chunk.as_mut_ptr().cast::<__m256i>();
+            let px = unsafe { read_unaligned(p) };
+
+            let c02 = _mm256_and_si256(px, even_bytes);
+            let c13 = _mm256_and_si256(_mm256_srli_epi32(px, 8), even_bytes);
+            let y = _mm256_add_epi32(
+                _mm256_madd_epi16(c02, weights02),
+                _mm256_madd_epi16(c13, weights13),
+            );
+            let y = _mm256_srli_epi32(y, 8);
+
+            // y in the three color channels
+            let gray = _mm256_or_si256(
+                _mm256_or_si256(y, _mm256_slli_epi32(y, 8)),
+                _mm256_slli_epi32(y, 16),
+            );
+            let res = _mm256_or_si256(gray, _mm256_and_si256(px, alpha_mask));
+            unsafe { write_unaligned(p, res) };
+        }
+
+        self.sse41.grayscale_slice(chunks.into_remainder(), weights);
+    }
+
+    /// Index of the first pixel that differs between `a` and `b`, `a.len()` if none
+    #[target_feature(enable = "avx2")]
+    fn first_changed_pixel_avx2(self, a: &[[u8; 4]], b: &[[u8; 4]]) -> usize {
+        let len = a.len().min(b.len());
+        let (a_chunks, b_chunks) = (a[..len].chunks_exact(8), b[..len].chunks_exact(8));
+        for (i, (a_chunk, b_chunk)) in a_chunks.zip(b_chunks).enumerate() {
+            let a_px = unsafe { read_unaligned(a_chunk.as_ptr().cast::<__m256i>()) };
+            let b_px = unsafe { read_unaligned(b_chunk.as_ptr().cast::<__m256i>()) };
+            // One bit per equal byte
+            let equal = _mm256_movemask_epi8(_mm256_cmpeq_epi8(a_px, b_px)) as u32;
+            if equal != u32::MAX {
+                return i * 8 + (!equal).trailing_zeros() as usize / 4;
+            }
+        }
+
+        let start = len / 8 * 8;
+        start
+            + self
+                .sse41
+                .first_changed_pixel(&a[start..len], &b[start..len])
+    }
+
+    /// Whether a pixel of `pixels` has a non zero channel
+    #[target_feature(enable = "avx2")]
+    fn any_nonzero_pixel_avx2(self, pixels: &[[u8; 4]]) -> bool {
+        let zero = _mm256_setzero_si256();
+        let mut chunks = pixels.chunks_exact(8);
+        for chunk in &mut chunks {
+            let px = unsafe { read_unaligned(chunk.as_ptr().cast::<__m256i>()) };
+            // One bit per zero byte
+            if _mm256_movemask_epi8(_mm256_cmpeq_epi8(px, zero)) != -1 {
+                return true;
+            }
+        }
+        self.sse41.any_nonzero_pixel(chunks.remainder())
+    }
+
+    /// pixels[i][c] = lut[pixels[i][c]] for the color channels, alpha is kept as is
+    #[target_feature(enable = "avx2")]
+    fn lut_rgb_slice_avx2(self, pixels: &mut [[u8; 4]], lut: &[u8; 256]) {
+        // Same tables in both 128 bit lanes, since `_mm256_shuffle_epi8` shuffles within each lane
+        let mut tables = [_mm256_setzero_si256(); 16];
+        for (table, lut) in tables.iter_mut().zip(lut.chunks_exact(16)) {
+            let lut = unsafe { read_unaligned(lut.as_ptr().cast::<__m128i>()) };
+            *table = _mm256_broadcastsi128_si256(lut);
+        }
+        let low_nibble = _mm256_set1_epi8(0x0F);
+        let alpha_mask = _mm256_set1_epi32(0xFF000000u32 as i32);
+
+        let mut chunks = pixels.chunks_exact_mut(8);
+        for chunk in &mut chunks {
+            let p = chunk.as_mut_ptr().cast::<__m256i>();
+            let px = unsafe { read_unaligned(p) };
+
+            let lo = _mm256_and_si256(px, low_nibble);
+            let hi = _mm256_and_si256(_mm256_srli_epi16(px, 4), low_nibble);
+            let mut res = _mm256_setzero_si256();
+            for (i, table) in tables.iter().enumerate() {
+                let in_table = _mm256_cmpeq_epi8(hi, _mm256_set1_epi8(i as i8));
+                let looked_up = _mm256_shuffle_epi8(*table, lo);
+                res = _mm256_or_si256(res, _mm256_and_si256(in_table, looked_up));
+            }
+
+            unsafe { write_unaligned(p, _mm256_blendv_epi8(res, px, alpha_mask)) };
+        }
+
+        self.sse41.lut_rgb_slice(chunks.into_remainder(), lut);
+    }
+
+    /// dst[i] = blend(src[i], dst[i]), with 16 bits per channel in dst
+    /// blend fn is (ONE, ONE_MINUS_SRC_ALPHA)
+    #[target_feature(enable = "avx2")]
+    fn egui_blend_u16_slice_avx2(self, src: &[[u8; 4]], dst: &mut [[u16; 4]]) {
+        assert_eq!(src.len(), dst.len());
+
+        let mut src_chunks = src.chunks_exact(4);
+        let mut dst_chunks = dst.chunks_exact_mut(4);
+        for (src, dst) in (&mut src_chunks).zip(&mut dst_chunks) {
+            // Load 4 src pixels
+            let src8: U8x4x4 = unsafe { read_unaligned(src.as_ptr().cast::<__m128i>()) };
+            let src16: U16x4x4 = x8_zeroextend16(src8);
+            // src * 257, the 16 bit equivalent of src
+            let src16 = _mm256_or_si256(_mm256_slli_epi16(src16, 8), src16);
+
+            // Load 4 dst pixels
+            let dst_ptr = dst.as_mut_ptr().cast::<__m256i>();
+            let dst16: U16x4x4 = unsafe { read_unaligned(dst_ptr) };
+
+            // Broadcast alpha within each pixel's 4 lanes
+            let a_broadcast_lo = _mm256_shufflelo_epi16(src16, 0b11111111);
+            let a_broadcast = _mm256_shufflehi_epi16(a_broadcast_lo, 0b11111111);
+
+            // round(dst * alpha / 65536)
+            let hi = _mm256_mulhi_epu16(dst16, a_broadcast);
+            let lo = _mm256_mullo_epi16(dst16, a_broadcast);
+            let dst_alpha = _mm256_add_epi16(hi, _mm256_srli_epi16(lo, 15));
+
+            // (dst - dst * alpha).saturating_add(src)
+            let res16 = _mm256_adds_epu16(_mm256_sub_epi16(dst16, dst_alpha), src16);
+            unsafe { write_unaligned(dst_ptr, res16) };
+        }
+
+        for (pixel, src) in dst_chunks
+            .into_remainder()
+            .iter_mut()
+            .zip(src_chunks.remainder())
+        {
+            *pixel = crate::color::egui_blend_u16(*src, *pixel);
+        }
+    }
+
     #[target_feature(enable = "avx2")]
     fn egui_blend_u8_slice_one_src_avx2(self, src: [u8; 4], dst: &mut [[u8; 4]]) {
         let n = dst.len();
@@ -237,6 +411,61 @@ impl SelectedImpl for Avx2Impl {
         self.sse41.egui_blend_u8(src, dst)
     }
 
+    #[inline]
+    fn egui_blend_u16_slice(self, src: &[[u8; 4]], dst: &mut [[u16; 4]]) {
+        unsafe { self.egui_blend_u16_slice_avx2(src, dst) }
+    }
+
+    #[inline]
+    fn bilinear_u8x4_4x(
+        self,
+        corners: &[[[u8; 4]; 4]; 4],
+        fx: [u32; 4],
+        fy: [u32; 4],
+    ) -> [[u8; 4]; 4] {
+        unsafe { super::sse2::bilinear_u8x4_4x(corners, fx, fy) }
+    }
+
+    #[inline]
+    fn swizzle_rgba_bgra_slice(self, pixels: &mut [[u8; 4]]) {
+        unsafe { self.swizzle_rgba_bgra_slice_avx2(pixels) }
+    }
+
+    #[inline]
+    fn premultiply_alpha_slice(self, pixels: &mut [[u8; 4]]) {
+        unsafe { super::sse2::premultiply_alpha_slice(pixels) }
+    }
+
+    #[inline]
+    fn grayscale_slice(self, pixels: &mut [[u8; 4]], weights: [u16; 3]) {
+        unsafe { self.grayscale_slice_avx2(pixels, weights) }
+    }
+
+    #[inline]
+    fn first_changed_pixel(self, a: &[[u8; 4]], b: &[[u8; 4]]) -> usize {
+        unsafe { self.first_changed_pixel_avx2(a, b) }
+    }
+
+    #[inline]
+    fn any_nonzero_pixel(self, pixels: &[[u8; 4]]) -> bool {
+        unsafe { self.any_nonzero_pixel_avx2(pixels) }
+    }
+
+    #[inline]
+    fn color_matrix_slice(self, pixels: &mut [[u8; 4]], matrix: &[[i16; 3]; 3]) {
+        self.sse41.color_matrix_slice(pixels, matrix);
+    }
+
+    #[inline]
+    fn dither_bayer4x4_slice(self, pixels: &mut [[u8; 4]], x: u32, y: u32, levels: u8) {
+        self.sse41.dither_bayer4x4_slice(pixels, x, y, levels);
+    }
+
+    #[inline]
+    fn lut_rgb_slice(self, pixels: &mut [[u8; 4]], lut: &[u8; 256]) {
+        unsafe { self.lut_rgb_slice_avx2(pixels, lut) }
+    }
+
     #[inline]
     fn unorm_mult4x4(self, a: [u8; 4], b: [u8; 4]) -> [u8; 4] {
         self.sse41.unorm_mult4x4(a, b)
@@ -1,26 +1,47 @@
+use alloc::vec::Vec;
+use egui::Color32;
+
 use crate::math::vec4::{Vec4, vec4};
 
-#[cfg(target_arch = "x86_64")]
+#[cfg(all(target_arch = "x86_64", not(any(miri, feature = "miri"))))]
 pub(crate) mod avx2;
-#[cfg(target_arch = "aarch64")]
+#[cfg(all(target_arch = "aarch64", not(any(miri, feature = "miri"))))]
 pub(crate) mod neon;
-#[cfg(target_arch = "x86_64")]
+#[cfg(feature = "portable_simd")]
+pub(crate) mod portable_simd;
+#[cfg(all(target_arch = "x86_64", not(any(miri, feature = "miri"))))]
+pub(crate) mod sse2;
+#[cfg(all(target_arch = "x86_64", not(any(miri, feature = "miri"))))]
 pub(crate) mod sse41;
 
+/// The hand written SIMD implementations are left out under Miri, or with the `miri` feature, since
+/// Miri can't run most of the intrinsics
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum SelectedInstr {
     Generic,
-    #[cfg(target_arch = "x86_64")]
+    #[cfg(all(target_arch = "x86_64", not(any(miri, feature = "miri"))))]
+    Sse2,
+    #[cfg(all(target_arch = "x86_64", not(any(miri, feature = "miri"))))]
     Sse41,
-    #[cfg(target_arch = "x86_64")]
+    #[cfg(all(target_arch = "x86_64", not(any(miri, feature = "miri"))))]
     Avx2,
-    #[cfg(target_arch = "aarch64")]
+    #[cfg(all(target_arch = "aarch64", not(any(miri, feature = "miri"))))]
     Neon,
+    #[cfg(feature = "portable_simd")]
+    PortableSimd,
 }
 
+// Only `Generic`, and `PortableSimd` with the `portable_simd` feature, are left with the `miri` feature
+#[cfg_attr(feature = "miri", allow(clippy::derivable_impls))]
 impl Default for SelectedInstr {
+    // The fallbacks below are only reachable on some architectures
+    #[allow(unreachable_code)]
     fn default() -> Self {
-        #[cfg(all(target_arch = "x86_64", feature = "std"))]
+        #[cfg(all(
+            target_arch = "x86_64",
+            feature = "std",
+            not(any(miri, feature = "miri"))
+        ))]
         if std::arch::is_x86_feature_detected!("sse4.1") {
             if std::arch::is_x86_feature_detected!("avx2") {
                 return SelectedInstr::Avx2;
@@ -29,11 +50,22 @@ impl Default for SelectedInstr {
             }
         }
 
-        #[cfg(all(target_arch = "aarch64", feature = "std"))]
+        #[cfg(all(
+            target_arch = "aarch64",
+            feature = "std",
+            not(any(miri, feature = "miri"))
+        ))]
         if std::arch::is_aarch64_feature_detected!("neon") {
             return SelectedInstr::Neon;
         }
 
+        // SSE2 is part of the x86_64 baseline, no need to detect it
+        #[cfg(all(target_arch = "x86_64", not(any(miri, feature = "miri"))))]
+        return SelectedInstr::Sse2;
+
+        #[cfg(feature = "portable_simd")]
+        return SelectedInstr::PortableSimd;
+
         SelectedInstr::Generic
     }
 }
@@ -46,24 +78,36 @@ macro_rules! dispatch_simd_impl {
             $crate::color::SelectedInstr::Generic => {
                 (|$simd_impl: $crate::color::GenericImpl| $body)($crate::color::GenericImpl)
             }
-            #[cfg(target_arch = "x86_64")]
+            #[cfg(all(target_arch = "x86_64", not(any(miri, feature = "miri"))))]
+            $crate::color::SelectedInstr::Sse2 => {
+                (|$simd_impl: $crate::color::sse2::Sse2Impl| $body)(unsafe {
+                    $crate::color::sse2::Sse2Impl::new()
+                })
+            }
+            #[cfg(all(target_arch = "x86_64", not(any(miri, feature = "miri"))))]
             $crate::color::SelectedInstr::Sse41 => {
                 (|$simd_impl: $crate::color::sse41::Sse41Impl| $body)(unsafe {
                     $crate::color::sse41::Sse41Impl::new()
                 })
             }
-            #[cfg(target_arch = "x86_64")]
+            #[cfg(all(target_arch = "x86_64", not(any(miri, feature = "miri"))))]
             $crate::color::SelectedInstr::Avx2 => {
                 (|$simd_impl: $crate::color::avx2::Avx2Impl| $body)(unsafe {
                     $crate::color::avx2::Avx2Impl::new()
                 })
             }
-            #[cfg(target_arch = "aarch64")]
+            #[cfg(all(target_arch = "aarch64", not(any(miri, feature = "miri"))))]
             $crate::color::SelectedInstr::Neon => {
                 (|$simd_impl: $crate::color::neon::NeonImpl| $body)(unsafe {
                     $crate::color::neon::NeonImpl::new()
                 })
             }
+            #[cfg(feature = "portable_simd")]
+            $crate::color::SelectedInstr::PortableSimd => {
+                (|$simd_impl: $crate::color::portable_simd::PortableSimdImpl| $body)(
+                    $crate::color::portable_simd::PortableSimdImpl,
+                )
+            }
         }
     };
 }
@@ -74,6 +118,23 @@ pub(crate) trait SelectedImpl: Copy + Sync + Send + 'static {
             *pixel = self.egui_blend_u8(*src, *pixel);
         }
     }
+    /// `egui_blend_u8_slice()` of `height` rows of `width` pixels, starting every `src_stride` pixels of `src`
+    /// and every `dst_stride` pixels of `dst`
+    fn egui_blend_u8_strided(
+        self,
+        src: &[[u8; 4]],
+        src_stride: usize,
+        dst: &mut [[u8; 4]],
+        dst_stride: usize,
+        width: usize,
+        height: usize,
+    ) {
+        for y in 0..height {
+            let src_row = &src[y * src_stride..][..width];
+            let dst_row = &mut dst[y * dst_stride..][..width];
+            self.egui_blend_u8_slice(src_row, dst_row);
+        }
+    }
     /// dst[i] = blend(src * tint_fn(), dst[i]) // As unorm
     /// blend fn is (ONE, ONE_MINUS_SRC_ALPHA)
     fn egui_blend_u8_slice_one_src_tinted_fn(
@@ -134,6 +195,89 @@ pub(crate) trait SelectedImpl: Copy + Sync + Send + 'static {
         ]
     }
 
+    /// dst[i] = blend(src[i], dst[i]), with 16 bits per channel in dst
+    /// blend fn is (ONE, ONE_MINUS_SRC_ALPHA)
+    fn egui_blend_u16_slice(self, src: &[[u8; 4]], dst: &mut [[u16; 4]]) {
+        for (pixel, src) in dst.iter_mut().zip(src) {
+            *pixel = egui_blend_u16(*src, *pixel);
+        }
+    }
+
+    /// out[i] = bilinear_u8x4(corners[0][i], corners[1][i], corners[2][i], corners[3][i], fx[i], fy[i])
+    fn bilinear_u8x4_4x(
+        self,
+        corners: &[[[u8; 4]; 4]; 4],
+        fx: [u32; 4],
+        fy: [u32; 4],
+    ) -> [[u8; 4]; 4] {
+        core::array::from_fn(|i| {
+            bilinear_u8x4(
+                corners[0][i],
+                corners[1][i],
+                corners[2][i],
+                corners[3][i],
+                fx[i],
+                fy[i],
+            )
+        })
+    }
+
+    /// pixels[i] = swizzle_rgba_bgra(pixels[i])
+    fn swizzle_rgba_bgra_slice(self, pixels: &mut [[u8; 4]]) {
+        for pixel in pixels {
+            *pixel = swizzle_rgba_bgra(*pixel);
+        }
+    }
+
+    /// pixels[i] = premultiply_alpha(pixels[i])
+    fn premultiply_alpha_slice(self, pixels: &mut [[u8; 4]]) {
+        for pixel in pixels {
+            *pixel = premultiply_alpha(*pixel);
+        }
+    }
+
+    /// pixels[i] = grayscale(pixels[i], weights)
+    fn grayscale_slice(self, pixels: &mut [[u8; 4]], weights: [u16; 3]) {
+        for pixel in pixels {
+            *pixel = grayscale(*pixel, weights);
+        }
+    }
+
+    /// Index of the first pixel that differs between `a` and `b`, `a.len()` if none
+    fn first_changed_pixel(self, a: &[[u8; 4]], b: &[[u8; 4]]) -> usize {
+        a.iter().zip(b).position(|(a, b)| a != b).unwrap_or(a.len())
+    }
+
+    /// Whether a pixel of `pixels` has a non zero channel
+    fn any_nonzero_pixel(self, pixels: &[[u8; 4]]) -> bool {
+        pixels.iter().any(|px| u32::from_le_bytes(*px) > 0)
+    }
+
+    /// pixels[i] = color_matrix(pixels[i], matrix)
+    fn color_matrix_slice(self, pixels: &mut [[u8; 4]], matrix: &[[i16; 3]; 3]) {
+        for pixel in pixels {
+            *pixel = color_matrix(*pixel, matrix);
+        }
+    }
+
+    /// pixels[i] = dither_bayer4x4(pixels[i], ...) of the pixel at `(x + i, y)`, quantized to `levels` levels
+    fn dither_bayer4x4_slice(self, pixels: &mut [[u8; 4]], x: u32, y: u32, levels: u8) {
+        let n = dither_steps(levels);
+        let thresholds = &BAYER4X4_THRESHOLDS[y as usize % 4];
+        for (i, pixel) in pixels.iter_mut().enumerate() {
+            *pixel = dither_bayer4x4(*pixel, thresholds[(x as usize + i) % 4], n);
+        }
+    }
+
+    /// pixels[i][c] = lut[pixels[i][c]] for the color channels, alpha is kept as is
+    fn lut_rgb_slice(self, pixels: &mut [[u8; 4]], lut: &[u8; 256]) {
+        for pixel in pixels {
+            for c in &mut pixel[..3] {
+                *c = lut[*c as usize];
+            }
+        }
+    }
+
     #[inline(always)]
     fn unorm_mult4x4(self, a: [u8; 4], b: [u8; 4]) -> [u8; 4] {
         [
@@ -183,7 +327,598 @@ pub fn unorm_mult(mut a: u32, b: u32) -> u32 {
     a >> 8
 }
 
+/// blend fn is (ONE, ONE_MINUS_SRC_ALPHA), with 16 bits per channel in dst
+///
+/// `dst * (1 - a)` is computed as `dst - round(dst * a / 65536)`, which leaves `dst` untouched for a transparent `src`,
+/// and maps to `mulhi_epu16`.
+#[inline(always)]
+pub fn egui_blend_u16(src: [u8; 4], dst: [u16; 4]) -> [u16; 4] {
+    let alpha = src[3] as u32 * 257;
+    core::array::from_fn(|i| {
+        let d = dst[i] as u32;
+        let d = d - ((d * alpha + 0x8000) >> 16);
+        (d + src[i] as u32 * 257).min(0xFFFF) as u16
+    })
+}
+
+/// Bilinear interpolation between the texels at (x0, y0), (x1, y0), (x0, y1) and (x1, y1)
+///
+/// `fx` and `fy` are the weights of x1 and y1, in `0..=BILINEAR_ONE`. The horizontal results fit in an `i16`, so
+/// both steps map to `madd_epi16`.
+#[inline(always)]
+pub fn bilinear_u8x4(
+    c00: [u8; 4],
+    c10: [u8; 4],
+    c01: [u8; 4],
+    c11: [u8; 4],
+    fx: u32,
+    fy: u32,
+) -> [u8; 4] {
+    core::array::from_fn(|i| {
+        let top = c00[i] as u32 * (BILINEAR_ONE - fx) + c10[i] as u32 * fx;
+        let bottom = c01[i] as u32 * (BILINEAR_ONE - fx) + c11[i] as u32 * fx;
+        let round = BILINEAR_ONE * BILINEAR_ONE / 2;
+        ((top * (BILINEAR_ONE - fy) + bottom * fy + round) / (BILINEAR_ONE * BILINEAR_ONE)) as u8
+    })
+}
+
+/// Weight of 1.0 in `bilinear_u8x4`
+pub const BILINEAR_ONE: u32 = 128;
+
 #[inline(always)]
 pub fn swizzle_rgba_bgra(a: [u8; 4]) -> [u8; 4] {
     [a[2], a[1], a[0], a[3]]
 }
+
+/// Copies egui colors to BGRA pixels, using SIMD when available
+///
+/// The colors are kept as is, since `Color32` is already premultiplied, which is checked in debug builds.
+pub fn swizzle_and_premultiply_rgba_bgra_slice(pixels: &[Color32]) -> Vec<[u8; 4]> {
+    debug_assert!(
+        pixels.iter().all(|p| p.a() >= p.r().max(p.g()).max(p.b())),
+        "Color32 should be premultiplied"
+    );
+    let mut bgra: Vec<[u8; 4]> = pixels.iter().map(|p| p.to_array()).collect();
+    crate::dispatch_simd_impl!(|simd_impl| simd_impl.swizzle_rgba_bgra_slice(&mut bgra));
+    bgra
+}
+
+/// Converts a straight alpha color to the premultiplied alpha egui expects
+///
+/// Works with both RGBA and BGRA, as long as alpha is last.
+#[inline(always)]
+pub fn premultiply_alpha(src: [u8; 4]) -> [u8; 4] {
+    let a = src[3] as u32;
+    [
+        unorm_mult(src[0] as u32, a) as u8,
+        unorm_mult(src[1] as u32, a) as u8,
+        unorm_mult(src[2] as u32, a) as u8,
+        src[3],
+    ]
+}
+
+/// Converts a premultiplied alpha color back to straight alpha, fully transparent colors become black
+///
+/// Works with both RGBA and BGRA, as long as alpha is last.
+#[inline(always)]
+pub fn unpremultiply_alpha(src: [u8; 4]) -> [u8; 4] {
+    let a = src[3] as u32;
+    match a {
+        0 => [0; 4],
+        255 => src,
+        _ => {
+            let unmult = |c: u8| ((c as u32 * 255 + a / 2) / a).min(255) as u8;
+            [unmult(src[0]), unmult(src[1]), unmult(src[2]), src[3]]
+        }
+    }
+}
+
+/// [`premultiply_alpha`] for every pixel, using SIMD when available
+pub fn premultiply_alpha_slice(pixels: &mut [[u8; 4]]) {
+    crate::dispatch_simd_impl!(|simd_impl| simd_impl.premultiply_alpha_slice(pixels));
+}
+
+/// [`unpremultiply_alpha`] for every pixel
+pub fn unpremultiply_alpha_slice(pixels: &mut [[u8; 4]]) {
+    for pixel in pixels {
+        *pixel = unpremultiply_alpha(*pixel);
+    }
+}
+
+/// Luminance weights of R, G and B in 1/256 units, `0.299`, `0.587` and `0.114` rounded to add up to 256
+pub(crate) const GRAYSCALE_WEIGHTS_RGB: [u16; 3] = [77, 150, 29];
+
+/// The color channels replaced by `(weights[0] * c0 + weights[1] * c1 + weights[2] * c2) >> 8`, alpha is kept as is
+///
+/// The weights must add up to 256 at most. Premultiplied colors stay premultiplied.
+#[inline(always)]
+pub(crate) fn grayscale(src: [u8; 4], weights: [u16; 3]) -> [u8; 4] {
+    let y = ((weights[0] as u32 * src[0] as u32
+        + weights[1] as u32 * src[1] as u32
+        + weights[2] as u32 * src[2] as u32)
+        >> 8) as u8;
+    [y, y, y, src[3]]
+}
+
+/// Replaces the color channels of RGBA pixels by their luminance, `(77 * R + 150 * G + 29 * B) >> 8`, using SIMD
+/// when available
+///
+/// Alpha is kept as is.
+pub fn grayscale_slice(pixels: &mut [[u8; 4]]) {
+    crate::dispatch_simd_impl!(|simd_impl| simd_impl.grayscale_slice(pixels, GRAYSCALE_WEIGHTS_RGB));
+}
+
+/// Color vision deficiency simulated on the output, see `EguiSoftwareRender::with_color_blind_mode()`
+///
+/// Uses the severity 1.0 matrices of Machado, Oliveira and Fernandes, "A Physiologically-based Model for Simulation
+/// of Color Vision Deficiency" (2009).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorBlindMode {
+    /// Colors are unchanged
+    #[default]
+    Normal,
+    /// No long wavelength (red) cones
+    Protanopia,
+    /// No medium wavelength (green) cones
+    Deuteranopia,
+    /// No short wavelength (blue) cones
+    Tritanopia,
+    /// No color vision, only the luminance is seen
+    Achromatopsia,
+}
+
+impl ColorBlindMode {
+    pub const ALL: [ColorBlindMode; 5] = [
+        ColorBlindMode::Normal,
+        ColorBlindMode::Protanopia,
+        ColorBlindMode::Deuteranopia,
+        ColorBlindMode::Tritanopia,
+        ColorBlindMode::Achromatopsia,
+    ];
+
+    /// RGB to RGB matrix in 1/256 units, each row adds up to 256 so white stays white
+    pub(crate) const fn matrix(self) -> Option<[[i16; 3]; 3]> {
+        let [r, g, b] = GRAYSCALE_WEIGHTS_RGB;
+        let [r, g, b] = [r as i16, g as i16, b as i16];
+        match self {
+            ColorBlindMode::Normal => None,
+            ColorBlindMode::Protanopia => Some([[39, 269, -52], [29, 202, 25], [-1, -12, 269]]),
+            ColorBlindMode::Deuteranopia => Some([[94, 220, -58], [72, 172, 12], [-3, 11, 248]]),
+            ColorBlindMode::Tritanopia => Some([[322, -20, -46], [-20, 238, 38], [1, 177, 78]]),
+            ColorBlindMode::Achromatopsia => Some([[r, g, b], [r, g, b], [r, g, b]]),
+        }
+    }
+}
+
+/// The color channels replaced by `(matrix[c][0] * c0 + matrix[c][1] * c1 + matrix[c][2] * c2 + 128) >> 8`, clamped
+/// to `0..=alpha` so premultiplied colors stay premultiplied
+#[inline(always)]
+pub(crate) fn color_matrix(src: [u8; 4], matrix: &[[i16; 3]; 3]) -> [u8; 4] {
+    let a = src[3] as i32;
+    let [c0, c1, c2] = matrix.map(|row| {
+        let sum = row[0] as i32 * src[0] as i32
+            + row[1] as i32 * src[1] as i32
+            + row[2] as i32 * src[2] as i32;
+        ((sum + 128) >> 8).clamp(0, a) as u8
+    });
+    [c0, c1, c2, src[3]]
+}
+
+/// Ordered dithering of the output, see `EguiSoftwareRender::with_dithering()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DitherMode {
+    /// Output the 8 bits colors as is
+    #[default]
+    None,
+    /// Quantize each color channel to `levels` evenly spaced levels (e.g. 16 for a 4 bits display, at least 2),
+    /// spreading the error with a 4x4 Bayer matrix
+    BayerMatrix4x4 { levels: u8 },
+}
+
+/// `254 - (2 * b + 1) * 255 / 32` of the 4x4 Bayer matrix `b`:
+/// ```text
+///  0  8  2 10
+/// 12  4 14  6
+///  3 11  1  9
+/// 15  7 13  5
+/// ```
+/// A channel goes up to the next level when its distance to the level below, in 1/255 of a level, is above the
+/// threshold of its pixel.
+pub(crate) const BAYER4X4_THRESHOLDS: [[u8; 4]; 4] = [
+    [247, 119, 215, 87],
+    [55, 183, 23, 151],
+    [199, 71, 231, 103],
+    [7, 135, 39, 167],
+];
+
+/// Number of steps between the `levels` levels
+#[inline(always)]
+pub(crate) fn dither_steps(levels: u8) -> u16 {
+    levels.max(2) as u16 - 1
+}
+
+/// `(q * scale + 128) >> 8` is the 8 bits value of the level `q` out of `n` steps, 255 for `q == n`
+#[inline(always)]
+pub(crate) fn dither_level_scale(n: u16) -> u16 {
+    ((255 * 256 + n as u32 / 2) / n as u32) as u16
+}
+
+/// The color channels quantized to `n + 1` levels, going to the next level when above `threshold`, and clamped to
+/// `0..=alpha` so premultiplied colors stay premultiplied
+#[inline(always)]
+pub(crate) fn dither_bayer4x4(src: [u8; 4], threshold: u8, n: u16) -> [u8; 4] {
+    let scale = dither_level_scale(n) as u32;
+    let [c0, c1, c2] = [src[0], src[1], src[2]].map(|c| {
+        let x = c as u32 * n as u32;
+        let level = x / 255 + (x % 255 > threshold as u32) as u32;
+        (((level * scale + 128) >> 8) as u8).min(src[3])
+    });
+    [c0, c1, c2, src[3]]
+}
+
+/// Color of a black body at `kelvin`, in `0.0..=255.0`, from Tanner Helland's fit of the Planckian locus
+fn blackbody_rgb(kelvin: f32) -> [f32; 3] {
+    let t = kelvin / 100.0;
+    let r = match t <= 66.0 {
+        true => 255.0,
+        false => 329.698_73 * (t - 60.0).powf(-0.133_204_76),
+    };
+    let g = match t <= 66.0 {
+        true => 99.470_8 * t.ln() - 161.119_57,
+        false => 288.122_17 * (t - 60.0).powf(-0.075_514_85),
+    };
+    let b = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.517_73 * (t - 10.0).ln() - 305.044_8
+    };
+    [r, g, b].map(|c| c.clamp(0.0, 255.0))
+}
+
+/// R, G and B scales of a display showing `kelvin` instead of 6500K, with 255 for alpha, to `unorm_mult` pixels by
+///
+/// `kelvin` is clamped to `1000.0..=10000.0`, 6500K gives `[255; 4]`.
+pub(crate) fn color_temperature_tint(kelvin: f32) -> [u8; 4] {
+    let rgb = blackbody_rgb(kelvin.clamp(1000.0, 10000.0));
+    let white = blackbody_rgb(6500.0);
+    let [r, g, b] = core::array::from_fn(|i| (rgb[i] / white[i] * 255.0).round().min(255.0) as u8);
+    [r, g, b, 255]
+}
+
+/// sRGB encoded channel to linear `0.0..=1.0`, the reference for the [`srgb_to_linear_slice`] table
+pub fn u8_to_linear_f32(c: u8) -> f32 {
+    egui::ecolor::linear_f32_from_gamma_u8(c)
+}
+
+/// Linear `0.0..=1.0` to sRGB encoded channel, the reference for the [`linear_to_srgb_slice`] table
+pub fn linear_f32_to_u8(l: f32) -> u8 {
+    egui::ecolor::gamma_u8_from_linear_f32(l)
+}
+
+/// `round(u8_to_linear_f32(c) * 255)`
+#[rustfmt::skip]
+static SRGB_TO_LINEAR: [u8; 256] = [
+    0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+    1, 1, 2, 2, 2, 2, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3,
+    4, 4, 4, 4, 4, 5, 5, 5, 5, 6, 6, 6, 6, 7, 7, 7,
+    8, 8, 8, 8, 9, 9, 9, 10, 10, 10, 11, 11, 12, 12, 12, 13,
+    13, 13, 14, 14, 15, 15, 16, 16, 17, 17, 17, 18, 18, 19, 19, 20,
+    20, 21, 22, 22, 23, 23, 24, 24, 25, 25, 26, 27, 27, 28, 29, 29,
+    30, 30, 31, 32, 32, 33, 34, 35, 35, 36, 37, 37, 38, 39, 40, 41,
+    41, 42, 43, 44, 45, 45, 46, 47, 48, 49, 50, 51, 51, 52, 53, 54,
+    55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70,
+    71, 72, 73, 74, 76, 77, 78, 79, 80, 81, 82, 84, 85, 86, 87, 88,
+    90, 91, 92, 93, 95, 96, 97, 99, 100, 101, 103, 104, 105, 107, 108, 109,
+    111, 112, 114, 115, 116, 118, 119, 121, 122, 124, 125, 127, 128, 130, 131, 133,
+    134, 136, 138, 139, 141, 142, 144, 146, 147, 149, 151, 152, 154, 156, 157, 159,
+    161, 163, 164, 166, 168, 170, 171, 173, 175, 177, 179, 181, 183, 184, 186, 188,
+    190, 192, 194, 196, 198, 200, 202, 204, 206, 208, 210, 212, 214, 216, 218, 220,
+    222, 224, 226, 229, 231, 233, 235, 237, 239, 242, 244, 246, 248, 250, 253, 255,
+];
+
+/// `linear_f32_to_u8(c / 255)`
+#[rustfmt::skip]
+static LINEAR_TO_SRGB: [u8; 256] = [
+    0, 13, 22, 28, 34, 38, 42, 46, 50, 53, 56, 59, 61, 64, 66, 69,
+    71, 73, 75, 77, 79, 81, 83, 85, 86, 88, 90, 92, 93, 95, 96, 98,
+    99, 101, 102, 104, 105, 106, 108, 109, 110, 112, 113, 114, 115, 117, 118, 119,
+    120, 121, 122, 124, 125, 126, 127, 128, 129, 130, 131, 132, 133, 134, 135, 136,
+    137, 138, 139, 140, 141, 142, 143, 144, 145, 146, 147, 148, 148, 149, 150, 151,
+    152, 153, 154, 155, 155, 156, 157, 158, 159, 159, 160, 161, 162, 163, 163, 164,
+    165, 166, 167, 167, 168, 169, 170, 170, 171, 172, 173, 173, 174, 175, 175, 176,
+    177, 178, 178, 179, 180, 180, 181, 182, 182, 183, 184, 185, 185, 186, 187, 187,
+    188, 189, 189, 190, 190, 191, 192, 192, 193, 194, 194, 195, 196, 196, 197, 197,
+    198, 199, 199, 200, 200, 201, 202, 202, 203, 203, 204, 205, 205, 206, 206, 207,
+    208, 208, 209, 209, 210, 210, 211, 212, 212, 213, 213, 214, 214, 215, 215, 216,
+    216, 217, 218, 218, 219, 219, 220, 220, 221, 221, 222, 222, 223, 223, 224, 224,
+    225, 226, 226, 227, 227, 228, 228, 229, 229, 230, 230, 231, 231, 232, 232, 233,
+    233, 234, 234, 235, 235, 236, 236, 237, 237, 238, 238, 238, 239, 239, 240, 240,
+    241, 241, 242, 242, 243, 243, 244, 244, 245, 245, 246, 246, 246, 247, 247, 248,
+    248, 249, 249, 250, 250, 251, 251, 251, 252, 252, 253, 253, 254, 254, 255, 255,
+];
+
+/// Converts the color channels from sRGB to linear, using SIMD table lookups when available
+///
+/// Alpha is kept as is. The result has 8 bits per channel, so dark colors don't survive a round trip.
+pub fn srgb_to_linear_slice(pixels: &mut [[u8; 4]]) {
+    crate::dispatch_simd_impl!(|simd_impl| simd_impl.lut_rgb_slice(pixels, &SRGB_TO_LINEAR));
+}
+
+/// Converts the color channels from linear to sRGB, see [`srgb_to_linear_slice`]
+pub fn linear_to_srgb_slice(pixels: &mut [[u8; 4]]) {
+    crate::dispatch_simd_impl!(|simd_impl| simd_impl.lut_rgb_slice(pixels, &LINEAR_TO_SRGB));
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    /// Every alpha, with a spread of color channels, in premultiplied and non premultiplied forms
+    fn colors() -> Vec<[u8; 4]> {
+        let mut state = 0x2545_f491_u32;
+        let mut next = move || {
+            // xorshift32
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state.to_le_bytes()
+        };
+        (0..=255u8)
+            .flat_map(|a| {
+                let [r, g, b, _] = next();
+                let [pr, pg, pb] = [r, g, b].map(|c| (c as u16 * a as u16 / 255) as u8);
+                [
+                    [r, g, b, a],
+                    [pr, pg, pb, a],
+                    [0, 0, 0, a],
+                    [255, 255, 255, a],
+                ]
+            })
+            .collect()
+    }
+
+    /// Checks that both implementations give the exact same output for every blend operation
+    pub(crate) fn assert_same_output(
+        actual_impl: impl SelectedImpl,
+        expected_impl: impl SelectedImpl,
+    ) {
+        let colors = colors();
+        let mut dst: Vec<[u8; 4]> = colors.iter().rev().copied().collect();
+        // Odd, so the scalar tail is covered too
+        dst.pop();
+        let src = &colors[..dst.len()];
+
+        for &a in &colors {
+            for &b in &colors[..64] {
+                assert_eq!(
+                    actual_impl.egui_blend_u8(a, b),
+                    expected_impl.egui_blend_u8(a, b)
+                );
+                assert_eq!(
+                    actual_impl.unorm_mult4x4(a, b),
+                    expected_impl.unorm_mult4x4(a, b)
+                );
+            }
+        }
+
+        let dst16: Vec<[u16; 4]> = dst
+            .iter()
+            .zip(src)
+            .map(|(d, s)| core::array::from_fn(|i| d[i] as u16 * 256 + s[i] as u16))
+            .collect();
+        let mut expected = dst16.clone();
+        let mut actual = dst16.clone();
+        expected_impl.egui_blend_u16_slice(src, &mut expected);
+        actual_impl.egui_blend_u16_slice(src, &mut actual);
+        assert_eq!(actual, expected);
+
+        for (i, corners) in colors.chunks_exact(16).enumerate() {
+            let corners: [[[u8; 4]; 4]; 4] =
+                core::array::from_fn(|c| core::array::from_fn(|p| corners[c * 4 + p]));
+            let fx = core::array::from_fn(|p| ((i * 4 + p) as u32 * 7) % (BILINEAR_ONE + 1));
+            let fy = core::array::from_fn(|p| ((i * 4 + p) as u32 * 13) % (BILINEAR_ONE + 1));
+            assert_eq!(
+                actual_impl.bilinear_u8x4_4x(&corners, fx, fy),
+                expected_impl.bilinear_u8x4_4x(&corners, fx, fy)
+            );
+        }
+
+        let mut expected = colors.clone();
+        let mut actual = colors.clone();
+        expected_impl.swizzle_rgba_bgra_slice(&mut expected);
+        actual_impl.swizzle_rgba_bgra_slice(&mut actual);
+        assert_eq!(actual, expected);
+
+        for lut in [&SRGB_TO_LINEAR, &LINEAR_TO_SRGB] {
+            let mut expected = colors.clone();
+            let mut actual = colors.clone();
+            expected_impl.lut_rgb_slice(&mut expected, lut);
+            actual_impl.lut_rgb_slice(&mut actual, lut);
+            assert_eq!(actual, expected);
+        }
+
+        for weights in [GRAYSCALE_WEIGHTS_RGB, [29, 150, 77], [256, 0, 0]] {
+            let mut expected = colors.clone();
+            let mut actual = colors.clone();
+            expected_impl.grayscale_slice(&mut expected, weights);
+            actual_impl.grayscale_slice(&mut actual, weights);
+            assert_eq!(actual, expected);
+        }
+
+        for mode in ColorBlindMode::ALL {
+            let Some(matrix) = mode.matrix() else {
+                continue;
+            };
+            let mut expected = colors.clone();
+            let mut actual = colors.clone();
+            expected_impl.color_matrix_slice(&mut expected, &matrix);
+            actual_impl.color_matrix_slice(&mut actual, &matrix);
+            assert_eq!(actual, expected, "{mode:?}");
+        }
+
+        for changed in [0, 3, 4, 7, 8, 31, 32, 33, dst.len() - 1] {
+            let mut b = src.to_vec();
+            for pixel in &mut b[changed..] {
+                pixel[changed % 4] ^= 1;
+            }
+            assert_eq!(
+                actual_impl.first_changed_pixel(src, &b),
+                expected_impl.first_changed_pixel(src, &b),
+            );
+            assert_eq!(actual_impl.first_changed_pixel(src, &b), changed);
+        }
+        assert_eq!(actual_impl.first_changed_pixel(src, src), src.len());
+
+        for nonzero in [0, 3, 4, 7, 8, 31, 32, 33, 63] {
+            let mut pixels = [[0u8; 4]; 64];
+            assert!(!actual_impl.any_nonzero_pixel(&pixels));
+            pixels[nonzero][nonzero % 4] = 1;
+            assert!(actual_impl.any_nonzero_pixel(&pixels), "{nonzero}");
+            assert!(
+                actual_impl.any_nonzero_pixel(&pixels[nonzero..]),
+                "{nonzero}"
+            );
+            assert!(
+                !actual_impl.any_nonzero_pixel(&pixels[..nonzero]),
+                "{nonzero}"
+            );
+        }
+
+        for (x, y, levels) in [(0, 0, 2), (1, 2, 4), (2, 1, 16), (7, 3, 7), (3, 5, 255)] {
+            let mut expected = colors.clone();
+            let mut actual = colors.clone();
+            expected_impl.dither_bayer4x4_slice(&mut expected, x, y, levels);
+            actual_impl.dither_bayer4x4_slice(&mut actual, x, y, levels);
+            assert_eq!(actual, expected, "{x} {y} {levels}");
+        }
+
+        let mut expected = colors.clone();
+        let mut actual = colors.clone();
+        expected_impl.premultiply_alpha_slice(&mut expected);
+        actual_impl.premultiply_alpha_slice(&mut actual);
+        assert_eq!(actual, expected);
+
+        // Every length up to a few SIMD widths, for the tails
+        for len in 0..=dst.len().min(19) {
+            let mut expected = dst[..len].to_vec();
+            let mut actual = dst[..len].to_vec();
+            expected_impl.egui_blend_u8_slice(&src[..len], &mut expected);
+            actual_impl.egui_blend_u8_slice(&src[..len], &mut actual);
+            assert_eq!(actual, expected, "len {len}");
+
+            let tint = colors[len];
+            let mut expected = dst[..len].to_vec();
+            let mut actual = dst[..len].to_vec();
+            expected_impl.egui_blend_u8_slice_tinted(&src[..len], tint, &mut expected);
+            actual_impl.egui_blend_u8_slice_tinted(&src[..len], tint, &mut actual);
+            assert_eq!(actual, expected, "len {len}, tint {tint:?}");
+        }
+        let mut expected = dst.clone();
+        let mut actual = dst.clone();
+        expected_impl.egui_blend_u8_slice(src, &mut expected);
+        actual_impl.egui_blend_u8_slice(src, &mut actual);
+        assert_eq!(actual, expected);
+
+        // Rows of 5 pixels, 7 apart in `src` and 9 in `dst`, the pixels between the `dst` rows are left as is
+        let rows = src.len().min(dst.len()) / 9;
+        let mut expected = dst.clone();
+        let mut actual = dst.clone();
+        for y in 0..rows {
+            expected_impl.egui_blend_u8_slice(&src[y * 7..][..5], &mut expected[y * 9..][..5]);
+        }
+        actual_impl.egui_blend_u8_strided(src, 7, &mut actual, 9, 5, rows);
+        assert_eq!(actual, expected);
+
+        for &tint in &colors[..64] {
+            let mut expected = dst.clone();
+            let mut actual = dst.clone();
+            expected_impl.egui_blend_u8_slice_tinted(src, tint, &mut expected);
+            actual_impl.egui_blend_u8_slice_tinted(src, tint, &mut actual);
+            assert_eq!(actual, expected);
+        }
+
+        for &one_src in &colors {
+            let mut expected = dst.clone();
+            let mut actual = dst.clone();
+            expected_impl.egui_blend_u8_slice_one_src(one_src, &mut expected);
+            actual_impl.egui_blend_u8_slice_one_src(one_src, &mut actual);
+            assert_eq!(actual, expected);
+        }
+
+        for &one_src in &colors[..64] {
+            let mut expected = dst.clone();
+            let mut actual = dst.clone();
+            let mut tints = colors.iter().copied().cycle();
+            expected_impl.egui_blend_u8_slice_one_src_tinted_fn(
+                one_src,
+                || tints.next().unwrap(),
+                &mut expected,
+            );
+            let mut tints = colors.iter().copied().cycle();
+            actual_impl.egui_blend_u8_slice_one_src_tinted_fn(
+                one_src,
+                || tints.next().unwrap(),
+                &mut actual,
+            );
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn color_temperature_tints() {
+        assert_eq!(color_temperature_tint(6500.0), [255; 4]);
+        // Warmer is less blue, colder less red
+        let [r, g, b, a] = color_temperature_tint(3000.0);
+        assert!(r == 255 && g < 255 && b < g && a == 255);
+        let [r, g, b, _] = color_temperature_tint(10000.0);
+        assert!(r < g && g < b && b == 255);
+        assert_eq!(
+            color_temperature_tint(500.0),
+            color_temperature_tint(1000.0)
+        );
+    }
+
+    #[test]
+    fn premultiply_round_trip() {
+        for c in 0..=255u8 {
+            for a in 0..=255u8 {
+                let expected = |c: u8| (c as f32 * a as f32 / 255.0).round() as u8;
+                let premultiplied = premultiply_alpha([c, 255 - c, 0, a]);
+                assert_eq!(premultiplied, [expected(c), expected(255 - c), 0, a]);
+                assert_eq!(
+                    premultiply_alpha(unpremultiply_alpha(premultiplied)),
+                    premultiplied
+                );
+            }
+        }
+        assert_eq!(unpremultiply_alpha([0, 0, 0, 0]), [0; 4]);
+        assert_eq!(unpremultiply_alpha([64, 128, 0, 128]), [128, 255, 0, 128]);
+    }
+
+    #[test]
+    fn srgb_tables_match_reference() {
+        for c in 0..=255u8 {
+            let linear = u8_to_linear_f32(c);
+            assert_eq!(SRGB_TO_LINEAR[c as usize], (linear * 255.0 + 0.5) as u8);
+            assert_eq!(
+                LINEAR_TO_SRGB[c as usize],
+                linear_f32_to_u8(c as f32 / 255.0)
+            );
+        }
+
+        let mut pixels = [[0, 128, 255, 77], [10, 200, 50, 255]];
+        srgb_to_linear_slice(&mut pixels);
+        assert_eq!(pixels, [[0, 55, 255, 77], [1, 147, 8, 255]]);
+        linear_to_srgb_slice(&mut pixels);
+        assert_eq!(pixels, [[0, 128, 255, 77], [13, 200, 50, 255]]);
+    }
+
+    #[test]
+    fn selected_matches_generic() {
+        crate::dispatch_simd_impl!(|simd_impl| assert_same_output(simd_impl, GenericImpl));
+    }
+}
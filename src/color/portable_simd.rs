@@ -0,0 +1,249 @@
+use core::simd::{Select, mask8x32, num::SimdUint, simd_swizzle, u8x32, u16x32, u32x32};
+
+use crate::{SelectedImpl, color::GenericImpl};
+
+/// Pixels per `u8x32`
+const LANES: usize = 8;
+
+/// Index of the alpha channel of each pixel, for every channel
+const ALPHA_INDEX: [usize; 4 * LANES] = {
+    let mut index = [0; 4 * LANES];
+    let mut i = 0;
+    while i < index.len() {
+        index[i] = i / 4 * 4 + 3;
+        i += 1;
+    }
+    index
+};
+
+/// Exchanges the first and third channel of each pixel
+const SWIZZLE_INDEX: [usize; 4 * LANES] = {
+    let mut index = [0; 4 * LANES];
+    let mut i = 0;
+    while i < index.len() {
+        index[i] = i / 4 * 4 + [2, 1, 0, 3][i % 4];
+        i += 1;
+    }
+    index
+};
+
+/// 255 in the alpha channel of each pixel, 0 elsewhere
+const ALPHA_ONE: [u16; 4 * LANES] = {
+    let mut one = [0; 4 * LANES];
+    let mut i = 3;
+    while i < one.len() {
+        one[i] = 0xFF;
+        i += 4;
+    }
+    one
+};
+
+/// Architecture independent implementation using `core::simd`
+///
+/// Used on the architectures without a hand written implementation, and as the reference for the
+/// hand written ones in the tests.
+#[derive(Clone, Copy)]
+pub(crate) struct PortableSimdImpl;
+
+impl SelectedImpl for PortableSimdImpl {
+    fn egui_blend_u8_slice(self, src: &[[u8; 4]], dst: &mut [[u8; 4]]) {
+        assert_eq!(src.len(), dst.len());
+
+        let mut src_chunks = src.chunks_exact(LANES);
+        let mut dst_chunks = dst.chunks_exact_mut(LANES);
+        for (src, dst) in (&mut src_chunks).zip(&mut dst_chunks) {
+            store(egui_blend_u8x32(load(src), load(dst)), dst);
+        }
+
+        for (pixel, src) in dst_chunks
+            .into_remainder()
+            .iter_mut()
+            .zip(src_chunks.remainder())
+        {
+            *pixel = self.egui_blend_u8(*src, *pixel);
+        }
+    }
+
+    fn egui_blend_u8_slice_one_src_tinted_fn(
+        self,
+        src: [u8; 4],
+        mut tint_fn: impl FnMut() -> [u8; 4],
+        dst: &mut [[u8; 4]],
+    ) {
+        let src16 = load(&[src; LANES]).cast::<u16>();
+
+        let mut dst_chunks = dst.chunks_exact_mut(LANES);
+        for dst in &mut dst_chunks {
+            let tint = load(&core::array::from_fn::<_, LANES, _>(|_| tint_fn()));
+            let src = unorm_mult_u16x32(src16, tint.cast()).cast();
+            store(egui_blend_u8x32(src, load(dst)), dst);
+        }
+
+        for pixel in dst_chunks.into_remainder() {
+            *pixel = self.egui_blend_u8(self.unorm_mult4x4(tint_fn(), src), *pixel);
+        }
+    }
+
+    fn egui_blend_u8_slice_tinted(self, src: &[[u8; 4]], tint: [u8; 4], dst: &mut [[u8; 4]]) {
+        assert_eq!(src.len(), dst.len());
+
+        let tint16 = load(&[tint; LANES]).cast::<u16>();
+
+        let mut src_chunks = src.chunks_exact(LANES);
+        let mut dst_chunks = dst.chunks_exact_mut(LANES);
+        for (src, dst) in (&mut src_chunks).zip(&mut dst_chunks) {
+            let src = unorm_mult_u16x32(load(src).cast(), tint16).cast();
+            store(egui_blend_u8x32(src, load(dst)), dst);
+        }
+
+        for (pixel, src) in dst_chunks
+            .into_remainder()
+            .iter_mut()
+            .zip(src_chunks.remainder())
+        {
+            *pixel = self.egui_blend_u8(self.unorm_mult4x4(tint, *src), *pixel);
+        }
+    }
+
+    fn egui_blend_u8_slice_one_src(self, src: [u8; 4], dst: &mut [[u8; 4]]) {
+        let src8 = load(&[src; LANES]);
+
+        let mut dst_chunks = dst.chunks_exact_mut(LANES);
+        for dst in &mut dst_chunks {
+            store(egui_blend_u8x32(src8, load(dst)), dst);
+        }
+
+        for pixel in dst_chunks.into_remainder() {
+            *pixel = self.egui_blend_u8(src, *pixel);
+        }
+    }
+
+    fn egui_blend_u16_slice(self, src: &[[u8; 4]], dst: &mut [[u16; 4]]) {
+        assert_eq!(src.len(), dst.len());
+
+        let mut src_chunks = src.chunks_exact(LANES);
+        let mut dst_chunks = dst.chunks_exact_mut(LANES);
+        for (src, dst) in (&mut src_chunks).zip(&mut dst_chunks) {
+            let src = load(src);
+            let src16 = src.cast::<u16>() * u16x32::splat(257);
+            let alpha = simd_swizzle!(src, ALPHA_INDEX).cast::<u32>() * u32x32::splat(257);
+
+            let dst16 = u16x32::from_slice(dst.as_flattened());
+            let dst_alpha = (dst16.cast::<u32>() * alpha + u32x32::splat(0x8000)) >> 16;
+            let res16 = (dst16 - dst_alpha.cast::<u16>()).saturating_add(src16);
+            res16.copy_to_slice(dst.as_flattened_mut());
+        }
+
+        for (pixel, src) in dst_chunks
+            .into_remainder()
+            .iter_mut()
+            .zip(src_chunks.remainder())
+        {
+            *pixel = crate::color::egui_blend_u16(*src, *pixel);
+        }
+    }
+
+    fn swizzle_rgba_bgra_slice(self, pixels: &mut [[u8; 4]]) {
+        let mut chunks = pixels.chunks_exact_mut(LANES);
+        for chunk in &mut chunks {
+            store(simd_swizzle!(load(chunk), SWIZZLE_INDEX), chunk);
+        }
+
+        for pixel in chunks.into_remainder() {
+            *pixel = crate::color::swizzle_rgba_bgra(*pixel);
+        }
+    }
+
+    fn lut_rgb_slice(self, pixels: &mut [[u8; 4]], lut: &[u8; 256]) {
+        let alpha = mask8x32::from_array(core::array::from_fn(|i| i % 4 == 3));
+
+        let mut chunks = pixels.chunks_exact_mut(LANES);
+        for chunk in &mut chunks {
+            let px = load(chunk);
+            let looked_up = u8x32::gather_or_default(lut, px.cast());
+            store(alpha.select(px, looked_up), chunk);
+        }
+
+        GenericImpl.lut_rgb_slice(chunks.into_remainder(), lut);
+    }
+
+    fn premultiply_alpha_slice(self, pixels: &mut [[u8; 4]]) {
+        let alpha_one = u16x32::from_array(ALPHA_ONE);
+
+        let mut chunks = pixels.chunks_exact_mut(LANES);
+        for chunk in &mut chunks {
+            let px = load(chunk);
+            let alpha = simd_swizzle!(px, ALPHA_INDEX).cast::<u16>() | alpha_one;
+            store(unorm_mult_u16x32(px.cast(), alpha).cast(), chunk);
+        }
+
+        for pixel in chunks.into_remainder() {
+            *pixel = crate::color::premultiply_alpha(*pixel);
+        }
+    }
+}
+
+#[inline(always)]
+fn load(pixels: &[[u8; 4]]) -> u8x32 {
+    u8x32::from_slice(pixels.as_flattened())
+}
+
+#[inline(always)]
+fn store(value: u8x32, pixels: &mut [[u8; 4]]) {
+    value.copy_to_slice(pixels.as_flattened_mut());
+}
+
+/// blend fn is (ONE, ONE_MINUS_SRC_ALPHA)
+#[inline(always)]
+fn egui_blend_u8x32(src: u8x32, dst: u8x32) -> u8x32 {
+    let alpha = simd_swizzle!(src, ALPHA_INDEX);
+    let alpha_compl = u16x32::splat(0xFF) - alpha.cast::<u16>();
+    let dst = unorm_mult_u16x32(dst.cast(), alpha_compl).cast::<u8>();
+    dst.saturating_add(src)
+}
+
+/// Same as `unorm_mult`, for each lane
+#[inline(always)]
+fn unorm_mult_u16x32(a: u16x32, b: u16x32) -> u16x32 {
+    let eight = u16x32::splat(8);
+    let x = a * b + u16x32::splat(0x80);
+    (x + (x >> eight)) >> eight
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::{GenericImpl, test::assert_same_output};
+
+    #[test]
+    fn matches_generic() {
+        assert_same_output(PortableSimdImpl, GenericImpl);
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", not(any(miri, feature = "miri"))))]
+    fn reference_for_hand_written() {
+        #[cfg(target_arch = "x86_64")]
+        {
+            use crate::color::{avx2::Avx2Impl, sse2::Sse2Impl, sse41::Sse41Impl};
+
+            assert_same_output(unsafe { Sse2Impl::new() }, PortableSimdImpl);
+            if std::arch::is_x86_feature_detected!("sse4.1") {
+                assert_same_output(unsafe { Sse41Impl::new() }, PortableSimdImpl);
+            }
+            if std::arch::is_x86_feature_detected!("sse4.1")
+                && std::arch::is_x86_feature_detected!("avx2")
+            {
+                assert_same_output(unsafe { Avx2Impl::new() }, PortableSimdImpl);
+            }
+        }
+
+        #[cfg(target_arch = "aarch64")]
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            assert_same_output(
+                unsafe { crate::color::neon::NeonImpl::new() },
+                PortableSimdImpl,
+            );
+        }
+    }
+}
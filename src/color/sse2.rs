@@ -0,0 +1,622 @@
+#![allow(unsafe_code)]
+
+use core::{arch::x86_64::*, ptr::read_unaligned};
+
+use crate::{SelectedImpl, color::BILINEAR_ONE};
+
+#[derive(Clone, Copy)]
+pub(crate) struct Sse2Impl(());
+
+impl Sse2Impl {
+    /// `std::arch::is_x86_feature_detected!("sse2")` MUST be true, it always is on x86_64
+    pub(crate) unsafe fn new() -> Self {
+        Self(())
+    }
+}
+
+impl SelectedImpl for Sse2Impl {
+    #[inline]
+    fn egui_blend_u8_slice(self, src: &[[u8; 4]], dst: &mut [[u8; 4]]) {
+        unsafe { egui_blend_u8_slice(src, dst) }
+    }
+
+    #[inline]
+    fn egui_blend_u8_slice_one_src_tinted_fn(
+        self,
+        src: [u8; 4],
+        tint_fn: impl FnMut() -> [u8; 4],
+        dst: &mut [[u8; 4]],
+    ) {
+        unsafe { egui_blend_u8_slice_one_src_tinted_fn(src, tint_fn, dst) }
+    }
+
+    #[inline]
+    fn egui_blend_u8_slice_tinted(self, src: &[[u8; 4]], tint: [u8; 4], dst: &mut [[u8; 4]]) {
+        unsafe { egui_blend_u8_slice_tinted(src, tint, dst) }
+    }
+
+    #[inline]
+    fn egui_blend_u8_slice_one_src(self, src: [u8; 4], dst: &mut [[u8; 4]]) {
+        unsafe { egui_blend_u8_slice_one_src(src, dst) }
+    }
+
+    #[inline]
+    fn egui_blend_u8(self, src: [u8; 4], dst: [u8; 4]) -> [u8; 4] {
+        unsafe { egui_blend_u8(src, dst) }
+    }
+
+    #[inline]
+    fn egui_blend_u16_slice(self, src: &[[u8; 4]], dst: &mut [[u16; 4]]) {
+        unsafe { egui_blend_u16_slice(src, dst) }
+    }
+
+    #[inline]
+    fn bilinear_u8x4_4x(
+        self,
+        corners: &[[[u8; 4]; 4]; 4],
+        fx: [u32; 4],
+        fy: [u32; 4],
+    ) -> [[u8; 4]; 4] {
+        unsafe { bilinear_u8x4_4x(corners, fx, fy) }
+    }
+
+    #[inline]
+    fn swizzle_rgba_bgra_slice(self, pixels: &mut [[u8; 4]]) {
+        unsafe { swizzle_rgba_bgra_slice(pixels) }
+    }
+
+    #[inline]
+    fn premultiply_alpha_slice(self, pixels: &mut [[u8; 4]]) {
+        unsafe { premultiply_alpha_slice(pixels) }
+    }
+
+    #[inline]
+    fn grayscale_slice(self, pixels: &mut [[u8; 4]], weights: [u16; 3]) {
+        unsafe { grayscale_slice(pixels, weights) }
+    }
+
+    #[inline]
+    fn first_changed_pixel(self, a: &[[u8; 4]], b: &[[u8; 4]]) -> usize {
+        unsafe { first_changed_pixel(a, b) }
+    }
+
+    #[inline]
+    fn any_nonzero_pixel(self, pixels: &[[u8; 4]]) -> bool {
+        unsafe { any_nonzero_pixel(pixels) }
+    }
+
+    #[inline]
+    fn unorm_mult4x4(self, a: [u8; 4], b: [u8; 4]) -> [u8; 4] {
+        unsafe { unorm_mult4x4(a, b) }
+    }
+}
+
+/// dst[i] = blend(src[i], dst[i]), with 16 bits per channel in dst, also used by the SSE4.1 implementation
+/// blend fn is (ONE, ONE_MINUS_SRC_ALPHA)
+#[target_feature(enable = "sse2")]
+pub(crate) fn egui_blend_u16_slice(src: &[[u8; 4]], dst: &mut [[u16; 4]]) {
+    assert_eq!(src.len(), dst.len());
+
+    let mut src_chunks = src.chunks_exact(2);
+    let mut dst_chunks = dst.chunks_exact_mut(2);
+    for (src, dst) in (&mut src_chunks).zip(&mut dst_chunks) {
+        // Load two src pixels
+        let src8 = _mm_cvtsi64_si128(unsafe { read_unaligned(src.as_ptr().cast::<i64>()) });
+        let src16 = unpacklo_epu8_epi16(src8);
+        // src * 257, the 16 bit equivalent of src
+        let src16 = _mm_or_si128(_mm_slli_epi16(src16, 8), src16);
+
+        // Load two dst pixels
+        let dst = dst.as_mut_ptr().cast::<__m128i>();
+        let dst16 = unsafe { _mm_loadu_si128(dst) };
+
+        // Broadcast alpha within each pixel's 4 lanes
+        let a_broadcast_lo = _mm_shufflelo_epi16(src16, 0b11111111);
+        let a_broadcast = _mm_shufflehi_epi16(a_broadcast_lo, 0b11111111);
+
+        // round(dst * alpha / 65536)
+        let hi = _mm_mulhi_epu16(dst16, a_broadcast);
+        let lo = _mm_mullo_epi16(dst16, a_broadcast);
+        let dst_alpha = _mm_add_epi16(hi, _mm_srli_epi16(lo, 15));
+
+        // (dst - dst * alpha).saturating_add(src)
+        let res16 = _mm_adds_epu16(_mm_sub_epi16(dst16, dst_alpha), src16);
+        unsafe { _mm_storeu_si128(dst, res16) };
+    }
+
+    for (pixel, src) in dst_chunks
+        .into_remainder()
+        .iter_mut()
+        .zip(src_chunks.remainder())
+    {
+        *pixel = crate::color::egui_blend_u16(*src, *pixel);
+    }
+}
+
+/// out[i] = bilinear_u8x4(corners[0][i], corners[1][i], corners[2][i], corners[3][i], fx[i], fy[i]), also used by the
+/// SSE4.1 and AVX2 implementations
+#[inline]
+#[target_feature(enable = "sse2")]
+pub(crate) fn bilinear_u8x4_4x(
+    corners: &[[[u8; 4]; 4]; 4],
+    fx: [u32; 4],
+    fy: [u32; 4],
+) -> [[u8; 4]; 4] {
+    let zero = _mm_setzero_si128();
+    let one = BILINEAR_ONE as i32;
+    // [1 - f, f] in each pair of i16 lanes
+    let weights = |f: u32| _mm_set1_epi32(((f as i32) << 16) | (one - f as i32));
+    let load = |i: usize| unsafe { _mm_loadu_si128(corners[i].as_ptr().cast::<__m128i>()) };
+
+    // c0 * (1 - fx) + c1 * fx, as i16: [rgba of pixel 0, rgba of pixel 1], [rgba of pixel 2, rgba of pixel 3]
+    let lerp_x = |c0: __m128i, c1: __m128i| {
+        // [c0.r, c1.r, c0.g, c1.g, ...]
+        let lo = _mm_unpacklo_epi8(c0, c1);
+        let hi = _mm_unpackhi_epi8(c0, c1);
+        let h0 = _mm_madd_epi16(_mm_unpacklo_epi8(lo, zero), weights(fx[0]));
+        let h1 = _mm_madd_epi16(_mm_unpackhi_epi8(lo, zero), weights(fx[1]));
+        let h2 = _mm_madd_epi16(_mm_unpacklo_epi8(hi, zero), weights(fx[2]));
+        let h3 = _mm_madd_epi16(_mm_unpackhi_epi8(hi, zero), weights(fx[3]));
+        [_mm_packs_epi32(h0, h1), _mm_packs_epi32(h2, h3)]
+    };
+    let top = lerp_x(load(0), load(1));
+    let bottom = lerp_x(load(2), load(3));
+
+    // (top * (1 - fy) + bottom * fy + round) / (one * one)
+    let round = _mm_set1_epi32(one * one / 2);
+    const SHIFT: i32 = (BILINEAR_ONE * BILINEAR_ONE).trailing_zeros() as i32;
+    let lerp_y = |top: __m128i, bottom: __m128i, fy0: u32, fy1: u32| {
+        // [top.r, bottom.r, top.g, bottom.g, ...]
+        let v0 = _mm_madd_epi16(_mm_unpacklo_epi16(top, bottom), weights(fy0));
+        let v1 = _mm_madd_epi16(_mm_unpackhi_epi16(top, bottom), weights(fy1));
+        let v0 = _mm_srli_epi32::<SHIFT>(_mm_add_epi32(v0, round));
+        let v1 = _mm_srli_epi32::<SHIFT>(_mm_add_epi32(v1, round));
+        _mm_packs_epi32(v0, v1)
+    };
+    let v01 = lerp_y(top[0], bottom[0], fy[0], fy[1]);
+    let v23 = lerp_y(top[1], bottom[1], fy[2], fy[3]);
+
+    let mut out = [[0u8; 4]; 4];
+    unsafe { _mm_storeu_si128(out.as_mut_ptr().cast(), _mm_packus_epi16(v01, v23)) };
+    out
+}
+
+/// pixels[i] = swizzle_rgba_bgra(pixels[i])
+#[target_feature(enable = "sse2")]
+fn swizzle_rgba_bgra_slice(pixels: &mut [[u8; 4]]) {
+    let green_alpha = _mm_set1_epi32(0xFF00FF00u32 as i32);
+    let red_blue = _mm_set1_epi32(0x000000FF);
+
+    let mut chunks = pixels.chunks_exact_mut(4);
+    for chunk in &mut chunks {
+        // Load four pixels
+        let p = chunk.as_mut_ptr().cast::<__m128i>();
+        let px = unsafe { _mm_loadu_si128(p) };
+
+        // Exchange the first and third byte of each pixel, no byte shuffle without SSSE3
+        let ga = _mm_and_si128(px, green_alpha);
+        let r = _mm_and_si128(px, red_blue);
+        let b = _mm_and_si128(_mm_srli_epi32(px, 16), red_blue);
+        let res = _mm_or_si128(_mm_or_si128(ga, _mm_slli_epi32(r, 16)), b);
+
+        unsafe { _mm_storeu_si128(p, res) };
+    }
+
+    for pixel in chunks.into_remainder() {
+        *pixel = crate::color::swizzle_rgba_bgra(*pixel);
+    }
+}
+
+/// pixels[i] = grayscale(pixels[i], weights), also used by the SSE4.1 and AVX2 implementations
+#[target_feature(enable = "sse2")]
+pub(crate) fn grayscale_slice(pixels: &mut [[u8; 4]], weights: [u16; 3]) {
+    let even_bytes = _mm_set1_epi32(0x00FF00FF);
+    let alpha_mask = _mm_set1_epi32(0xFF000000u32 as i32);
+    // Weights of the channels 0 and 2, then 1 and 3, in the low and high 16 bits of each pixel
+    let weights02 = _mm_set1_epi32(((weights[2] as i32) << 16) | weights[0] as i32);
+    let weights13 = _mm_set1_epi32(weights[1] as i32);
+
+    let mut chunks = pixels.chunks_exact_mut(4);
+    for chunk in &mut chunks {
+        // Load four pixels
+        let p = chunk.as_mut_ptr().cast::<__m128i>();
+        let px = unsafe { _mm_loadu_si128(p) };
+
+        let c02 = _mm_and_si128(px, even_bytes);
+        let c13 = _mm_and_si128(_mm_srli_epi32(px, 8), even_bytes);
+        let y = _mm_add_epi32(
+            _mm_madd_epi16(c02, weights02),
+            _mm_madd_epi16(c13, weights13),
+        );
+        let y = _mm_srli_epi32(y, 8);
+
+        // y in the three color channels
+        let gray = _mm_or_si128(_mm_or_si128(y, _mm_slli_epi32(y, 8)), _mm_slli_epi32(y, 16));
+        let res = _mm_or_si128(gray, _mm_and_si128(px, alpha_mask));
+        unsafe { _mm_storeu_si128(p, res) };
+    }
+
+    for pixel in chunks.into_remainder() {
+        *pixel = crate::color::grayscale(*pixel, weights);
+    }
+}
+
+/// Index of the first pixel that differs between `a` and `b`, also used by the SSE4.1 and AVX2 implementations
+#[target_feature(enable = "sse2")]
+pub(crate) fn first_changed_pixel(a: &[[u8; 4]], b: &[[u8; 4]]) -> usize {
+    let len = a.len().min(b.len());
+    let (a_chunks, b_chunks) = (a[..len].chunks_exact(4), b[..len].chunks_exact(4));
+    for (i, (a_chunk, b_chunk)) in a_chunks.zip(b_chunks).enumerate() {
+        let a_px = unsafe { _mm_loadu_si128(a_chunk.as_ptr().cast::<__m128i>()) };
+        let b_px = unsafe { _mm_loadu_si128(b_chunk.as_ptr().cast::<__m128i>()) };
+        // One bit per equal byte
+        let equal = _mm_movemask_epi8(_mm_cmpeq_epi8(a_px, b_px)) as u32;
+        if equal != 0xFFFF {
+            return i * 4 + (!equal).trailing_zeros() as usize / 4;
+        }
+    }
+
+    let start = len / 4 * 4;
+    a[start..len]
+        .iter()
+        .zip(&b[start..len])
+        .position(|(a, b)| a != b)
+        .map_or(len, |i| start + i)
+}
+
+/// Whether a pixel of `pixels` has a non zero channel, also used by the SSE4.1 and AVX2 implementations
+#[target_feature(enable = "sse2")]
+pub(crate) fn any_nonzero_pixel(pixels: &[[u8; 4]]) -> bool {
+    let zero = _mm_setzero_si128();
+    let mut chunks = pixels.chunks_exact(4);
+    for chunk in &mut chunks {
+        let px = unsafe { _mm_loadu_si128(chunk.as_ptr().cast::<__m128i>()) };
+        // One bit per zero byte
+        if _mm_movemask_epi8(_mm_cmpeq_epi8(px, zero)) != 0xFFFF {
+            return true;
+        }
+    }
+    chunks
+        .remainder()
+        .iter()
+        .any(|px| u32::from_le_bytes(*px) > 0)
+}
+
+/// pixels[i] = premultiply_alpha(pixels[i]), also used by the SSE4.1 and AVX2 implementations
+#[target_feature(enable = "sse2")]
+pub(crate) fn premultiply_alpha_slice(pixels: &mut [[u8; 4]]) {
+    let e1 = _mm_set1_epi16(0x0080);
+    let e2 = _mm_set1_epi16(0x0101);
+    // 255 in the alpha lanes, so alpha is multiplied by 1
+    let alpha_one = _mm_set1_epi64x(0x00FF_0000_0000_0000);
+
+    let premultiply_two_u16x4 = |px16: __m128i| {
+        // Broadcast alpha within each pixel's 4 lanes
+        let a_broadcast_lo = _mm_shufflelo_epi16(px16, 0b11111111);
+        let a_broadcast = _mm_shufflehi_epi16(a_broadcast_lo, 0b11111111);
+        let alpha = _mm_or_si128(a_broadcast, alpha_one);
+
+        // px * alpha + 0x0080, then the rounded / 255
+        let res16 = _mm_add_epi16(_mm_mullo_epi16(px16, alpha), e1);
+        _mm_mulhi_epu16(res16, e2)
+    };
+
+    let mut chunks = pixels.chunks_exact_mut(4);
+    for chunk in &mut chunks {
+        // Load four pixels
+        let p = chunk.as_mut_ptr().cast::<__m128i>();
+        let px8 = unsafe { _mm_loadu_si128(p) };
+
+        let lo = premultiply_two_u16x4(unpacklo_epu8_epi16(px8));
+        let hi = premultiply_two_u16x4(_mm_unpackhi_epi8(px8, _mm_setzero_si128()));
+
+        unsafe { _mm_storeu_si128(p, _mm_packus_epi16(lo, hi)) };
+    }
+
+    for pixel in chunks.into_remainder() {
+        *pixel = crate::color::premultiply_alpha(*pixel);
+    }
+}
+
+/// blend fn is (ONE, ONE_MINUS_SRC_ALPHA)
+#[target_feature(enable = "sse2")]
+fn egui_blend_u8(src: [u8; 4], dst: [u8; 4]) -> [u8; 4] {
+    let alpha = src[3];
+    if alpha == 255 {
+        return src;
+    }
+
+    let alpha_compl = _mm_set1_epi16(0xFFi16 ^ (alpha as i16));
+    let e1 = _mm_set1_epi16(0x0080);
+    let mut dst = _mm_cvtsi32_si128(i32::from_le_bytes(dst)); // Load dst into element a
+    dst = unpacklo_epu8_epi16(dst); // [0,0,0,0,0,0,0,rgba] -> [0,0,0,0,r,g,b,a]
+
+    // dst * alpha_compl + 0x0080
+    dst = _mm_add_epi16(_mm_mullo_epi16(dst, alpha_compl), e1);
+
+    // ((x >> 8) + x) >> 8
+    dst = _mm_add_epi16(dst, _mm_srli_epi16(dst, 8));
+    dst = _mm_srli_epi16(dst, 8);
+
+    // Pack to back to u8
+    let dst = _mm_packus_epi16(dst, _mm_setzero_si128());
+
+    let src = _mm_cvtsi32_si128(i32::from_le_bytes(src)); // Load src into element a
+    let dst = _mm_adds_epu8(dst, src); // dst.saturating_add(src)
+
+    i32::to_le_bytes(_mm_cvtsi128_si32(dst)) // Return first element of dst
+}
+
+// https://www.lgfae.com/posts/2025-09-01-AlphaBlendWithSIMD.html
+/// blend fn is (ONE, ONE_MINUS_SRC_ALPHA)
+#[target_feature(enable = "sse2")]
+fn egui_blend_u8_slice_one_src(src: [u8; 4], dst: &mut [[u8; 4]]) {
+    let n = dst.len();
+    if n == 0 {
+        return;
+    }
+
+    let a = src[3];
+
+    let src32 = u32::from_le_bytes(src);
+    let src64 = (src32 as u64) | ((src32 as u64) << 32);
+    let src8 = _mm_set1_epi64x(src64 as i64);
+
+    let alpha = src[3] as i32;
+    let alpha_compl = 0xFF ^ alpha;
+
+    let e1 = _mm_set1_epi16(0x0080);
+    let e2 = _mm_set1_epi16(0x0101);
+
+    // (255 - a) in all u16 lanes
+    let simd_alpha_compl = _mm_set1_epi16(alpha_compl as i16);
+
+    let mut i = 0;
+    if a == 0 {
+        // Only need to saturating_add src to dst. We can do 4 at a time in this case.
+        while i + 3 < n {
+            let p = unsafe { dst.as_mut_ptr().add(i) } as *mut __m128i;
+            let d128 = unsafe { _mm_loadu_si128(p) };
+            let out = _mm_adds_epu8(d128, src8);
+            unsafe { _mm_storeu_si128(p, out) };
+            i += 4;
+        }
+    } else {
+        while i + 1 < n {
+            let dst = unsafe { dst.as_mut_ptr().add(i) }.cast::<u64>();
+            // Load two dst pixels
+            let d8 = _mm_cvtsi64_si128(unsafe { read_unaligned(dst) } as i64);
+            // [0,0,0,0,rg,ba,rg,ba] -> [r,g,b,a,r,g,b,a]
+            let dst16 = unpacklo_epu8_epi16(d8);
+
+            // dst * alpha_compl + 0x0080008000800080
+            let res16 = _mm_add_epi16(_mm_mullo_epi16(dst16, simd_alpha_compl), e1);
+
+            // This mulhi is equivalent to the ((x >> 8) + x) >> 8 operation. (can you see why?)
+            let res16 = _mm_mulhi_epu16(res16, e2);
+            let mut dst8 = _mm_packus_epi16(res16, res16); // Pack back to u8
+
+            // dst.saturating_add(src)
+            dst8 = _mm_adds_epu8(dst8, src8);
+
+            let lo64 = _mm_cvtsi128_si64(dst8) as u64;
+            unsafe { core::ptr::write_unaligned(dst, lo64) };
+            i += 2;
+        }
+    }
+
+    while i < n {
+        dst[i] = egui_blend_u8(src, dst[i]);
+        i += 1;
+    }
+}
+
+// https://www.lgfae.com/posts/2025-09-01-AlphaBlendWithSIMD.html
+/// dst[i] = blend(src[i], dst[i]) // As unorm
+/// blend fn is (ONE, ONE_MINUS_SRC_ALPHA)
+#[target_feature(enable = "sse2")]
+fn egui_blend_u8_slice(src: &[[u8; 4]], dst: &mut [[u8; 4]]) {
+    assert_eq!(src.len(), dst.len());
+
+    let n = dst.len();
+    if n == 0 {
+        return;
+    }
+
+    let mut i = 0;
+    while i + 1 < n {
+        // Load two src pixels
+        let src = unsafe { src.as_ptr().add(i) }.cast::<u64>();
+        let src8 = _mm_cvtsi64_si128(unsafe { read_unaligned(src) } as i64);
+        // [0,0,0,0,rg,ba,rg,ba] -> [r,g,b,a,r,g,b,a]
+        let src16 = unpacklo_epu8_epi16(src8);
+
+        // Load two dst pixels
+        let dst = unsafe { dst.as_mut_ptr().add(i) }.cast::<u64>();
+        let d8 = _mm_cvtsi64_si128(unsafe { read_unaligned(dst) } as i64);
+        let dst16 = unpacklo_epu8_epi16(d8);
+
+        let dst8 = egui_blend_two_u16x4(src8, src16, dst16);
+
+        let lo64 = _mm_cvtsi128_si64(dst8) as u64;
+        unsafe { core::ptr::write_unaligned(dst, lo64) };
+        i += 2;
+    }
+
+    if i < n {
+        dst[i] = egui_blend_u8(src[i], dst[i]);
+    }
+}
+
+// https://www.lgfae.com/posts/2025-09-01-AlphaBlendWithSIMD.html
+/// dst[i] = blend(src[i] * vert, dst[i]) // As unorm
+/// blend fn is (ONE, ONE_MINUS_SRC_ALPHA)
+#[target_feature(enable = "sse2")]
+fn egui_blend_u8_slice_tinted(src: &[[u8; 4]], tint: [u8; 4], dst: &mut [[u8; 4]]) {
+    assert_eq!(src.len(), dst.len());
+    let n = dst.len();
+    if n == 0 {
+        return;
+    }
+
+    let e1 = _mm_set1_epi16(0x0080);
+    let e2 = _mm_set1_epi16(0x0101);
+
+    let t32 = _mm_set1_epi32(i32::from_le_bytes(tint));
+    let tint16 = unpacklo_epu8_epi16(t32);
+
+    let mut i = 0usize;
+    while i + 1 < n {
+        // Load two src pixels
+        let src = unsafe { src.as_ptr().add(i) }.cast::<u64>();
+        let src8 = _mm_cvtsi64_si128(unsafe { read_unaligned(src) } as i64);
+        // [0,0,0,0,rg,ba,rg,ba] -> [r,g,b,a,r,g,b,a]
+        let src16 = unpacklo_epu8_epi16(src8);
+
+        // Load two dst pixels
+        let dst = unsafe { dst.as_mut_ptr().add(i) }.cast::<u64>();
+        let dst8 = _mm_cvtsi64_si128(unsafe { read_unaligned(dst) } as i64);
+        let dst16 = unpacklo_epu8_epi16(dst8);
+
+        // src_tinted = (src16 * vert16 + 128) * 257 >> 16  (rounded /255)
+        let tint_mul = _mm_mullo_epi16(src16, tint16);
+        let tint_rounded = _mm_add_epi16(tint_mul, e1);
+        let src_tinted16 = _mm_mulhi_epu16(tint_rounded, e2);
+        let src_tinted8 = _mm_packus_epi16(src_tinted16, src_tinted16);
+
+        let dst8 = egui_blend_two_u16x4(src_tinted8, src_tinted16, dst16);
+
+        let lo64 = _mm_cvtsi128_si64(dst8) as u64;
+        unsafe { core::ptr::write_unaligned(dst, lo64) };
+        i += 2;
+    }
+
+    // Tail: handle the last pixel (if any) in scalar
+    if i < n {
+        dst[i] = egui_blend_u8(unorm_mult4x4(src[i], tint), dst[i]);
+    }
+}
+
+// https://www.lgfae.com/posts/2025-09-01-AlphaBlendWithSIMD.html
+/// dst[i] = blend(src * tint_fn(), dst[i]) // As unorm
+/// blend fn is (ONE, ONE_MINUS_SRC_ALPHA)
+#[target_feature(enable = "sse2")]
+fn egui_blend_u8_slice_one_src_tinted_fn(
+    src: [u8; 4],
+    mut tint_fn: impl FnMut() -> [u8; 4],
+    dst: &mut [[u8; 4]],
+) {
+    let n = dst.len();
+    if n == 0 {
+        return;
+    }
+
+    let src32 = u32::from_le_bytes(src);
+    let src64 = (src32 as u64) | ((src32 as u64) << 32);
+
+    let e1 = _mm_set1_epi16(0x0080);
+    let e2 = _mm_set1_epi16(0x0101);
+
+    let mut i = 0usize;
+    while i + 1 < n {
+        // Load two tint values
+        let tint_a = u32::from_le_bytes(tint_fn()) as i64;
+        let tint_b = u32::from_le_bytes(tint_fn()) as i64;
+        let tint_simd = _mm_cvtsi64_si128((tint_b << 32) | tint_a);
+        let tint16 = unpacklo_epu8_epi16(tint_simd);
+
+        let src8 = _mm_cvtsi64_si128(src64 as i64);
+        // [0,0,0,0,rg,ba,rg,ba] -> [r,g,b,a,r,g,b,a]
+        let src16 = unpacklo_epu8_epi16(src8);
+
+        // Load two dst pixels
+        let dst = unsafe { dst.as_mut_ptr().add(i) }.cast::<u64>();
+        let dst8 = _mm_cvtsi64_si128(unsafe { read_unaligned(dst) } as i64);
+        let dst16 = unpacklo_epu8_epi16(dst8);
+
+        // src_tinted = (src16 * vert16 + 128) * 257 >> 16  (rounded /255)
+        let tint_mul = _mm_mullo_epi16(src16, tint16);
+        let tint_rounded = _mm_add_epi16(tint_mul, e1);
+        let src_tinted16 = _mm_mulhi_epu16(tint_rounded, e2);
+        let src_tinted8 = _mm_packus_epi16(src_tinted16, src_tinted16);
+
+        let dst8 = egui_blend_two_u16x4(src_tinted8, src_tinted16, dst16);
+
+        let lo64 = _mm_cvtsi128_si64(dst8) as u64;
+        unsafe { core::ptr::write_unaligned(dst, lo64) };
+        i += 2;
+    }
+
+    // Tail: handle the last pixel (if any) in scalar
+    if i < n {
+        dst[i] = egui_blend_u8(unorm_mult4x4(src, tint_fn()), dst[i]);
+    }
+}
+
+#[inline]
+#[target_feature(enable = "sse2")]
+fn unorm_mult4x4(a: [u8; 4], b: [u8; 4]) -> [u8; 4] {
+    let e1 = _mm_set1_epi16(0x0080);
+    let a = unpacklo_epu8_epi16(_mm_cvtsi32_si128(i32::from_le_bytes(a)));
+    let b = unpacklo_epu8_epi16(_mm_cvtsi32_si128(i32::from_le_bytes(b)));
+
+    // a * b + 0x0080
+    let mut dst = _mm_add_epi16(_mm_mullo_epi16(a, b), e1);
+
+    // ((a >> 8) + a) >> 8
+    dst = _mm_add_epi16(dst, _mm_srli_epi16(dst, 8));
+    dst = _mm_srli_epi16(dst, 8);
+
+    // Pack to back to u8
+    let dst = _mm_packus_epi16(dst, _mm_setzero_si128());
+
+    i32::to_le_bytes(_mm_cvtsi128_si32(dst)) // Return first element of dst
+}
+
+#[inline]
+/// src8 is should have two 8 bit per channel rgba samples stored in the low bits
+/// src16 is should have two 16 bit per channel rgba samples
+/// dst16 is should have two 16 bit per channel rgba samples
+#[target_feature(enable = "sse2")]
+fn egui_blend_two_u16x4(src8: __m128i, src16: __m128i, dst16: __m128i) -> __m128i {
+    let ones = _mm_set1_epi16(0x00FF);
+    let e1 = _mm_set1_epi16(0x0080);
+    let e2 = _mm_set1_epi16(0x0101);
+
+    // Broadcast alpha within each pixel's 4 lanes
+    let a_broadcast_lo = _mm_shufflelo_epi16(src16, 0b11111111);
+    let a_broadcast = _mm_shufflehi_epi16(a_broadcast_lo, 0b11111111);
+
+    // simd_alpha_compl = 255 - A for each lane, per pixel
+    let simd_alpha_compl = _mm_sub_epi16(ones, a_broadcast);
+
+    // dst * alpha_compl + 0x0080008000800080
+    let dst_term = _mm_mullo_epi16(dst16, simd_alpha_compl);
+    let res16 = _mm_add_epi16(dst_term, e1);
+
+    // This mulhi is equivalent to the ((x >> 8) + x) >> 8 operation. (can you see why?)
+    let res16 = _mm_mulhi_epu16(res16, e2);
+    let dst8 = _mm_packus_epi16(res16, res16);
+    // Pack back to u8
+
+    // dst.saturating_add(src)
+    _mm_adds_epu8(dst8, src8)
+}
+
+/// SSE2 replacement for SSE4.1 `_mm_cvtepu8_epi16`, zero extends the 8 low u8 lanes to u16
+#[inline]
+#[target_feature(enable = "sse2")]
+fn unpacklo_epu8_epi16(a: __m128i) -> __m128i {
+    _mm_unpacklo_epi8(a, _mm_setzero_si128())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::{GenericImpl, test::assert_same_output};
+
+    #[test]
+    fn matches_generic() {
+        assert_same_output(unsafe { Sse2Impl::new() }, GenericImpl);
+    }
+}
@@ -2,7 +2,7 @@
 
 use core::{arch::x86_64::*, ptr::read_unaligned};
 
-use crate::SelectedImpl;
+use crate::{SelectedImpl, color::GenericImpl};
 
 #[derive(Clone, Copy)]
 pub(crate) struct Sse41Impl(());
@@ -45,12 +45,228 @@ impl SelectedImpl for Sse41Impl {
         unsafe { egui_blend_u8(src, dst) }
     }
 
+    #[inline]
+    fn egui_blend_u16_slice(self, src: &[[u8; 4]], dst: &mut [[u16; 4]]) {
+        unsafe { super::sse2::egui_blend_u16_slice(src, dst) }
+    }
+
+    #[inline]
+    fn bilinear_u8x4_4x(
+        self,
+        corners: &[[[u8; 4]; 4]; 4],
+        fx: [u32; 4],
+        fy: [u32; 4],
+    ) -> [[u8; 4]; 4] {
+        unsafe { super::sse2::bilinear_u8x4_4x(corners, fx, fy) }
+    }
+
+    #[inline]
+    fn swizzle_rgba_bgra_slice(self, pixels: &mut [[u8; 4]]) {
+        unsafe { swizzle_rgba_bgra_slice(pixels) }
+    }
+
+    #[inline]
+    fn premultiply_alpha_slice(self, pixels: &mut [[u8; 4]]) {
+        unsafe { super::sse2::premultiply_alpha_slice(pixels) }
+    }
+
+    #[inline]
+    fn grayscale_slice(self, pixels: &mut [[u8; 4]], weights: [u16; 3]) {
+        unsafe { super::sse2::grayscale_slice(pixels, weights) }
+    }
+
+    #[inline]
+    fn first_changed_pixel(self, a: &[[u8; 4]], b: &[[u8; 4]]) -> usize {
+        unsafe { super::sse2::first_changed_pixel(a, b) }
+    }
+
+    #[inline]
+    fn any_nonzero_pixel(self, pixels: &[[u8; 4]]) -> bool {
+        unsafe { super::sse2::any_nonzero_pixel(pixels) }
+    }
+
+    #[inline]
+    fn color_matrix_slice(self, pixels: &mut [[u8; 4]], matrix: &[[i16; 3]; 3]) {
+        unsafe { color_matrix_slice(pixels, matrix) }
+    }
+
+    #[inline]
+    fn dither_bayer4x4_slice(self, pixels: &mut [[u8; 4]], x: u32, y: u32, levels: u8) {
+        unsafe { dither_bayer4x4_slice(pixels, x, y, levels) }
+    }
+
+    #[inline]
+    fn lut_rgb_slice(self, pixels: &mut [[u8; 4]], lut: &[u8; 256]) {
+        unsafe { lut_rgb_slice(pixels, lut) }
+    }
+
     #[inline]
     fn unorm_mult4x4(self, a: [u8; 4], b: [u8; 4]) -> [u8; 4] {
         unsafe { unorm_mult4x4(a, b) }
     }
 }
 
+/// pixels[i] = swizzle_rgba_bgra(pixels[i])
+#[target_feature(enable = "sse4.1")]
+fn swizzle_rgba_bgra_slice(pixels: &mut [[u8; 4]]) {
+    let shuffle = _mm_setr_epi8(2, 1, 0, 3, 6, 5, 4, 7, 10, 9, 8, 11, 14, 13, 12, 15);
+
+    let mut chunks = pixels.chunks_exact_mut(4);
+    for chunk in &mut chunks {
+        let p = chunk.as_mut_ptr().cast::<__m128i>();
+        let px = unsafe { _mm_loadu_si128(p) };
+        unsafe { _mm_storeu_si128(p, _mm_shuffle_epi8(px, shuffle)) };
+    }
+
+    for pixel in chunks.into_remainder() {
+        *pixel = crate::color::swizzle_rgba_bgra(*pixel);
+    }
+}
+
+/// pixels[i] = color_matrix(pixels[i], matrix), also used by the AVX2 implementation
+#[target_feature(enable = "sse4.1")]
+pub(crate) fn color_matrix_slice(pixels: &mut [[u8; 4]], matrix: &[[i16; 3]; 3]) {
+    let even_bytes = _mm_set1_epi32(0x00FF00FF);
+    let alpha_mask = _mm_set1_epi32(0xFF000000u32 as i32);
+    let round = _mm_set1_epi32(128);
+    let zero = _mm_setzero_si128();
+    // For each output channel, the weights of the channels 0 and 2, then 1 and 3, in the low and high 16 bits of
+    // each pixel
+    let weights02 =
+        matrix.map(|row| _mm_set1_epi32(((row[2] as i32) << 16) | row[0] as u16 as i32));
+    let weights13 = matrix.map(|row| _mm_set1_epi32(row[1] as u16 as i32));
+
+    let mut chunks = pixels.chunks_exact_mut(4);
+    for chunk in &mut chunks {
+        // Load four pixels
+        let p = chunk.as_mut_ptr().cast::<__m128i>();
+        let px = unsafe { _mm_loadu_si128(p) };
+
+        let c02 = _mm_and_si128(px, even_bytes);
+        let c13 = _mm_and_si128(_mm_srli_epi32(px, 8), even_bytes);
+        let alpha = _mm_srli_epi32(px, 24);
+        // (sum + 128) >> 8 clamped to 0..=alpha, in the low byte of each pixel
+        let channel = |c: usize| {
+            let sum = _mm_add_epi32(
+                _mm_madd_epi16(c02, weights02[c]),
+                _mm_madd_epi16(c13, weights13[c]),
+            );
+            let y = _mm_srai_epi32(_mm_add_epi32(sum, round), 8);
+            _mm_min_epi32(_mm_max_epi32(y, zero), alpha)
+        };
+
+        let res = _mm_or_si128(
+            _mm_or_si128(channel(0), _mm_slli_epi32(channel(1), 8)),
+            _mm_or_si128(
+                _mm_slli_epi32(channel(2), 16),
+                _mm_and_si128(px, alpha_mask),
+            ),
+        );
+        unsafe { _mm_storeu_si128(p, res) };
+    }
+
+    for pixel in chunks.into_remainder() {
+        *pixel = crate::color::color_matrix(*pixel, matrix);
+    }
+}
+
+/// pixels[i] = dither_bayer4x4(pixels[i], ...) of the pixel at `(x + i, y)`, also used by the AVX2 implementation
+#[target_feature(enable = "sse4.1")]
+pub(crate) fn dither_bayer4x4_slice(pixels: &mut [[u8; 4]], x: u32, y: u32, levels: u8) {
+    let n = crate::color::dither_steps(levels);
+    let steps = _mm_set1_epi16(n as i16);
+    let scale = _mm_set1_epi16(crate::color::dither_level_scale(n) as i16);
+    let one = _mm_set1_epi16(1);
+    let half = _mm_set1_epi16(128);
+    let v255 = _mm_set1_epi16(255);
+    let zero = _mm_setzero_si128();
+    let sign_bit = _mm_set1_epi8(i8::MIN);
+    let alpha_mask = _mm_set1_epi32(0xFF000000u32 as i32);
+    let broadcast_alpha = _mm_set_epi8(15, 15, 15, 15, 11, 11, 11, 11, 7, 7, 7, 7, 3, 3, 3, 3);
+
+    // Every chunk starts at the same x % 4, so one vector holds the thresholds of all the chunks of the row. They
+    // are biased to compare unsigned bytes with `_mm_cmpgt_epi8`, alpha is never above its threshold.
+    let row = &crate::color::BAYER4X4_THRESHOLDS[y as usize % 4];
+    let [t0, t1, t2, t3] = core::array::from_fn(|i| (row[(x as usize + i) % 4] ^ 0x80) as i8);
+    let max = i8::MAX;
+    let thresholds = _mm_set_epi8(
+        max, t3, t3, t3, max, t2, t2, t2, max, t1, t1, t1, max, t0, t0, t0,
+    );
+
+    // x / 255 of 16 bits lanes below 65280
+    let div255 = |x: __m128i| {
+        _mm_srli_epi16(
+            _mm_add_epi16(_mm_add_epi16(x, one), _mm_srli_epi16(x, 8)),
+            8,
+        )
+    };
+    let level_value =
+        |level: __m128i| _mm_srli_epi16(_mm_add_epi16(_mm_mullo_epi16(level, scale), half), 8);
+
+    let remainder_x = x as usize + pixels.len() / 4 * 4;
+    let mut chunks = pixels.chunks_exact_mut(4);
+    for chunk in &mut chunks {
+        // Load four pixels
+        let p = chunk.as_mut_ptr().cast::<__m128i>();
+        let px = unsafe { _mm_loadu_si128(p) };
+
+        let x_lo = _mm_mullo_epi16(_mm_unpacklo_epi8(px, zero), steps);
+        let x_hi = _mm_mullo_epi16(_mm_unpackhi_epi8(px, zero), steps);
+        let level_lo = div255(x_lo);
+        let level_hi = div255(x_hi);
+        let frac_lo = _mm_sub_epi16(x_lo, _mm_mullo_epi16(level_lo, v255));
+        let frac_hi = _mm_sub_epi16(x_hi, _mm_mullo_epi16(level_hi, v255));
+
+        // The level below, plus one (minus the all ones mask) above the threshold
+        let frac = _mm_xor_si128(_mm_packus_epi16(frac_lo, frac_hi), sign_bit);
+        let up = _mm_cmpgt_epi8(frac, thresholds);
+        let level = _mm_sub_epi8(_mm_packus_epi16(level_lo, level_hi), up);
+
+        let values = _mm_packus_epi16(
+            level_value(_mm_unpacklo_epi8(level, zero)),
+            level_value(_mm_unpackhi_epi8(level, zero)),
+        );
+        let values = _mm_min_epu8(values, _mm_shuffle_epi8(px, broadcast_alpha));
+        let res = _mm_blendv_epi8(values, px, alpha_mask);
+        unsafe { _mm_storeu_si128(p, res) };
+    }
+
+    for (i, pixel) in chunks.into_remainder().iter_mut().enumerate() {
+        *pixel = crate::color::dither_bayer4x4(*pixel, row[(remainder_x + i) % 4], n);
+    }
+}
+
+/// pixels[i][c] = lut[pixels[i][c]] for the color channels, alpha is kept as is.
+/// Also used by the AVX2 implementation for the remainder.
+#[target_feature(enable = "sse4.1")]
+pub(crate) fn lut_rgb_slice(pixels: &mut [[u8; 4]], lut: &[u8; 256]) {
+    // `_mm_shuffle_epi8` looks up 16 entries, so the table is split by the high nibble of the index
+    let mut tables = [_mm_setzero_si128(); 16];
+    for (table, lut) in tables.iter_mut().zip(lut.chunks_exact(16)) {
+        *table = unsafe { _mm_loadu_si128(lut.as_ptr().cast::<__m128i>()) };
+    }
+    let low_nibble = _mm_set1_epi8(0x0F);
+    let alpha_mask = _mm_set1_epi32(0xFF000000u32 as i32);
+
+    let mut chunks = pixels.chunks_exact_mut(4);
+    for chunk in &mut chunks {
+        let p = chunk.as_mut_ptr().cast::<__m128i>();
+        let px = unsafe { _mm_loadu_si128(p) };
+
+        let lo = _mm_and_si128(px, low_nibble);
+        let hi = _mm_and_si128(_mm_srli_epi16(px, 4), low_nibble);
+        let mut res = _mm_setzero_si128();
+        for (i, table) in tables.iter().enumerate() {
+            let in_table = _mm_cmpeq_epi8(hi, _mm_set1_epi8(i as i8));
+            res = _mm_or_si128(res, _mm_and_si128(in_table, _mm_shuffle_epi8(*table, lo)));
+        }
+
+        unsafe { _mm_storeu_si128(p, _mm_blendv_epi8(res, px, alpha_mask)) };
+    }
+
+    GenericImpl.lut_rgb_slice(chunks.into_remainder(), lut);
+}
+
 /// blend fn is (ONE, ONE_MINUS_SRC_ALPHA)
 #[target_feature(enable = "sse4.1")]
 fn egui_blend_u8(src: [u8; 4], dst: [u8; 4]) -> [u8; 4] {
@@ -197,9 +413,33 @@ fn egui_blend_u8_slice_tinted(src: &[[u8; 4]], tint: [u8; 4], dst: &mut [[u8; 4]
 
     let t32 = _mm_set1_epi32(i32::from_le_bytes(tint));
     let tint16 = _mm_cvtepu8_epi16(t32);
+    let zero = _mm_setzero_si128();
+
+    // src_tinted = (src16 * vert16 + 128) * 257 >> 16  (rounded /255)
+    let tint_two = |src16: __m128i| {
+        let tint_rounded = _mm_add_epi16(_mm_mullo_epi16(src16, tint16), e1);
+        let src_tinted16 = _mm_mulhi_epu16(tint_rounded, e2);
+        (_mm_packus_epi16(src_tinted16, src_tinted16), src_tinted16)
+    };
 
     let mut i = 0usize;
-    while i + 1 < n {
+    while i + 3 < n {
+        // Load four src and dst pixels, widened two at a time
+        let src = unsafe { src.as_ptr().add(i) }.cast::<__m128i>();
+        let src8 = unsafe { _mm_loadu_si128(src) };
+        let dst = unsafe { dst.as_mut_ptr().add(i) }.cast::<__m128i>();
+        let dst8 = unsafe { _mm_loadu_si128(dst) };
+
+        let (src_tinted8, src_tinted16) = tint_two(_mm_cvtepu8_epi16(src8));
+        let lo = egui_blend_two_u16x4(src_tinted8, src_tinted16, _mm_cvtepu8_epi16(dst8));
+        let (src_tinted8, src_tinted16) = tint_two(_mm_unpackhi_epi8(src8, zero));
+        let hi = egui_blend_two_u16x4(src_tinted8, src_tinted16, _mm_unpackhi_epi8(dst8, zero));
+
+        unsafe { _mm_storeu_si128(dst, _mm_unpacklo_epi64(lo, hi)) };
+        i += 4;
+    }
+
+    if i + 1 < n {
         // Load two src pixels
         let src = unsafe { src.as_ptr().add(i) }.cast::<u64>();
         let src8 = _mm_cvtsi64_si128(unsafe { read_unaligned(src) } as i64);
@@ -211,12 +451,7 @@ fn egui_blend_u8_slice_tinted(src: &[[u8; 4]], tint: [u8; 4], dst: &mut [[u8; 4]
         let dst8 = _mm_cvtsi64_si128(unsafe { read_unaligned(dst) } as i64);
         let dst16 = _mm_cvtepu8_epi16(dst8);
 
-        // src_tinted = (src16 * vert16 + 128) * 257 >> 16  (rounded /255)
-        let tint_mul = _mm_mullo_epi16(src16, tint16);
-        let tint_rounded = _mm_add_epi16(tint_mul, e1);
-        let src_tinted16 = _mm_mulhi_epu16(tint_rounded, e2);
-        let src_tinted8 = _mm_packus_epi16(src_tinted16, src_tinted16);
-
+        let (src_tinted8, src_tinted16) = tint_two(src16);
         let dst8 = egui_blend_two_u16x4(src_tinted8, src_tinted16, dst16);
 
         let lo64 = _mm_cvtsi128_si64(dst8) as u64;
@@ -335,3 +570,17 @@ fn egui_blend_two_u16x4(src8: __m128i, src16: __m128i, dst16: __m128i) -> __m128
     // dst.saturating_add(src)
     _mm_adds_epu8(dst8, src8)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::color::{GenericImpl, test::assert_same_output};
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn matches_generic() {
+        if std::arch::is_x86_feature_detected!("sse4.1") {
+            assert_same_output(unsafe { Sse41Impl::new() }, GenericImpl);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use core::ops::{BitOr, BitOrAssign};
+
+use crate::{
+    BufferMutRef, ColorFieldOrder, DirtyRect, EguiSoftwareRender, EguiSoftwareRenderInner,
+    SelectedImpl, SoftwareRenderCaching, TILE_SIZE, as_usize, dispatch_simd_impl,
+    draw_rect_border_f32, swizzle_rgba_bgra,
+};
+
+/// Debug overlays drawn over the output buffer, see [`EguiSoftwareRender::with_debug_overlay`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DebugOverlay(u8);
+
+impl DebugOverlay {
+    /// 1px semitransparent grid at `TILE_SIZE` intervals
+    pub const TILE_GRID: Self = Self(1 << 0);
+    /// Outline the regions redrawn this frame in red
+    pub const DIRTY_RECTS: Self = Self(1 << 1);
+    /// Outline the bounding box of each cached primitive in blue
+    pub const PRIMITIVE_BBOXES: Self = Self(1 << 2);
+    /// Tint the tiles containing cached pixels in yellow (`BlendTiled` only)
+    pub const CACHE_OCCUPANCY: Self = Self(1 << 3);
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn all() -> Self {
+        Self(
+            Self::TILE_GRID.0
+                | Self::DIRTY_RECTS.0
+                | Self::PRIMITIVE_BBOXES.0
+                | Self::CACHE_OCCUPANCY.0,
+        )
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub const fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// Add or remove `other` flags
+    pub const fn set(&mut self, other: Self, value: bool) {
+        *self = if value {
+            self.union(other)
+        } else {
+            self.difference(other)
+        };
+    }
+}
+
+impl BitOr for DebugOverlay {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.union(rhs)
+    }
+}
+
+impl BitOrAssign for DebugOverlay {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = self.union(rhs);
+    }
+}
+
+impl EguiSoftwareRender {
+    /// Draw the enabled debug overlays directly over `buffer`
+    ///
+    /// Run after compositing, `buffer` must not be the canvas used for caching as the overlays would then be cached
+    /// with it. `EguiSoftwareRenderCanvas::render()` calls this automatically.
+    pub fn draw_debug_overlay(&self, buffer: &mut BufferMutRef) {
+        let overlay = self.inner.debug_overlay;
+        if overlay.is_empty() {
+            return;
+        }
+        let inner = &self.inner;
+
+        if overlay.contains(DebugOverlay::CACHE_OCCUPANCY) && inner.mode.is_blend_tiled() {
+            let color = inner.overlay_color([64, 64, 0, 64]);
+            for (tile_idx, mask) in inner.dirty_tiles.iter().enumerate() {
+                if mask & EguiSoftwareRenderInner::OCCUPIED_TILE_MASK != 0 {
+                    let rect = inner.tile_rect(tile_idx as u32);
+                    dispatch_simd_impl!(|simd_impl| blend_rect(simd_impl, buffer, rect, color));
+                }
+            }
+        }
+
+        if overlay.contains(DebugOverlay::TILE_GRID) {
+            let color = inner.overlay_color([64, 64, 64, 64]);
+            dispatch_simd_impl!(|simd_impl| {
+                for y in 0..buffer.height {
+                    let row = buffer.get_mut_span(0, buffer.width, y);
+                    if y % TILE_SIZE == 0 {
+                        simd_impl.egui_blend_u8_slice_one_src(color, row);
+                    } else {
+                        for pixel in row.iter_mut().step_by(as_usize(TILE_SIZE)) {
+                            *pixel = simd_impl.egui_blend_u8(color, *pixel);
+                        }
+                    }
+                }
+            });
+        }
+
+        if overlay.contains(DebugOverlay::PRIMITIVE_BBOXES) {
+            let color = inner.overlay_color([0, 0, 255, 255]);
+            let rects = self
+                .tiledcached_primitives
+                .values()
+                .map(|prim| &prim.inner)
+                .chain(self.dirtycached_primitives.values().map(|prim| &prim.inner))
+                .filter(|prim| prim.seen_this_frame)
+                .map(|prim| prim.rect);
+            for rect in rects {
+                draw_rect_border_f32(buffer, rect.to_egui_rect(), 1.0, color);
+            }
+        }
+
+        if overlay.contains(DebugOverlay::DIRTY_RECTS) {
+            let color = inner.overlay_color([255, 0, 0, 255]);
+            match inner.mode {
+                SoftwareRenderCaching::BlendTiled | SoftwareRenderCaching::BlendTiledParallel => {
+                    for (tile_idx, mask) in inner.dirty_tiles.iter().enumerate() {
+                        if mask & EguiSoftwareRenderInner::DIRTY_TILE_MASK != 0 {
+                            let rect = inner.tile_rect(tile_idx as u32);
+                            draw_rect_border_f32(buffer, rect.to_egui_rect(), 1.0, color);
+                        }
+                    }
+                }
+                SoftwareRenderCaching::MeshTiled => {
+                    for rect in inner.dirty_rects.iter() {
+                        draw_rect_border_f32(buffer, rect.to_egui_rect(), 1.0, color);
+                    }
+                }
+                SoftwareRenderCaching::Mesh
+                | SoftwareRenderCaching::Direct
+                | SoftwareRenderCaching::Auto => {
+                    draw_rect_border_f32(buffer, inner.last_dirty_rect.to_egui_rect(), 1.0, color);
+                }
+            }
+        }
+    }
+}
+
+impl EguiSoftwareRenderInner {
+    /// Swizzle a premultiplied rgba color to the output field order
+    fn overlay_color(&self, color: [u8; 4]) -> [u8; 4] {
+        match self.output_field_order {
+            ColorFieldOrder::Rgba => color,
+            ColorFieldOrder::Bgra => swizzle_rgba_bgra(color),
+        }
+    }
+
+    fn tile_rect(&self, tile_idx: u32) -> DirtyRect {
+        let tile_x = tile_idx % self.tiles_dim[0];
+        let tile_y = tile_idx / self.tiles_dim[0];
+        DirtyRect {
+            min_x: tile_x * TILE_SIZE,
+            min_y: tile_y * TILE_SIZE,
+            max_x: (tile_x + 1) * TILE_SIZE,
+            max_y: (tile_y + 1) * TILE_SIZE,
+        }
+    }
+}
+
+fn blend_rect(
+    simd_impl: impl SelectedImpl,
+    buffer: &mut BufferMutRef,
+    rect: DirtyRect,
+    color: [u8; 4],
+) {
+    let max_x = rect.max_x.min(buffer.width);
+    let max_y = rect.max_y.min(buffer.height);
+    if rect.min_x >= max_x {
+        return;
+    }
+    for y in rect.min_y..max_y {
+        simd_impl.egui_blend_u8_slice_one_src(color, buffer.get_mut_span(rect.min_x, max_x, y));
+    }
+}
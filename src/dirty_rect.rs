@@ -4,7 +4,8 @@ use alloc::vec::Vec;
 
 use crate::TILE_SIZE;
 
-#[derive(Debug, Clone, Copy)]
+/// Rect in pixels, `min` inclusive and `max` exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct DirtyRect {
     pub min_x: u32,
     pub min_y: u32,
@@ -22,6 +23,36 @@ impl DirtyRect {
         }
     }
 
+    /// `None` if `max_x < min_x` or `max_y < min_y`
+    #[inline]
+    pub const fn try_new(min_x: u32, min_y: u32, max_x: u32, max_y: u32) -> Option<Self> {
+        if max_x < min_x || max_y < min_y {
+            return None;
+        }
+        Some(Self {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        })
+    }
+
+    /// Rect with `x` edges clamped to `0..=bounds.0` and `y` edges to `0..=bounds.1`, for rects out of pixel math
+    ///
+    /// Rects with a `max` edge before their `min` edge are empty.
+    #[inline]
+    pub fn clamped(min_x: i64, min_y: i64, max_x: i64, max_y: i64, bounds: (u32, u32)) -> Self {
+        let clamp_x = |x: i64| x.clamp(0, i64::from(bounds.0)) as u32;
+        let clamp_y = |y: i64| y.clamp(0, i64::from(bounds.1)) as u32;
+        let (min_x, min_y) = (clamp_x(min_x), clamp_y(min_y));
+        Self {
+            min_x,
+            min_y,
+            max_x: clamp_x(max_x).max(min_x),
+            max_y: clamp_y(max_y).max(min_y),
+        }
+    }
+
     #[inline]
     pub const fn tiled<const TILE_SIZE: u32>(self) -> Self {
         Self {
@@ -41,6 +72,78 @@ impl DirtyRect {
         self.max_y - self.min_y
     }
 
+    /// Number of pixels covered, saturates at `u32::MAX`
+    #[inline]
+    pub const fn area(self) -> u32 {
+        self.width().saturating_mul(self.height())
+    }
+
+    #[inline]
+    pub const fn contains_point(self, x: u32, y: u32) -> bool {
+        self.min_x <= x && x < self.max_x && self.min_y <= y && y < self.max_y
+    }
+
+    /// Move each edge inward by `amount`, returns an empty rect if nothing is left
+    #[inline]
+    pub fn shrink(self, amount: u32) -> Self {
+        let twice = amount.saturating_mul(2);
+        if twice >= self.width() || twice >= self.height() {
+            return Self::new_empty();
+        }
+        Self {
+            min_x: self.min_x + amount,
+            min_y: self.min_y + amount,
+            max_x: self.max_x - amount,
+            max_y: self.max_y - amount,
+        }
+    }
+
+    /// Move each edge outward by `amount`, clamped to `0..max_x` and `0..max_y`
+    ///
+    /// Empty rects stay empty.
+    #[inline]
+    pub fn expand(self, amount: u32, max_x: u32, max_y: u32) -> Self {
+        if self.is_empty() {
+            return self;
+        }
+        Self {
+            min_x: self.min_x.saturating_sub(amount).min(max_x),
+            min_y: self.min_y.saturating_sub(amount).min(max_y),
+            max_x: self.max_x.saturating_add(amount).min(max_x),
+            max_y: self.max_y.saturating_add(amount).min(max_y),
+        }
+    }
+
+    /// Pixels covered by `rect` in points, clamped to a `width` x `height` buffer
+    ///
+    /// `min` is floored and `max` ceiled, so every partially covered pixel is included.
+    #[inline]
+    pub fn from_egui_rect(
+        rect: egui::Rect,
+        pixels_per_point: f32,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let min_x = (rect.min.x * pixels_per_point)
+            .floor()
+            .clamp(0.0, width as f32) as u32;
+        let min_y = (rect.min.y * pixels_per_point)
+            .floor()
+            .clamp(0.0, height as f32) as u32;
+        let max_x = (rect.max.x * pixels_per_point)
+            .ceil()
+            .clamp(0.0, width as f32) as u32;
+        let max_y = (rect.max.y * pixels_per_point)
+            .ceil()
+            .clamp(0.0, height as f32) as u32;
+        Self {
+            min_x,
+            min_y,
+            max_x: max_x.max(min_x),
+            max_y: max_y.max(min_y),
+        }
+    }
+
     #[inline]
     pub const fn to_egui_rect(self) -> egui::Rect {
         egui::Rect {
@@ -84,14 +187,188 @@ impl DirtyRect {
             max_y: self.max_y.max(other.max_y),
         }
     }
+
+    /// Left and right parts split at `x`, `None` unless `x` is inside `min_x + 1..max_x`, so neither part is empty
+    #[inline]
+    pub fn split_vertical(self, x: u32) -> Option<(Self, Self)> {
+        if self.is_empty() || x <= self.min_x || x >= self.max_x {
+            return None;
+        }
+        Some((Self { max_x: x, ..self }, Self { min_x: x, ..self }))
+    }
+
+    /// Top and bottom parts split at `y`, `None` unless `y` is inside `min_y + 1..max_y`, so neither part is empty
+    #[inline]
+    pub fn split_horizontal(self, y: u32) -> Option<(Self, Self)> {
+        if self.is_empty() || y <= self.min_y || y >= self.max_y {
+            return None;
+        }
+        Some((Self { max_y: y, ..self }, Self { min_y: y, ..self }))
+    }
+
+    /// Parts of the rect in each cell of a `tile_size` grid starting at 0, row by row
+    ///
+    /// # Panics
+    ///
+    /// If `tile_size` is 0.
+    pub fn subdivide_to_tiles(self, tile_size: u32) -> impl Iterator<Item = Self> {
+        assert!(tile_size > 0, "tile_size must not be 0");
+        let (tiles_x, tiles_y) = match self.is_empty() {
+            true => (0..0, 0..0),
+            false => (
+                self.min_x / tile_size..self.max_x.div_ceil(tile_size),
+                self.min_y / tile_size..self.max_y.div_ceil(tile_size),
+            ),
+        };
+        tiles_y.flat_map(move |tile_y| {
+            tiles_x.clone().map(move |tile_x| Self {
+                min_x: (tile_x * tile_size).max(self.min_x),
+                min_y: (tile_y * tile_size).max(self.min_y),
+                max_x: (tile_x + 1).saturating_mul(tile_size).min(self.max_x),
+                max_y: (tile_y + 1).saturating_mul(tile_size).min(self.max_y),
+            })
+        })
+    }
+}
+
+/// Quadtree depth, each root covers `2^DEPTH` x `2^DEPTH` tiles
+const QUADTREE_DEPTH: u32 = 4;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum NodeState {
+    #[default]
+    Clean,
+    /// Some of the children are dirty
+    Partial,
+    /// The whole node is dirty
+    Dirty,
+}
+
+/// Grid of implicit quadtrees, each leaf covers one `TILE_SIZE`×`TILE_SIZE` tile
+#[derive(Debug, Default)]
+struct QuadTree<const DEPTH: u32> {
+    /// Roots grid size (cols, rows)
+    roots_dim: [u32; 2],
+    /// Node states from level 0 (one node per root) to level `DEPTH` (one node per tile)
+    levels: Vec<Vec<NodeState>>,
+}
+
+impl<const DEPTH: u32> QuadTree<DEPTH> {
+    const fn level_dim(&self, level: u32) -> [u32; 2] {
+        [self.roots_dim[0] << level, self.roots_dim[1] << level]
+    }
+
+    /// Clear all nodes, and resize to cover at least `tiles_dim`
+    fn reset(&mut self, tiles_dim: [u32; 2]) {
+        self.roots_dim = [
+            tiles_dim[0].div_ceil(1 << DEPTH),
+            tiles_dim[1].div_ceil(1 << DEPTH),
+        ];
+        self.levels.resize_with(DEPTH as usize + 1, Vec::new);
+        for level in 0..=DEPTH {
+            let [w, h] = self.level_dim(level);
+            let nodes = &mut self.levels[level as usize];
+            nodes.clear();
+            nodes.resize(w as usize * h as usize, NodeState::Clean);
+        }
+    }
+
+    /// Mark the leaves in the given tile range as dirty, O(tiles covered)
+    fn mark(&mut self, min: [u32; 2], max: [u32; 2]) {
+        let [w, _] = self.level_dim(DEPTH);
+        let leaves = &mut self.levels[DEPTH as usize];
+        for y in min[1]..max[1] {
+            let row = (y * w) as usize;
+            leaves[row + min[0] as usize..row + max[0] as usize].fill(NodeState::Dirty);
+        }
+    }
+
+    /// Update inner nodes from the leaves
+    fn build(&mut self) {
+        for level in (0..DEPTH).rev() {
+            let [w, h] = self.level_dim(level);
+            let (parents, children) = self.levels.split_at_mut(level as usize + 1);
+            let parents = &mut parents[level as usize];
+            let children = &children[0];
+            let cw = (w * 2) as usize;
+            for y in 0..h as usize {
+                for x in 0..w as usize {
+                    let i = y * 2 * cw + x * 2;
+                    let quad = [
+                        children[i],
+                        children[i + 1],
+                        children[i + cw],
+                        children[i + cw + 1],
+                    ];
+                    parents[y * w as usize + x] = if quad.iter().all(|&s| s == NodeState::Dirty) {
+                        NodeState::Dirty
+                    } else if quad.iter().all(|&s| s == NodeState::Clean) {
+                        NodeState::Clean
+                    } else {
+                        NodeState::Partial
+                    };
+                }
+            }
+        }
+    }
+
+    /// Call `f` with the pixel rect of each maximal dirty node intersecting `area`
+    fn for_each_dirty(&self, area: DirtyRect, f: &mut impl FnMut(DirtyRect)) {
+        let root_px = TILE_SIZE << DEPTH;
+        let max_x = area.max_x.div_ceil(root_px).min(self.roots_dim[0]);
+        let max_y = area.max_y.div_ceil(root_px).min(self.roots_dim[1]);
+        for y in area.min_y / root_px..max_y {
+            for x in area.min_x / root_px..max_x {
+                self.visit(0, x, y, area, f);
+            }
+        }
+    }
+
+    fn visit(&self, level: u32, x: u32, y: u32, area: DirtyRect, f: &mut impl FnMut(DirtyRect)) {
+        let [w, _] = self.level_dim(level);
+        let state = self.levels[level as usize][(y * w + x) as usize];
+        if state == NodeState::Clean {
+            return;
+        }
+        let node_px = TILE_SIZE << (DEPTH - level);
+        let node = DirtyRect {
+            min_x: x * node_px,
+            min_y: y * node_px,
+            max_x: (x + 1) * node_px,
+            max_y: (y + 1) * node_px,
+        };
+        if !(node.min_x < area.max_x
+            && node.max_x > area.min_x
+            && node.min_y < area.max_y
+            && node.max_y > area.min_y)
+        {
+            return;
+        }
+        if state == NodeState::Dirty {
+            f(node);
+        } else {
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                self.visit(level + 1, x * 2 + dx, y * 2 + dy, area, f);
+            }
+        }
+    }
+
+    fn memory_usage_bytes(&self) -> usize {
+        self.levels.capacity() * size_of::<Vec<NodeState>>()
+            + self
+                .levels
+                .iter()
+                .map(|nodes| nodes.capacity() * size_of::<NodeState>())
+                .sum::<usize>()
+    }
 }
 
+/// Minimal set of non overlapping, tile aligned, rects covering a set of bboxes
 #[derive(Debug, Default)]
 pub struct ComputeTiledDirtyRects {
     minimal_non_overlapping_bboxes: Vec<DirtyRect>,
     pub(crate) bboxes: Vec<DirtyRect>,
-    x_intervals: Vec<(u32, u32)>,
-    ys: Vec<u32>,
+    quadtree: QuadTree<QUADTREE_DEPTH>,
 }
 
 impl Deref for ComputeTiledDirtyRects {
@@ -103,74 +380,98 @@ impl Deref for ComputeTiledDirtyRects {
 }
 
 impl ComputeTiledDirtyRects {
-    pub fn intersections(&self, other: DirtyRect) -> impl Iterator<Item = DirtyRect> + '_ {
+    /// Bytes allocated by the internal buffers
+    pub fn memory_usage_bytes(&self) -> usize {
+        self.minimal_non_overlapping_bboxes.capacity() * size_of::<DirtyRect>()
+            + self.bboxes.capacity() * size_of::<DirtyRect>()
+            + self.quadtree.memory_usage_bytes()
+    }
+
+    /// Number of non overlapping dirty rects
+    pub fn rect_count(&self) -> usize {
+        self.minimal_non_overlapping_bboxes.len()
+    }
+
+    /// Sum of the area of the non overlapping dirty rects
+    pub fn total_dirty_area(&self) -> u64 {
         self.minimal_non_overlapping_bboxes
             .iter()
-            .filter(move |bbox| bbox.intersects(other))
-            .map(move |bbox| bbox.intersection(other))
+            .map(|rect| rect.area() as u64)
+            .sum()
     }
 
-    pub fn set_bboxes(&mut self, boxes: impl Iterator<Item = DirtyRect>) {
-        fn merge_intervals(intervals: &mut [(u32, u32)], mut f_yield: impl FnMut((u32, u32))) {
-            if intervals.is_empty() {
-                return;
-            }
-            intervals.sort_unstable_by(|a, b| a.0.cmp(&b.0));
-            let mut it = intervals.iter().copied();
-            if let Some(mut last) = it.next() {
-                for (start, end) in it {
-                    if start <= last.1 {
-                        last.1 = last.1.max(end);
-                    } else {
-                        f_yield(last);
-                        last = (start, end);
-                    }
-                }
-                f_yield(last);
-            }
+    /// Call `f` with each non overlapping part of the dirty region intersecting `other`
+    ///
+    /// Walks the quadtree, so only the nodes around `other` are visited.
+    pub fn for_each_intersection(&self, other: DirtyRect, mut f: impl FnMut(DirtyRect)) {
+        if other.is_empty() {
+            return;
         }
+        self.quadtree
+            .for_each_dirty(other, &mut |node| f(node.intersection(other)));
+    }
 
+    /// Clear the dirty rects and release the internal buffers
+    ///
+    /// `set_bboxes()` keeps the buffers capacity between frames, this is for `EguiSoftwareRender::clear_cache()`.
+    pub fn reset(&mut self) {
         self.minimal_non_overlapping_bboxes.clear();
+        self.minimal_non_overlapping_bboxes.shrink_to_fit();
         self.bboxes.clear();
-        self.bboxes.extend(boxes.map(|b| b.tiled::<TILE_SIZE>()));
-        // Step 1: collect all unique y-coordinates
-        self.ys.clear();
-        self.ys
-            .extend(self.bboxes.iter().flat_map(|b| [b.min_y, b.max_y]));
-        self.ys.sort_unstable();
-        self.ys.dedup();
-
-        // Step 2: iterate over horizontal strips
-        for strip in self.ys.windows(2) {
-            let min_y = strip[0];
-            let max_y = strip[1];
-
-            // Find boxes intersecting this horizontal strip
-            self.x_intervals.clear();
-            for b in &self.bboxes {
-                if b.min_y < max_y && b.max_y > min_y {
-                    self.x_intervals.push((b.min_x, b.max_x));
-                }
-            }
+        self.bboxes.shrink_to_fit();
+        self.quadtree = Default::default();
+    }
 
-            // Merge overlapping x-intervals
-            merge_intervals(&mut self.x_intervals, |(min_x, max_x)| {
-                match self.minimal_non_overlapping_bboxes.last_mut() {
-                    Some(rect)
-                        if rect.min_x == min_x && rect.max_x == max_x && rect.max_y == min_y =>
-                    {
-                        rect.max_y = max_y;
-                    }
-                    _ => {
-                        self.minimal_non_overlapping_bboxes.push(DirtyRect {
-                            min_x,
-                            min_y,
-                            max_x,
-                            max_y,
-                        });
-                    }
-                }
-            });
+    /// Replace the dirty rects by the ones covering `boxes`
+    ///
+    /// Without any non empty box, the previous rects are cleared and the quadtree is not rebuilt.
+    pub fn set_bboxes(&mut self, boxes: impl Iterator<Item = DirtyRect>) {
+        self.minimal_non_overlapping_bboxes.clear();
+        self.bboxes.clear();
+        self.bboxes.extend(
+            boxes
+                .filter(|b| !b.is_empty())
+                .map(|b| b.tiled::<TILE_SIZE>()),
+        );
+        if self.bboxes.is_empty() {
+            self.quadtree.reset([0, 0]);
+            return;
         }
+
+        // Step 1: mark the tiles covered by each bbox
+        let tiles_dim = self.bboxes.iter().fold([0, 0], |dim, b| {
+            [
+                dim[0].max(b.max_x / TILE_SIZE),
+                dim[1].max(b.max_y / TILE_SIZE),
+            ]
+        });
+        self.quadtree.reset(tiles_dim);
+        for b in &self.bboxes {
+            self.quadtree.mark(
+                [b.min_x / TILE_SIZE, b.min_y / TILE_SIZE],
+                [b.max_x / TILE_SIZE, b.max_y / TILE_SIZE],
+            );
+        }
+        self.quadtree.build();
+
+        // Step 2: collect the maximal dirty nodes, merging horizontal neighbours of the same height
+        let everything = DirtyRect {
+            min_x: 0,
+            min_y: 0,
+            max_x: u32::MAX,
+            max_y: u32::MAX,
+        };
+        let rects = &mut self.minimal_non_overlapping_bboxes;
+        self.quadtree
+            .for_each_dirty(everything, &mut |node| rects.push(node));
+        rects.sort_unstable_by_key(|r| (r.min_y, r.max_y, r.min_x));
+        rects.dedup_by(|next, prev| {
+            let merge =
+                prev.min_y == next.min_y && prev.max_y == next.max_y && prev.max_x == next.min_x;
+            if merge {
+                prev.max_x = next.max_x;
+            }
+            merge
+        });
     }
 }
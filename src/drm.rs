@@ -0,0 +1,338 @@
+use crate::winit::{App, SoftwareBackend, SoftwareBackendAppConfiguration, screenshot};
+use crate::{BufferMutRef, ColorFieldOrder, DirtyRect, EguiSoftwareRender};
+use drm::buffer::{Buffer, DrmFourcc};
+use drm::control::{
+    Device as ControlDevice, Event, Mode, ModeTypeFlags, PageFlipFlags, connector, crtc,
+    dumbbuffer::DumbBuffer, framebuffer,
+};
+use egui::{Context, Pos2, RawInput, Rect, ViewportCommand, vec2};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::mem;
+use std::os::fd::{AsFd, BorrowedFd};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Instant;
+use std::vec::Vec;
+
+/// Opened when `SoftwareBackendAppConfiguration::drm_device` is not set
+pub const DEFAULT_DRM_DEVICE: &str = "/dev/dri/card0";
+
+/// Errors that can occur when using the egui software backend with DRM/KMS.
+#[derive(Debug)]
+pub enum DrmBackendAppError {
+    /// A DRM ioctl, or opening the device, has failed.
+    Io {
+        io_error: io::Error,
+        function: &'static str,
+    },
+
+    /// No connector of the device has a display connected.
+    NoConnectedDisplay,
+
+    /// No CRTC can drive the connected display.
+    NoCrtc,
+}
+
+impl Display for DrmBackendAppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DrmBackendAppError::Io { function, .. } => {
+                f.write_str("error calling ")?;
+                f.write_str(function)
+            }
+            DrmBackendAppError::NoConnectedDisplay => f.write_str("no connected display found"),
+            DrmBackendAppError::NoCrtc => f.write_str("no crtc found for the connected display"),
+        }
+    }
+}
+
+impl Error for DrmBackendAppError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DrmBackendAppError::Io { io_error, .. } => Some(io_error),
+            _ => None,
+        }
+    }
+}
+
+impl DrmBackendAppError {
+    fn io(function: &'static str) -> impl FnOnce(io::Error) -> DrmBackendAppError {
+        move |io_error| Self::Io { io_error, function }
+    }
+}
+
+/// DRM device node, e.g. `/dev/dri/card0`
+struct Card(File);
+
+impl AsFd for Card {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+
+impl drm::Device for Card {}
+impl ControlDevice for Card {}
+
+/// First connected connector, its preferred mode and a CRTC able to drive it
+fn find_display(
+    card: &Card,
+) -> Result<(connector::Handle, Mode, crtc::Handle), DrmBackendAppError> {
+    let resources = card.resource_handles().map_err(DrmBackendAppError::io(
+        "drm::control::Device::resource_handles",
+    ))?;
+    for &handle in resources.connectors() {
+        let connector = card
+            .get_connector(handle, true)
+            .map_err(DrmBackendAppError::io(
+                "drm::control::Device::get_connector",
+            ))?;
+        if connector.state() != connector::State::Connected {
+            continue;
+        }
+        let Some(&mode) = connector
+            .modes()
+            .iter()
+            .find(|mode| mode.mode_type().contains(ModeTypeFlags::PREFERRED))
+            .or(connector.modes().first())
+        else {
+            continue;
+        };
+
+        // Keep the CRTC already driving the connector, or pick any compatible one
+        let current_crtc = connector
+            .current_encoder()
+            .and_then(|encoder| card.get_encoder(encoder).ok())
+            .and_then(|encoder| encoder.crtc());
+        let crtc = current_crtc.or_else(|| {
+            connector
+                .encoders()
+                .iter()
+                .filter_map(|&encoder| card.get_encoder(encoder).ok())
+                .find_map(|encoder| {
+                    resources
+                        .filter_crtcs(encoder.possible_crtcs())
+                        .first()
+                        .copied()
+                })
+        });
+        return crtc
+            .map(|crtc| (handle, mode, crtc))
+            .ok_or(DrmBackendAppError::NoCrtc);
+    }
+    Err(DrmBackendAppError::NoConnectedDisplay)
+}
+
+/// Run `egui_app_factory()` full screen on a DRM/KMS display, without a display server.
+///
+/// Opens `settings.drm_device`, renders to two `XRGB8888` dumb buffers and page flips between them on VBLANK.
+/// The window related settings of `settings.viewport_builder` are ignored, the preferred mode of the first connected
+/// display is used.
+///
+/// Input events are not handled yet, the app only repaints when egui requests it.
+pub fn run_app_with_drm_backend<T: App>(
+    settings: SoftwareBackendAppConfiguration,
+    egui_app_factory: impl FnMut(Context) -> T,
+) -> Result<(), DrmBackendAppError> {
+    let path = settings
+        .drm_device
+        .as_deref()
+        .unwrap_or(Path::new(DEFAULT_DRM_DEVICE));
+    let card = Card(
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .map_err(DrmBackendAppError::io("std::fs::File::open"))?,
+    );
+    let (connector, mode, crtc) = find_display(&card)?;
+    let (width, height) = mode.size();
+    let size = (width as u32, height as u32);
+
+    let mut dumb_buffers = Vec::with_capacity(2);
+    let mut framebuffers = Vec::with_capacity(2);
+    let result = (|| {
+        for _ in 0..2 {
+            let dumb_buffer = card
+                .create_dumb_buffer(size, DrmFourcc::Xrgb8888, 32)
+                .map_err(DrmBackendAppError::io(
+                    "drm::control::Device::create_dumb_buffer",
+                ))?;
+            framebuffers.push(card.add_framebuffer(&dumb_buffer, 24, 32).map_err(
+                DrmBackendAppError::io("drm::control::Device::add_framebuffer"),
+            )?);
+            dumb_buffers.push(dumb_buffer);
+        }
+        card.set_crtc(
+            crtc,
+            Some(framebuffers[0]),
+            (0, 0),
+            &[connector],
+            Some(mode),
+        )
+        .map_err(DrmBackendAppError::io("drm::control::Device::set_crtc"))?;
+        run_render_loop(
+            &card,
+            crtc,
+            size,
+            &mut dumb_buffers,
+            &framebuffers,
+            settings,
+            egui_app_factory,
+        )
+    })();
+
+    for framebuffer in framebuffers {
+        _ = card.destroy_framebuffer(framebuffer);
+    }
+    for dumb_buffer in dumb_buffers {
+        _ = card.destroy_dumb_buffer(dumb_buffer);
+    }
+    result
+}
+
+fn run_render_loop<T: App>(
+    card: &Card,
+    crtc: crtc::Handle,
+    (width, height): (u32, u32),
+    dumb_buffers: &mut [DumbBuffer],
+    framebuffers: &[framebuffer::Handle],
+    settings: SoftwareBackendAppConfiguration,
+    mut egui_app_factory: impl FnMut(Context) -> T,
+) -> Result<(), DrmBackendAppError> {
+    // XRGB8888 is stored as B, G, R, X bytes
+    let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Bgra)
+        .with_allow_raster_opt(settings.allow_raster_opt)
+        .with_convert_tris_to_rects(settings.convert_tris_to_rects)
+        .with_mode(settings.caching)
+        .with_color_temperature(settings.color_temperature)
+        .with_max_cache_memory_mb(settings.max_cache_memory_mb)
+        .with_max_cache_entries(settings.max_cache_entries)
+        .with_max_idle_frames(settings.max_idle_frames.unwrap_or_default());
+
+    // Rows may be padded, render to the whole pitch and show `width` pixels of each row
+    let stride = dumb_buffers[0].pitch() / 4;
+    let [front_buffer, back_buffer] = dumb_buffers else {
+        unreachable!("two dumb buffers");
+    };
+    let mut mappings = [
+        card.map_dumb_buffer(front_buffer)
+            .map_err(DrmBackendAppError::io(
+                "drm::control::Device::map_dumb_buffer",
+            ))?,
+        card.map_dumb_buffer(back_buffer)
+            .map_err(DrmBackendAppError::io(
+                "drm::control::Device::map_dumb_buffer",
+            ))?,
+    ];
+
+    let egui_ctx = Context::default();
+    let (repaint_sender, repaint_receiver) = mpsc::channel();
+    egui_ctx.set_request_repaint_callback(move |info| {
+        _ = repaint_sender.send(Instant::now() + info.delay);
+    });
+    let mut egui_app = egui_app_factory(egui_ctx.clone());
+
+    let start_time = Instant::now();
+    let mut last_frame_time = None;
+    let mut screenshot_requested = false;
+    let mut front = 0;
+    // Pixels of the front buffer that the back buffer is missing
+    let mut front_dirty_rect = DirtyRect::new_empty();
+    let mut redraw_everything_this_frame = true;
+    loop {
+        let start = Instant::now();
+        let raw_input = RawInput {
+            screen_rect: Some(Rect::from_min_size(
+                Pos2::ZERO,
+                vec2(width as f32, height as f32),
+            )),
+            time: Some(start_time.elapsed().as_secs_f64()),
+            ..Default::default()
+        };
+        let full_output = egui_ctx.run(raw_input, |ctx| {
+            egui_app.update(
+                ctx,
+                &mut SoftwareBackend {
+                    last_frame_time,
+                    renderer: &mut renderer,
+                    screenshot_requested: &mut screenshot_requested,
+                    stylus: None,
+                },
+            );
+        });
+        let close = full_output
+            .viewport_output
+            .values()
+            .any(|output| output.commands.contains(&ViewportCommand::Close));
+        let clipped_primitives =
+            egui_ctx.tessellate(full_output.shapes, full_output.pixels_per_point);
+
+        let back = 1 - front;
+        let [front_mapping, back_mapping] = &mut mappings;
+        let (front_mapping, back_mapping) = match back {
+            1 => (front_mapping, back_mapping),
+            _ => (back_mapping, front_mapping),
+        };
+        let front_pixels: &[[u8; 4]] = bytemuck::cast_slice(front_mapping);
+        let back_pixels: &mut [[u8; 4]] = bytemuck::cast_slice_mut(back_mapping);
+        // Bring the back buffer up to date, so only the pixels changed since the last frame are rendered
+        for y in front_dirty_rect.min_y..front_dirty_rect.max_y {
+            let row = (y * stride) as usize;
+            let span = row + front_dirty_rect.min_x as usize..row + front_dirty_rect.max_x as usize;
+            back_pixels[span.clone()].copy_from_slice(&front_pixels[span]);
+        }
+
+        let buffer_ref = &mut BufferMutRef::new(back_pixels, stride, height);
+        let dirty_rect = renderer.render(
+            buffer_ref,
+            mem::take(&mut redraw_everything_this_frame),
+            clipped_primitives,
+            &full_output.textures_delta,
+            full_output.pixels_per_point,
+        );
+        let pending_screenshot =
+            mem::take(&mut screenshot_requested).then(|| screenshot(buffer_ref));
+
+        if dirty_rect.is_empty() {
+            front_dirty_rect = DirtyRect::new_empty();
+        } else {
+            card.page_flip(crtc, framebuffers[back], PageFlipFlags::EVENT, None)
+                .map_err(DrmBackendAppError::io("drm::control::Device::page_flip"))?;
+            // Blocks until the flip happens on VBLANK
+            'vblank: loop {
+                for event in card.receive_events().map_err(DrmBackendAppError::io(
+                    "drm::control::Device::receive_events",
+                ))? {
+                    if let Event::PageFlip(_) = event {
+                        break 'vblank;
+                    }
+                }
+            }
+            front = back;
+            front_dirty_rect = dirty_rect;
+        }
+
+        if let Some(image) = pending_screenshot {
+            egui_app.on_screenshot(image);
+        }
+        last_frame_time = Some(start.elapsed());
+
+        if close {
+            egui_app.on_exit(&egui_ctx);
+            return Ok(());
+        }
+
+        // Sleep until the earliest repaint requested by egui
+        let Ok(mut when) = repaint_receiver.recv() else {
+            return Ok(());
+        };
+        while let Ok(next) =
+            repaint_receiver.recv_timeout(when.saturating_duration_since(Instant::now()))
+        {
+            when = when.min(next);
+        }
+    }
+}
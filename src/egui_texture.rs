@@ -1,41 +1,99 @@
-use alloc::vec::Vec;
+use alloc::boxed::Box;
+
 use egui::{Color32, TextureFilter, TextureOptions, Vec2, vec2};
 
 use crate::{
     ColorFieldOrder,
-    color::{swizzle_rgba_bgra, u8x4_to_vec4, vec4_to_u8x4},
+    color::{BILINEAR_ONE, SelectedImpl, premultiply_alpha_slice, u8x4_to_vec4, vec4_to_u8x4},
+    pixel_alloc::{PixelAlloc, PixelVec},
 };
 
+/// How texel coordinates outside of the texture are resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    /// Use the texel at the nearest edge
+    Clamp,
+    /// Tile the texture
+    Repeat,
+    /// Tile the texture, flipping every other tile
+    Mirror,
+}
+
+impl From<egui::TextureWrapMode> for WrapMode {
+    fn from(wrap_mode: egui::TextureWrapMode) -> Self {
+        match wrap_mode {
+            egui::TextureWrapMode::ClampToEdge => WrapMode::Clamp,
+            egui::TextureWrapMode::Repeat => WrapMode::Repeat,
+            egui::TextureWrapMode::MirroredRepeat => WrapMode::Mirror,
+        }
+    }
+}
+
+impl WrapMode {
+    /// Map texel coordinate `v` into `0..=extent`
+    #[inline(always)]
+    fn apply(self, v: i32, extent: i32) -> i32 {
+        match self {
+            WrapMode::Clamp => v.max(0).min(extent),
+            WrapMode::Repeat => v.rem_euclid(extent + 1),
+            WrapMode::Mirror => {
+                let size = extent + 1;
+                let v = v.rem_euclid(2 * size);
+                if v < size { v } else { 2 * size - 1 - v }
+            }
+        }
+    }
+}
+
+/// A texture uploaded by egui, as sampled by the renderer. See `EguiSoftwareRender::get_texture()`
+///
+/// Invariants:
+/// - `data()` holds `width() * height()` pixels, row by row, premultiplied, in the `ColorFieldOrder` of the renderer
+///   (`output_field_order`)
+/// - the sampled size, `fsize`, is `width() x height()` as floats
+/// - the premips, if any, are kept in sync with `data()` on every update
 pub struct EguiTexture {
-    pub data: Vec<[u8; 4]>,
+    pub(crate) data: PixelVec<[u8; 4]>,
     // Common case: The default egui texture has the top-left corner pixel fully white.
     // https://github.com/emilk/egui/blob/c97c065a575ec6e657bb42872890a00d0fb391c1/crates/epaint/src/lib.rs#L92
-    pub uv_zero_val: [u8; 4],
+    pub(crate) uv_zero_val: [u8; 4],
     /// width - 1
-    pub width_extent: i32,
+    pub(crate) width_extent: i32,
     /// height - 1
-    pub height_extent: i32,
-    pub width: usize,
-    #[allow(dead_code)]
-    pub height: usize,
-    pub fsize: Vec2,
-    pub options: TextureOptions,
+    pub(crate) height_extent: i32,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) fsize: Vec2,
+    /// Bumped by `EguiSoftwareRender::update_wgpu_texture()`, hashed with the meshes to invalidate their cache
+    #[cfg(feature = "wgpu")]
+    pub(crate) generation: u32,
+    pub(crate) options: TextureOptions,
+    pub(crate) wrap_mode: WrapMode,
+    /// Half the size, 2x2 box filtered, sampled instead when minified by 2 or more. See `EguiTexture::with_premip()`
+    pub(crate) premip: Option<Box<EguiTexture>>,
 }
 
 impl EguiTexture {
-    pub fn new(
+    pub(crate) fn new(
+        pixel_alloc: PixelAlloc,
         field_order: ColorFieldOrder,
         options: TextureOptions,
         size: [usize; 2],
         pixels: &[Color32],
     ) -> EguiTexture {
-        let data = pixels
-            .iter()
-            .map(|p| match field_order {
-                ColorFieldOrder::Rgba => p.to_array(),
-                ColorFieldOrder::Bgra => swizzle_rgba_bgra(p.to_array()),
-            })
-            .collect::<Vec<_>>();
+        let mut data = pixel_alloc.vec();
+        data.extend(pixels.iter().map(|p| p.to_array()));
+        if let ColorFieldOrder::Bgra = field_order {
+            crate::dispatch_simd_impl!(|simd_impl| simd_impl.swizzle_rgba_bgra_slice(&mut data));
+        }
+        Self::from_data(data, options, size)
+    }
+
+    fn from_data(
+        data: PixelVec<[u8; 4]>,
+        options: TextureOptions,
+        size: [usize; 2],
+    ) -> EguiTexture {
         let uv_zero_val = data[0];
         EguiTexture {
             data,
@@ -45,16 +103,188 @@ impl EguiTexture {
             height: size[1],
             fsize: vec2(size[0] as f32, size[1] as f32),
             options,
+            wrap_mode: options.wrap_mode.into(),
             uv_zero_val,
+            #[cfg(feature = "wgpu")]
+            generation: 0,
+            premip: None,
         }
     }
 
-    #[allow(dead_code)]
+    /// Pixels, `width()` per row, in the `ColorFieldOrder` of the renderer
+    pub fn data(&self) -> &[[u8; 4]] {
+        &self.data
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn options(&self) -> TextureOptions {
+        self.options
+    }
+
+    /// Generates up to `levels` premips, each half the size of the previous one, down to 1x1
+    ///
+    /// Minified textures skip most of their texels when sampled, and alias. A premip costs a third of the texture
+    /// memory at most, for all the levels.
+    pub(crate) fn with_premip(mut self, levels: u8) -> Self {
+        self.premip = self.half_level(levels);
+        self
+    }
+
+    fn half_level(&self, levels: u8) -> Option<Box<EguiTexture>> {
+        if levels == 0 || (self.width <= 1 && self.height <= 1) {
+            return None;
+        }
+        let size = [self.width.div_ceil(2), self.height.div_ceil(2)];
+        let mut data = PixelAlloc::of(&self.data).vec();
+        data.reserve_exact(size[0] * size[1]);
+        for y in 0..size[1] {
+            // The last row and column of odd sizes are averaged with themselves
+            let rows = [2 * y, (2 * y + 1).min(self.height - 1)]
+                .map(|row| &self.data[row * self.width..(row + 1) * self.width]);
+            for x in 0..size[0] {
+                let mut sum = [0u32; 4];
+                for row in rows {
+                    for col in [2 * x, (2 * x + 1).min(self.width - 1)] {
+                        for (sum, channel) in sum.iter_mut().zip(row[col]) {
+                            *sum += u32::from(channel);
+                        }
+                    }
+                }
+                data.push(sum.map(|sum| ((sum + 2) / 4) as u8));
+            }
+        }
+        let half = Self::from_data(data, self.options, size);
+        Some(Box::new(half.with_premip(levels - 1)))
+    }
+
+    /// Levels of `self.premip`
+    fn premip_levels(&self) -> u8 {
+        let mut levels = 0;
+        let mut texture = self;
+        while let Some(half) = &texture.premip {
+            levels += 1;
+            texture = half;
+        }
+        levels
+    }
+
+    /// `self` or the premip level to sample, minified by `texels_per_px`
+    #[inline]
+    pub fn level_for(&self, mut texels_per_px: f32) -> &EguiTexture {
+        let mut texture = self;
+        while let Some(half) = &texture.premip
+            && texels_per_px >= 2.0
+        {
+            texture = half;
+            texels_per_px *= 0.5;
+        }
+        texture
+    }
+
+    /// Overwrites the `size` pixels at `pos` with `pixels`, a row at a time
+    pub(crate) fn update_region(
+        &mut self,
+        field_order: ColorFieldOrder,
+        straight_alpha: bool,
+        pos: [usize; 2],
+        size: [usize; 2],
+        pixels: &[Color32],
+    ) {
+        if size[0] == 0 {
+            return;
+        }
+        crate::dispatch_simd_impl!(|simd_impl| {
+            for (y, src) in pixels.chunks_exact(size[0]).take(size[1]).enumerate() {
+                let start = pos[0] + (pos[1] + y) * self.width;
+                let dest = &mut self.data[start..start + size[0]];
+                for (dest, src) in dest.iter_mut().zip(src) {
+                    *dest = src.to_array();
+                }
+                if let ColorFieldOrder::Bgra = field_order {
+                    simd_impl.swizzle_rgba_bgra_slice(dest);
+                }
+                if straight_alpha {
+                    simd_impl.premultiply_alpha_slice(dest);
+                }
+            }
+        });
+        self.pixels_changed(pos, size);
+    }
+
+    /// Keeps `uv_zero_val` and the premip in sync with the `size` pixels at `pos`, once overwritten
+    fn pixels_changed(&mut self, pos: [usize; 2], size: [usize; 2]) {
+        if pos == [0, 0] && size[0] > 0 && size[1] > 0 {
+            self.uv_zero_val = self.data[0];
+        }
+        self.premip = self.half_level(self.premip_levels());
+    }
+
+    /// `rect`, as `[x, y, width, height]`, is within the texture
+    fn contains(&self, [x, y, width, height]: [usize; 4]) -> bool {
+        x.checked_add(width)
+            .is_some_and(|max_x| max_x <= self.width)
+            && y.checked_add(height)
+                .is_some_and(|max_y| max_y <= self.height)
+    }
+
+    /// Overwrites the pixels of `rect`, as `[x, y, width, height]`, with `color` in the order of the texture
+    ///
+    /// Returns false, leaving the texture as is, if `rect` isn't within the texture.
+    pub(crate) fn fill_rect(&mut self, rect: [usize; 4], color: [u8; 4]) -> bool {
+        if !self.contains(rect) {
+            return false;
+        }
+        let [x, y, width, height] = rect;
+        for row in y..y + height {
+            let start = x + row * self.width;
+            self.data[start..start + width].fill(color);
+        }
+        self.pixels_changed([x, y], [width, height]);
+        true
+    }
+
+    /// `blit()` within the same texture, the rects can overlap
+    pub(crate) fn copy_within(&mut self, src_rect: [usize; 4], dst_origin: [usize; 2]) -> bool {
+        let [x, y, width, height] = src_rect;
+        if !self.contains(src_rect) || !self.contains([dst_origin[0], dst_origin[1], width, height])
+        {
+            return false;
+        }
+        // Rows are copied away from the overlap, so none is overwritten before being copied
+        let mut copy_row = |row: usize| {
+            let src_start = x + (y + row) * self.width;
+            let dst_start = dst_origin[0] + (dst_origin[1] + row) * self.width;
+            self.data
+                .copy_within(src_start..src_start + width, dst_start);
+        };
+        if dst_origin[1] > y {
+            (0..height).rev().for_each(&mut copy_row);
+        } else {
+            (0..height).for_each(&mut copy_row);
+        }
+        self.pixels_changed(dst_origin, [width, height]);
+        true
+    }
+
+    /// Premultiplies straight alpha `data`, see `EguiSoftwareRender::with_straight_alpha_input()`
+    pub(crate) fn premultiply_alpha(&mut self) {
+        premultiply_alpha_slice(&mut self.data);
+        self.uv_zero_val = self.data[0];
+        self.premip = self.half_level(self.premip_levels());
+    }
+
     pub fn sample_nearest(&self, uv: Vec2) -> [u8; 4] {
-        let ss_x = ((uv.x * self.fsize.x) as i32).max(0).min(self.width_extent);
-        let ss_y = ((uv.y * self.fsize.y) as i32)
-            .max(0)
-            .min(self.height_extent);
+        let ss_x = (uv.x * self.fsize.x).floor() as i32;
+        let ss_y = (uv.y * self.fsize.y).floor() as i32;
+        let ss_x = self.wrap_mode.apply(ss_x, self.width_extent);
+        let ss_y = self.wrap_mode.apply(ss_y, self.height_extent);
         self.data[ss_x as usize + ss_y as usize * self.width]
     }
 
@@ -63,39 +293,32 @@ impl EguiTexture {
             return self.uv_zero_val;
         }
 
-        let w = self.fsize.x;
-        let h = self.fsize.y;
-
-        #[inline(always)]
-        fn mirror(v: f32) -> f32 {
-            ((v * 0.5 + 0.5).fract() - 0.5) * 2.0
+        if self.options.magnification == TextureFilter::Nearest {
+            return self.sample_nearest(uv);
         }
 
-        let uv = match self.options.wrap_mode {
-            egui::TextureWrapMode::ClampToEdge => uv,
-            egui::TextureWrapMode::Repeat => vec2(uv.x.fract(), uv.y.fract()),
-            egui::TextureWrapMode::MirroredRepeat => vec2(mirror(uv.x), mirror(uv.y)),
-        };
+        let w = self.fsize.x;
+        let h = self.fsize.y;
 
         let sx = uv.x * w - 0.5;
         let sy = uv.y * h - 0.5;
 
         let x0 = sx.floor() as i32;
         let y0 = sy.floor() as i32;
-        let x1 = x0 + 1;
-        let y1 = y0 + 1;
+        let x1 = x0.saturating_add(1);
+        let y1 = y0.saturating_add(1);
 
         let fx = sx - x0 as f32;
         let fy = sy - y0 as f32;
 
-        let x0c = x0.max(0).min(self.width_extent);
-        let y0c = y0.max(0).min(self.height_extent);
-        let x1c = x1.max(0).min(self.width_extent);
-        let y1c = y1.max(0).min(self.height_extent);
+        let x0c = self.wrap_mode.apply(x0, self.width_extent);
+        let y0c = self.wrap_mode.apply(y0, self.height_extent);
+        let x1c = self.wrap_mode.apply(x1, self.width_extent);
+        let y1c = self.wrap_mode.apply(y1, self.height_extent);
 
         let c00 = self.data[(x0c as usize) + (y0c as usize) * self.width];
 
-        if self.options.magnification == TextureFilter::Nearest || (fx == 0.0 && fy == 0.0) {
+        if fx == 0.0 && fy == 0.0 {
             // if these are 0 the px at 0,0 will have full influence. Equivalent to nearest sampling.
             return c00;
         }
@@ -116,4 +339,194 @@ impl EguiTexture {
 
         vec4_to_u8x4(&(v00 * w00 + v01 * w01 + v10 * w10 + v11 * w11))
     }
+
+    /// `sample_bilinear()` for 4 uvs at once, with `bilinear_u8x4` weights so the interpolation can use SIMD
+    ///
+    /// Results can differ by 1 from `sample_bilinear()`.
+    pub(crate) fn sample_bilinear_4x(
+        &self,
+        simd_impl: impl SelectedImpl,
+        uvs: [Vec2; 4],
+    ) -> [[u8; 4]; 4] {
+        if self.options.magnification == TextureFilter::Nearest {
+            return uvs.map(|uv| self.sample_nearest(uv));
+        }
+
+        // Per lane arithmetic first, so it can be vectorized
+        let sx = uvs.map(|uv| uv.x * self.fsize.x - 0.5);
+        let sy = uvs.map(|uv| uv.y * self.fsize.y - 0.5);
+        let x0 = sx.map(floor_i32);
+        let y0 = sy.map(floor_i32);
+        let weight = |s: f32, s0: i32| ((s - s0 as f32) * BILINEAR_ONE as f32 + 0.5) as u32;
+        let fx = core::array::from_fn(|i| weight(sx[i], x0[i]));
+        let fy = core::array::from_fn(|i| weight(sy[i], y0[i]));
+
+        let mut corners = [[[0; 4]; 4]; 4];
+        for i in 0..4 {
+            let x0c = self.wrap_mode.apply(x0[i], self.width_extent) as usize;
+            let y0c = self.wrap_mode.apply(y0[i], self.height_extent) as usize;
+            let x1c = self
+                .wrap_mode
+                .apply(x0[i].saturating_add(1), self.width_extent) as usize;
+            let y1c = self
+                .wrap_mode
+                .apply(y0[i].saturating_add(1), self.height_extent) as usize;
+
+            corners[0][i] = self.data[x0c + y0c * self.width];
+            corners[1][i] = self.data[x1c + y0c * self.width];
+            corners[2][i] = self.data[x0c + y1c * self.width];
+            corners[3][i] = self.data[x1c + y1c * self.width];
+        }
+
+        let mut colors = simd_impl.bilinear_u8x4_4x(&corners, fx, fy);
+        for (color, uv) in colors.iter_mut().zip(uvs) {
+            if uv == Vec2::ZERO {
+                *color = self.uv_zero_val;
+            }
+        }
+        colors
+    }
+}
+
+/// Copies the pixels of `src_rect`, as `[x, y, width, height]`, from `src` to `dst` at `dst_origin`, e.g. to pack
+/// sprites in an atlas. The textures must have the same `ColorFieldOrder`.
+///
+/// Returns false, copying nothing, if `src_rect` isn't within `src` or doesn't fit in `dst` at `dst_origin`.
+pub(crate) fn blit(
+    src: &EguiTexture,
+    src_rect: [usize; 4],
+    dst: &mut EguiTexture,
+    dst_origin: [usize; 2],
+) -> bool {
+    let [x, y, width, height] = src_rect;
+    if !src.contains(src_rect) || !dst.contains([dst_origin[0], dst_origin[1], width, height]) {
+        return false;
+    }
+    for row in 0..height {
+        let src_start = x + (y + row) * src.width;
+        let dst_start = dst_origin[0] + (dst_origin[1] + row) * dst.width;
+        dst.data[dst_start..dst_start + width]
+            .copy_from_slice(&src.data[src_start..src_start + width]);
+    }
+    dst.pixels_changed(dst_origin, [width, height]);
+    true
+}
+
+/// `v.floor() as i32` without the `floorf` call `core` lowers `floor()` to when SSE4.1 isn't enabled
+#[inline(always)]
+fn floor_i32(v: f32) -> i32 {
+    let t = v as i32;
+    t - ((t as f32) > v) as i32
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use egui::TextureWrapMode;
+
+    /// 4 x 2 texture, the last column is red on the first row and green on the second
+    fn edge_texture(wrap_mode: TextureWrapMode) -> EguiTexture {
+        let mut pixels = [Color32::from_rgb(0, 0, 200); 8];
+        pixels[3] = Color32::from_rgb(200, 0, 0);
+        pixels[7] = Color32::from_rgb(0, 200, 0);
+        let options = TextureOptions {
+            wrap_mode,
+            ..TextureOptions::LINEAR
+        };
+        EguiTexture::new(
+            PixelAlloc::default(),
+            ColorFieldOrder::Rgba,
+            options,
+            [4, 2],
+            &pixels,
+        )
+    }
+
+    #[test]
+    fn premip_box_filter() {
+        // 3 x 2, the odd column is averaged with itself
+        let pixels =
+            [10, 30, 200, 50, 70, 100].map(|v| Color32::from_rgba_premultiplied(v, v, v, v));
+        let texture = EguiTexture::new(
+            PixelAlloc::default(),
+            ColorFieldOrder::Rgba,
+            TextureOptions::LINEAR,
+            [3, 2],
+            &pixels,
+        )
+        .with_premip(u8::MAX);
+        let half = texture.premip.as_deref().unwrap();
+        assert_eq!((half.width, half.height), (2, 1));
+        assert_eq!(half.data[..], [[40; 4], [150; 4]]);
+        assert_eq!(half.premip.as_deref().unwrap().data[..], [[95; 4]]);
+        assert_eq!(texture.premip_levels(), 2);
+
+        assert!(core::ptr::eq(texture.level_for(1.9), &texture));
+        assert!(core::ptr::eq(texture.level_for(2.0), half));
+        assert_eq!(texture.level_for(100.0).width, 1);
+    }
+
+    #[test]
+    fn blit_and_fill_rect() {
+        let new_texture = |size: [usize; 2], pixels: &[Color32]| {
+            EguiTexture::new(
+                PixelAlloc::default(),
+                ColorFieldOrder::Rgba,
+                TextureOptions::LINEAR,
+                size,
+                pixels,
+            )
+        };
+        let gray = |v| Color32::from_gray(v);
+        let src = new_texture([3, 2], &[1, 2, 3, 4, 5, 6].map(gray));
+        let mut dst = new_texture([4, 3], &[gray(0); 12]).with_premip(1);
+
+        assert!(blit(&src, [1, 0, 2, 2], &mut dst, [2, 1]));
+        let values = |texture: &EguiTexture| {
+            texture
+                .data
+                .iter()
+                .map(|p| p[0])
+                .collect::<alloc::vec::Vec<_>>()
+        };
+        assert_eq!(values(&dst), [0, 0, 0, 0, 0, 0, 2, 3, 0, 0, 5, 6]);
+        assert_eq!(dst.premip.as_deref().unwrap().data[1][0], 1);
+
+        // Out of bounds, or overflowing, changes nothing
+        assert!(!blit(&src, [2, 0, 2, 1], &mut dst, [0, 0]));
+        assert!(!blit(&src, [0, 0, 2, 2], &mut dst, [3, 0]));
+        assert!(!blit(&src, [0, 0, usize::MAX, 1], &mut dst, [0, 0]));
+        assert!(!dst.fill_rect([0, 2, 1, 2], [9; 4]));
+        assert_eq!(values(&dst), [0, 0, 0, 0, 0, 0, 2, 3, 0, 0, 5, 6]);
+
+        assert!(dst.fill_rect([0, 0, 2, 1], [9; 4]));
+        assert_eq!(dst.uv_zero_val, [9; 4]);
+
+        // Overlapping, downwards then upwards
+        assert!(dst.copy_within([2, 1, 2, 2], [1, 0]));
+        assert_eq!(values(&dst), [9, 2, 3, 0, 0, 5, 6, 3, 0, 0, 5, 6]);
+        assert!(dst.copy_within([1, 0, 2, 2], [0, 1]));
+        assert_eq!(values(&dst), [9, 2, 3, 0, 2, 3, 6, 3, 5, 6, 5, 6]);
+    }
+
+    #[test]
+    fn bilinear_right_edge() {
+        let uv = vec2(1.0, 0.5);
+
+        // Clamped: only the last column, halfway between the two rows
+        let texture = edge_texture(TextureWrapMode::ClampToEdge);
+        assert_eq!(texture.sample_bilinear(uv), [100, 100, 0, 255]);
+        let just_inside = texture.sample_bilinear(vec2(1.0 - f32::EPSILON, 0.5));
+        assert_eq!(just_inside, [100, 100, 0, 255]);
+        crate::dispatch_simd_impl!(|simd_impl| {
+            assert_eq!(
+                texture.sample_bilinear_4x(simd_impl, [uv; 4]),
+                [[100, 100, 0, 255]; 4]
+            );
+        });
+
+        // Repeated: blends with the first column, like a GPU sampler
+        let texture = edge_texture(TextureWrapMode::Repeat);
+        assert_eq!(texture.sample_bilinear(uv), [50, 50, 100, 255]);
+    }
 }
@@ -0,0 +1,308 @@
+#![allow(unsafe_code)]
+//! Reads the `/dev/input/event*` devices for `run_app_with_framebuffer()`, without `libevdev`
+
+use egui::{Event, Key, Modifiers, PointerButton, Pos2, Vec2};
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Read};
+use std::mem::{offset_of, size_of};
+use std::os::fd::{AsRawFd, RawFd};
+use std::os::unix::fs::OpenOptionsExt;
+use std::vec::Vec;
+
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_REL: u16 = 0x02;
+const EV_ABS: u16 = 0x03;
+
+const REL_X: u16 = 0x00;
+const REL_Y: u16 = 0x01;
+const REL_HWHEEL: u16 = 0x06;
+const REL_WHEEL: u16 = 0x08;
+
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+
+const BTN_LEFT: u16 = 0x110;
+const BTN_RIGHT: u16 = 0x111;
+const BTN_MIDDLE: u16 = 0x112;
+const BTN_TOUCH: u16 = 0x14a;
+
+const KEY_LEFTCTRL: u16 = 29;
+const KEY_LEFTSHIFT: u16 = 42;
+const KEY_RIGHTSHIFT: u16 = 54;
+const KEY_LEFTALT: u16 = 56;
+const KEY_RIGHTCTRL: u16 = 97;
+const KEY_RIGHTALT: u16 = 100;
+
+/// `_IOR('E', 0x40 + abs, struct input_absinfo)`
+const fn eviocgabs(abs: u16) -> libc::c_ulong {
+    (2 << 30)
+        | ((size_of::<libc::input_absinfo>() as libc::c_ulong) << 16)
+        | (0x45 << 8)
+        | (0x40 + abs as libc::c_ulong)
+}
+
+/// Points scrolled by a `REL_WHEEL` notch
+const WHEEL_NOTCH_POINTS: f32 = 50.0;
+
+/// Keyboard (US layout) and pointer state of all the input devices
+pub(crate) struct InputDevices {
+    devices: Vec<InputDevice>,
+    pointer: Pos2,
+    modifiers: Modifiers,
+    shift: bool,
+}
+
+struct InputDevice {
+    file: File,
+    /// `(minimum, maximum)` of the touchscreen or tablet axes
+    abs_x: Option<(i32, i32)>,
+    abs_y: Option<(i32, i32)>,
+    /// A pointer move to report on the next `EV_SYN`
+    moved: bool,
+    /// Button changes to report on the next `EV_SYN`, after the touch position of the same report is known
+    buttons: Vec<(PointerButton, bool)>,
+    touch_released: bool,
+}
+
+fn abs_range(file: &File, abs: u16) -> Option<(i32, i32)> {
+    let mut info = libc::input_absinfo {
+        value: 0,
+        minimum: 0,
+        maximum: 0,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+    // SAFETY: EVIOCGABS writes an `input_absinfo`
+    let result = unsafe { libc::ioctl(file.as_raw_fd(), eviocgabs(abs) as _, &mut info) };
+    (result >= 0 && info.maximum > info.minimum).then_some((info.minimum, info.maximum))
+}
+
+impl InputDevices {
+    /// Opens every readable `/dev/input/event*` device, devices that can't be opened are skipped
+    pub fn open_all() -> Self {
+        let mut devices = Vec::new();
+        if let Ok(entries) = fs::read_dir("/dev/input") {
+            for entry in entries.flatten() {
+                if !entry.file_name().to_string_lossy().starts_with("event") {
+                    continue;
+                }
+                let Ok(file) = OpenOptions::new()
+                    .read(true)
+                    .custom_flags(libc::O_NONBLOCK)
+                    .open(entry.path())
+                else {
+                    continue;
+                };
+                devices.push(InputDevice {
+                    abs_x: abs_range(&file, ABS_X),
+                    abs_y: abs_range(&file, ABS_Y),
+                    file,
+                    moved: false,
+                    buttons: Vec::new(),
+                    touch_released: false,
+                });
+            }
+        }
+        Self {
+            devices,
+            pointer: Pos2::ZERO,
+            modifiers: Modifiers::NONE,
+            shift: false,
+        }
+    }
+
+    pub fn fds(&self) -> impl Iterator<Item = RawFd> + '_ {
+        self.devices.iter().map(|device| device.file.as_raw_fd())
+    }
+
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// Appends the egui events of everything read since the last call, `screen_size` in points
+    pub fn read_events(&mut self, screen_size: Vec2, events: &mut Vec<Event>) {
+        const EVENT_SIZE: usize = size_of::<libc::input_event>();
+        let mut buf = [0u8; EVENT_SIZE * 64];
+        for device_idx in 0..self.devices.len() {
+            loop {
+                let read = match self.devices[device_idx].file.read(&mut buf) {
+                    Ok(read) => read,
+                    Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                    Err(_) => break,
+                };
+                if read == 0 {
+                    break;
+                }
+                for raw in buf[..read].chunks_exact(EVENT_SIZE) {
+                    let field = |offset: usize| [raw[offset], raw[offset + 1]];
+                    let kind = u16::from_ne_bytes(field(offset_of!(libc::input_event, type_)));
+                    let code = u16::from_ne_bytes(field(offset_of!(libc::input_event, code)));
+                    let value_offset = offset_of!(libc::input_event, value);
+                    let value = i32::from_ne_bytes([
+                        raw[value_offset],
+                        raw[value_offset + 1],
+                        raw[value_offset + 2],
+                        raw[value_offset + 3],
+                    ]);
+                    self.handle_event(device_idx, kind, code, value, screen_size, events);
+                }
+            }
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        device_idx: usize,
+        kind: u16,
+        code: u16,
+        value: i32,
+        screen_size: Vec2,
+        events: &mut Vec<Event>,
+    ) {
+        let device = &mut self.devices[device_idx];
+        match (kind, code) {
+            (EV_SYN, _) => {
+                if core::mem::take(&mut device.moved) {
+                    events.push(Event::PointerMoved(self.pointer));
+                }
+                for (button, pressed) in device.buttons.drain(..) {
+                    events.push(Event::PointerButton {
+                        pos: self.pointer,
+                        button,
+                        pressed,
+                        modifiers: self.modifiers,
+                    });
+                }
+                if core::mem::take(&mut device.touch_released) {
+                    events.push(Event::PointerGone);
+                }
+            }
+            (EV_REL, REL_X) => {
+                self.pointer.x = (self.pointer.x + value as f32).clamp(0.0, screen_size.x);
+                device.moved = true;
+            }
+            (EV_REL, REL_Y) => {
+                self.pointer.y = (self.pointer.y + value as f32).clamp(0.0, screen_size.y);
+                device.moved = true;
+            }
+            (EV_REL, REL_WHEEL | REL_HWHEEL) => {
+                let delta = value as f32 * WHEEL_NOTCH_POINTS;
+                events.push(Event::MouseWheel {
+                    unit: egui::MouseWheelUnit::Point,
+                    delta: match code {
+                        REL_WHEEL => Vec2::new(0.0, delta),
+                        _ => Vec2::new(delta, 0.0),
+                    },
+                    modifiers: self.modifiers,
+                });
+            }
+            (EV_ABS, ABS_X | ABS_Y) => {
+                let (range, size, coord) = match code {
+                    ABS_X => (device.abs_x, screen_size.x, &mut self.pointer.x),
+                    _ => (device.abs_y, screen_size.y, &mut self.pointer.y),
+                };
+                if let Some((min, max)) = range {
+                    *coord = (value - min) as f32 / (max - min) as f32 * size;
+                    device.moved = true;
+                }
+            }
+            (EV_KEY, BTN_LEFT | BTN_RIGHT | BTN_MIDDLE | BTN_TOUCH) => {
+                let button = match code {
+                    BTN_RIGHT => PointerButton::Secondary,
+                    BTN_MIDDLE => PointerButton::Middle,
+                    _ => PointerButton::Primary,
+                };
+                device.buttons.push((button, value != 0));
+                device.touch_released |= code == BTN_TOUCH && value == 0;
+            }
+            (EV_KEY, _) => self.handle_key(code, value, events),
+            _ => {}
+        }
+    }
+
+    fn handle_key(&mut self, code: u16, value: i32, events: &mut Vec<Event>) {
+        // 0 release, 1 press, 2 autorepeat
+        let pressed = value != 0;
+        match code {
+            KEY_LEFTSHIFT | KEY_RIGHTSHIFT => {
+                self.shift = pressed;
+                self.modifiers.shift = pressed;
+                return;
+            }
+            KEY_LEFTCTRL | KEY_RIGHTCTRL => {
+                self.modifiers.ctrl = pressed;
+                self.modifiers.command = pressed;
+                return;
+            }
+            KEY_LEFTALT | KEY_RIGHTALT => {
+                self.modifiers.alt = pressed;
+                return;
+            }
+            _ => {}
+        }
+
+        let (key, text) = key_from_code(code, self.shift);
+        if let Some(key) = key {
+            events.push(Event::Key {
+                key,
+                physical_key: None,
+                pressed,
+                repeat: value == 2,
+                modifiers: self.modifiers,
+            });
+        }
+        if let Some(text) = text
+            && pressed
+            && !self.modifiers.ctrl
+            && !self.modifiers.alt
+        {
+            events.push(Event::Text(text.into()));
+        }
+    }
+}
+
+/// `egui::Key` and US layout text of a Linux `KEY_*` code
+fn key_from_code(code: u16, shift: bool) -> (Option<Key>, Option<&'static str>) {
+    const ROWS: [(u16, &str, &str); 4] = [
+        (2, "1234567890-=", "!@#$%^&*()_+"),
+        (16, "qwertyuiop[]", "QWERTYUIOP{}"),
+        (30, "asdfghjkl;'`", "ASDFGHJKL:\"~"),
+        (43, "\\zxcvbnm,./", "|ZXCVBNM<>?"),
+    ];
+    for (first, lower, upper) in ROWS {
+        if let Some(idx) = code.checked_sub(first).map(usize::from)
+            && idx < lower.len()
+        {
+            let text = match shift {
+                false => &lower[idx..idx + 1],
+                true => &upper[idx..idx + 1],
+            };
+            return (
+                Key::from_name(&lower[idx..idx + 1].to_uppercase()),
+                Some(text),
+            );
+        }
+    }
+    let key = match code {
+        1 => Key::Escape,
+        14 => Key::Backspace,
+        15 => Key::Tab,
+        28 | 96 => Key::Enter,
+        57 => return (Some(Key::Space), Some(" ")),
+        102 => Key::Home,
+        103 => Key::ArrowUp,
+        104 => Key::PageUp,
+        105 => Key::ArrowLeft,
+        106 => Key::ArrowRight,
+        107 => Key::End,
+        108 => Key::ArrowDown,
+        109 => Key::PageDown,
+        110 => Key::Insert,
+        111 => Key::Delete,
+        59..=68 => Key::from_name(&std::format!("F{}", code - 58)).unwrap_or(Key::F1),
+        _ => return (None, None),
+    };
+    (Some(key), None)
+}
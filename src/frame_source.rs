@@ -0,0 +1,78 @@
+use alloc::vec::Vec;
+
+use crate::{BufferMutRef, DirtyRect, EguiSoftwareRender, EguiSoftwareRenderCanvas};
+
+/// Everything `EguiSoftwareRender` needs to render one egui frame
+pub struct SoftwareFrame {
+    pub paint_jobs: Vec<egui::ClippedPrimitive>,
+    pub textures_delta: egui::TexturesDelta,
+    pub pixels_per_point: f32,
+    /// Size of the output in pixels, the buffer to render to should have this size
+    pub size: (u32, u32),
+}
+
+impl SoftwareFrame {
+    /// Tessellate the shapes of `output`, returned by `ctx.run()`
+    pub fn from_full_output(ctx: &egui::Context, output: egui::FullOutput) -> Self {
+        let size = output_size(ctx, output.pixels_per_point);
+        Self {
+            paint_jobs: ctx.tessellate(output.shapes, output.pixels_per_point),
+            textures_delta: output.textures_delta,
+            pixels_per_point: output.pixels_per_point,
+            size,
+        }
+    }
+}
+
+/// Size in pixels of the content rect of the last `ctx.run()`
+pub(crate) fn output_size(ctx: &egui::Context, pixels_per_point: f32) -> (u32, u32) {
+    let width = (ctx.content_rect().width() * pixels_per_point) as u32;
+    let height = (ctx.content_rect().height() * pixels_per_point) as u32;
+    (width, height)
+}
+
+/// Produces the egui frames to render, separating what is rendered from the buffer it is rendered to
+///
+/// Integrating with a windowing system (SDL2, raw Wayland, ...) only takes an implementation of this trait, that
+/// feeds the window events to `ctx.run()`, and a buffer to give to `EguiSoftwareRender::render_frame()`.
+pub trait SoftwareFrameSource {
+    fn next_frame(&mut self, ctx: &egui::Context) -> SoftwareFrame;
+}
+
+/// Any `|ctx| ctx.run(raw_input, |ctx| ...)` closure
+impl<F: FnMut(&egui::Context) -> egui::FullOutput> SoftwareFrameSource for F {
+    fn next_frame(&mut self, ctx: &egui::Context) -> SoftwareFrame {
+        let output = self(ctx);
+        SoftwareFrame::from_full_output(ctx, output)
+    }
+}
+
+impl EguiSoftwareRender {
+    /// `EguiSoftwareRender::render()` of a `SoftwareFrame`
+    pub fn render_frame(
+        &mut self,
+        buffer_ref: &mut BufferMutRef,
+        redraw_everything_this_frame: bool,
+        frame: SoftwareFrame,
+    ) -> DirtyRect {
+        self.render(
+            buffer_ref,
+            redraw_everything_this_frame,
+            frame.paint_jobs,
+            &frame.textures_delta,
+            frame.pixels_per_point,
+        )
+    }
+}
+
+impl EguiSoftwareRenderCanvas {
+    /// `EguiSoftwareRenderCanvas::render()` of a `SoftwareFrame`
+    pub fn render_frame(&mut self, buffer_ref: &mut BufferMutRef, frame: SoftwareFrame) {
+        self.render(
+            buffer_ref,
+            frame.paint_jobs,
+            &frame.textures_delta,
+            frame.pixels_per_point,
+        );
+    }
+}
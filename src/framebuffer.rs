@@ -0,0 +1,392 @@
+#![allow(unsafe_code)]
+use crate::evdev::InputDevices;
+use crate::winit::{App, SoftwareBackend, SoftwareBackendAppConfiguration};
+use crate::{BufferMutRef, ColorFieldOrder, DirtyRect, EguiSoftwareRender};
+use egui::mutex::Mutex;
+use egui::{Context, Pos2, RawInput, Rect, ViewportCommand, vec2};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::mem;
+use std::os::fd::AsRawFd;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+use std::vec::Vec;
+
+const FBIOGET_VSCREENINFO: libc::c_ulong = 0x4600;
+const FBIOGET_FSCREENINFO: libc::c_ulong = 0x4602;
+/// `_IOW('F', 0x20, __u32)`
+const FBIO_WAITFORVSYNC: libc::c_ulong = 0x4004_4620;
+
+/// Errors that can occur when using the egui software backend with a Linux framebuffer device.
+#[derive(Debug)]
+pub enum FramebufferAppError {
+    /// Opening, mapping or querying the framebuffer device has failed.
+    Io {
+        io_error: io::Error,
+        function: &'static str,
+    },
+
+    /// The framebuffer is neither ARGB8888 nor RGB565.
+    UnsupportedFormat { bits_per_pixel: u32 },
+}
+
+impl Display for FramebufferAppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FramebufferAppError::Io { function, .. } => {
+                f.write_str("error calling ")?;
+                f.write_str(function)
+            }
+            FramebufferAppError::UnsupportedFormat { bits_per_pixel } => {
+                write!(
+                    f,
+                    "unsupported {bits_per_pixel} bits per pixel framebuffer format"
+                )
+            }
+        }
+    }
+}
+
+impl Error for FramebufferAppError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FramebufferAppError::Io { io_error, .. } => Some(io_error),
+            _ => None,
+        }
+    }
+}
+
+impl FramebufferAppError {
+    fn io(function: &'static str) -> impl FnOnce(io::Error) -> FramebufferAppError {
+        move |io_error| Self::Io { io_error, function }
+    }
+}
+
+/// `struct fb_bitfield`
+#[repr(C)]
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+struct FbBitfield {
+    offset: u32,
+    length: u32,
+    msb_right: u32,
+}
+
+/// `struct fb_var_screeninfo`
+#[repr(C)]
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+struct FbVarScreenInfo {
+    xres: u32,
+    yres: u32,
+    xres_virtual: u32,
+    yres_virtual: u32,
+    xoffset: u32,
+    yoffset: u32,
+    bits_per_pixel: u32,
+    grayscale: u32,
+    red: FbBitfield,
+    green: FbBitfield,
+    blue: FbBitfield,
+    transp: FbBitfield,
+    nonstd: u32,
+    activate: u32,
+    height: u32,
+    width: u32,
+    accel_flags: u32,
+    pixclock: u32,
+    left_margin: u32,
+    right_margin: u32,
+    upper_margin: u32,
+    lower_margin: u32,
+    hsync_len: u32,
+    vsync_len: u32,
+    sync: u32,
+    vmode: u32,
+    rotate: u32,
+    colorspace: u32,
+    reserved: [u32; 4],
+}
+
+/// `struct fb_fix_screeninfo`
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct FbFixScreenInfo {
+    id: [u8; 16],
+    smem_start: libc::c_ulong,
+    smem_len: u32,
+    type_: u32,
+    type_aux: u32,
+    visual: u32,
+    xpanstep: u16,
+    ypanstep: u16,
+    ywrapstep: u16,
+    line_length: u32,
+    mmio_start: libc::c_ulong,
+    mmio_len: u32,
+    accel: u32,
+    capabilities: u16,
+    reserved: [u16; 2],
+}
+
+#[derive(Clone, Copy)]
+enum PixelFormat {
+    /// 32 bits per pixel, with the red channel at bit 0 (`Rgba`) or 16 (`Bgra`) in memory
+    Argb8888(ColorFieldOrder),
+    Rgb565,
+}
+
+impl PixelFormat {
+    fn new(var: &FbVarScreenInfo) -> Result<Self, FramebufferAppError> {
+        let rgb_lengths = (var.red.length, var.green.length, var.blue.length);
+        match (var.bits_per_pixel, var.red.offset, rgb_lengths) {
+            (32, 16, (8, 8, 8)) => Ok(Self::Argb8888(ColorFieldOrder::Bgra)),
+            (32, 0, (8, 8, 8)) => Ok(Self::Argb8888(ColorFieldOrder::Rgba)),
+            (16, 11, (5, 6, 5)) => Ok(Self::Rgb565),
+            _ => Err(FramebufferAppError::UnsupportedFormat {
+                bits_per_pixel: var.bits_per_pixel,
+            }),
+        }
+    }
+
+    /// Order of the canvas rendered by `EguiSoftwareRender`
+    const fn canvas_order(self) -> ColorFieldOrder {
+        match self {
+            Self::Argb8888(order) => order,
+            Self::Rgb565 => ColorFieldOrder::Bgra,
+        }
+    }
+}
+
+/// Memory mapped `/dev/fb*` device
+struct Framebuffer {
+    file: File,
+    var: FbVarScreenInfo,
+    format: PixelFormat,
+    line_length: usize,
+    map: memmap2::MmapMut,
+}
+
+impl Framebuffer {
+    fn open(path: &Path) -> Result<Self, FramebufferAppError> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .map_err(FramebufferAppError::io("std::fs::File::open"))?;
+        let var = Self::var_screen_info(&file)?;
+        Self::map(file, var)
+    }
+
+    fn var_screen_info(file: &File) -> Result<FbVarScreenInfo, FramebufferAppError> {
+        let mut var = FbVarScreenInfo::default();
+        // SAFETY: FBIOGET_VSCREENINFO writes a `fb_var_screeninfo`
+        if unsafe { libc::ioctl(file.as_raw_fd(), FBIOGET_VSCREENINFO as _, &mut var) } < 0 {
+            return Err(FramebufferAppError::io("FBIOGET_VSCREENINFO")(
+                io::Error::last_os_error(),
+            ));
+        }
+        Ok(var)
+    }
+
+    fn map(file: File, var: FbVarScreenInfo) -> Result<Self, FramebufferAppError> {
+        let format = PixelFormat::new(&var)?;
+        let mut fix = FbFixScreenInfo::default();
+        // SAFETY: FBIOGET_FSCREENINFO writes a `fb_fix_screeninfo`
+        if unsafe { libc::ioctl(file.as_raw_fd(), FBIOGET_FSCREENINFO as _, &mut fix) } < 0 {
+            return Err(FramebufferAppError::io("FBIOGET_FSCREENINFO")(
+                io::Error::last_os_error(),
+            ));
+        }
+        // SAFETY: The framebuffer memory is only written by this process while the app runs
+        let map = unsafe {
+            memmap2::MmapOptions::new()
+                .len(fix.smem_len as usize)
+                .map_mut(&file)
+        }
+        .map_err(FramebufferAppError::io("memmap2::MmapOptions::map_mut"))?;
+        Ok(Self {
+            file,
+            var,
+            format,
+            line_length: fix.line_length as usize,
+            map,
+        })
+    }
+
+    /// Size of the visible area in pixels
+    fn size(&self) -> (u32, u32) {
+        (self.var.xres, self.var.yres)
+    }
+
+    /// Best effort, not every driver supports `FBIO_WAITFORVSYNC`
+    fn wait_for_vsync(&self) {
+        let screen: u32 = 0;
+        // SAFETY: FBIO_WAITFORVSYNC reads a `__u32`
+        unsafe { libc::ioctl(self.file.as_raw_fd(), FBIO_WAITFORVSYNC as _, &screen) };
+    }
+
+    /// Convert `dirty_rect` of `canvas` to the framebuffer format, and copy it to the visible area
+    fn copy_from(&mut self, canvas: &BufferMutRef, dirty_rect: DirtyRect) {
+        let bytes_per_pixel = self.var.bits_per_pixel as usize / 8;
+        let visible_start = self.var.yoffset as usize * self.line_length
+            + self.var.xoffset as usize * bytes_per_pixel;
+        for y in dirty_rect.min_y..dirty_rect.max_y {
+            let src = canvas.get_span(dirty_rect.min_x, dirty_rect.max_x, y);
+            let start = visible_start
+                + y as usize * self.line_length
+                + dirty_rect.min_x as usize * bytes_per_pixel;
+            let dst = &mut self.map[start..start + src.len() * bytes_per_pixel];
+            match self.format {
+                PixelFormat::Argb8888(_) => dst.copy_from_slice(bytemuck::cast_slice(src)),
+                PixelFormat::Rgb565 => {
+                    for (dst, &[b, g, r, _]) in dst.chunks_exact_mut(2).zip(src) {
+                        let rgb565 =
+                            ((r as u16 >> 3) << 11) | ((g as u16 >> 2) << 5) | (b as u16 >> 3);
+                        dst.copy_from_slice(&rgb565.to_ne_bytes());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Run `egui_app_factory()` full screen on a Linux framebuffer device, e.g. `/dev/fb0`.
+///
+/// Supports ARGB8888 and RGB565 framebuffers. The window related settings of `settings.viewport_builder` are
+/// ignored, the app is drawn on the visible area of the framebuffer and follows its resolution changes.
+///
+/// Input is read from every readable `/dev/input/event*` device: mice, touchscreens and keyboards with a US layout.
+pub fn run_app_with_framebuffer<T: App>(
+    fb_path: &Path,
+    settings: SoftwareBackendAppConfiguration,
+    mut egui_app_factory: impl FnMut(Context) -> T,
+) -> Result<(), FramebufferAppError> {
+    let mut framebuffer = Framebuffer::open(fb_path)?;
+    let mut renderer = EguiSoftwareRender::new(framebuffer.format.canvas_order())
+        .with_allow_raster_opt(settings.allow_raster_opt)
+        .with_convert_tris_to_rects(settings.convert_tris_to_rects)
+        .with_mode(settings.caching)
+        .with_color_temperature(settings.color_temperature)
+        .with_max_cache_memory_mb(settings.max_cache_memory_mb)
+        .with_max_cache_entries(settings.max_cache_entries)
+        .with_max_idle_frames(settings.max_idle_frames.unwrap_or_default());
+    let mut input_devices = InputDevices::open_all();
+
+    let egui_ctx = Context::default();
+    // Requested repaint time, and a socket waking up the `poll()` of the render loop
+    let next_repaint = Arc::new(Mutex::new(Some(Instant::now())));
+    let (mut wake_receiver, wake_sender) =
+        UnixStream::pair().map_err(FramebufferAppError::io("UnixStream::pair"))?;
+    wake_receiver
+        .set_nonblocking(true)
+        .map_err(FramebufferAppError::io("UnixStream::set_nonblocking"))?;
+    wake_sender
+        .set_nonblocking(true)
+        .map_err(FramebufferAppError::io("UnixStream::set_nonblocking"))?;
+    {
+        let next_repaint = next_repaint.clone();
+        egui_ctx.set_request_repaint_callback(move |info| {
+            let when = Instant::now() + info.delay;
+            let mut next_repaint = next_repaint.lock();
+            *next_repaint = Some(next_repaint.map_or(when, |next| next.min(when)));
+            _ = (&wake_sender).write(&[0]);
+        });
+    }
+    let mut egui_app = egui_app_factory(egui_ctx.clone());
+
+    let start_time = Instant::now();
+    let mut last_frame_time = None;
+    let mut screenshot_requested = false;
+    let mut canvas = Vec::new();
+    let mut input_events = Vec::new();
+    loop {
+        // Follow resolution and format changes, e.g. from `fbset`
+        let var = Framebuffer::var_screen_info(&framebuffer.file)?;
+        if var != framebuffer.var {
+            framebuffer = Framebuffer::map(framebuffer.file, var)?;
+            renderer.force_redraw_next_frame();
+        }
+        let (width, height) = framebuffer.size();
+        let screen_size = vec2(width as f32, height as f32);
+
+        input_devices.read_events(screen_size, &mut input_events);
+        let repaint_due = next_repaint
+            .lock()
+            .is_some_and(|when| when <= Instant::now());
+        if input_events.is_empty() && !repaint_due {
+            let timeout = next_repaint.lock().map_or(-1, |when| {
+                when.saturating_duration_since(Instant::now())
+                    .as_millis()
+                    .min(i32::MAX as u128) as i32
+            });
+            let mut fds: Vec<libc::pollfd> = input_devices
+                .fds()
+                .chain([wake_receiver.as_raw_fd()])
+                .map(|fd| libc::pollfd {
+                    fd,
+                    events: libc::POLLIN,
+                    revents: 0,
+                })
+                .collect();
+            // SAFETY: `fds` is a valid array of `pollfd`
+            unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as _, timeout) };
+            while wake_receiver.read(&mut [0; 64]).is_ok_and(|read| read > 0) {}
+            continue;
+        }
+        *next_repaint.lock() = None;
+
+        let start = Instant::now();
+        let raw_input = RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, screen_size)),
+            time: Some(start_time.elapsed().as_secs_f64()),
+            modifiers: input_devices.modifiers(),
+            events: mem::take(&mut input_events),
+            ..Default::default()
+        };
+        let full_output = egui_ctx.run(raw_input, |ctx| {
+            egui_app.update(
+                ctx,
+                &mut SoftwareBackend {
+                    last_frame_time,
+                    renderer: &mut renderer,
+                    screenshot_requested: &mut screenshot_requested,
+                    stylus: None,
+                },
+            );
+        });
+        let close = full_output
+            .viewport_output
+            .values()
+            .any(|output| output.commands.contains(&ViewportCommand::Close));
+        let clipped_primitives =
+            egui_ctx.tessellate(full_output.shapes, full_output.pixels_per_point);
+
+        canvas.resize((width * height) as usize, [0; 4]);
+        let canvas_ref = &mut BufferMutRef::new(&mut canvas, width, height);
+        let redraw_everything_this_frame = renderer.cached_size() != (width, height);
+        let dirty_rect = renderer.render(
+            canvas_ref,
+            redraw_everything_this_frame,
+            clipped_primitives,
+            &full_output.textures_delta,
+            full_output.pixels_per_point,
+        );
+        if !dirty_rect.is_empty() {
+            framebuffer.wait_for_vsync();
+            framebuffer.copy_from(canvas_ref, dirty_rect);
+        }
+
+        if mem::take(&mut screenshot_requested) {
+            egui_app.on_screenshot(canvas_ref.to_rgba_image(framebuffer.format.canvas_order()));
+        }
+        last_frame_time = Some(start.elapsed());
+
+        if close {
+            egui_app.on_exit(&egui_ctx);
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Gamepad input of `run_app_with_software_backend()`, read with `gilrs`
+
+use egui::{Event, Key, Modifiers, Pos2, Vec2};
+use gilrs::{Axis, Button, EventType, Gilrs};
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+
+/// Keys pressed by the gamepad buttons by default, for the keyboard navigation of egui
+pub const DEFAULT_GAMEPAD_KEY_MAP: &[(Button, Key)] = &[
+    (Button::DPadUp, Key::ArrowUp),
+    (Button::DPadDown, Key::ArrowDown),
+    (Button::DPadLeft, Key::ArrowLeft),
+    (Button::DPadRight, Key::ArrowRight),
+    (Button::South, Key::Enter),
+    (Button::East, Key::Escape),
+    (Button::North, Key::Space),
+    (Button::RightTrigger, Key::Tab),
+];
+
+/// Interval the gamepads are polled at, gilrs can't wake up the event loop
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Points the pointer moves per second with the left stick fully tilted
+const POINTER_SPEED: f32 = 800.0;
+
+/// Points scrolled per second with a trigger fully pressed
+const SCROLL_SPEED: f32 = 1200.0;
+
+/// Stick tilt and trigger press ignored, worn sticks don't rest exactly centered
+const DEADZONE: f32 = 0.15;
+
+/// Buttons, left stick and triggers of all the connected gamepads
+pub(crate) struct GamepadInput {
+    gilrs: Gilrs,
+    key_map: Vec<(Button, Key)>,
+    /// Pointer moved by the left stick, `None` while the stick is centered
+    pointer: Option<Pos2>,
+    last_poll: Instant,
+}
+
+impl GamepadInput {
+    /// `None` if the gamepads can't be read on this platform
+    pub fn new(key_map: Vec<(Button, Key)>) -> Option<Self> {
+        match Gilrs::new() {
+            Ok(gilrs) => Some(Self {
+                gilrs,
+                key_map,
+                pointer: None,
+                last_poll: Instant::now(),
+            }),
+            Err(_error) => {
+                #[cfg(feature = "log")]
+                log::error!("Failed to read the gamepads: {_error}");
+                None
+            }
+        }
+    }
+
+    /// Appends the egui events of the gamepad input since the last call. `pointer` is the latest egui pointer
+    /// position, the left stick moves the pointer from there, and `screen_size` is in points.
+    pub fn poll(&mut self, pointer: Option<Pos2>, screen_size: Vec2, events: &mut Vec<Event>) {
+        let now = Instant::now();
+        // The event loop sleeps longer than the poll interval while nothing happens
+        let dt = (now - self.last_poll).min(POLL_INTERVAL * 2).as_secs_f32();
+        self.last_poll = now;
+
+        while let Some(gilrs::Event { event, .. }) = self.gilrs.next_event() {
+            let (button, pressed, repeat) = match event {
+                EventType::ButtonPressed(button, _) => (button, true, false),
+                EventType::ButtonRepeated(button, _) => (button, true, true),
+                EventType::ButtonReleased(button, _) => (button, false, false),
+                _ => continue,
+            };
+            for &(_, key) in self.key_map.iter().filter(|(mapped, _)| *mapped == button) {
+                events.push(Event::Key {
+                    key,
+                    physical_key: None,
+                    pressed,
+                    repeat,
+                    modifiers: Modifiers::NONE,
+                });
+            }
+        }
+
+        let (mut stick, mut scroll) = (Vec2::ZERO, 0.0);
+        for (_id, gamepad) in self.gilrs.gamepads() {
+            // gilrs Y axis points up
+            stick += Vec2::new(
+                gamepad.value(Axis::LeftStickX),
+                -gamepad.value(Axis::LeftStickY),
+            );
+            let trigger = |button| gamepad.button_data(button).map_or(0.0, |data| data.value());
+            scroll += trigger(Button::LeftTrigger2) - trigger(Button::RightTrigger2);
+        }
+
+        if stick.length() > DEADZONE {
+            let from = self
+                .pointer
+                .or(pointer)
+                .unwrap_or((screen_size / 2.0).to_pos2());
+            let to = (from + stick * POINTER_SPEED * dt).clamp(Pos2::ZERO, screen_size.to_pos2());
+            self.pointer = Some(to);
+            events.push(Event::PointerMoved(to));
+        } else {
+            // Moves from the latest egui pointer again next time, which the mouse may have moved
+            self.pointer = None;
+        }
+        if scroll.abs() > DEADZONE {
+            events.push(Event::MouseWheel {
+                unit: egui::MouseWheelUnit::Point,
+                delta: Vec2::new(0.0, scroll * SCROLL_SPEED * dt),
+                modifiers: Modifiers::NONE,
+            });
+        }
+    }
+}
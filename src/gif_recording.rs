@@ -0,0 +1,194 @@
+//! Animated GIF recording of `EguiSoftwareRenderCanvas::begin_recording()`
+
+use alloc::{borrow::Cow, vec::Vec};
+use core::ops::Range;
+
+use gif::{Encoder, Frame, Repeat};
+
+use crate::{BufferMutRef, ColorFieldOrder, as_usize, color::swizzle_rgba_bgra};
+
+/// Colors of the palette of each frame, the most a GIF palette holds
+const PALETTE_SIZE: usize = 256;
+
+/// Frames rendered since `EguiSoftwareRenderCanvas::begin_recording()`, encoded as they are captured
+pub(crate) struct GifRecording {
+    fps: u32,
+    /// Created with the size of the first frame
+    encoder: Option<Encoder<Vec<u8>>>,
+    /// Size of the first frame, the later ones are cropped to it
+    screen_size: (u16, u16),
+    frame_count: u64,
+}
+
+impl GifRecording {
+    pub fn new(fps: u32) -> Self {
+        Self {
+            fps: fps.max(1),
+            encoder: None,
+            screen_size: (0, 0),
+            frame_count: 0,
+        }
+    }
+
+    /// Appends `buffer` as the next frame, from the top left corner and cropped to the size of the first frame
+    pub fn capture(&mut self, buffer: &BufferMutRef, order: ColorFieldOrder) {
+        let encoder = self.encoder.get_or_insert_with(|| {
+            self.screen_size = (
+                buffer.width.min(u16::MAX.into()) as u16,
+                buffer.height.min(u16::MAX.into()) as u16,
+            );
+            let (width, height) = self.screen_size;
+            let mut encoder =
+                Encoder::new(Vec::new(), width, height, &[]).expect("writing to a Vec can't fail");
+            encoder
+                .set_repeat(Repeat::Infinite)
+                .expect("writing to a Vec can't fail");
+            encoder
+        });
+
+        let width = buffer.width.min(self.screen_size.0.into());
+        let height = buffer.height.min(self.screen_size.1.into());
+        let mut pixels = Vec::with_capacity(as_usize(width) * as_usize(height));
+        for y in 0..height {
+            let row_start = as_usize(y) * as_usize(buffer.width);
+            pixels.extend(
+                buffer.data[row_start..row_start + as_usize(width)]
+                    .iter()
+                    .map(|&pixel| {
+                        let [r, g, b, _] = match order {
+                            ColorFieldOrder::Rgba => pixel,
+                            ColorFieldOrder::Bgra => swizzle_rgba_bgra(pixel),
+                        };
+                        [r, g, b]
+                    }),
+            );
+        }
+        if pixels.is_empty() {
+            return;
+        }
+
+        let (palette, indices) = median_cut(&pixels);
+        // Delays are in hundredths of a second, rounded from the start of the recording so they don't drift
+        let centis = |frame: u64| (frame * 100 + u64::from(self.fps) / 2) / u64::from(self.fps);
+        let delay = centis(self.frame_count + 1) - centis(self.frame_count);
+        let frame = Frame {
+            width: width as u16,
+            height: height as u16,
+            delay: delay.min(u16::MAX.into()) as u16,
+            palette: Some(palette),
+            buffer: Cow::Owned(indices),
+            ..Frame::default()
+        };
+        encoder
+            .write_frame(&frame)
+            .expect("frame is within the screen and has a palette");
+        self.frame_count += 1;
+    }
+
+    /// The encoded GIF, without frames if nothing was captured
+    pub fn finish(self) -> Vec<u8> {
+        let encoder = match self.encoder {
+            Some(encoder) => encoder,
+            None => Encoder::new(Vec::new(), 0, 0, &[]).expect("writing to a Vec can't fail"),
+        };
+        encoder.into_inner().expect("writing to a Vec can't fail")
+    }
+}
+
+/// Palette of up to `PALETTE_SIZE` colors for `pixels` with median cut, as `[r, g, b, ...]`, and the palette
+/// index of each pixel
+fn median_cut(pixels: &[[u8; 3]]) -> (Vec<u8>, Vec<u8>) {
+    // Distinct colors and their pixel counts
+    let mut sorted = pixels.to_vec();
+    sorted.sort_unstable();
+    let mut histogram: Vec<([u8; 3], u32)> = Vec::new();
+    for color in sorted {
+        match histogram.last_mut() {
+            Some((last, count)) if *last == color => *count += 1,
+            _ => histogram.push((color, 1)),
+        }
+    }
+
+    // Boxes of colors, as ranges of `histogram`
+    let mut boxes: Vec<Range<usize>> = Vec::with_capacity(PALETTE_SIZE);
+    boxes.push(0..histogram.len());
+    while boxes.len() < PALETTE_SIZE {
+        // Splits the box with the widest channel, boxes of a single color can't be split
+        let Some((box_idx, channel, _)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, colors)| colors.len() > 1)
+            .map(|(idx, colors)| {
+                let (channel, extent) = widest_channel(&histogram[colors.clone()]);
+                (idx, channel, extent)
+            })
+            .max_by_key(|&(_, _, extent)| extent)
+        else {
+            break;
+        };
+        let colors = boxes[box_idx].clone();
+        let box_histogram = &mut histogram[colors.clone()];
+        box_histogram.sort_unstable_by_key(|(color, _)| color[channel]);
+
+        // Splits at the median pixel, each half keeps at least one color
+        let total: u64 = box_histogram
+            .iter()
+            .map(|&(_, count)| u64::from(count))
+            .sum();
+        let mut below = 0;
+        let median = box_histogram
+            .iter()
+            .position(|&(_, count)| {
+                below += u64::from(count);
+                below * 2 >= total
+            })
+            .unwrap_or(0);
+        let split = colors.start + (median + 1).min(colors.len() - 1);
+        boxes[box_idx] = colors.start..split;
+        boxes.push(split..colors.end);
+    }
+
+    // Each box is the pixel weighted average of its colors
+    let mut palette = Vec::with_capacity(boxes.len() * 3);
+    let mut color_indices = Vec::with_capacity(histogram.len());
+    for (box_idx, colors) in boxes.iter().enumerate() {
+        let mut sum = [0u64; 3];
+        let mut total = 0u64;
+        for &(color, count) in &histogram[colors.clone()] {
+            for (sum, channel) in sum.iter_mut().zip(color) {
+                *sum += u64::from(channel) * u64::from(count);
+            }
+            total += u64::from(count);
+            color_indices.push((color, box_idx as u8));
+        }
+        palette.extend(sum.map(|sum| ((sum + total / 2) / total) as u8));
+    }
+
+    color_indices.sort_unstable_by_key(|&(color, _)| color);
+    let indices = pixels
+        .iter()
+        .map(|color| {
+            let idx = color_indices
+                .binary_search_by_key(color, |&(color, _)| color)
+                .expect("every pixel color is in a box");
+            color_indices[idx].1
+        })
+        .collect();
+    (palette, indices)
+}
+
+/// Channel with the widest range of values in `colors`, and that range
+fn widest_channel(colors: &[([u8; 3], u32)]) -> (usize, u8) {
+    let mut min = [u8::MAX; 3];
+    let mut max = [0; 3];
+    for (color, _) in colors {
+        for channel in 0..3 {
+            min[channel] = min[channel].min(color[channel]);
+            max[channel] = max[channel].max(color[channel]);
+        }
+    }
+    (0..3)
+        .map(|channel| (channel, max[channel] - min[channel]))
+        .max_by_key(|&(_, extent)| extent)
+        .expect("3 channels")
+}
@@ -1,46 +1,122 @@
 #![no_std]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+#![cfg_attr(feature = "portable_simd", feature(portable_simd))]
 extern crate alloc;
 
 #[cfg(feature = "std")]
 extern crate std;
 
 use core::ops::{Deref, DerefMut, Range};
+use core::sync::atomic::{AtomicU32, Ordering::Relaxed};
 
-use alloc::{borrow::Cow, vec, vec::Vec};
+#[cfg(feature = "std")]
+use alloc::sync::Arc;
+use alloc::{borrow::Cow, boxed::Box, vec, vec::Vec};
 
-use egui::{Color32, Mesh, Pos2, Vec2, ahash::HashMap, vec2};
+use egui::{Color32, Mesh, Vec2, ahash::HashMap, pos2, vec2};
 #[cfg(feature = "rayon")]
 use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
 
+#[cfg(feature = "cache_stats")]
+use crate::cache_stats::CacheStats;
+use crate::render_cost::{RasterWork, RenderCostModel};
 #[cfg(feature = "raster_stats")]
 use crate::stats::RenderStats;
 use crate::{
-    color::{SelectedImpl, swizzle_rgba_bgra},
-    dirty_rect::{ComputeTiledDirtyRects, DirtyRect},
-    egui_texture::EguiTexture,
+    blend_precision::CanvasPixel,
+    color::{
+        BILINEAR_ONE, GRAYSCALE_WEIGHTS_RGB, SelectedImpl, bilinear_u8x4, color_temperature_tint,
+        swizzle_rgba_bgra,
+    },
     hash::Hash32,
+    pixel_alloc::{PixelAlloc, PixelBox, PixelVec},
     render::{draw_egui_mesh, egui_orient2df},
 };
 
+#[cfg(feature = "alloc_stats")]
+mod alloc_stats;
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench;
+pub(crate) mod blend_precision;
+#[cfg(feature = "cache_stats")]
+pub mod cache_stats;
 pub(crate) mod color;
+pub(crate) mod debug_overlay;
 pub(crate) mod dirty_rect;
+#[cfg(feature = "drm")]
+mod drm;
 pub(crate) mod egui_texture;
+#[cfg(all(feature = "framebuffer", target_os = "linux"))]
+mod evdev;
+mod frame_source;
+#[cfg(all(feature = "framebuffer", target_os = "linux"))]
+mod framebuffer;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+#[cfg(feature = "gif_export")]
+mod gif_recording;
 pub(crate) mod hash;
 pub(crate) mod math;
+#[cfg(feature = "threading")]
+mod offscreen_batch;
+pub(crate) mod pixel_alloc;
 pub(crate) mod raster;
 pub(crate) mod render;
+mod render_cost;
+#[cfg(feature = "image")]
+mod rgba_image;
 #[cfg(feature = "raster_stats")]
 pub mod stats;
 #[cfg(feature = "test_render")]
 pub mod test_render;
+pub(crate) mod texture_utils;
+#[cfg(feature = "wgpu")]
+mod wgpu_texture;
 
-#[cfg(feature = "winit")]
+// Left out under Miri: softbuffer links tiny-xlib, that calls `dlopen` before `main`, which Miri can't run. The
+//   `winit` feature is enabled by the dev-dependency on this crate even with `--no-default-features`.
+#[cfg(all(feature = "winit", not(miri)))]
 mod winit;
+#[cfg(all(feature = "x11_shm", target_os = "linux"))]
+mod x11_shm;
+
+#[cfg(feature = "alloc_stats")]
+pub use alloc_stats::GlobalAllocStats;
+pub use blend_precision::BlendPrecision;
+pub use color::{
+    ColorBlindMode, DitherMode, grayscale_slice, linear_f32_to_u8, linear_to_srgb_slice,
+    premultiply_alpha, premultiply_alpha_slice, srgb_to_linear_slice,
+    swizzle_and_premultiply_rgba_bgra_slice, u8_to_linear_f32, unpremultiply_alpha,
+    unpremultiply_alpha_slice,
+};
+pub use debug_overlay::DebugOverlay;
+pub use dirty_rect::{ComputeTiledDirtyRects, DirtyRect};
+#[cfg(feature = "drm")]
+pub use drm::{DEFAULT_DRM_DEVICE, DrmBackendAppError, run_app_with_drm_backend};
+pub use egui_texture::EguiTexture;
+pub use frame_source::{SoftwareFrame, SoftwareFrameSource};
+#[cfg(all(feature = "framebuffer", target_os = "linux"))]
+pub use framebuffer::{FramebufferAppError, run_app_with_framebuffer};
+#[cfg(feature = "gamepad")]
+pub use gamepad::DEFAULT_GAMEPAD_KEY_MAP;
+#[cfg(feature = "gamepad")]
+pub use gilrs::Button as GamepadButton;
+#[cfg(feature = "threading")]
+pub use offscreen_batch::{JobHandle, OffscreenRenderBatch};
+#[cfg(feature = "allocator_api")]
+pub use pixel_alloc::PixelAllocator;
+pub use texture_utils::{
+    GradientDirection, create_linear_gradient_texture, create_radial_gradient_texture,
+};
 
-#[cfg(feature = "winit")]
+#[cfg(all(feature = "winit", not(miri)))]
 pub use winit::{
-    App, SoftwareBackend, SoftwareBackendAppConfiguration, run_app_with_software_backend,
+    App, SoftwareBackend, SoftwareBackendAppConfiguration, StylusState,
+    run_app_with_software_backend, run_app_with_software_backend_and_return,
 };
+#[cfg(all(feature = "x11_shm", target_os = "linux"))]
+pub use x11_shm::{X11ShmError, X11ShmSurface};
 
 const TILE_SIZE: u32 = 64;
 
@@ -66,6 +142,12 @@ pub enum SoftwareRenderCaching {
     /// * Reclaim unused cached meshes renders
     /// * Render dirty tiles by blending cache renders
     BlendTiled,
+    /// `BlendTiled`, documenting that the primitives are rasterized and the tile rows composited on the
+    /// [`rayon`](https://docs.rs/rayon) thread pool
+    ///
+    /// Requires the `rayon` feature, rendering panics without it. `BlendTiled` shares the same parallel paths when
+    /// the `rayon` feature is enabled and is sequential otherwise.
+    BlendTiledParallel,
     /// Cache primitives meshes, redraw primitives intersecting a set of changed bboxes
     ///
     /// Primitives are rendered clipped per intersection with a non overlapping set
@@ -91,6 +173,51 @@ pub enum SoftwareRenderCaching {
     Mesh,
     /// No cache, always redraw the whole frame (slow, for testing mostly)
     Direct,
+    /// Render the first frame under `BlendTiled`, `MeshTiled` and `Mesh`, then keep the fastest one
+    ///
+    /// The first render costs four frames and a copy of the buffer. The selected mode is returned by `EguiSoftwareRender::caching()` and
+    /// `EguiSoftwareRender::detected_caching_mode()`. Without the `std` feature there is no clock, `BlendTiled` is
+    /// selected.
+    Auto,
+}
+
+impl SoftwareRenderCaching {
+    /// `BlendTiled` or `BlendTiledParallel`, rendered through the tiled cache
+    pub(crate) const fn is_blend_tiled(self) -> bool {
+        matches!(self, Self::BlendTiled | Self::BlendTiledParallel)
+    }
+}
+
+/// A primitive prepared by a cached render, see `EguiSoftwareRender::set_prim_profiler()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrimProfile {
+    /// Hash of the primitive, for `EguiSoftwareRender::invalidate_hash()`
+    pub hash: u32,
+    /// Pixels the primitive covers
+    pub rect: DirtyRect,
+    /// Microseconds spent hashing, and rasterizing if `is_new`
+    pub raster_us: u64,
+    /// Rasterized this frame, because it wasn't cached or was invalidated
+    pub is_new: bool,
+}
+
+/// Bytes allocated by the renderer, see `EguiSoftwareRender::memory_stats()`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// Texture pixels
+    pub texture_bytes: u64,
+    /// Tile buffers of the `BlendTiled` cached primitives
+    pub tiled_cache_bytes: u64,
+    /// Meshes of the `Mesh` and `MeshTiled` cached primitives
+    pub mesh_cache_bytes: u64,
+    /// Dirty tiles and dirty rects tracking
+    pub dirty_rect_bytes: u64,
+}
+
+impl MemoryStats {
+    pub const fn total_bytes(&self) -> u64 {
+        self.texture_bytes + self.tiled_cache_bytes + self.mesh_cache_bytes + self.dirty_rect_bytes
+    }
 }
 
 struct EguiSoftwareRenderInner {
@@ -103,9 +230,79 @@ struct EguiSoftwareRenderInner {
     output_field_order: ColorFieldOrder,
     convert_tris_to_rects: bool,
     allow_raster_opt: bool,
+    /// See `EguiSoftwareRender::with_subpixel_bits()`
+    subpix_bits_override: Option<i32>,
+    /// See `EguiSoftwareRender::with_subpixel_bits_threshold()`
+    subpix_bits_threshold: u32,
     mode: SoftwareRenderCaching,
+    /// Mode selected by the first render with `SoftwareRenderCaching::Auto`
+    detected_caching: Option<SoftwareRenderCaching>,
+    blend_precision: BlendPrecision,
+    overdraw_visualization: bool,
+    /// Number of prims written to each canvas pixel, only used with `overdraw_visualization`
+    overdraw: Vec<u8>,
+    /// `overdraw_visualization` was enabled on a path without the heat map, and it was logged once
+    #[cfg(feature = "log")]
+    overdraw_unsupported_logged: bool,
+    debug_overlay: DebugOverlay,
+    /// Premultiply the textures and vertex colors, see `EguiSoftwareRender::with_straight_alpha_input()`
+    straight_alpha_input: bool,
+    /// Returned by the last `EguiSoftwareRender::render()`
+    last_dirty_rect: DirtyRect,
+    /// Set by `EguiSoftwareRender::force_redraw_next_frame()`, reset after the next render
+    force_full_redraw: bool,
+    pixel_alloc: PixelAlloc,
+    /// Threads blitting the canvas to the buffer, 0 for `std::thread::available_parallelism()`
+    #[cfg(feature = "threading")]
+    blit_threads: usize,
+    /// Id of the next `egui::TextureId::User` texture imported by `EguiSoftwareRender::import_wgpu_texture()`
+    #[cfg(feature = "wgpu")]
+    next_user_texture_id: u64,
+    /// New primitives are skipped once rasterizing took longer, see `EguiSoftwareRender::with_render_budget_ms()`
+    #[cfg(feature = "std")]
+    render_budget: Option<core::time::Duration>,
+    #[cfg(feature = "std")]
+    budget_exceeded_callback: Option<Arc<dyn Fn(u32) + Send + Sync>>,
+    /// See `EguiSoftwareRender::set_prim_profiler()`
+    #[cfg(feature = "std")]
+    prim_profiler: Option<Box<dyn Fn(PrimProfile) + Send + Sync>>,
+    /// See `EguiSoftwareRender::set_pre_render_callback()`
+    pre_render_callback: Option<Box<dyn Fn(&mut BufferMutRef) + Send + Sync>>,
+    /// See `EguiSoftwareRender::set_post_render_callback()`
+    post_render_callback: Option<Box<dyn Fn(&mut BufferMutRef) + Send + Sync>>,
+    /// Textures freed by the last delta given to the `egui_kittest::TestRenderer`, freed after its next render
+    #[cfg(feature = "test_render")]
+    pending_free_textures: Vec<egui::TextureId>,
+    /// Incremented by each cached render
+    frame_counter: u64,
+    /// Frames a cached primitive is kept without being painted, see `EguiSoftwareRender::with_max_idle_frames()`
+    max_idle_frames: u32,
+    /// See `EguiSoftwareRender::with_max_cache_memory_mb()`
+    max_cache_bytes: Option<usize>,
+    /// See `EguiSoftwareRender::with_max_cache_entries()`
+    max_cache_entries: Option<usize>,
+    /// See `EguiSoftwareRender::with_resize_fade()`
+    resize_fade: bool,
+    /// See `EguiSoftwareRender::with_color_blind_mode()`
+    color_blind_mode: ColorBlindMode,
+    /// Desaturate the output, see `EguiSoftwareRender::with_grayscale()`
+    grayscale: bool,
+    /// See `EguiSoftwareRender::with_color_temperature()`
+    color_temperature: f32,
+    /// `color_temperature_tint()` of `color_temperature`, in the output field order
+    color_temperature_tint: [u8; 4],
+    /// See `EguiSoftwareRender::with_dithering()`
+    dithering: DitherMode,
+    /// The last cached render skipped primitives over the render budget
+    partial_frame: bool,
+    /// Of the meshes being rasterized, their tris match rect corners more loosely when fractional
+    pixels_per_point: f32,
+    /// Calibrated by the first renders, see `EguiSoftwareRender::estimate_render_time_us()`
+    render_cost: RenderCostModel,
     #[cfg(feature = "raster_stats")]
     pub stats: RenderStats,
+    #[cfg(all(feature = "cache_stats", not(feature = "raster_stats")))]
+    cache_stats: CacheStats,
 }
 
 /// egui software renderer
@@ -117,10 +314,46 @@ pub struct EguiSoftwareRender {
 
 /// egui software renderer to canvas
 pub struct EguiSoftwareRenderCanvas {
-    canvas: Vec<[u8; 4]>,
+    canvas: PixelVec<[u8; 4]>,
+    /// Replaces `canvas` with `BlendPrecision::Bits16`
+    canvas16: PixelVec<[u16; 4]>,
+    /// Last frame before a resize, scaled to the new size, shown under the partial frames following the resize
+    pre_resize_canvas: Option<Vec<[u8; 4]>>,
+    /// `canvas` as of the last `diff_from_prev_frame()` or `take_diff_rects()`
+    prev_canvas: PixelVec<[u8; 4]>,
+    /// Frames rendered since `EguiSoftwareRenderCanvas::begin_recording()`
+    #[cfg(feature = "gif_export")]
+    gif_recording: Option<gif_recording::GifRecording>,
     renderer: EguiSoftwareRender,
 }
 
+/// Iterator of `EguiSoftwareRenderCanvas::diff_from_prev_frame()`
+struct CanvasDiff<'a> {
+    canvas: &'a [[u8; 4]],
+    prev_canvas: &'a mut [[u8; 4]],
+    width: u32,
+    /// Pixels before `idx` were already compared
+    idx: usize,
+}
+
+impl Iterator for CanvasDiff<'_> {
+    type Item = (u32, u32, [u8; 4]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Changed pixels are usually next to each other, only search the next one with SIMD when it isn't
+        if self.canvas.get(self.idx) == self.prev_canvas.get(self.idx) {
+            self.idx += dispatch_simd_impl!(|simd_impl| simd_impl
+                .first_changed_pixel(&self.canvas[self.idx..], &self.prev_canvas[self.idx..]));
+        }
+        let idx = self.idx;
+        let color = *self.canvas.get(idx)?;
+        self.prev_canvas[idx] = color;
+        self.idx += 1;
+        let width = as_usize(self.width);
+        Some(((idx % width) as u32, (idx / width) as u32, color))
+    }
+}
+
 impl Deref for EguiSoftwareRenderCanvas {
     type Target = EguiSoftwareRender;
 
@@ -135,22 +368,104 @@ impl DerefMut for EguiSoftwareRenderCanvas {
     }
 }
 
-fn blit_rect(
+/// `src` stretched to `dst_width` x `dst_height`, sampling pixel centers
+fn scale_bilinear(
+    src: &[[u8; 4]],
+    (src_width, src_height): (u32, u32),
+    (dst_width, dst_height): (u32, u32),
+) -> Vec<[u8; 4]> {
+    let mut dst = Vec::with_capacity(as_usize(dst_width) * as_usize(dst_height));
+    // Source position of a destination pixel center, with `BILINEAR_ONE` sub pixel steps
+    let sample = |dst: u32, dst_len: u32, src_len: u32| {
+        let pos = ((2 * dst as u64 + 1) * src_len as u64 * BILINEAR_ONE as u64
+            / (2 * dst_len as u64))
+            .saturating_sub(BILINEAR_ONE as u64 / 2);
+        let idx = (pos / BILINEAR_ONE as u64) as u32;
+        let weight = (pos % BILINEAR_ONE as u64) as u32;
+        (idx.min(src_len - 1), (idx + 1).min(src_len - 1), weight)
+    };
+    for y in 0..dst_height {
+        let (y0, y1, fy) = sample(y, dst_height, src_height);
+        let (row0, row1) = (as_usize(y0 * src_width), as_usize(y1 * src_width));
+        for x in 0..dst_width {
+            let (x0, x1, fx) = sample(x, dst_width, src_width);
+            let (x0, x1) = (as_usize(x0), as_usize(x1));
+            dst.push(bilinear_u8x4(
+                src[row0 + x0],
+                src[row0 + x1],
+                src[row1 + x0],
+                src[row1 + x1],
+                fx,
+                fy,
+            ));
+        }
+    }
+    dst
+}
+
+/// `canvas` over `pre_resize_canvas` in `buffer`, with both drawn over `buffer` with `blend_over_buffer`
+fn composite_pre_resize_canvas(
+    simd_impl: impl SelectedImpl,
+    pre_resize_canvas: &[[u8; 4]],
+    canvas: &[[u8; 4]],
+    buffer: &mut BufferMutRef,
+    blend_over_buffer: bool,
+) {
+    let (width, height) = (as_usize(buffer.width), as_usize(buffer.height));
+    let len = width * height;
+    match blend_over_buffer {
+        true => simd_impl.egui_blend_u8_strided(
+            pre_resize_canvas,
+            width,
+            buffer.data,
+            width,
+            width,
+            height,
+        ),
+        false => buffer.data[..len].copy_from_slice(&pre_resize_canvas[..len]),
+    }
+    simd_impl.egui_blend_u8_strided(canvas, width, buffer.data, width, width, height);
+}
+
+fn blit_rect<P: CanvasPixel>(
     simd_impl: impl SelectedImpl,
-    canvas: &BufferMutRef,
+    canvas: &[P],
+    canvas_width: u32,
     buffer: &mut BufferMutRef,
     rect: DirtyRect,
     canvas_row_offset: u32,
 ) {
     for y in rect.min_y..rect.max_y {
-        let src_row = canvas.get_span(rect.min_x, rect.max_x, y + canvas_row_offset);
+        let canvas_y = y + canvas_row_offset;
+        let row_start = canvas_y * canvas_width;
+        let src_row = &canvas[as_usize(row_start + rect.min_x)..as_usize(row_start + rect.max_x)];
         let dst_row = &mut buffer.get_mut_span(rect.min_x, rect.max_x, y);
 
-        simd_impl.egui_blend_u8_slice(src_row, dst_row)
+        P::blit_slice(simd_impl, src_row, dst_row, rect.min_x, canvas_y)
     }
 }
 
 impl EguiSoftwareRenderCanvas {
+    /// `EguiSoftwareRenderCanvas::render()` into a new `width` x `height` buffer, see
+    /// `EguiSoftwareRender::render_to_buffer()`
+    pub fn render_to_buffer(
+        &mut self,
+        paint_jobs: Vec<egui::ClippedPrimitive>,
+        textures_delta: &egui::TexturesDelta,
+        pixels_per_point: f32,
+        width: u32,
+        height: u32,
+    ) -> Vec<[u8; 4]> {
+        let mut buffer = vec![[0u8; 4]; as_usize(width) * as_usize(height)];
+        self.render(
+            &mut BufferMutRef::new(&mut buffer, width, height),
+            paint_jobs,
+            textures_delta,
+            pixels_per_point,
+        );
+        buffer
+    }
+
     pub fn render(
         &mut self,
         buffer_ref: &mut BufferMutRef,
@@ -158,7 +473,52 @@ impl EguiSoftwareRenderCanvas {
         textures_delta: &egui::TexturesDelta,
         pixels_per_point: f32,
     ) {
+        self.render_and_blit(buffer_ref, paint_jobs, textures_delta, pixels_per_point);
+        #[cfg(feature = "gif_export")]
+        if let Some(recording) = &mut self.gif_recording {
+            recording.capture(buffer_ref, self.renderer.inner.output_field_order);
+        }
+    }
+
+    /// Starts capturing the frames of `EguiSoftwareRenderCanvas::render()` as an animated GIF played at `fps`,
+    /// until `EguiSoftwareRenderCanvas::end_recording()`
+    ///
+    /// Drops the frames of the recording already started, if any. GIF delays are in hundredths of a second, most
+    /// viewers play the GIFs above 50 fps slower.
+    #[cfg(feature = "gif_export")]
+    pub fn begin_recording(&mut self, fps: u32) {
+        self.gif_recording = Some(gif_recording::GifRecording::new(fps));
+    }
+
+    /// Frames rendered since `EguiSoftwareRenderCanvas::begin_recording()` as a looping animated GIF, `None` when
+    /// not recording
+    ///
+    /// Each frame has its own 256 colors palette, quantized with median cut. The frames are cropped to the size of
+    /// the first one.
+    #[cfg(feature = "gif_export")]
+    pub fn end_recording(&mut self) -> Option<Vec<u8>> {
+        self.gif_recording
+            .take()
+            .map(gif_recording::GifRecording::finish)
+    }
+
+    fn render_and_blit(
+        &mut self,
+        buffer_ref: &mut BufferMutRef,
+        paint_jobs: Vec<egui::ClippedPrimitive>,
+        textures_delta: &egui::TexturesDelta,
+        pixels_per_point: f32,
+    ) {
+        let full_rect = DirtyRect {
+            min_x: 0,
+            min_y: 0,
+            max_x: buffer_ref.width,
+            max_y: buffer_ref.height,
+        };
+        // The overlays of the last frame are drawn over the buffer, outside of the tiles or rect updated this frame
+        let blit_everything = !self.renderer.inner.debug_overlay.is_empty();
         if self.renderer.inner.mode == SoftwareRenderCaching::Direct {
+            // Warned by `render()`
             self.renderer.render(
                 buffer_ref,
                 true,
@@ -166,10 +526,54 @@ impl EguiSoftwareRenderCanvas {
                 textures_delta,
                 pixels_per_point,
             );
+        } else if self.renderer.inner.mode.is_blend_tiled()
+            && self.renderer.inner.blend_precision == BlendPrecision::Bits16
+        {
+            let redraw_everything_this_frame = self.renderer.cached_size()
+                != (buffer_ref.width, buffer_ref.height)
+                || self.canvas16.len() != buffer_ref.data.len();
+            if redraw_everything_this_frame {
+                self.canvas16.clear();
+                let len = as_usize(buffer_ref.width) * as_usize(buffer_ref.height);
+                self.canvas16.resize(len, [0; 4]);
+            }
+            // The 8 bit canvas stays empty, `buffer_ref` is only used for its size until the blit
+            self.renderer.render_impl(
+                buffer_ref,
+                redraw_everything_this_frame,
+                paint_jobs,
+                textures_delta,
+                pixels_per_point,
+                Some(&mut self.canvas16),
+            );
+            if blit_everything {
+                dispatch_simd_impl!(|simd_impl| blit_rect(
+                    simd_impl,
+                    &self.canvas16,
+                    buffer_ref.width,
+                    buffer_ref,
+                    full_rect,
+                    0
+                ));
+            } else {
+                dispatch_simd_impl!(|simd_impl| self
+                    .renderer
+                    .inner
+                    .blit_to_buffer_from_tiledcanvas(simd_impl, &self.canvas16, buffer_ref));
+            }
+            self.renderer.inner.post_process_rect(buffer_ref, full_rect);
+            if self.renderer.inner.overdraw_visualization {
+                dispatch_simd_impl!(|simd_impl| self
+                    .renderer
+                    .inner
+                    .blend_overdraw_heat_map(simd_impl, buffer_ref));
+            }
         } else {
-            let redraw_everything_this_frame =
-                self.renderer.cached_size() != (buffer_ref.width, buffer_ref.height);
+            let redraw_everything_this_frame = self.renderer.cached_size()
+                != (buffer_ref.width, buffer_ref.height)
+                || self.canvas.len() != buffer_ref.data.len();
             if redraw_everything_this_frame {
+                self.save_pre_resize_canvas(buffer_ref.width, buffer_ref.height);
                 self.canvas.clear();
                 let len = as_usize(buffer_ref.width) * as_usize(buffer_ref.height);
                 self.canvas.resize(len, [0; 4]);
@@ -177,24 +581,206 @@ impl EguiSoftwareRenderCanvas {
             }
             let mut canvas =
                 BufferMutRef::new(&mut self.canvas, buffer_ref.width, buffer_ref.height);
-            let dirty_rect = self.renderer.render(
+            let mut dirty_rect = self.renderer.render_impl(
                 &mut canvas,
                 redraw_everything_this_frame,
                 paint_jobs,
                 textures_delta,
                 pixels_per_point,
+                None,
             );
-            if self.renderer.inner.mode == SoftwareRenderCaching::BlendTiled {
-                dispatch_simd_impl!(|simd_impl| self
-                    .renderer
-                    .inner
-                    .blit_to_buffer_from_tiledcanvas(simd_impl, &canvas, buffer_ref));
+            if let Some(pre_resize_canvas) = self.pre_resize_canvas.take() {
+                if self.renderer.inner.partial_frame {
+                    dispatch_simd_impl!(|simd_impl| composite_pre_resize_canvas(
+                        simd_impl,
+                        &pre_resize_canvas,
+                        canvas.data,
+                        buffer_ref,
+                        self.renderer.inner.mode.is_blend_tiled()
+                    ));
+                    self.renderer.inner.post_process_rect(buffer_ref, full_rect);
+                    self.pre_resize_canvas = Some(pre_resize_canvas);
+                    self.renderer.draw_debug_overlay(buffer_ref);
+                    return;
+                }
+                // Replace the whole composited frame
+                dirty_rect = full_rect;
+            }
+            if blit_everything {
+                dirty_rect = full_rect;
+            }
+            if self.renderer.inner.mode.is_blend_tiled() {
+                if blit_everything {
+                    dispatch_simd_impl!(|simd_impl| blit_rect(
+                        simd_impl,
+                        canvas.data,
+                        canvas.width,
+                        buffer_ref,
+                        full_rect,
+                        0
+                    ));
+                } else {
+                    dispatch_simd_impl!(|simd_impl| self
+                        .renderer
+                        .inner
+                        .blit_to_buffer_from_tiledcanvas(simd_impl, canvas.data, buffer_ref));
+                }
+                self.renderer.inner.post_process_rect(buffer_ref, full_rect);
+                if self.renderer.inner.overdraw_visualization {
+                    dispatch_simd_impl!(|simd_impl| self
+                        .renderer
+                        .inner
+                        .blend_overdraw_heat_map(simd_impl, buffer_ref));
+                }
             } else {
+                self.renderer.inner.warn_overdraw_unsupported();
                 dispatch_simd_impl!(|simd_impl| blit_rect(
-                    simd_impl, &canvas, buffer_ref, dirty_rect, 0
+                    simd_impl,
+                    canvas.data,
+                    canvas.width,
+                    buffer_ref,
+                    dirty_rect,
+                    0
                 ));
+                self.renderer
+                    .inner
+                    .post_process_rect(buffer_ref, dirty_rect);
+            }
+        }
+        self.renderer.draw_debug_overlay(buffer_ref);
+    }
+
+    /// Each canvas pixel changed since the last call, as `(x, y, new_color)`, for protocols sending only the
+    /// changed pixels like VNC
+    ///
+    /// Colors are the premultiplied canvas pixels, before the output filters and the debug overlay. The first call,
+    /// and the first call after a resize, compare with a transparent canvas. The canvas is only kept by the `Mesh`,
+    /// `MeshTiled` and 8 bits `BlendTiled` modes, nothing is yielded by the others. Pixels are marked as sent when
+    /// yielded, the ones left when the iterator is dropped early are yielded by the next call.
+    pub fn diff_from_prev_frame(&mut self) -> impl Iterator<Item = (u32, u32, [u8; 4])> + '_ {
+        let width = self.prepare_prev_canvas();
+        CanvasDiff {
+            canvas: match width {
+                0 => &[],
+                _ => &self.canvas,
+            },
+            prev_canvas: &mut self.prev_canvas,
+            width,
+            idx: 0,
+        }
+    }
+
+    /// Like `diff_from_prev_frame()`, but coalesces the changed pixels into rects, to be sent with their
+    /// `canvas_pixels()`
+    ///
+    /// Each rect covers a run of rows with changed pixels, from the leftmost to the rightmost change of these rows.
+    pub fn take_diff_rects(&mut self) -> Vec<DirtyRect> {
+        let width = self.prepare_prev_canvas();
+        let mut rects = Vec::new();
+        if width == 0 {
+            return rects;
+        }
+        let mut band: Option<DirtyRect> = None;
+        let rows = self.canvas.chunks_exact(as_usize(width));
+        let prev_rows = self.prev_canvas.chunks_exact_mut(as_usize(width));
+        for (y, (row, prev_row)) in rows.zip(prev_rows).enumerate() {
+            let first =
+                dispatch_simd_impl!(|simd_impl| simd_impl.first_changed_pixel(row, prev_row));
+            if first == row.len() {
+                rects.extend(band.take());
+                continue;
             }
+            let last = row
+                .iter()
+                .zip(prev_row.iter())
+                .rposition(|(pixel, prev_pixel)| pixel != prev_pixel)
+                .unwrap_or(first);
+            prev_row[first..=last].copy_from_slice(&row[first..=last]);
+            let y = y as u32;
+            let changed = DirtyRect {
+                min_x: first as u32,
+                min_y: y,
+                max_x: last as u32 + 1,
+                max_y: y + 1,
+            };
+            band = Some(band.map_or(changed, |band| band.union(changed)));
+        }
+        rects.extend(band);
+        rects
+    }
+
+    /// The canvas the diffs are computed from, `width` * `height` premultiplied pixels in the output field order
+    pub fn canvas_pixels(&self) -> &[[u8; 4]] {
+        &self.canvas
+    }
+
+    /// Clear `rect` of the canvas to transparent, clipped to the size of the last render
+    ///
+    /// The next render composites the whole canvas again, so the cached primitives covering `rect` are drawn back
+    /// over the cleared pixels.
+    pub fn clear_region(&mut self, rect: DirtyRect) {
+        let (width, height) = self.renderer.cached_size();
+        let rect = rect.intersection(DirtyRect {
+            min_x: 0,
+            min_y: 0,
+            max_x: width,
+            max_y: height,
+        });
+        if rect.min_x >= rect.max_x || rect.min_y >= rect.max_y {
+            return;
+        }
+        let len = as_usize(width) * as_usize(height);
+        for y in rect.min_y..rect.max_y {
+            let row = as_usize(y) * as_usize(width);
+            let span = row + as_usize(rect.min_x)..row + as_usize(rect.max_x);
+            if self.canvas.len() == len {
+                self.canvas[span.clone()].fill([0; 4]);
+            }
+            if self.canvas16.len() == len {
+                self.canvas16[span].fill([0; 4]);
+            }
+        }
+        self.renderer.force_redraw_next_frame();
+    }
+
+    /// Width of the canvas, with `prev_canvas` cleared to its size if it changed
+    fn prepare_prev_canvas(&mut self) -> u32 {
+        let (width, height) = self.renderer.cached_size();
+        if self.canvas.len() != as_usize(width) * as_usize(height) {
+            self.prev_canvas.clear();
+            return 0;
         }
+        if self.prev_canvas.len() != self.canvas.len() {
+            self.prev_canvas.clear();
+            self.prev_canvas.resize(self.canvas.len(), [0; 4]);
+        }
+        width
+    }
+
+    /// Keep the last frame, with the partial frame shown over it if any, scaled to `width` x `height`
+    fn save_pre_resize_canvas(&mut self, width: u32, height: u32) {
+        let (old_width, old_height) = self.renderer.cached_size();
+        if !self.renderer.inner.resize_fade
+            || self.canvas.is_empty()
+            || width == 0
+            || height == 0
+            || self.canvas.len() != as_usize(old_width) * as_usize(old_height)
+        {
+            self.pre_resize_canvas = None;
+            return;
+        }
+        let mut last_frame = match self.pre_resize_canvas.take() {
+            Some(pre_resize_canvas) => pre_resize_canvas,
+            None => vec![[0; 4]; self.canvas.len()],
+        };
+        dispatch_simd_impl!(
+            |simd_impl| simd_impl.egui_blend_u8_slice(&self.canvas, &mut last_frame)
+        );
+        self.pre_resize_canvas = Some(scale_bilinear(
+            &last_frame,
+            (old_width, old_height),
+            (width, height),
+        ));
     }
 }
 
@@ -202,6 +788,9 @@ impl EguiSoftwareRender {
     /// # Arguments
     /// * `output_field_order` - egui textures and vertex colors will be swizzled before rendering to match the desired
     ///   output buffer order.
+    ///
+    /// Textures and vertex colors are expected to be premultiplied, like egui does, see
+    /// `EguiSoftwareRender::with_straight_alpha_input()` otherwise.
     pub fn new(output_field_order: ColorFieldOrder) -> Self {
         EguiSoftwareRender {
             tiledcached_primitives: Default::default(),
@@ -215,9 +804,51 @@ impl EguiSoftwareRender {
                 output_field_order,
                 convert_tris_to_rects: true,
                 allow_raster_opt: true,
+                subpix_bits_override: None,
+                subpix_bits_threshold: 4096,
                 mode: SoftwareRenderCaching::BlendTiled,
+                detected_caching: None,
+                blend_precision: BlendPrecision::Bits8,
+                overdraw_visualization: false,
+                overdraw: Vec::new(),
+                #[cfg(feature = "log")]
+                overdraw_unsupported_logged: false,
+                debug_overlay: DebugOverlay::empty(),
+                straight_alpha_input: false,
+                last_dirty_rect: DirtyRect::new_empty(),
+                force_full_redraw: false,
+                pixel_alloc: PixelAlloc::default(),
+                #[cfg(feature = "threading")]
+                blit_threads: 0,
+                #[cfg(feature = "wgpu")]
+                next_user_texture_id: 0,
+                #[cfg(feature = "std")]
+                render_budget: None,
+                #[cfg(feature = "std")]
+                budget_exceeded_callback: None,
+                #[cfg(feature = "std")]
+                prim_profiler: None,
+                pre_render_callback: None,
+                post_render_callback: None,
+                #[cfg(feature = "test_render")]
+                pending_free_textures: Vec::new(),
+                frame_counter: 0,
+                max_idle_frames: 0,
+                max_cache_bytes: None,
+                max_cache_entries: None,
+                resize_fade: false,
+                color_blind_mode: ColorBlindMode::Normal,
+                grayscale: false,
+                color_temperature: 6500.0,
+                color_temperature_tint: [255; 4],
+                dithering: DitherMode::None,
+                partial_frame: false,
+                pixels_per_point: 1.0,
+                render_cost: RenderCostModel::default(),
                 #[cfg(feature = "raster_stats")]
                 stats: Default::default(),
+                #[cfg(all(feature = "cache_stats", not(feature = "raster_stats")))]
+                cache_stats: Default::default(),
             },
         }
     }
@@ -236,17 +867,210 @@ impl EguiSoftwareRender {
         self
     }
 
+    /// Subpixel bits of the triangle rasterizer, `None` by default to use 8 bits, or 2 bits for the meshes larger
+    ///   than `with_subpixel_bits_threshold()`. Clamped to 1 to 8 bits.
+    ///
+    /// 8 bits snap the vertices to 1/256 pixel, which matters for the edges of small text. The edge functions
+    ///   are computed in `i64` subpixels, 2 bits (1/4 pixel) keep them from overflowing on very large meshes, at
+    ///   the cost of visibly misplaced edges on small shapes.
+    pub fn with_subpixel_bits(mut self, set: Option<i32>) -> Self {
+        self.inner.subpix_bits_override = set;
+        self
+    }
+
+    /// Width or height in pixels above which a mesh is rasterized with 2 subpixel bits instead of 8, 4096 by
+    ///   default. Unused when `with_subpixel_bits()` is set.
+    pub fn with_subpixel_bits_threshold(mut self, set: u32) -> Self {
+        self.inner.subpix_bits_threshold = set;
+        self
+    }
+
     /// If true: rasterized ClippedPrimitives are cached and rendered to an intermediate tiled canvas. That canvas is
     /// then rendered over the frame buffer. If false ClippedPrimitives are rendered directly to the frame buffer.
     /// Rendering without caching is much slower and primarily intended for testing.
     pub fn with_mode(mut self, set: SoftwareRenderCaching) -> Self {
         self.inner.mode = set;
+        self.inner.detected_caching = None;
+        self
+    }
+
+    /// Precision of the canvas the cached primitives are blended into, `BlendPrecision::Bits8` by default.
+    /// Only used with `SoftwareRenderCaching::BlendTiled` when rendering through `EguiSoftwareRenderCanvas`.
+    pub fn with_blend_precision(mut self, set: BlendPrecision) -> Self {
+        self.inner.blend_precision = set;
+        self
+    }
+
+    /// If true: blend a heat map of how many primitives were written to each pixel over the output, from green
+    /// (1 write) to red (8+ writes).
+    /// Only available with `SoftwareRenderCaching::BlendTiled` when rendering through `EguiSoftwareRenderCanvas`, a
+    /// warning is logged otherwise.
+    pub fn with_overdraw_visualization(mut self, set: bool) -> Self {
+        self.inner.overdraw_visualization = set;
+        self
+    }
+
+    /// Debug overlays drawn over the output buffer after compositing, see `EguiSoftwareRender::draw_debug_overlay()`
+    ///
+    /// While any is enabled, `EguiSoftwareRenderCanvas` blits its whole canvas each frame, so no overlay of the
+    /// previous frame is left over.
+    pub fn with_debug_overlay(mut self, set: DebugOverlay) -> Self {
+        self.inner.debug_overlay = set;
+        self
+    }
+
+    /// If true: textures and vertex colors use straight alpha, and are premultiplied when uploaded or prepared.
+    ///   If false, the default, all input is assumed to already be premultiplied, as egui outputs it.
+    pub fn with_straight_alpha_input(mut self, set: bool) -> Self {
+        self.inner.straight_alpha_input = set;
+        self
+    }
+
+    /// Allocate the textures, tiled cache and canvas pixel buffers with `allocator`
+    ///
+    /// Buffers allocated before this call keep their allocator.
+    #[cfg(feature = "allocator_api")]
+    pub fn with_pixel_allocator(mut self, allocator: PixelAllocator) -> Self {
+        self.inner.pixel_alloc = PixelAlloc::new(allocator);
+        self
+    }
+
+    /// Number of threads blitting the `BlendTiled` canvas to the output buffer, each taking a stripe of rows of
+    /// tiles. 0, the default, uses `std::thread::available_parallelism()`. Unused with the `rayon` feature.
+    #[cfg(feature = "threading")]
+    pub fn with_blit_threads(mut self, set: usize) -> Self {
+        self.inner.blit_threads = set;
+        self
+    }
+
+    /// Keep the cached primitives egui stopped painting for `set` frames, 0 by default. Popups and tooltips shown
+    ///   again within that delay reuse their cached render, at the cost of the memory of the idle primitives.
+    pub fn with_max_idle_frames(mut self, set: u32) -> Self {
+        self.inner.max_idle_frames = set;
+        self
+    }
+
+    /// Evict cached primitives once their pixels or meshes take more than `set` megabytes, `None` (unlimited) by
+    ///   default. The least recently painted primitives are evicted first, and rasterized again if egui paints them
+    ///   again.
+    pub fn with_max_cache_memory_mb(mut self, set: Option<f32>) -> Self {
+        self.inner.max_cache_bytes = set.map(|mb| (mb.max(0.0) * (1024.0 * 1024.0)) as usize);
+        self
+    }
+
+    /// Evict cached primitives once there are more than `set` of them, `None` (unlimited) by default. The least
+    ///   recently painted primitives are evicted first, like with `with_max_cache_memory_mb()`.
+    pub fn with_max_cache_entries(mut self, set: Option<usize>) -> Self {
+        self.inner.max_cache_entries = set;
+        self
+    }
+
+    /// If true: on resize, the last frame is scaled to the new size and shown under the new frame until a frame
+    ///   renders every primitive, instead of showing the partial frames of `with_render_budget_ms()` over nothing.
+    /// Only used with `BlendPrecision::Bits8` caching modes when rendering through `EguiSoftwareRenderCanvas`.
+    pub fn with_resize_fade(mut self, set: bool) -> Self {
+        self.inner.resize_fade = set;
+        self
+    }
+
+    /// Simulate a color vision deficiency on the output, `ColorBlindMode::Normal` by default. Applied after
+    ///   compositing, before `with_grayscale()` and `with_color_temperature()`. To check that a UI stays usable by
+    ///   color blind users.
+    pub fn with_color_blind_mode(mut self, mode: ColorBlindMode) -> Self {
+        self.inner.color_blind_mode = mode;
+        self
+    }
+
+    /// If true: the output is desaturated after compositing, each color channel replaced by the luminance
+    ///   `(77 * R + 150 * G + 29 * B) >> 8`. For accessibility, or for grayscale displays like e-ink.
+    pub fn with_grayscale(mut self, set: bool) -> Self {
+        self.inner.grayscale = set;
+        self
+    }
+
+    /// Color temperature in kelvin the output is adjusted to, from 1000K to 10000K, 6500K (unchanged) by default.
+    ///   Lower values give the warmer colors of a "night mode". Applied after compositing, and after
+    ///   `with_grayscale()`.
+    pub fn with_color_temperature(mut self, kelvin: f32) -> Self {
+        self.inner.color_temperature = kelvin.clamp(1000.0, 10000.0);
+        let tint = color_temperature_tint(self.inner.color_temperature);
+        self.inner.color_temperature_tint = match self.inner.output_field_order {
+            ColorFieldOrder::Rgba => tint,
+            ColorFieldOrder::Bgra => swizzle_rgba_bgra(tint),
+        };
+        self
+    }
+
+    /// Quantize the output for displays with fewer than 8 bits per channel, `DitherMode::None` by default.
+    ///   `DitherMode::BayerMatrix4x4` only keeps `levels` values of each channel, and uses the pixel position in the
+    ///   buffer to spread the rounding error, so smooth gradients don't turn into bands. Applied after every other
+    ///   output filter, e.g. after `with_grayscale()` for a 4 bits grayscale e-paper display.
+    pub fn with_dithering(mut self, mode: DitherMode) -> Self {
+        self.inner.dithering = mode;
+        self
+    }
+
+    /// Stop rasterizing new primitives once a render spent `set` milliseconds on it, `None` by default.
+    ///   Skipped primitives are not drawn, and not cached, they are rasterized by a later render if egui still
+    ///   paints them. Cached primitives are always drawn.
+    #[cfg(feature = "std")]
+    pub fn with_render_budget_ms(mut self, set: Option<f32>) -> Self {
+        self.inner.render_budget = set.map(|ms| core::time::Duration::from_secs_f32(ms / 1000.0));
+        self
+    }
+
+    /// Called with the number of skipped primitives after a render exceeded the render budget, e.g. to request a
+    /// repaint. See `EguiSoftwareRender::with_render_budget_ms()`.
+    #[cfg(feature = "std")]
+    pub fn with_budget_exceeded_callback(
+        mut self,
+        callback: impl Fn(u32) + Send + Sync + 'static,
+    ) -> Self {
+        self.inner.budget_exceeded_callback = Some(Arc::new(callback));
         self
     }
 
+    /// Called for each primitive of the cached modes once prepared, with the time it took, e.g. to find the widget
+    /// that is slow to rasterize. Not called in `Direct` mode, nor for the primitives skipped by the render budget.
+    ///
+    /// Called from the `rayon` threads with the `rayon` feature.
+    #[cfg(feature = "std")]
+    pub fn set_prim_profiler(&mut self, profiler: impl Fn(PrimProfile) + Send + Sync + 'static) {
+        self.inner.prim_profiler = Some(Box::new(profiler));
+    }
+
+    /// Called each frame with the buffer the primitives are composited into, right before they are, e.g. to draw a
+    /// background. `EguiSoftwareRenderCanvas` gives its intermediate canvas, before the blit to the output buffer.
+    ///
+    /// `BlendTiled` replaces the dirty tiles it composites, so only the pixels of the other tiles are kept. It is not
+    /// called by the 16 bits `BlendTiled` canvas, see `BlendPrecision::Bits16`.
+    pub fn set_pre_render_callback(
+        &mut self,
+        callback: impl Fn(&mut BufferMutRef) + Send + Sync + 'static,
+    ) {
+        self.inner.pre_render_callback = Some(Box::new(callback));
+    }
+
+    /// Called each frame with the buffer the primitives were composited into, right after, e.g. to draw a watermark.
+    /// This is before the output filters and the blit of `EguiSoftwareRenderCanvas`, like
+    /// `EguiSoftwareRender::set_pre_render_callback()`.
+    ///
+    /// Only the pixels of the returned dirty rect are guaranteed to reach the screen.
+    pub fn set_post_render_callback(
+        &mut self,
+        callback: impl Fn(&mut BufferMutRef) + Send + Sync + 'static,
+    ) {
+        self.inner.post_render_callback = Some(Box::new(callback));
+    }
+
     pub fn with_canvas(self) -> EguiSoftwareRenderCanvas {
         EguiSoftwareRenderCanvas {
-            canvas: Vec::new(),
+            canvas: self.inner.pixel_alloc.vec(),
+            canvas16: self.inner.pixel_alloc.vec(),
+            pre_resize_canvas: None,
+            prev_canvas: self.inner.pixel_alloc.vec(),
+            #[cfg(feature = "gif_export")]
+            gif_recording: None,
             renderer: self,
         }
     }
@@ -256,20 +1080,118 @@ impl EguiSoftwareRender {
         &self.inner.stats
     }
 
+    /// Cache hits, misses and evictions of the last render
+    #[cfg(feature = "cache_stats")]
+    pub fn cache_stats(&self) -> &CacheStats {
+        self.inner.cache_stats()
+    }
+
     /// Get the caching mode of the renderer
     pub fn caching(&self) -> SoftwareRenderCaching {
         self.inner.mode
     }
 
+    /// Mode selected by the first render with `SoftwareRenderCaching::Auto`, `None` before it or with another mode
+    pub fn detected_caching_mode(&self) -> Option<SoftwareRenderCaching> {
+        self.inner.detected_caching
+    }
+
+    /// Get the enabled debug overlays
+    pub fn debug_overlay(&self) -> DebugOverlay {
+        self.inner.debug_overlay
+    }
+
+    /// Change the enabled debug overlays
+    pub fn set_debug_overlay(&mut self, debug_overlay: DebugOverlay) {
+        self.inner.debug_overlay = debug_overlay;
+    }
+
     /// Change the caching mode of the renderer
     pub fn set_caching(&mut self, caching: SoftwareRenderCaching) {
         if self.inner.mode == caching {
             return;
         }
         self.inner.mode = caching;
+        self.inner.detected_caching = None;
         self.clear_cache();
     }
 
+    /// Are triangle pairs converted to rects, see `EguiSoftwareRender::with_convert_tris_to_rects()`
+    pub const fn convert_tris_to_rects(&self) -> bool {
+        self.inner.convert_tris_to_rects
+    }
+
+    /// Change the conversion of triangle pairs to rects, the cache is cleared when it changes
+    pub fn set_convert_tris_to_rects(&mut self, set: bool) {
+        if self.inner.convert_tris_to_rects != set {
+            self.inner.convert_tris_to_rects = set;
+            self.clear_cache();
+        }
+    }
+
+    /// Are the rasterization optimizations allowed, see `EguiSoftwareRender::with_allow_raster_opt()`
+    pub const fn allow_raster_opt(&self) -> bool {
+        self.inner.allow_raster_opt
+    }
+
+    /// Change the rasterization optimizations, the cache is cleared when it changes
+    pub fn set_allow_raster_opt(&mut self, set: bool) {
+        if self.inner.allow_raster_opt != set {
+            self.inner.allow_raster_opt = set;
+            self.clear_cache();
+        }
+    }
+
+    /// Color vision deficiency simulated on the output, see `EguiSoftwareRender::with_color_blind_mode()`
+    pub const fn color_blind_mode(&self) -> ColorBlindMode {
+        self.inner.color_blind_mode
+    }
+
+    /// Is the output desaturated, see `EguiSoftwareRender::with_grayscale()`
+    pub const fn grayscale(&self) -> bool {
+        self.inner.grayscale
+    }
+
+    /// Color temperature of the output, see `EguiSoftwareRender::with_color_temperature()`
+    pub const fn color_temperature(&self) -> f32 {
+        self.inner.color_temperature
+    }
+
+    /// Dithering of the output, see `EguiSoftwareRender::with_dithering()`
+    pub const fn dithering(&self) -> DitherMode {
+        self.inner.dithering
+    }
+
+    /// Change the simulated color vision deficiency, the next render redraws everything when it changes
+    pub fn set_color_blind_mode(&mut self, mode: ColorBlindMode) {
+        if self.inner.color_blind_mode != mode {
+            self.inner.color_blind_mode = mode;
+            self.inner.force_full_redraw = true;
+        }
+    }
+
+    /// Change the output dithering, the next render redraws everything when it changes
+    pub fn set_dithering(&mut self, mode: DitherMode) {
+        if self.inner.dithering != mode {
+            self.inner.dithering = mode;
+            self.inner.force_full_redraw = true;
+        }
+    }
+
+    /// Change the output desaturation, the next render redraws everything when it changes
+    pub fn set_grayscale(&mut self, grayscale: bool) {
+        if self.inner.grayscale != grayscale {
+            self.inner.grayscale = grayscale;
+            self.inner.force_full_redraw = true;
+        }
+    }
+
+    /// Change the render budget, see `EguiSoftwareRender::with_render_budget_ms()`
+    #[cfg(feature = "std")]
+    pub fn set_render_budget_ms(&mut self, set: Option<f32>) {
+        self.inner.render_budget = set.map(|ms| core::time::Duration::from_secs_f32(ms / 1000.0));
+    }
+
     /// Clear cache and reclaim memory
     ///
     /// This will cause the next render to redraw everything
@@ -277,21 +1199,226 @@ impl EguiSoftwareRender {
         self.tiledcached_primitives = Default::default();
         self.dirtycached_primitives = Default::default();
         self.inner.dirty_tiles = Default::default();
-        self.inner.dirty_rects = Default::default();
+        self.inner.dirty_rects.reset();
+        self.inner.overdraw = Default::default();
     }
 
-    /// The latest renderer `buffer_ref` width and height, if a cacheing mode is selected
-    pub const fn cached_size(&self) -> (u32, u32) {
-        self.inner.cached_size
+    /// Redraw the whole buffer on the next render, without clearing the cache
+    ///
+    /// Cached primitives are not rasterized again, but every tile (or the whole canvas with `Mesh`) is composited again.
+    pub fn force_redraw_next_frame(&mut self) {
+        self.inner.force_full_redraw = true;
     }
 
-    /// Renders the given paint jobs to buffer_ref. Alternatively, when using caching
-    /// EguiSoftwareRender::render_to_canvas() and subsequently EguiSoftwareRender::blit_canvas_to_buffer() can be run
-    /// separately so that the primary rendering in render_to_canvas() can happen without a lock on the frame buffer.
-    ///
+    /// Mark the cached primitive with the prepared mesh `hash` stale, so the next render painting it rasterizes it
+    ///   again and redraws its area
     ///
-    /// # Arguments
-    /// * `buffer_ref` - Buffer to render into.
+    /// Returns false if no primitive with this hash is cached. It is kept until then, so if egui stops painting it
+    /// its area is redrawn like for any other primitive that disappears.
+    pub fn invalidate_hash(&mut self, hash: u32) -> bool {
+        let mut found = false;
+        if let Some(prim) = self.tiledcached_primitives.get_mut(&hash) {
+            prim.stale = true;
+            found = true;
+        }
+        if let Some(prim) = self.dirtycached_primitives.get_mut(&hash) {
+            prim.stale = true;
+            found = true;
+        }
+        found
+    }
+
+    /// Mark the cached primitives drawn with the texture `id` stale, e.g. after updating a user texture in place
+    ///
+    /// Returns the number of invalidated primitives, see `EguiSoftwareRender::invalidate_hash()`.
+    pub fn invalidate_all_with_texture_id(&mut self, id: egui::TextureId) -> usize {
+        let tiled = self
+            .tiledcached_primitives
+            .values_mut()
+            .map(DerefMut::deref_mut);
+        let mesh = self
+            .dirtycached_primitives
+            .values_mut()
+            .map(DerefMut::deref_mut);
+        let mut invalidated = 0;
+        for prim in tiled.chain(mesh).filter(|prim| prim.texture_id == id) {
+            prim.stale = true;
+            invalidated += 1;
+        }
+        invalidated
+    }
+
+    /// Copies the pixels of `src_rect`, as `[x, y, width, height]`, from the texture `src` to `dst` at `dst_origin`,
+    /// e.g. to pack sprites in an atlas. `src` and `dst` can be the same texture, with overlapping rects.
+    ///
+    /// The primitives drawn with `dst` are invalidated. Returns false, copying nothing, if either texture isn't loaded
+    /// or a rect isn't within its texture. egui overwrites the pixels again if it updates `dst`.
+    pub fn blit_texture(
+        &mut self,
+        src: egui::TextureId,
+        src_rect: [usize; 4],
+        dst: egui::TextureId,
+        dst_origin: [usize; 2],
+    ) -> bool {
+        let blitted = if src == dst {
+            self.inner
+                .textures
+                .get_mut(&dst)
+                .is_some_and(|texture| texture.copy_within(src_rect, dst_origin))
+        } else if let Some(src_texture) = self.inner.textures.remove(&src) {
+            let blitted = self
+                .inner
+                .textures
+                .get_mut(&dst)
+                .is_some_and(|dst_texture| {
+                    egui_texture::blit(&src_texture, src_rect, dst_texture, dst_origin)
+                });
+            self.inner.textures.insert(src, src_texture);
+            blitted
+        } else {
+            false
+        };
+        if blitted {
+            self.invalidate_all_with_texture_id(dst);
+        }
+        blitted
+    }
+
+    /// Overwrites the pixels of `rect`, as `[x, y, width, height]`, of the texture `id` with `color`, e.g. to clear
+    /// a slot of an atlas
+    ///
+    /// Like `EguiSoftwareRender::blit_texture()`, returns false if the texture isn't loaded or `rect` isn't within it.
+    pub fn fill_texture_rect(
+        &mut self,
+        id: egui::TextureId,
+        rect: [usize; 4],
+        color: Color32,
+    ) -> bool {
+        let color = match self.inner.output_field_order {
+            ColorFieldOrder::Rgba => color.to_array(),
+            ColorFieldOrder::Bgra => swizzle_rgba_bgra(color.to_array()),
+        };
+        let filled = self
+            .inner
+            .textures
+            .get_mut(&id)
+            .is_some_and(|texture| texture.fill_rect(rect, color));
+        if filled {
+            self.invalidate_all_with_texture_id(id);
+        }
+        filled
+    }
+
+    /// Dirty rects computed during the last render (`MeshTiled` only)
+    pub fn dirty_rects(&self) -> &ComputeTiledDirtyRects {
+        &self.inner.dirty_rects
+    }
+
+    /// Estimate of the bytes allocated by the render caches
+    ///
+    /// Includes the cached primitives (tile buffers or meshes), dirty tiles and dirty rects buffers.
+    /// Textures and the canvas of `EguiSoftwareRenderCanvas` are not included.
+    pub fn cache_memory_usage(&self) -> usize {
+        let stats = self.memory_stats();
+        (stats.tiled_cache_bytes + stats.mesh_cache_bytes + stats.dirty_rect_bytes) as usize
+    }
+
+    /// Microseconds rasterizing every mesh of `paint_jobs` would take, e.g. to drop frames or degrade quality
+    ///   before rendering. Cached primitives drawn again cost almost nothing, this is the cost of the first render,
+    ///   or of `SoftwareRenderCaching::Direct`.
+    ///
+    /// Intentionally approximate: each mesh costs a time per vertex plus a time per pixel of its bounds within its
+    ///   clip rect. With `std`, both times are fitted to the first 100 renders rasterizing something, the same
+    ///   estimates let `with_render_budget_ms()` skip the primitives that would exceed the budget.
+    pub fn estimate_render_time_us(
+        &self,
+        paint_jobs: &[egui::ClippedPrimitive],
+        pixels_per_point: f32,
+    ) -> u64 {
+        let to_px = |rect: egui::Rect| {
+            egui::Rect::from_min_max(
+                (rect.min.to_vec2() * pixels_per_point).to_pos2(),
+                (rect.max.to_vec2() * pixels_per_point).to_pos2(),
+            )
+        };
+        let nanos: f64 = paint_jobs
+            .iter()
+            .filter_map(|paint_job| match &paint_job.primitive {
+                egui::epaint::Primitive::Mesh(mesh) => Some(RasterWork::of_mesh(
+                    mesh.vertices.len(),
+                    to_px(mesh.calc_bounds()),
+                    to_px(paint_job.clip_rect),
+                )),
+                egui::epaint::Primitive::Callback(_) => None,
+            })
+            .map(|work| self.inner.render_cost.estimate_ns(work))
+            .sum();
+        (nanos / 1000.0) as u64
+    }
+
+    /// Number of cached primitives, in any caching mode
+    pub fn primitive_count(&self) -> usize {
+        self.tiledcached_primitives.len() + self.dirtycached_primitives.len()
+    }
+
+    /// Number of loaded textures
+    pub fn texture_count(&self) -> usize {
+        self.inner.textures.len()
+    }
+
+    /// The loaded texture `id`, e.g. for a post render callback to sample the same texels as the meshes
+    pub fn get_texture(&self, id: egui::TextureId) -> Option<&EguiTexture> {
+        self.inner.textures.get(&id)
+    }
+
+    /// Bytes currently allocated by the textures and each cache
+    ///
+    /// Computed from the live data structures. The canvas of `EguiSoftwareRenderCanvas` is not included.
+    pub fn memory_stats(&self) -> MemoryStats {
+        let texture_bytes = self.inner.textures.capacity()
+            * size_of::<(egui::TextureId, EguiTexture)>()
+            + self
+                .inner
+                .textures
+                .values()
+                .map(|texture| texture.data.capacity() * size_of::<[u8; 4]>())
+                .sum::<usize>();
+        let tiled_cache_bytes = self.tiledcached_primitives.capacity()
+            * size_of::<(u32, TiledCachedPrimitive)>()
+            + self
+                .tiledcached_primitives
+                .values()
+                .map(CachedPrimitive::memory_usage_bytes)
+                .sum::<usize>();
+        let mesh_cache_bytes = self.dirtycached_primitives.capacity()
+            * size_of::<(u32, MeshCachedPrimitive)>()
+            + self
+                .dirtycached_primitives
+                .values()
+                .map(CachedPrimitive::memory_usage_bytes)
+                .sum::<usize>();
+        let dirty_rect_bytes =
+            self.inner.dirty_tiles.capacity() + self.inner.dirty_rects.memory_usage_bytes();
+        MemoryStats {
+            texture_bytes: texture_bytes as u64,
+            tiled_cache_bytes: tiled_cache_bytes as u64,
+            mesh_cache_bytes: mesh_cache_bytes as u64,
+            dirty_rect_bytes: dirty_rect_bytes as u64,
+        }
+    }
+
+    /// The latest renderer `buffer_ref` width and height, if a cacheing mode is selected
+    pub const fn cached_size(&self) -> (u32, u32) {
+        self.inner.cached_size
+    }
+
+    /// Renders the given paint jobs to buffer_ref. Alternatively, when using caching
+    /// EguiSoftwareRender::render_to_canvas() and subsequently EguiSoftwareRender::blit_canvas_to_buffer() can be run
+    /// separately so that the primary rendering in render_to_canvas() can happen without a lock on the frame buffer.
+    ///
+    ///
+    /// # Arguments
+    /// * `buffer_ref` - Buffer to render into.
     /// * `redraw_everything_this_frame` - Redraw the whole buffer (ie. resize)
     /// * `paint_jobs` - List of `egui::ClippedPrimitive` from egui to be rendered.
     /// * `paint_jobs` - List of `egui::ClippedPrimitive` from egui to be rendered.
@@ -313,9 +1440,222 @@ impl EguiSoftwareRender {
         textures_delta: &egui::TexturesDelta,
         pixels_per_point: f32,
     ) -> DirtyRect {
+        self.inner.warn_overdraw_unsupported();
+        let dirty_rect = self.render_impl(
+            buffer_ref,
+            redraw_everything_this_frame,
+            paint_jobs,
+            textures_delta,
+            pixels_per_point,
+            None,
+        );
+        self.inner.post_process_rect(buffer_ref, dirty_rect);
+        dirty_rect
+    }
+
+    /// `EguiSoftwareRender::render()` into a new `width` x `height` buffer, redrawing everything.
+    /// Convenient for headless rendering, where the buffer doesn't come from a window.
+    ///
+    /// # Returns
+    /// The transparent initialized buffer with the paint jobs rendered over it, and the updated rect
+    ///
+    /// # Panics
+    /// See `EguiSoftwareRender::render()`
+    pub fn render_to_buffer(
+        &mut self,
+        paint_jobs: Vec<egui::ClippedPrimitive>,
+        textures_delta: &egui::TexturesDelta,
+        pixels_per_point: f32,
+        width: u32,
+        height: u32,
+    ) -> (Vec<[u8; 4]>, DirtyRect) {
+        let mut buffer = vec![[0u8; 4]; as_usize(width) * as_usize(height)];
+        let dirty_rect = self.render(
+            &mut BufferMutRef::new(&mut buffer, width, height),
+            true,
+            paint_jobs,
+            textures_delta,
+            pixels_per_point,
+        );
+        (buffer, dirty_rect)
+    }
+
+    /// Rasterize `paint_jobs` into the cache of a `width` x `height` buffer, without drawing to any buffer. Can run
+    ///   in a background thread before the window is shown, so the first rendered frame reuses the whole cache.
+    ///
+    /// The next `EguiSoftwareRender::render()` redraws the whole buffer from the cache. It must be given a buffer
+    ///   of the same size, and `redraw_everything_this_frame` set to false, which would clear the cache otherwise.
+    ///
+    /// # Returns
+    /// The smallest rect containing all the pixels the paint jobs would have updated
+    ///
+    /// # Panics
+    /// * `width` or `height` non positive
+    /// * `pixels_per_point` non positive
+    pub fn warm_cache(
+        &mut self,
+        paint_jobs: Vec<egui::ClippedPrimitive>,
+        textures_delta: &egui::TexturesDelta,
+        pixels_per_point: f32,
+        width: u32,
+        height: u32,
+    ) -> DirtyRect {
+        if self.inner.mode == SoftwareRenderCaching::Auto {
+            let mut buffer = vec![[0u8; 4]; as_usize(width) * as_usize(height)];
+            self.detect_caching_mode(
+                &mut BufferMutRef::new(&mut buffer, width, height),
+                &paint_jobs,
+                textures_delta,
+                pixels_per_point,
+            );
+        }
+        // Keeps what an earlier warm up or render of the same size cached
+        let redraw_everything = self.inner.cached_size != (width, height);
+        let dirty_rect = match self.inner.mode {
+            SoftwareRenderCaching::Direct => {
+                // Nothing to cache, but the next render expects the textures of this delta
+                self.inner.set_textures(textures_delta);
+                self.inner.free_textures(textures_delta);
+                DirtyRect {
+                    min_x: 0,
+                    min_y: 0,
+                    max_x: width,
+                    max_y: height,
+                }
+            }
+            SoftwareRenderCaching::MeshTiled | SoftwareRenderCaching::Mesh => {
+                let dirty_rect = self.inner.prepare_render_cache(
+                    &mut self.dirtycached_primitives,
+                    (width, height),
+                    redraw_everything,
+                    paint_jobs,
+                    textures_delta,
+                    pixels_per_point,
+                    EguiSoftwareRenderInner::mesh_prim,
+                    EguiSoftwareRenderInner::update_dirty_rects,
+                );
+                self.inner
+                    .evict_over_cache_limits(&mut self.dirtycached_primitives);
+                dirty_rect
+            }
+            SoftwareRenderCaching::BlendTiled | SoftwareRenderCaching::BlendTiledParallel => {
+                let dirty_rect = self.inner.prepare_render_cache(
+                    &mut self.tiledcached_primitives,
+                    (width, height),
+                    redraw_everything,
+                    paint_jobs,
+                    textures_delta,
+                    pixels_per_point,
+                    EguiSoftwareRenderInner::render_prim,
+                    EguiSoftwareRenderInner::update_dirty_tiles,
+                );
+                self.inner
+                    .evict_over_cache_limits(&mut self.tiledcached_primitives);
+                dirty_rect
+            }
+            SoftwareRenderCaching::Auto => unreachable!("resolved by detect_caching_mode()"),
+        };
+        // Nothing was drawn, the next render draws everything
+        self.inner.force_full_redraw = true;
+        dirty_rect
+    }
+
+    /// Render `paint_jobs` under each cached mode, and keep the fastest, see `SoftwareRenderCaching::Auto`
+    ///
+    /// The buffer and the cache are restored afterwards, so the next render redraws everything in the selected mode.
+    fn detect_caching_mode(
+        &mut self,
+        buffer_ref: &mut BufferMutRef,
+        paint_jobs: &[egui::ClippedPrimitive],
+        textures_delta: &egui::TexturesDelta,
+        pixels_per_point: f32,
+    ) {
+        #[cfg(feature = "std")]
+        let detected = {
+            // Mesh modes draw over the buffer content, each mode starts from the caller's buffer
+            let original = buffer_ref.data.to_vec();
+            let mut fastest = (SoftwareRenderCaching::BlendTiled, core::time::Duration::MAX);
+            for mode in [
+                SoftwareRenderCaching::BlendTiled,
+                SoftwareRenderCaching::MeshTiled,
+                SoftwareRenderCaching::Mesh,
+            ] {
+                self.inner.mode = mode;
+                self.clear_cache();
+                buffer_ref.data.copy_from_slice(&original);
+                let start = std::time::Instant::now();
+                self.render_impl(
+                    buffer_ref,
+                    true,
+                    paint_jobs.to_vec(),
+                    textures_delta,
+                    pixels_per_point,
+                    None,
+                );
+                let elapsed = start.elapsed();
+                if elapsed < fastest.1 {
+                    fastest = (mode, elapsed);
+                }
+            }
+            self.clear_cache();
+            self.inner.force_full_redraw = true;
+            buffer_ref.data.copy_from_slice(&original);
+            fastest.0
+        };
+        #[cfg(not(feature = "std"))]
+        let detected = {
+            let _ = (buffer_ref, paint_jobs, textures_delta, pixels_per_point);
+            SoftwareRenderCaching::BlendTiled
+        };
+        self.inner.mode = detected;
+        self.inner.detected_caching = Some(detected);
+    }
+
+    /// `canvas16` replaces `buffer_ref` as the `BlendTiled` canvas, see `BlendPrecision::Bits16`
+    fn render_impl(
+        &mut self,
+        buffer_ref: &mut BufferMutRef,
+        redraw_everything_this_frame: bool,
+        paint_jobs: Vec<egui::ClippedPrimitive>,
+        textures_delta: &egui::TexturesDelta,
+        pixels_per_point: f32,
+        canvas16: Option<&mut [[u16; 4]]>,
+    ) -> DirtyRect {
+        #[cfg(feature = "puffin")]
+        puffin::profile_scope!("EguiSoftwareRender::render");
         #[cfg(feature = "raster_stats")]
         self.inner.stats.clear();
-        match self.inner.mode {
+        #[cfg(feature = "alloc_stats")]
+        let allocs_start = alloc_stats::AllocSnapshot::now();
+        #[cfg(all(feature = "cache_stats", not(feature = "raster_stats")))]
+        {
+            self.inner.cache_stats = Default::default();
+        }
+        if self.inner.mode == SoftwareRenderCaching::Auto {
+            self.detect_caching_mode(buffer_ref, &paint_jobs, textures_delta, pixels_per_point);
+        }
+        // On resize every `BlendTiled` primitive would be rasterized to the cache only to be composited once: draw
+        // them directly, the next render fills the cache. The faded last frame must not be drawn over.
+        let resized = redraw_everything_this_frame
+            && self.inner.cached_size != (0, 0)
+            && self.inner.cached_size != (buffer_ref.width, buffer_ref.height);
+        let direct_resize = resized
+            && canvas16.is_none()
+            && self.inner.mode.is_blend_tiled()
+            && !self.inner.resize_fade;
+        let mode = if direct_resize {
+            // Like the composited tiles, the primitives are drawn over a transparent buffer
+            buffer_ref.data.fill([0; 4]);
+            SoftwareRenderCaching::Direct
+        } else {
+            self.inner.mode
+        };
+        // `buffer_ref` is not composited into with a 16 bits canvas
+        let callback_buffer = canvas16.is_none();
+        if callback_buffer && let Some(callback) = &self.inner.pre_render_callback {
+            callback(buffer_ref);
+        }
+        let dirty_rect = match mode {
             SoftwareRenderCaching::Direct => {
                 self.inner
                     .render_direct(buffer_ref, paint_jobs, textures_delta, pixels_per_point);
@@ -334,14 +1674,75 @@ impl EguiSoftwareRender {
                     textures_delta,
                     pixels_per_point,
                 ),
-            SoftwareRenderCaching::BlendTiled => self.render_blendtiled(
-                buffer_ref,
-                redraw_everything_this_frame,
-                paint_jobs,
-                textures_delta,
-                pixels_per_point,
-            ),
+            SoftwareRenderCaching::BlendTiled | SoftwareRenderCaching::BlendTiledParallel => {
+                assert!(
+                    self.inner.mode == SoftwareRenderCaching::BlendTiled || cfg!(feature = "rayon"),
+                    "SoftwareRenderCaching::BlendTiledParallel requires the `rayon` feature"
+                );
+                self.render_blendtiled(
+                    buffer_ref,
+                    redraw_everything_this_frame,
+                    paint_jobs,
+                    textures_delta,
+                    pixels_per_point,
+                    canvas16,
+                )
+            }
+            SoftwareRenderCaching::Auto => unreachable!("resolved by detect_caching_mode()"),
+        };
+        if callback_buffer && let Some(callback) = &self.inner.post_render_callback {
+            callback(buffer_ref);
+        }
+        self.inner.last_dirty_rect = dirty_rect;
+        self.inner.force_full_redraw = false;
+        if direct_resize {
+            // The cache of the old size is stale, the next render rasterizes everything at the new size
+            self.tiledcached_primitives.clear();
+            self.inner.cached_size = (buffer_ref.width, buffer_ref.height);
+            self.inner.force_full_redraw = true;
+            // The whole canvas was drawn, `blit_to_buffer_from_tiledcanvas()` must copy every tile of the new size
+            self.inner.tiles_dim = [
+                buffer_ref.width.div_ceil(TILE_SIZE),
+                buffer_ref.height.div_ceil(TILE_SIZE),
+            ];
+            self.inner.dirty_tiles.clear();
+            self.inner.dirty_tiles.resize(
+                as_usize(self.inner.tiles_dim[0] * self.inner.tiles_dim[1]),
+                EguiSoftwareRenderInner::DIRTY_TILE_MASK
+                    | EguiSoftwareRenderInner::OCCUPIED_TILE_MASK,
+            );
+        }
+        // Once rendered, so the primitives painted this frame are evicted from the next frame on
+        match mode {
+            SoftwareRenderCaching::MeshTiled | SoftwareRenderCaching::Mesh => self
+                .inner
+                .evict_over_cache_limits(&mut self.dirtycached_primitives),
+            SoftwareRenderCaching::BlendTiled | SoftwareRenderCaching::BlendTiledParallel => self
+                .inner
+                .evict_over_cache_limits(&mut self.tiledcached_primitives),
+            SoftwareRenderCaching::Direct | SoftwareRenderCaching::Auto => {}
         }
+        #[cfg(feature = "raster_stats")]
+        {
+            self.inner.stats.memory = self.memory_stats();
+            self.inner.stats.primitive_count = self.primitive_count();
+            self.inner.stats.texture_count = self.texture_count();
+            self.inner.stats.buffer_size = [buffer_ref.width, buffer_ref.height];
+            #[cfg(feature = "alloc_stats")]
+            {
+                let (allocs, bytes) = allocs_start.elapsed();
+                self.inner.stats.allocs_this_frame = allocs;
+                self.inner.stats.alloc_bytes_this_frame = bytes;
+            }
+        }
+        #[cfg(feature = "tracing")]
+        tracing::event!(
+            tracing::Level::INFO,
+            pixels_dirty = dirty_rect.area(),
+            primitives_cached = self.primitive_count(),
+            "egui_sw::render"
+        );
+        dirty_rect
     }
 
     fn render_blendtiled(
@@ -351,12 +1752,13 @@ impl EguiSoftwareRender {
         paint_jobs: Vec<egui::ClippedPrimitive>,
         textures_delta: &egui::TexturesDelta,
         pixels_per_point: f32,
+        canvas16: Option<&mut [[u16; 4]]>,
     ) -> DirtyRect {
         // TODO: need to deal with user textures. Either make the fields of EguiUserTextures pub or need to come up with a replacement.
 
         let dirty_rect = self.inner.prepare_render_cache(
             &mut self.tiledcached_primitives,
-            canvas,
+            (canvas.width, canvas.height),
             redraw_everything_this_frame,
             paint_jobs,
             textures_delta,
@@ -366,8 +1768,21 @@ impl EguiSoftwareRender {
         );
 
         if !dirty_rect.is_empty() {
-            self.inner
-                .render_from_tiledcache(&self.tiledcached_primitives, canvas);
+            let (width, height) = (canvas.width, canvas.height);
+            match canvas16 {
+                Some(canvas16) => self.inner.render_from_tiledcache(
+                    &self.tiledcached_primitives,
+                    canvas16,
+                    width,
+                    height,
+                ),
+                None => self.inner.render_from_tiledcache(
+                    &self.tiledcached_primitives,
+                    canvas.data,
+                    width,
+                    height,
+                ),
+            }
         }
         dirty_rect
     }
@@ -381,16 +1796,12 @@ impl EguiSoftwareRender {
     ) -> DirtyRect {
         let dirty_rect = self.inner.prepare_render_cache(
             &mut self.dirtycached_primitives,
-            canvas,
+            (canvas.width, canvas.height),
             redraw_everything_this_frame,
             paint_jobs,
             textures_delta,
             pixels_per_point,
-            |_self, prim, _cropped_min, _cropped_max, clip_rect, px_mesh| MeshCachedPrimitive {
-                inner: prim,
-                px_mesh,
-                clip_rect,
-            },
+            EguiSoftwareRenderInner::mesh_prim,
             EguiSoftwareRenderInner::update_dirty_rects,
         );
         if !dirty_rect.is_empty() {
@@ -402,11 +1813,23 @@ impl EguiSoftwareRender {
 }
 
 impl EguiSoftwareRenderInner {
+    #[cfg(feature = "cache_stats")]
+    fn cache_stats(&self) -> &CacheStats {
+        #[cfg(feature = "raster_stats")]
+        {
+            &self.stats.cache
+        }
+        #[cfg(not(feature = "raster_stats"))]
+        {
+            &self.cache_stats
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn prepare_render_cache<F, U, P>(
         &mut self,
         cached_primitives: &mut HashMap<u32, P>,
-        canvas: &mut BufferMutRef,
+        (width, height): (u32, u32),
         redraw_everything_this_frame: bool,
         paint_jobs: Vec<egui::ClippedPrimitive>,
         textures_delta: &egui::TexturesDelta,
@@ -415,71 +1838,150 @@ impl EguiSoftwareRenderInner {
         f_update_dirty_tiles: U,
     ) -> DirtyRect
     where
-        F: Fn(&Self, CacheReuse, Vec2, Vec2, egui::Rect, Mesh) -> P + Sync + Send,
+        F: Fn(&Self, CacheReuse, egui::Rect, Mesh) -> P + Sync + Send,
         U: Fn(&mut Self, &HashMap<u32, P>),
-        P: DerefMut<Target = CacheReuse> + Sync + Send,
+        P: CachedPrimitive,
     {
         // TODO: need to deal with user textures. Either make the fields of EguiUserTextures pub or need to come up with a replacement.
 
-        assert!(canvas.width > 0);
-        assert!(canvas.height > 0);
+        assert!(width > 0);
+        assert!(height > 0);
         assert!(pixels_per_point > 0.0);
 
         if redraw_everything_this_frame {
             cached_primitives.clear();
         } else {
-            assert_eq!(self.cached_size, (canvas.width, canvas.height));
+            assert_eq!(self.cached_size, (width, height));
         }
-        self.cached_size = (canvas.width, canvas.height);
-
-        for (_hash, prim) in cached_primitives.iter_mut() {
-            prim.deref_mut().seen_this_frame = false;
+        self.cached_size = (width, height);
+        self.pixels_per_point = pixels_per_point;
+        self.frame_counter += 1;
+
+        for prim in cached_primitives.values_mut() {
+            let prim = prim.deref_mut();
+            prim.seen_this_frame = false;
+            prim.rendered_this_frame = false;
         }
 
-        self.tiles_dim = [
-            canvas.width.div_ceil(TILE_SIZE),
-            canvas.height.div_ceil(TILE_SIZE),
-        ];
+        self.tiles_dim = [width.div_ceil(TILE_SIZE), height.div_ceil(TILE_SIZE)];
 
         self.set_textures(textures_delta);
 
-        self.render_prims_to_cache(
+        #[cfg(feature = "std")]
+        let start = std::time::Instant::now();
+        let (over_budget, work) = self.render_prims_to_cache(
             cached_primitives,
             paint_jobs,
             pixels_per_point,
             f_render_prims_to_cache,
         );
+        #[cfg(feature = "std")]
+        self.render_cost.calibrate(work, start.elapsed());
+        #[cfg(not(feature = "std"))]
+        let _ = work;
+        self.partial_frame = over_budget > 0;
+        #[cfg(feature = "raster_stats")]
+        {
+            self.stats.primitives_over_budget = over_budget;
+        }
+        #[cfg(feature = "std")]
+        if over_budget > 0
+            && let Some(callback) = &self.budget_exceeded_callback
+        {
+            callback(over_budget);
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = over_budget;
 
         let mut dirty_rect = self.update_dirty_rect(cached_primitives);
+        if redraw_everything_this_frame || self.force_full_redraw {
+            dirty_rect = DirtyRect {
+                min_x: 0,
+                min_y: 0,
+                max_x: width,
+                max_y: height,
+            };
+        }
 
         if !dirty_rect.is_empty() {
             f_update_dirty_tiles(self, cached_primitives);
         }
 
         // clear_unused_cached_prims
-        cached_primitives.retain(|_hash, prim| prim.deref().seen_this_frame);
-
-        if redraw_everything_this_frame {
-            dirty_rect = DirtyRect {
-                min_x: 0,
-                min_y: 0,
-                max_x: canvas.width,
-                max_y: canvas.height,
-            };
+        #[cfg(feature = "cache_stats")]
+        let cached_len = cached_primitives.len();
+        let (frame_counter, max_idle_frames) =
+            (self.frame_counter, u64::from(self.max_idle_frames));
+        cached_primitives.retain(|_hash, prim| {
+            frame_counter - prim.deref().last_seen_frame_idx <= max_idle_frames
+        });
+        #[cfg(feature = "cache_stats")]
+        self.cache_stats()
+            .add_evictions((cached_len - cached_primitives.len()) as u32);
+
+        #[cfg(feature = "lz4_compression")]
+        for prim in cached_primitives.values_mut() {
+            if !prim.seen_this_frame {
+                prim.compress();
+            }
         }
 
         self.free_textures(textures_delta);
         dirty_rect
     }
+
+    /// Evict the least recently painted primitives, then the largest, until the cache is within
+    /// `EguiSoftwareRender::with_max_cache_memory_mb()` and `EguiSoftwareRender::with_max_cache_entries()`
+    fn evict_over_cache_limits<P: CachedPrimitive>(
+        &mut self,
+        cached_primitives: &mut HashMap<u32, P>,
+    ) {
+        if self.max_cache_bytes.is_none() && self.max_cache_entries.is_none() {
+            return;
+        }
+        let max_bytes = self.max_cache_bytes.unwrap_or(usize::MAX);
+        let max_entries = self.max_cache_entries.unwrap_or(usize::MAX);
+        let mut bytes: usize = cached_primitives
+            .values()
+            .map(CachedPrimitive::memory_usage_bytes)
+            .sum();
+        let mut len = cached_primitives.len();
+        if bytes <= max_bytes && len <= max_entries {
+            return;
+        }
+
+        let mut eviction_order: Vec<(u64, usize, u32)> = cached_primitives
+            .iter()
+            .map(|(&hash, prim)| (prim.last_seen_frame_idx, prim.memory_usage_bytes(), hash))
+            .collect();
+        eviction_order.sort_unstable_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+        for (last_seen_frame_idx, prim_bytes, hash) in eviction_order {
+            if bytes <= max_bytes && len <= max_entries {
+                break;
+            }
+            cached_primitives.remove(&hash);
+            bytes -= prim_bytes;
+            len -= 1;
+            #[cfg(feature = "cache_stats")]
+            self.cache_stats().add_evictions(1);
+            // Its pixels are left on screen if egui stops painting it
+            self.force_full_redraw |= last_seen_frame_idx == self.frame_counter;
+        }
+    }
+
     /// Draw canvas alpha over given buffer.
     /// Only run after EguiSoftwareRender::render() with TiledCacheing to run both.
     /// Only writes tile regions that contain pixels that are not fully transparent.
-    fn blit_to_buffer_from_tiledcanvas(
+    fn blit_to_buffer_from_tiledcanvas<P: CanvasPixel>(
         &self,
         simd_impl: impl SelectedImpl,
-        canvas: &BufferMutRef,
+        canvas: &[P],
         buffer: &mut BufferMutRef,
     ) {
+        #[cfg(feature = "puffin")]
+        puffin::profile_scope!("blit_canvas_to_buffer");
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("egui_sw::blit_to_buffer_from_tiledcanvas").entered();
         #[cfg(feature = "raster_stats")]
         let start = std::time::Instant::now();
 
@@ -488,7 +1990,7 @@ impl EguiSoftwareRenderInner {
         //     *pixel = egui_blend_u8(*src, *pixel);
         // });
 
-        if canvas.data.is_empty() {
+        if canvas.is_empty() {
             #[cfg(feature = "log")]
             log::error!(
                 "Canvas not initialized, call EguiSoftwareRender::blit_canvas_to_buffer() only after EguiSoftwareRender::render_to_canvas()"
@@ -496,13 +1998,11 @@ impl EguiSoftwareRenderInner {
             return;
         }
 
-        let width = canvas.width;
-        let height = canvas.height;
-        assert_eq!(canvas.data.len(), as_usize(width * height));
+        let width = buffer.width;
+        let height = buffer.height;
+        assert_eq!(canvas.len(), as_usize(width * height));
         assert_eq!(buffer.data.len(), as_usize(width * height));
 
-        let tiles_x = self.tiles_dim[0];
-
         #[cfg(feature = "rayon")]
         {
             use rayon::{
@@ -510,56 +2010,54 @@ impl EguiSoftwareRenderInner {
                 slice::ParallelSliceMut,
             };
             // blit rows of tiles in parallel
-
-            let width = buffer.width;
-            let px_per_row_of_tiles = as_usize(width) * as_usize(TILE_SIZE);
-
             buffer
                 .data
-                .par_chunks_mut(px_per_row_of_tiles)
+                .par_chunks_mut(as_usize(width) * as_usize(TILE_SIZE))
                 .enumerate()
                 .for_each(|(tile_row, tile_height_row)| {
-                    let tile_row = tile_row as u32;
-                    let height = tile_height_row.len() as u32 / width; // Might be less than TILE_SIZE
-                    let buffer_tile_row = &mut BufferMutRef::new(tile_height_row, width, height);
-
-                    for (tile_idx, &mask) in self.dirty_tiles.iter().enumerate() {
-                        if mask & EguiSoftwareRenderInner::OCCUPIED_TILE_MASK == 0 {
-                            continue;
-                        }
-
-                        let tile_idx = tile_idx as u32;
-                        let tile_y = tile_idx / tiles_x;
-                        if tile_y != tile_row {
-                            continue;
-                        }
-
-                        let tile_x = tile_idx % tiles_x;
-
-                        let x_start = tile_x * TILE_SIZE;
-                        let y_start = 0;
-                        let x_end = (x_start + TILE_SIZE).min(width);
-                        let y_end = TILE_SIZE.min(height);
-
-                        let canvas_row_offset = tile_row * TILE_SIZE;
-
-                        blit_rect(
-                            simd_impl,
-                            canvas,
-                            buffer_tile_row,
-                            DirtyRect {
-                                min_x: x_start,
-                                min_y: y_start,
-                                max_x: x_end,
-                                max_y: y_end,
-                            },
-                            canvas_row_offset,
-                        );
-                    }
+                    self.blit_row_of_tiles(
+                        simd_impl,
+                        canvas,
+                        width,
+                        tile_row as u32,
+                        tile_height_row,
+                    );
                 });
         }
-        #[cfg(not(feature = "rayon"))]
+        #[cfg(all(feature = "threading", not(feature = "rayon")))]
+        {
+            // blit stripes of rows of tiles on scoped threads
+            let mut rows: Vec<_> = buffer
+                .data
+                .chunks_mut(as_usize(width) * as_usize(TILE_SIZE))
+                .enumerate()
+                .collect();
+            let threads = match self.blit_threads {
+                0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+                n => n,
+            };
+            let rows_per_thread = rows.len().div_ceil(threads).max(1);
+
+            std::thread::scope(|scope| {
+                for stripe in rows.chunks_mut(rows_per_thread) {
+                    scope.spawn(move || {
+                        for (tile_row, tile_height_row) in stripe {
+                            let tile_row = *tile_row as u32;
+                            self.blit_row_of_tiles(
+                                simd_impl,
+                                canvas,
+                                width,
+                                tile_row,
+                                tile_height_row,
+                            );
+                        }
+                    });
+                }
+            });
+        }
+        #[cfg(not(any(feature = "rayon", feature = "threading")))]
         {
+            let tiles_x = self.tiles_dim[0];
             for (tile_idx, &mask) in self.dirty_tiles.iter().enumerate() {
                 if mask & Self::OCCUPIED_TILE_MASK == 0 {
                     continue;
@@ -569,21 +2067,22 @@ impl EguiSoftwareRenderInner {
                 let tile_x = tile_idx % tiles_x;
                 let tile_y = tile_idx / tiles_x;
 
-                let x_start = tile_x * TILE_SIZE;
-                let y_start = tile_y * TILE_SIZE;
-                let x_end = (x_start + TILE_SIZE).min(width);
-                let y_end = (y_start + TILE_SIZE).min(height);
+                let x_start = i64::from(tile_x * TILE_SIZE);
+                let y_start = i64::from(tile_y * TILE_SIZE);
+                let tile_size = i64::from(TILE_SIZE);
 
                 blit_rect(
                     simd_impl,
                     canvas,
+                    width,
                     buffer,
-                    DirtyRect {
-                        min_x: x_start,
-                        min_y: y_start,
-                        max_x: x_end,
-                        max_y: y_end,
-                    },
+                    DirtyRect::clamped(
+                        x_start,
+                        y_start,
+                        x_start + tile_size,
+                        y_start + tile_size,
+                        (width, height),
+                    ),
                     0,
                 )
             }
@@ -595,6 +2094,135 @@ impl EguiSoftwareRenderInner {
         }
     }
 
+    /// `blit_to_buffer_from_tiledcanvas()` for the occupied tiles of row `tile_row`
+    /// * `tile_height_row` - The buffer rows of `tile_row`, might be less than `TILE_SIZE` rows
+    #[cfg(any(feature = "rayon", feature = "threading"))]
+    fn blit_row_of_tiles<P: CanvasPixel>(
+        &self,
+        simd_impl: impl SelectedImpl,
+        canvas: &[P],
+        width: u32,
+        tile_row: u32,
+        tile_height_row: &mut [[u8; 4]],
+    ) {
+        let tiles_x = self.tiles_dim[0];
+        let height = tile_height_row.len() as u32 / width; // Might be less than TILE_SIZE
+        let buffer_tile_row = &mut BufferMutRef::new(tile_height_row, width, height);
+
+        for (tile_idx, &mask) in self.dirty_tiles.iter().enumerate() {
+            if mask & Self::OCCUPIED_TILE_MASK == 0 {
+                continue;
+            }
+
+            let tile_idx = tile_idx as u32;
+            let tile_y = tile_idx / tiles_x;
+            if tile_y != tile_row {
+                continue;
+            }
+
+            let tile_x = tile_idx % tiles_x;
+
+            let x_start = i64::from(tile_x * TILE_SIZE);
+            let tile_size = i64::from(TILE_SIZE);
+
+            let canvas_row_offset = tile_row * TILE_SIZE;
+
+            blit_rect(
+                simd_impl,
+                canvas,
+                width,
+                buffer_tile_row,
+                DirtyRect::clamped(x_start, 0, x_start + tile_size, tile_size, (width, height)),
+                canvas_row_offset,
+            );
+        }
+    }
+
+    /// Apply `EguiSoftwareRender::with_color_blind_mode()`, `EguiSoftwareRender::with_grayscale()`,
+    /// `EguiSoftwareRender::with_color_temperature()` and `EguiSoftwareRender::with_dithering()` to `rect` of the
+    /// composited `buffer`
+    fn post_process_rect(&self, buffer: &mut BufferMutRef, rect: DirtyRect) {
+        let tint = self.color_temperature_tint;
+        let matrix = self.color_blind_mode.matrix();
+        if matrix.is_none()
+            && !self.grayscale
+            && tint == [255; 4]
+            && self.dithering == DitherMode::None
+        {
+            return;
+        }
+        let [r, g, b] = GRAYSCALE_WEIGHTS_RGB;
+        let (weights, matrix) = match self.output_field_order {
+            ColorFieldOrder::Rgba => ([r, g, b], matrix),
+            // B and R swapped in both the input and output channels
+            ColorFieldOrder::Bgra => (
+                [b, g, r],
+                matrix.map(|m| [2, 1, 0].map(|row| [m[row][2], m[row][1], m[row][0]])),
+            ),
+        };
+        dispatch_simd_impl!(|simd_impl| {
+            for y in rect.min_y..rect.max_y {
+                let row = buffer.get_mut_span(rect.min_x, rect.max_x, y);
+                if let Some(matrix) = &matrix {
+                    simd_impl.color_matrix_slice(row, matrix);
+                }
+                if self.grayscale {
+                    simd_impl.grayscale_slice(row, weights);
+                }
+                if tint != [255; 4] {
+                    for pixel in row.iter_mut() {
+                        *pixel = simd_impl.unorm_mult4x4(*pixel, tint);
+                    }
+                }
+                if let DitherMode::BayerMatrix4x4 { levels } = self.dithering {
+                    simd_impl.dither_bayer4x4_slice(row, rect.min_x, y, levels);
+                }
+            }
+        });
+    }
+
+    /// Logs once that `overdraw_visualization` does nothing on this render path, see
+    /// `EguiSoftwareRender::with_overdraw_visualization()`
+    fn warn_overdraw_unsupported(&mut self) {
+        #[cfg(feature = "log")]
+        if self.overdraw_visualization && !self.overdraw_unsupported_logged {
+            self.overdraw_unsupported_logged = true;
+            log::warn!(
+                "Overdraw visualization is only drawn through EguiSoftwareRenderCanvas with BlendTiled (mode {:?})",
+                self.mode
+            );
+        }
+    }
+
+    /// Blend the overdraw heat map over the given buffer, from green (1 write) to red (8+ writes).
+    /// Only run after EguiSoftwareRender::render() with BlendTiled.
+    fn blend_overdraw_heat_map(&self, simd_impl: impl SelectedImpl, buffer: &mut BufferMutRef) {
+        if self.overdraw.len() != buffer.data.len() {
+            return;
+        }
+        let alpha = 128u32;
+        let gradient: [[u8; 4]; 8] = core::array::from_fn(|i| {
+            let red = i as u32 * 255 / 7;
+            let green = 255 - red;
+            let color = [
+                (red * alpha / 255) as u8,
+                (green * alpha / 255) as u8,
+                0,
+                alpha as u8,
+            ];
+            match self.output_field_order {
+                ColorFieldOrder::Rgba => color,
+                ColorFieldOrder::Bgra => swizzle_rgba_bgra(color),
+            }
+        });
+        for (pixel, &count) in buffer.data.iter_mut().zip(&self.overdraw) {
+            if count > 0 {
+                let src = gradient[(count as usize - 1).min(7)];
+                *pixel = simd_impl.egui_blend_u8(src, *pixel);
+            }
+        }
+    }
+
     /// Render directly into buffer without cache. This is much slower and mainly intended for testing.
     fn render_direct(
         &mut self,
@@ -604,13 +2232,17 @@ impl EguiSoftwareRenderInner {
         pixels_per_point: f32,
     ) {
         self.set_textures(textures_delta);
+        self.pixels_per_point = pixels_per_point;
 
-        #[cfg(feature = "raster_stats")]
+        #[cfg(feature = "puffin")]
+        puffin::profile_scope!("render_direct");
+        #[cfg(feature = "std")]
         let start = std::time::Instant::now();
 
+        let mut work = RasterWork::default();
         for paint_job in paint_jobs {
-            // TODO not sure why +1.5 is needed here. Occasionally things are cropped out without it.
-            let splat = 1.5f32;
+            // Same as the caching modes, so they draw the same pixels
+            let splat = 0.5f32;
             let (clip_rect, mesh_min, mesh_max, px_mesh) =
                 match self.prim_prepare_px_mesh(splat, pixels_per_point, paint_job) {
                     Some(x) => x,
@@ -623,33 +2255,28 @@ impl EguiSoftwareRenderInner {
                 continue;
             }
 
-            let render_in_low_precision = mesh_size.x > 4096.0 || mesh_size.y > 4096.0;
-            if render_in_low_precision {
-                draw_egui_mesh::<2>(
-                    &self.textures,
-                    direct_draw_buffer,
-                    &clip_rect,
-                    &px_mesh,
-                    Vec2::ZERO,
-                    self.allow_raster_opt,
-                    self.convert_tris_to_rects,
-                    #[cfg(all(feature = "raster_stats", not(feature = "rayon")))]
-                    &mut self.stats,
-                );
-            } else {
-                draw_egui_mesh::<8>(
-                    &self.textures,
-                    direct_draw_buffer,
-                    &clip_rect,
-                    &px_mesh,
-                    Vec2::ZERO,
-                    self.allow_raster_opt,
-                    self.convert_tris_to_rects,
-                    #[cfg(all(feature = "raster_stats", not(feature = "rayon")))]
-                    &mut self.stats,
-                );
-            }
+            let mesh_work = RasterWork::of_mesh(
+                px_mesh.vertices.len(),
+                egui::Rect::from_min_max(mesh_min.to_pos2(), mesh_max.to_pos2()),
+                clip_rect,
+            );
+            work.vertices += mesh_work.vertices;
+            work.pixels += mesh_work.pixels;
+
+            let subpix_bits =
+                self.subpix_bits(mesh_size.x.ceil() as u32, mesh_size.y.ceil() as u32);
+            self.draw_mesh(
+                direct_draw_buffer,
+                &clip_rect,
+                &px_mesh,
+                Vec2::ZERO,
+                subpix_bits,
+            );
         }
+        #[cfg(feature = "std")]
+        self.render_cost.calibrate(work, start.elapsed());
+        #[cfg(not(feature = "std"))]
+        let _ = work;
         #[cfg(feature = "raster_stats")]
         {
             self.stats.render_direct.mark(start);
@@ -658,63 +2285,64 @@ impl EguiSoftwareRenderInner {
         self.free_textures(textures_delta);
     }
 
-    fn render_prim(
+    fn mesh_prim(
         &self,
         prim: CacheReuse,
-        cropped_min: Vec2,
-        cropped_max: Vec2,
-        _clip_rect: egui::Rect,
+        clip_rect: egui::Rect,
         px_mesh: Mesh,
-    ) -> TiledCachedPrimitive {
-        let (width, height) = (prim.rect.width(), prim.rect.height());
-        let mut prim = TiledCachedPrimitive {
+    ) -> MeshCachedPrimitive {
+        MeshCachedPrimitive {
             inner: prim,
-            buffer: vec![[0u8; 4]; as_usize(width) * as_usize(height)],
-            occupied_tiles: Vec::with_capacity(64),
-        };
-        let mut buffer_ref = BufferMutRef {
-            data: &mut prim.buffer,
-            width,
-            height,
-            width_extent: width - 1,
-            height_extent: height - 1,
-        };
+            px_mesh,
+            clip_rect,
+        }
+    }
 
-        let clip_rect = egui::Rect {
-            min: Pos2::ZERO,
-            max: (cropped_max - cropped_min).to_pos2(),
+    fn render_prim(
+        &self,
+        prim: CacheReuse,
+        clip_rect: egui::Rect,
+        px_mesh: Mesh,
+    ) -> TiledCachedPrimitive {
+        // Same precision as when rendered in one piece, so the result does not depend on the tiles
+        let subpix_bits = self.subpix_bits(prim.rect.width(), prim.rect.height());
+        let mut tile_indices = tile_indices(&px_mesh, prim.rect, self.tiles_dim);
+        let mut tile_mesh = Mesh {
+            indices: Vec::new(),
+            ..px_mesh
         };
-        let offset = -vec2(cropped_min.x.floor(), cropped_min.y.floor());
-
-        let render_in_low_precision = width > 4096 || height > 4096;
-        if render_in_low_precision {
-            // Seems to not be an issue in direct draw? Seems like a bug.
-            draw_egui_mesh::<2>(
-                &self.textures,
-                &mut buffer_ref,
-                &clip_rect,
-                &px_mesh,
-                offset,
-                self.allow_raster_opt,
-                self.convert_tris_to_rects,
-                #[cfg(all(feature = "raster_stats", not(feature = "rayon")))]
-                &self.stats,
-            );
-        } else {
-            draw_egui_mesh::<8>(
-                &self.textures,
-                &mut buffer_ref,
-                &clip_rect,
-                &px_mesh,
-                offset,
-                self.allow_raster_opt,
-                self.convert_tris_to_rects,
-                #[cfg(all(feature = "raster_stats", not(feature = "rayon")))]
-                &self.stats,
-            );
+        let buffer = SparseBuffer::from_fn(
+            self.pixel_alloc,
+            prim.rect,
+            self.tiles_dim,
+            |tile, [tile_x, tile_y], tile_rect| {
+                let Some(indices) = tile_indices.remove(&[tile_x, tile_y]) else {
+                    return;
+                };
+                tile_mesh.indices = indices;
+                // Same clip rect as direct draw, restricted to the part of the primitive in the tile and moved into
+                // the tile space. Not `Rect::translate()`, which goes through the size and turns an infinite clip
+                // rect into NaN.
+                let origin = vec2((tile_x * TILE_SIZE) as f32, (tile_y * TILE_SIZE) as f32);
+                let clip_rect = egui::Rect {
+                    min: clip_rect
+                        .min
+                        .max(pos2(tile_rect.min_x as f32, tile_rect.min_y as f32))
+                        - origin,
+                    max: clip_rect
+                        .max
+                        .min(pos2(tile_rect.max_x as f32, tile_rect.max_y as f32))
+                        - origin,
+                };
+                self.draw_mesh(tile, &clip_rect, &tile_mesh, -origin, subpix_bits);
+            },
+        );
+        TiledCachedPrimitive {
+            buffer,
+            #[cfg(feature = "lz4_compression")]
+            compressed_buffer: None,
+            inner: prim,
         }
-        prim.update_occupied_tiles(self.tiles_dim[0], self.tiles_dim[1]);
-        prim
     }
 
     fn prim_prepare_update<F, P>(
@@ -724,9 +2352,10 @@ impl EguiSoftwareRenderInner {
         prim_idx: u32,
         paint_job: egui::ClippedPrimitive,
         f: F,
+        over_budget: &(impl Fn(RasterWork) -> bool + Sync),
     ) -> CacheUpdate<P>
     where
-        F: Fn(&Self, CacheReuse, Vec2, Vec2, egui::Rect, Mesh) -> P + Sync + Send,
+        F: Fn(&Self, CacheReuse, egui::Rect, Mesh) -> P + Sync + Send,
         P: DerefMut<Target = CacheReuse> + Sync + Send,
     {
         let splat = 0.5f32;
@@ -749,6 +2378,10 @@ impl EguiSoftwareRenderInner {
                 egui::TextureId::Managed(id) => id as u32,
                 egui::TextureId::User(id) => id as u32 + 9358476,
             });
+            #[cfg(feature = "wgpu")]
+            if let Some(texture) = self.textures.get(&px_mesh.texture_id) {
+                hasher.hash_wrap(texture.generation);
+            }
             for ind in &px_mesh.indices {
                 let v = px_mesh.vertices[*ind as usize];
 
@@ -768,22 +2401,30 @@ impl EguiSoftwareRenderInner {
             hasher.finalize()
         };
 
-        let width = (cropped_max.x - cropped_min.x + 0.5) as u32;
-        let height = (cropped_max.y - cropped_min.y + 0.5) as u32;
-        let rect = DirtyRect {
-            min_x: cropped_min.x as u32,
-            min_y: cropped_min.y as u32,
-            max_x: cropped_min.x as u32 + width,
-            max_y: cropped_min.y as u32 + height,
-        };
-        if cached_primitives.contains_key(&hash) {
+        // Every pixel the mesh could touch, so caching modes draw the same pixels as direct draw
+        let (canvas_width, canvas_height) = self.cached_size;
+        let rect = DirtyRect::from_egui_rect(
+            egui::Rect::from_min_max(cropped_min.to_pos2(), cropped_max.to_pos2()),
+            1.0,
+            canvas_width,
+            canvas_height,
+        );
+        let (width, height) = (rect.width(), rect.height());
+        if let Some(cached) = cached_primitives.get(&hash)
+            && !cached.deref().stale
+        {
+            // An idle primitive painted again is redrawn like a new one
+            let reappeared = cached.deref().last_seen_frame_idx + 1 != self.frame_counter;
             CacheUpdate::CacheReuse(
                 hash,
                 CacheReuse {
                     z_order: prim_idx,
                     rect,
+                    texture_id: px_mesh.texture_id,
+                    last_seen_frame_idx: self.frame_counter,
                     seen_this_frame: true,
-                    rendered_this_frame: false,
+                    rendered_this_frame: reappeared,
+                    stale: false,
                 },
             )
         } else {
@@ -791,21 +2432,79 @@ impl EguiSoftwareRenderInner {
                 // TODO it occasionally tries to make giant buffers in the first couple frames initially for some reason.
                 return CacheUpdate::None;
             }
-
             if width == 0 || height == 0 {
                 return CacheUpdate::None;
             }
 
+            let work = RasterWork {
+                vertices: px_mesh.vertices.len() as u64,
+                pixels: u64::from(rect.area()),
+            };
+            if over_budget(work) {
+                return CacheUpdate::OverBudget;
+            }
+
             let prim = CacheReuse {
                 z_order: prim_idx,
                 rect,
+                texture_id: px_mesh.texture_id,
+                last_seen_frame_idx: self.frame_counter,
                 seen_this_frame: true,
                 rendered_this_frame: true,
+                stale: false,
             };
-            CacheUpdate::New(
-                hash,
-                f(self, prim, cropped_min, cropped_max, clip_rect, px_mesh),
-            )
+            let prim = f(self, prim, clip_rect, px_mesh);
+            if cached_primitives.contains_key(&hash) {
+                CacheUpdate::Invalidated(hash, prim)
+            } else {
+                CacheUpdate::New(hash, prim)
+            }
+        }
+    }
+
+    /// Subpixel bits of a mesh spanning `width` x `height` pixels, see `EguiSoftwareRender::with_subpixel_bits()`
+    fn subpix_bits(&self, width: u32, height: u32) -> i32 {
+        match self.subpix_bits_override {
+            Some(bits) => bits,
+            None if width > self.subpix_bits_threshold || height > self.subpix_bits_threshold => 2,
+            None => 8,
+        }
+    }
+
+    /// `draw_egui_mesh()` with `subpix_bits` clamped to the supported 1 to 8 bits
+    fn draw_mesh(
+        &self,
+        buffer: &mut BufferMutRef,
+        clip_rect: &egui::Rect,
+        mesh: &Mesh,
+        vert_offset: Vec2,
+        subpix_bits: i32,
+    ) {
+        macro_rules! draw {
+            ($bits:literal) => {
+                draw_egui_mesh::<$bits>(
+                    &self.textures,
+                    buffer,
+                    clip_rect,
+                    mesh,
+                    vert_offset,
+                    self.allow_raster_opt,
+                    self.convert_tris_to_rects,
+                    self.pixels_per_point,
+                    #[cfg(all(feature = "raster_stats", not(feature = "rayon")))]
+                    &self.stats,
+                )
+            };
+        }
+        match subpix_bits {
+            ..=1 => draw!(1),
+            2 => draw!(2),
+            3 => draw!(3),
+            4 => draw!(4),
+            5 => draw!(5),
+            6 => draw!(6),
+            7 => draw!(7),
+            8.. => draw!(8),
         }
     }
 
@@ -829,6 +2528,29 @@ impl EguiSoftwareRenderInner {
         if input_mesh.vertices.is_empty() || input_mesh.indices.is_empty() {
             return None;
         }
+        if input_mesh.indices.len() % 3 != 0
+            || input_mesh
+                .indices
+                .iter()
+                .any(|&i| i as usize >= input_mesh.vertices.len())
+        {
+            #[cfg(feature = "log")]
+            log::error!("egui::Mesh with invalid indices");
+            return None;
+        }
+        if !input_mesh
+            .vertices
+            .iter()
+            .all(|v| v.pos.is_finite() && v.uv.is_finite())
+        {
+            #[cfg(feature = "log")]
+            log::error!("egui::Mesh with non finite vertex positions or uvs");
+            return None;
+        }
+        // Infinite clip rects, like `Rect::EVERYTHING`, saturate to the buffer bounds
+        if clip_rect.any_nan() {
+            return None;
+        }
         let clip_rect = egui::Rect {
             min: clip_rect.min * pixels_per_point,
             max: clip_rect.max * pixels_per_point + egui::Vec2::splat(splat),
@@ -841,6 +2563,11 @@ impl EguiSoftwareRenderInner {
         for v in px_mesh.vertices.iter_mut() {
             v.pos *= pixels_per_point;
 
+            if self.straight_alpha_input {
+                let [r, g, b, a] = premultiply_alpha(v.color.to_array());
+                v.color = Color32::from_rgba_premultiplied(r, g, b, a);
+            }
+
             match self.output_field_order {
                 ColorFieldOrder::Rgba => (), // egui uses rgba
                 ColorFieldOrder::Bgra => {
@@ -852,6 +2579,12 @@ impl EguiSoftwareRenderInner {
             mesh_min = mesh_min.min(v.pos.to_vec2());
             mesh_max = mesh_max.max(v.pos.to_vec2());
         }
+        // Finite positions can still overflow once scaled
+        if !mesh_min.is_finite() || !mesh_max.is_finite() {
+            #[cfg(feature = "log")]
+            log::warn!("egui::Mesh with vertex positions overflowing in pixels, skipped");
+            return None;
+        }
 
         // Make all the tris face forward (ccw) to simplify rasterization.
         // TODO perf: could store the area so it's not recomputed later.
@@ -871,18 +2604,46 @@ impl EguiSoftwareRenderInner {
         Some((clip_rect, mesh_min, mesh_max, px_mesh))
     }
 
+    /// Returns the number of new primitives skipped by the render budget, and the work of the rasterized ones
     fn render_prims_to_cache<F, P>(
         &self,
         cached_primitives: &mut HashMap<u32, P>,
         paint_jobs: Vec<egui::ClippedPrimitive>,
         pixels_per_point: f32,
         f: F,
-    ) where
-        F: Fn(&Self, CacheReuse, Vec2, Vec2, egui::Rect, Mesh) -> P + Sync + Send,
-        P: DerefMut<Target = CacheReuse> + Sync + Send,
+    ) -> (u32, RasterWork)
+    where
+        F: Fn(&Self, CacheReuse, egui::Rect, Mesh) -> P + Sync + Send,
+        P: CachedPrimitive,
     {
+        #[cfg(feature = "puffin")]
+        puffin::profile_scope!("render_prims_to_cache");
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("egui_sw::render_prims_to_cache").entered();
         #[cfg(feature = "raster_stats")]
         let start = std::time::Instant::now();
+        #[cfg(feature = "std")]
+        let deadline = self
+            .render_budget
+            .map(|budget| std::time::Instant::now() + budget);
+        // 32 bits like `CacheStats`, wide enough for a frame of work
+        let (vertices, pixels) = (AtomicU32::new(0), AtomicU32::new(0));
+        let over_budget = |work: RasterWork| {
+            #[cfg(feature = "std")]
+            if let Some(deadline) = deadline {
+                let now = std::time::Instant::now();
+                // The first new primitive is always rasterized, so a primitive estimated over the whole budget
+                // still renders eventually
+                let estimate =
+                    core::time::Duration::from_nanos(self.render_cost.estimate_ns(work) as u64);
+                if now >= deadline || (vertices.load(Relaxed) > 0 && now + estimate >= deadline) {
+                    return true;
+                }
+            }
+            vertices.fetch_add(work.vertices as u32, Relaxed);
+            pixels.fetch_add(work.pixels as u32, Relaxed);
+            false
+        };
 
         // Render paint jobs in parallel
         #[cfg(feature = "rayon")]
@@ -893,32 +2654,81 @@ impl EguiSoftwareRenderInner {
 
         let updates: Vec<CacheUpdate<P>> = iter
             .map(|(prim_idx, paint_job)| {
-                self.prim_prepare_update(
+                #[cfg(feature = "std")]
+                let start = self.prim_profiler.is_some().then(std::time::Instant::now);
+                let update = self.prim_prepare_update(
                     cached_primitives,
                     pixels_per_point,
                     prim_idx as u32,
                     paint_job,
                     &f,
-                )
+                    &over_budget,
+                );
+                #[cfg(feature = "std")]
+                if let Some((profiler, start)) = self.prim_profiler.as_ref().zip(start)
+                    && let Some(profile) = update.profile(start.elapsed().as_micros() as u64)
+                {
+                    profiler(profile);
+                }
+                update
             })
             .collect::<Vec<_>>();
 
+        // Identical primitives painted several times share a hash, the last painted one wins whatever the order
+        // the updates are applied in
+        #[cfg(debug_assertions)]
+        let mut latest_z_orders = HashMap::<u32, u32>::default();
+        #[cfg(debug_assertions)]
+        for (hash, z_order) in updates.iter().filter_map(CacheUpdate::hash_and_z_order) {
+            let latest = latest_z_orders.entry(hash).or_insert(z_order);
+            *latest = (*latest).max(z_order);
+        }
+        let painted_later = |cached: Option<&P>, z_order: u32| {
+            cached.is_some_and(|cached| cached.seen_this_frame && cached.z_order > z_order)
+        };
+
+        let mut skipped = 0;
         updates.into_iter().for_each(|update| match update {
             CacheUpdate::CacheReuse(hash, cache_reuse) => {
-                if let Some(cached_primitive) = cached_primitives.get_mut(&hash) {
+                #[cfg(feature = "cache_stats")]
+                self.cache_stats().add_hit();
+                if let Some(cached_primitive) = cached_primitives.get_mut(&hash)
+                    && !painted_later(Some(cached_primitive), cache_reuse.z_order)
+                {
+                    #[cfg(feature = "lz4_compression")]
+                    cached_primitive.decompress(self.pixel_alloc);
                     *cached_primitive.deref_mut() = cache_reuse;
                 }
             }
-            CacheUpdate::New(hash, prim) => {
-                cached_primitives.insert(hash, prim);
+            CacheUpdate::New(hash, prim) | CacheUpdate::Invalidated(hash, prim) => {
+                #[cfg(feature = "cache_stats")]
+                self.cache_stats().add_miss();
+                if !painted_later(cached_primitives.get(&hash), prim.z_order) {
+                    cached_primitives.insert(hash, prim);
+                }
             }
+            CacheUpdate::OverBudget => skipped += 1,
             CacheUpdate::None => (),
         });
 
+        #[cfg(debug_assertions)]
+        for (hash, z_order) in latest_z_orders {
+            debug_assert_eq!(
+                cached_primitives.get(&hash).map(|cached| cached.z_order),
+                Some(z_order),
+                "cached primitive {hash:#x} is not the last painted one"
+            );
+        }
+
         #[cfg(feature = "raster_stats")]
         {
             self.stats.render_prims_to_cache.mark(start);
         }
+        let work = RasterWork {
+            vertices: vertices.into_inner().into(),
+            pixels: pixels.into_inner().into(),
+        };
+        (skipped, work)
     }
 
     fn render_from_meshcache(
@@ -927,49 +2737,37 @@ impl EguiSoftwareRenderInner {
         direct_draw_buffer: &mut BufferMutRef,
         dirty_rect: DirtyRect,
     ) {
+        #[cfg(feature = "puffin")]
+        puffin::profile_scope!("render_from_meshcache");
         #[cfg(feature = "raster_stats")]
         let start = std::time::Instant::now();
 
-        let mut sorted_prim_cache = cached_primitives.values().collect::<Vec<_>>();
+        let mut sorted_prim_cache = cached_primitives
+            .values()
+            .filter(|prim| prim.inner.seen_this_frame)
+            .collect::<Vec<_>>();
         sorted_prim_cache.sort_unstable_by_key(|prim| prim.inner.z_order);
 
         let mut render_from_meshcache_prim = |prim: &MeshCachedPrimitive, dirty_rect: DirtyRect| {
             let clip_rect = prim.clip_rect.intersect(dirty_rect.to_egui_rect());
             let (width, height) = (prim.rect.width(), prim.rect.height());
-            let render_in_low_precision = width > 4096 || height > 4096;
-            if render_in_low_precision {
-                draw_egui_mesh::<2>(
-                    &self.textures,
-                    direct_draw_buffer,
-                    &clip_rect,
-                    &prim.px_mesh,
-                    Vec2::ZERO,
-                    self.allow_raster_opt,
-                    self.convert_tris_to_rects,
-                    #[cfg(all(feature = "raster_stats", not(feature = "rayon")))]
-                    &self.stats,
-                );
-            } else {
-                draw_egui_mesh::<8>(
-                    &self.textures,
-                    direct_draw_buffer,
-                    &clip_rect,
-                    &prim.px_mesh,
-                    Vec2::ZERO,
-                    self.allow_raster_opt,
-                    self.convert_tris_to_rects,
-                    #[cfg(all(feature = "raster_stats", not(feature = "rayon")))]
-                    &self.stats,
-                );
-            }
+            let subpix_bits = self.subpix_bits(width, height);
+            self.draw_mesh(
+                direct_draw_buffer,
+                &clip_rect,
+                &prim.px_mesh,
+                Vec2::ZERO,
+                subpix_bits,
+            );
         };
 
         match self.mode {
             SoftwareRenderCaching::MeshTiled => {
                 for &prim in &sorted_prim_cache {
-                    for dirty_rect in self.dirty_rects.intersections(prim.rect) {
-                        render_from_meshcache_prim(prim, dirty_rect);
-                    }
+                    self.dirty_rects
+                        .for_each_intersection(prim.rect, |dirty_rect| {
+                            render_from_meshcache_prim(prim, dirty_rect);
+                        });
                 }
             }
             SoftwareRenderCaching::Mesh => {
@@ -986,88 +2784,84 @@ impl EguiSoftwareRenderInner {
         }
     }
 
-    fn render_from_tiledcache(
+    fn render_from_tiledcache<P: CanvasPixel>(
         &mut self,
         cached_primitives: &HashMap<u32, TiledCachedPrimitive>,
-        canvas: &mut BufferMutRef,
+        canvas: &mut [P],
+        width: u32,
+        height: u32,
     ) {
+        #[cfg(feature = "puffin")]
+        puffin::profile_scope!("render_from_tiledcache");
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("egui_sw::render_from_tiledcache").entered();
         #[cfg(feature = "raster_stats")]
         let start = std::time::Instant::now();
 
-        let mut sorted_prim_cache = cached_primitives.values().collect::<Vec<_>>();
+        let mut sorted_prim_cache = cached_primitives
+            .values()
+            .filter(|prim| prim.inner.seen_this_frame)
+            .collect::<Vec<_>>();
         sorted_prim_cache.sort_unstable_by_key(|prim| prim.inner.z_order);
 
+        let mut overdraw = core::mem::take(&mut self.overdraw);
+        if self.overdraw_visualization {
+            let len = canvas.len();
+            if overdraw.len() != len {
+                overdraw.clear();
+                overdraw.resize(len, 0);
+            }
+        } else {
+            overdraw = Vec::new();
+        }
+
         #[cfg(feature = "rayon")]
         {
             use rayon::{
-                iter::{IndexedParallelIterator, ParallelIterator},
+                iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator},
                 slice::ParallelSliceMut,
             };
             // composite rows of tiles in parallel
 
-            let full_height = canvas.height;
-
-            let width = canvas.width;
             let px_per_row_of_tiles = as_usize(width) * as_usize(TILE_SIZE);
 
+            let mut overdraw_rows: Vec<&mut [u8]> = if overdraw.is_empty() {
+                (0..self.tiles_dim[1]).map(|_| &mut [][..]).collect()
+            } else {
+                overdraw.chunks_mut(px_per_row_of_tiles).collect()
+            };
+
             canvas
-                .data
                 .par_chunks_mut(px_per_row_of_tiles)
+                .zip(overdraw_rows.par_iter_mut())
                 .enumerate()
-                .for_each(|(tile_row, tile_height_row)| {
-                    let height = tile_height_row.len() as u32 / width; // Might be less than TILE_SIZE
-                    let canvas_tile_row = &mut BufferMutRef::new(tile_height_row, width, height);
-
-                    let dirty_tile_row_start = tile_row * as_usize(self.tiles_dim[0]);
-                    let dirty_tile_row_end = dirty_tile_row_start + as_usize(self.tiles_dim[0]);
-
+                .for_each(|(tile_row, (tile_height_row, overdraw_row))| {
                     let tile_row = tile_row as u32;
-                    self.dirty_tiles
-                        .iter()
-                        .enumerate()
-                        .skip(dirty_tile_row_start)
-                        .take(dirty_tile_row_end)
-                        .filter(|(_, mask)| **mask & Self::DIRTY_TILE_MASK != 0)
-                        .map(|(idx, _)| idx)
-                        .for_each(|tile_idx| {
-                            let tile_idx = tile_idx as u32;
-                            let tile_y = tile_idx / self.tiles_dim[0];
-
-                            if tile_y != tile_row {
-                                return;
-                            }
-                            let canvas_row_offset = tile_row * TILE_SIZE;
-
-                            let tile_x = tile_idx % self.tiles_dim[0];
-
-                            update_canvas_tile(
-                                &sorted_prim_cache,
-                                canvas_tile_row,
-                                tile_x,
-                                tile_y,
-                                full_height,
-                                canvas_row_offset,
-                            );
-                        });
+                    composite_canvas_tiles(
+                        &sorted_prim_cache,
+                        &self.dirty_tiles,
+                        self.tiles_dim,
+                        tile_height_row,
+                        width,
+                        height,
+                        overdraw_row,
+                        tile_row..tile_row + 1,
+                    );
                 });
         }
 
         #[cfg(not(feature = "rayon"))]
-        {
-            for tile_idx in self
-                .dirty_tiles
-                .iter()
-                .enumerate()
-                .filter(|(_, mask)| **mask & Self::DIRTY_TILE_MASK != 0)
-                .map(|(idx, _)| idx)
-            {
-                let tile_idx = tile_idx as u32;
-                let tile_x = tile_idx % self.tiles_dim[0];
-                let tile_y = tile_idx / self.tiles_dim[0];
-                let full_height = canvas.height;
-                update_canvas_tile(&sorted_prim_cache, canvas, tile_x, tile_y, full_height, 0);
-            }
-        }
+        composite_canvas_tiles(
+            &sorted_prim_cache,
+            &self.dirty_tiles,
+            self.tiles_dim,
+            canvas,
+            width,
+            height,
+            &mut overdraw,
+            0..self.tiles_dim[1],
+        );
+        self.overdraw = overdraw;
 
         #[cfg(feature = "raster_stats")]
         {
@@ -1078,17 +2872,30 @@ impl EguiSoftwareRenderInner {
     const DIRTY_TILE_MASK: u8 = 0b00000001;
     const OCCUPIED_TILE_MASK: u8 = 0b000000010;
     fn update_dirty_tiles(&mut self, cached_primitives: &HashMap<u32, TiledCachedPrimitive>) {
+        #[cfg(feature = "puffin")]
+        puffin::profile_scope!("update_dirty_tiles");
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("egui_sw::update_dirty_tiles").entered();
         #[cfg(feature = "raster_stats")]
         let start = std::time::Instant::now();
 
         self.dirty_tiles
             .resize(as_usize(self.tiles_dim[0] * self.tiles_dim[1]), 0);
-        self.dirty_tiles.fill(0);
+        let dirty = if self.force_full_redraw {
+            Self::DIRTY_TILE_MASK
+        } else {
+            0
+        };
+        self.dirty_tiles.fill(dirty);
         for prim in cached_primitives.values() {
-            for tile in &prim.occupied_tiles {
+            let needs_redraw = prim.inner.needs_redraw(self.frame_counter);
+            if !needs_redraw && !prim.inner.seen_this_frame {
+                continue;
+            }
+            for tile in prim.buffer.tiles.keys() {
                 let mask = &mut self.dirty_tiles
                     [tile[0] as usize + tile[1] as usize * self.tiles_dim[0] as usize];
-                if !prim.inner.seen_this_frame || prim.inner.rendered_this_frame {
+                if needs_redraw {
                     *mask |= Self::DIRTY_TILE_MASK;
                 }
                 *mask |= Self::OCCUPIED_TILE_MASK;
@@ -1102,13 +2909,17 @@ impl EguiSoftwareRenderInner {
     }
 
     fn update_dirty_rects(&mut self, cached_primitives: &HashMap<u32, MeshCachedPrimitive>) {
+        #[cfg(feature = "puffin")]
+        puffin::profile_scope!("update_dirty_rects");
         #[cfg(feature = "raster_stats")]
         let start = std::time::Instant::now();
         if self.mode == SoftwareRenderCaching::MeshTiled {
+            let force_full_redraw = self.force_full_redraw;
+            let frame_counter = self.frame_counter;
             self.dirty_rects.set_bboxes(
                 cached_primitives
                     .values()
-                    .filter(|prim| !prim.inner.seen_this_frame || prim.inner.rendered_this_frame)
+                    .filter(|prim| force_full_redraw || prim.inner.needs_redraw(frame_counter))
                     .map(|prim| prim.rect),
             );
         }
@@ -1116,6 +2927,9 @@ impl EguiSoftwareRenderInner {
         #[cfg(feature = "raster_stats")]
         {
             self.stats.update_dirty_rects.mark(start);
+            self.stats.dirty_rects_count = self.dirty_rects.rect_count();
+            self.stats.dirty_rects_area = self.dirty_rects.total_dirty_area();
+            self.stats.dirty_rects_memory_bytes = self.dirty_rects.memory_usage_bytes();
         }
     }
 
@@ -1123,13 +2937,15 @@ impl EguiSoftwareRenderInner {
     where
         P: Deref<Target = CacheReuse>,
     {
+        #[cfg(feature = "puffin")]
+        puffin::profile_scope!("update_dirty_rect");
         #[cfg(feature = "raster_stats")]
         let start = std::time::Instant::now();
 
         let mut dirty_rect = DirtyRect::new_empty();
         for prim in cached_primitives.values() {
             let prim = prim.deref();
-            if !prim.seen_this_frame || prim.rendered_this_frame {
+            if prim.needs_redraw(self.frame_counter) {
                 if dirty_rect.is_empty() {
                     dirty_rect = prim.rect;
                 } else {
@@ -1146,10 +2962,23 @@ impl EguiSoftwareRenderInner {
     }
 
     fn set_textures(&mut self, textures_delta: &egui::TexturesDelta) {
+        #[cfg(feature = "puffin")]
+        puffin::profile_scope!("set_textures");
         #[cfg(feature = "raster_stats")]
         let start = std::time::Instant::now();
 
-        for (id, delta) in &textures_delta.set {
+        // A full replacement discards the earlier updates of the same texture in this delta
+        let mut last_full_replace: HashMap<egui::TextureId, usize> = HashMap::default();
+        for (i, (id, delta)) in textures_delta.set.iter().enumerate() {
+            if delta.pos.is_none() {
+                last_full_replace.insert(*id, i);
+            }
+        }
+
+        for (i, (id, delta)) in textures_delta.set.iter().enumerate() {
+            if last_full_replace.get(id).is_some_and(|&last| i < last) {
+                continue;
+            }
             if delta.options.magnification != delta.options.minification {
                 // Would need helper lanes to impl?
                 #[cfg(feature = "log")]
@@ -1166,22 +2995,28 @@ impl EguiSoftwareRenderInner {
             let size = delta.image.size();
             if let Some(pos) = delta.pos {
                 if let Some(texture) = self.textures.get_mut(id) {
-                    for y in 0..size[1] {
-                        for x in 0..size[0] {
-                            let src_pos = x + y * size[0];
-                            let dest_pos = (x + pos[0]) + (y + pos[1]) * texture.width;
-                            texture.data[dest_pos] = match self.output_field_order {
-                                ColorFieldOrder::Rgba => pixels[src_pos].to_array(),
-                                ColorFieldOrder::Bgra => {
-                                    swizzle_rgba_bgra(pixels[src_pos].to_array())
-                                }
-                            };
-                        }
-                    }
+                    texture.update_region(
+                        self.output_field_order,
+                        self.straight_alpha_input,
+                        pos,
+                        size,
+                        &pixels,
+                    );
                 }
             } else {
-                let new_texture =
-                    EguiTexture::new(self.output_field_order, delta.options, size, &pixels);
+                let mut new_texture = EguiTexture::new(
+                    self.pixel_alloc,
+                    self.output_field_order,
+                    delta.options,
+                    size,
+                    &pixels,
+                );
+                if self.straight_alpha_input {
+                    new_texture.premultiply_alpha();
+                }
+                if delta.options.mipmap_mode.is_some() {
+                    new_texture = new_texture.with_premip(u8::MAX);
+                }
 
                 self.textures.insert(*id, new_texture);
             }
@@ -1200,9 +3035,98 @@ impl EguiSoftwareRenderInner {
     }
 }
 
-fn update_canvas_tile(
+/// Clear the dirty tiles of `tile_rows`, and blend the cached prims over them, in z-order.
+///
+/// `canvas` holds the pixel rows of `tile_rows`, of a `full_height` canvas. `overdraw` is either empty or matches
+/// `canvas`, and counts prims writing non transparent pixels.
+///
+/// Each prim is blended over all its dirty tiles before the next one, so its buffer is read once and in order, a
+/// tile is only cleared before the first prim blended over it.
+#[allow(clippy::too_many_arguments)]
+fn composite_canvas_tiles<P: CanvasPixel>(
     sorted_prim_cache: &[&TiledCachedPrimitive],
-    canvas: &mut BufferMutRef,
+    dirty_tiles: &[u8],
+    tiles_dim: [u32; 2],
+    canvas: &mut [P],
+    width: u32,
+    full_height: u32,
+    overdraw: &mut [u8],
+    tile_rows: Range<u32>,
+) {
+    let is_dirty = |tile_x: u32, tile_y: u32| {
+        dirty_tiles[as_usize(tile_x + tile_y * tiles_dim[0])]
+            & EguiSoftwareRenderInner::DIRTY_TILE_MASK
+            != 0
+    };
+    let canvas_row_offset = tile_rows.start * TILE_SIZE;
+    let cleared_idx =
+        |tile_x: u32, tile_y: u32| as_usize(tile_x + (tile_y - tile_rows.start) * tiles_dim[0]);
+    let mut cleared = vec![false; as_usize(tiles_dim[0] * tile_rows.len() as u32)];
+
+    for prim in sorted_prim_cache {
+        let rect = prim.inner.rect;
+        let first_tile_x = (rect.min_x / TILE_SIZE).min(tiles_dim[0]);
+        let last_tile_x = rect.max_x.div_ceil(TILE_SIZE).min(tiles_dim[0]);
+        let first_tile_y = (rect.min_y / TILE_SIZE).max(tile_rows.start);
+        let last_tile_y = rect.max_y.div_ceil(TILE_SIZE).min(tile_rows.end);
+        for tile_y in first_tile_y..last_tile_y {
+            for tile_x in first_tile_x..last_tile_x {
+                if !is_dirty(tile_x, tile_y) {
+                    continue;
+                }
+                let Some(tile) = prim.buffer.tiles.get(&[tile_x as u16, tile_y as u16]) else {
+                    continue;
+                };
+                let cleared = &mut cleared[cleared_idx(tile_x, tile_y)];
+                if !*cleared {
+                    clear_canvas_tile(
+                        canvas,
+                        width,
+                        overdraw,
+                        tile_x,
+                        tile_y,
+                        full_height,
+                        canvas_row_offset,
+                    );
+                    *cleared = true;
+                }
+                blit_canvas_tile(
+                    prim,
+                    tile,
+                    canvas,
+                    width,
+                    overdraw,
+                    tile_x,
+                    tile_y,
+                    full_height,
+                    canvas_row_offset,
+                );
+            }
+        }
+    }
+
+    // Dirty tiles no prim covers anymore
+    for tile_y in tile_rows.clone() {
+        for tile_x in 0..tiles_dim[0] {
+            if is_dirty(tile_x, tile_y) && !cleared[cleared_idx(tile_x, tile_y)] {
+                clear_canvas_tile(
+                    canvas,
+                    width,
+                    overdraw,
+                    tile_x,
+                    tile_y,
+                    full_height,
+                    canvas_row_offset,
+                );
+            }
+        }
+    }
+}
+
+fn clear_canvas_tile<P: CanvasPixel>(
+    canvas: &mut [P],
+    width: u32,
+    overdraw: &mut [u8],
     tile_x: u32,
     tile_y: u32,
     full_height: u32,
@@ -1210,82 +3134,195 @@ fn update_canvas_tile(
 ) {
     let tile_x_start = tile_x * TILE_SIZE;
     let tile_y_start = tile_y * TILE_SIZE;
-    let tile_x_end = (tile_x_start + TILE_SIZE).min(canvas.width);
+    let tile_x_end = (tile_x_start + TILE_SIZE).min(width);
     let tile_y_end = (tile_y_start + TILE_SIZE).min(full_height);
 
-    // clear tile
     for y in (tile_y_start - canvas_row_offset)..(tile_y_end - canvas_row_offset) {
-        let row_start = y * canvas.width;
+        let row_start = y * width;
         let start = row_start + tile_x_start;
         let end = row_start + tile_x_end;
-        canvas.data[as_usize(start)..as_usize(end)].fill([0; 4]);
-    }
-
-    let tile_n = [tile_x as u16, tile_y as u16];
-    // redraw cached prims on tile
-    for prim in sorted_prim_cache {
-        if !prim.occupied_tiles.contains(&tile_n) {
-            continue;
+        canvas[as_usize(start)..as_usize(end)].fill(P::TRANSPARENT);
+        if !overdraw.is_empty() {
+            overdraw[as_usize(start)..as_usize(end)].fill(0);
         }
+    }
+}
 
-        let mut min_x = prim.inner.rect.min_x;
-        let mut min_y = prim.inner.rect.min_y;
-        let mut max_x = prim.inner.rect.max_x;
-        let mut max_y = prim.inner.rect.max_y;
-
-        min_x = min_x.max(tile_x_start).min(canvas.width);
-        min_y = min_y
-            .max(tile_y_start)
-            .min(canvas.height + canvas_row_offset);
-        max_x = max_x.min(tile_x_end).min(canvas.width);
-        max_y = max_y.min(tile_y_end).min(canvas.height + canvas_row_offset);
-
-        let prim_buf = prim.get_buffer_ref();
+/// Blend `tile`, of `prim`, over the canvas
+#[allow(clippy::too_many_arguments)]
+fn blit_canvas_tile<P: CanvasPixel>(
+    prim: &TiledCachedPrimitive,
+    tile: &[[u8; 4]; TILE_PIXELS],
+    canvas: &mut [P],
+    width: u32,
+    overdraw: &mut [u8],
+    tile_x: u32,
+    tile_y: u32,
+    full_height: u32,
+    canvas_row_offset: u32,
+) {
+    let height = canvas.len() as u32 / width;
+    let tile_x_start = tile_x * TILE_SIZE;
+    let tile_y_start = tile_y * TILE_SIZE;
+    let tile_x_end = (tile_x_start + TILE_SIZE).min(width);
+    let tile_y_end = (tile_y_start + TILE_SIZE).min(full_height);
 
-        if max_x <= min_x || max_y <= min_y {
-            continue;
-        }
-        let prim_x_min = (min_x - prim.inner.rect.min_x).min(prim_buf.width);
-        let prim_x_max = (max_x - prim.inner.rect.min_x).min(prim_buf.width);
+    let min_x = prim.inner.rect.min_x.max(tile_x_start).min(width);
+    let min_y = prim
+        .inner
+        .rect
+        .min_y
+        .max(tile_y_start)
+        .min(height + canvas_row_offset);
+    let max_x = prim.inner.rect.max_x.min(tile_x_end).min(width);
+    let max_y = prim
+        .inner
+        .rect
+        .max_y
+        .min(tile_y_end)
+        .min(height + canvas_row_offset);
+
+    if max_x <= min_x || max_y <= min_y {
+        return;
+    }
 
-        let get_ranges = |y: u32| -> (Range<usize>, Range<usize>) {
-            let canvas_row_start = (y - canvas_row_offset).min(canvas.height) * canvas.width;
-            let canvas_start = canvas_row_start + min_x;
-            let canvas_end = canvas_row_start + max_x;
+    let get_ranges = |y: u32| -> (Range<usize>, Range<usize>) {
+        let canvas_row_start = (y - canvas_row_offset).min(height) * width;
+        let canvas_start = canvas_row_start + min_x;
+        let canvas_end = canvas_row_start + max_x;
 
-            let prim_y = (y - prim.inner.rect.min_y).min(prim_buf.height);
-            let prim_row_start = prim_y * prim_buf.width;
-            let prim_start = prim_row_start + prim_x_min;
-            let prim_end = prim_row_start + prim_x_max;
+        let tile_row_start = (y - tile_y_start) * TILE_SIZE;
+        let tile_start = tile_row_start + min_x - tile_x_start;
+        let tile_end = tile_row_start + max_x - tile_x_start;
 
-            (
-                as_usize(canvas_start)..as_usize(canvas_end),
-                as_usize(prim_start)..as_usize(prim_end),
-            )
-        };
+        (
+            as_usize(canvas_start)..as_usize(canvas_end),
+            as_usize(tile_start)..as_usize(tile_end),
+        )
+    };
 
-        dispatch_simd_impl!(|simd_impl| {
-            for y in min_y..max_y {
-                let (canvas_slice, prim_slice) = get_ranges(y);
-                let src_row = &prim_buf.data[prim_slice];
-                let dst_row = &mut canvas.data[canvas_slice];
-                simd_impl.egui_blend_u8_slice(src_row, dst_row);
+    dispatch_simd_impl!(|simd_impl| {
+        for y in min_y..max_y {
+            let (canvas_slice, tile_slice) = get_ranges(y);
+            let src_row = &tile[tile_slice];
+            if !overdraw.is_empty() {
+                for (count, src) in overdraw[canvas_slice.clone()].iter_mut().zip(src_row) {
+                    if u32::from_le_bytes(*src) > 0 {
+                        *count = count.saturating_add(1);
+                    }
+                }
             }
-        });
-    }
+            let dst_row = &mut canvas[canvas_slice];
+            P::blend_slice(simd_impl, src_row, dst_row);
+        }
+    });
 }
 
 enum CacheUpdate<P> {
     CacheReuse(u32, CacheReuse),
     New(u32, P),
+    /// Rasterized again, the cached primitive was marked stale by `EguiSoftwareRender::invalidate_hash()`
+    Invalidated(u32, P),
+    /// New primitive not rasterized, the render budget is exhausted
+    OverBudget,
     None,
 }
 
+impl<P: Deref<Target = CacheReuse>> CacheUpdate<P> {
+    #[cfg(debug_assertions)]
+    fn hash_and_z_order(&self) -> Option<(u32, u32)> {
+        match self {
+            CacheUpdate::CacheReuse(hash, cache_reuse) => Some((*hash, cache_reuse.z_order)),
+            CacheUpdate::New(hash, prim) | CacheUpdate::Invalidated(hash, prim) => {
+                Some((*hash, prim.z_order))
+            }
+            CacheUpdate::OverBudget | CacheUpdate::None => None,
+        }
+    }
+
+    /// `PrimProfile` of a primitive prepared in `raster_us`
+    #[cfg(feature = "std")]
+    fn profile(&self, raster_us: u64) -> Option<PrimProfile> {
+        let (hash, reuse, is_new) = match self {
+            CacheUpdate::CacheReuse(hash, cache_reuse) => (*hash, cache_reuse, false),
+            CacheUpdate::New(hash, prim) | CacheUpdate::Invalidated(hash, prim) => {
+                (*hash, prim.deref(), true)
+            }
+            CacheUpdate::OverBudget | CacheUpdate::None => return None,
+        };
+        Some(PrimProfile {
+            hash,
+            rect: reuse.rect,
+            raster_us,
+            is_new,
+        })
+    }
+}
+
 struct CacheReuse {
     z_order: u32,
     rect: DirtyRect,
+    /// Texture of the mesh, see `EguiSoftwareRender::invalidate_all_with_texture_id()`
+    texture_id: egui::TextureId,
+    /// `EguiSoftwareRenderInner::frame_counter` of the last render painting it
+    last_seen_frame_idx: u64,
     seen_this_frame: bool,
     rendered_this_frame: bool,
+    /// Rasterized again the next time it is painted, see `EguiSoftwareRender::invalidate_hash()`
+    stale: bool,
+}
+
+impl CacheReuse {
+    /// Its pixels changed: rasterized or painted again this frame, or painted by the last frame only
+    fn needs_redraw(&self, frame_counter: u64) -> bool {
+        self.rendered_this_frame
+            || (!self.seen_this_frame && self.last_seen_frame_idx + 1 == frame_counter)
+    }
+}
+
+/// `MeshCachedPrimitive` or `TiledCachedPrimitive`
+trait CachedPrimitive: DerefMut<Target = CacheReuse> + Sync + Send {
+    /// Bytes allocated by the pixels or the mesh of the primitive
+    fn memory_usage_bytes(&self) -> usize;
+
+    /// Compress the pixels of a primitive that wasn't painted this frame
+    #[cfg(feature = "lz4_compression")]
+    fn compress(&mut self) {}
+
+    /// Undo `compress()`, before the primitive is painted again
+    #[cfg(feature = "lz4_compression")]
+    fn decompress(&mut self, _pixel_alloc: PixelAlloc) {}
+}
+
+impl CachedPrimitive for MeshCachedPrimitive {
+    fn memory_usage_bytes(&self) -> usize {
+        self.px_mesh.vertices.capacity() * size_of::<egui::epaint::Vertex>()
+            + self.px_mesh.indices.capacity() * size_of::<u32>()
+    }
+}
+
+impl CachedPrimitive for TiledCachedPrimitive {
+    fn memory_usage_bytes(&self) -> usize {
+        #[cfg(feature = "lz4_compression")]
+        if let Some(compressed_buffer) = &self.compressed_buffer {
+            return self.buffer.memory_usage_bytes() + compressed_buffer.capacity();
+        }
+        self.buffer.memory_usage_bytes()
+    }
+
+    #[cfg(feature = "lz4_compression")]
+    fn compress(&mut self) {
+        if self.compressed_buffer.is_none() {
+            self.compressed_buffer = Some(self.buffer.compress());
+        }
+    }
+
+    #[cfg(feature = "lz4_compression")]
+    fn decompress(&mut self, pixel_alloc: PixelAlloc) {
+        if let Some(compressed_buffer) = self.compressed_buffer.take() {
+            self.buffer = SparseBuffer::decompress(pixel_alloc, &compressed_buffer);
+        }
+    }
 }
 
 struct MeshCachedPrimitive {
@@ -1312,8 +3349,10 @@ impl DerefMut for MeshCachedPrimitive {
 
 struct TiledCachedPrimitive {
     inner: CacheReuse,
-    buffer: Vec<[u8; 4]>,
-    occupied_tiles: Vec<[u16; 2]>,
+    buffer: SparseBuffer,
+    /// LZ4 compressed `buffer` while the primitive isn't painted, `buffer` is then empty
+    #[cfg(feature = "lz4_compression")]
+    compressed_buffer: Option<Vec<u8>>,
 }
 impl Deref for TiledCachedPrimitive {
     type Target = CacheReuse;
@@ -1331,54 +3370,145 @@ impl DerefMut for TiledCachedPrimitive {
     }
 }
 
-impl TiledCachedPrimitive {
-    fn get_buffer_ref(&self) -> BufferRef<'_> {
-        BufferRef {
-            data: &self.buffer,
-            width: self.inner.rect.width(),
-            height: self.inner.rect.height(),
-            width_extent: self.inner.rect.width() - 1,
-            height_extent: self.inner.rect.height() - 1,
-        }
-    }
-    fn update_occupied_tiles(&mut self, tiles_wide: u32, tiles_tall: u32) {
-        // list which tiles contain a pixel with that isn't fully transparent (also containing not color info)
-        self.occupied_tiles.clear();
-        let width = self.inner.rect.width();
-        let max_x = self.inner.rect.max_x;
-        let max_y = self.inner.rect.max_y;
-        let first_tile_x = (self.inner.rect.min_x / TILE_SIZE).min(tiles_wide);
-        let first_tile_y = (self.inner.rect.min_y / TILE_SIZE).min(tiles_tall);
-        let last_tile_x = max_x.div_ceil(TILE_SIZE).min(tiles_wide);
-        let last_tile_y = max_y.div_ceil(TILE_SIZE).min(tiles_tall);
+const TILE_PIXELS: usize = (TILE_SIZE * TILE_SIZE) as usize;
+
+/// Pixels of a cached primitive, split along the canvas tile grid
+///
+/// Only tiles containing a pixel that isn't fully transparent are allocated. Each tile is stored in tile space, pixels
+/// outside of the primitive rect are left transparent.
+struct SparseBuffer {
+    tiles: HashMap<[u16; 2], PixelBox<[[u8; 4]; TILE_PIXELS]>>,
+}
+
+/// Indices of the triangles of `mesh` by the tiles within `rect` their bounding box overlaps, in drawing order
+///
+/// Triangles referencing missing vertices are left out, `draw_egui_mesh()` skips them.
+fn tile_indices(mesh: &Mesh, rect: DirtyRect, tiles_dim: [u32; 2]) -> HashMap<[u32; 2], Vec<u32>> {
+    let mut tile_indices = HashMap::<[u32; 2], Vec<u32>>::default();
+    let first_tile = [
+        (rect.min_x / TILE_SIZE).min(tiles_dim[0]),
+        (rect.min_y / TILE_SIZE).min(tiles_dim[1]),
+    ];
+    let last_tile = [
+        rect.max_x.div_ceil(TILE_SIZE).min(tiles_dim[0]),
+        rect.max_y.div_ceil(TILE_SIZE).min(tiles_dim[1]),
+    ];
+    // Saturating, NaN is 0. A pixel of margin keeps the pixels touched by rounding.
+    let tile = |coord: f32, axis: usize| {
+        ((coord.max(0.0) as u32) / TILE_SIZE).clamp(first_tile[axis], last_tile[axis])
+    };
+    for tri in mesh.indices.chunks_exact(3) {
+        let (Some(v0), Some(v1), Some(v2)) = (
+            mesh.vertices.get(tri[0] as usize),
+            mesh.vertices.get(tri[1] as usize),
+            mesh.vertices.get(tri[2] as usize),
+        ) else {
+            continue;
+        };
+        let min = v0.pos.min(v1.pos).min(v2.pos) - vec2(1.0, 1.0);
+        let max = v0.pos.max(v1.pos).max(v2.pos) + vec2(1.0, 1.0);
+        for tile_y in tile(min.y, 1)..tile(max.y, 1).saturating_add(1).min(last_tile[1]) {
+            for tile_x in tile(min.x, 0)..tile(max.x, 0).saturating_add(1).min(last_tile[0]) {
+                tile_indices
+                    .entry([tile_x, tile_y])
+                    .or_default()
+                    .extend_from_slice(tri);
+            }
+        }
+    }
+    tile_indices
+}
 
+impl SparseBuffer {
+    /// Tiles covering `rect` of the canvas, each drawn by `draw_tile` from a transparent tile along its x and y and
+    /// the part of `rect` it covers. Only the tiles that are not fully transparent are kept.
+    fn from_fn(
+        pixel_alloc: PixelAlloc,
+        rect: DirtyRect,
+        tiles_dim: [u32; 2],
+        mut draw_tile: impl FnMut(&mut BufferMutRef, [u32; 2], DirtyRect),
+    ) -> Self {
+        let mut tiles = HashMap::default();
+        let first_tile_x = (rect.min_x / TILE_SIZE).min(tiles_dim[0]);
+        let first_tile_y = (rect.min_y / TILE_SIZE).min(tiles_dim[1]);
+        let last_tile_x = rect.max_x.div_ceil(TILE_SIZE).min(tiles_dim[0]);
+        let last_tile_y = rect.max_y.div_ceil(TILE_SIZE).min(tiles_dim[1]);
+
+        // A transparent tile is left untouched, it is reused for the next one
+        let mut spare_tile = None;
         for tile_y in first_tile_y..last_tile_y {
-            let mut px_start_y = (tile_y * TILE_SIZE).max(self.inner.rect.min_y);
-            let mut px_end_y = (px_start_y + TILE_SIZE).min(max_y);
-            px_start_y -= self.inner.rect.min_y;
-            px_end_y -= self.inner.rect.min_y;
             for tile_x in first_tile_x..last_tile_x {
-                let mut px_start_x = (tile_x * TILE_SIZE).max(self.inner.rect.min_x);
-                let mut px_end_x = (px_start_x + TILE_SIZE).min(max_x);
-                px_start_x -= self.inner.rect.min_x;
-                px_end_x -= self.inner.rect.min_x;
-
-                'px_outer: for y in px_start_y..px_end_y {
-                    for x in px_start_x..px_end_x {
-                        // Purposefully panicing when out of bounds. If it's out of bounds then the math is wrong and
-                        // the tile is not being calculated correctly.
-                        let offset = as_usize(x) + as_usize(y) * as_usize(width);
-                        if u32::from_le_bytes(self.buffer[offset]) > 0 {
-                            self.occupied_tiles.push([tile_x as u16, tile_y as u16]);
-                            break 'px_outer;
-                        }
-                    }
+                let tile_rect = DirtyRect {
+                    min_x: (tile_x * TILE_SIZE).max(rect.min_x),
+                    min_y: (tile_y * TILE_SIZE).max(rect.min_y),
+                    max_x: ((tile_x + 1) * TILE_SIZE).min(rect.max_x),
+                    max_y: ((tile_y + 1) * TILE_SIZE).min(rect.max_y),
+                };
+                let mut tile = spare_tile
+                    .take()
+                    .unwrap_or_else(|| pixel_alloc.boxed_array([0u8; 4]));
+                draw_tile(
+                    &mut BufferMutRef::new(&mut tile[..], TILE_SIZE, TILE_SIZE),
+                    [tile_x, tile_y],
+                    tile_rect,
+                );
+
+                // skip tiles where every pixel is fully transparent (also containing no color info)
+                if dispatch_simd_impl!(|simd_impl| simd_impl.any_nonzero_pixel(&tile[..])) {
+                    tiles.insert([tile_x as u16, tile_y as u16], tile);
+                } else {
+                    spare_tile = Some(tile);
                 }
             }
         }
+
+        Self { tiles }
+    }
+
+    /// Bytes allocated by the tiles
+    fn memory_usage_bytes(&self) -> usize {
+        self.tiles.capacity() * size_of::<([u16; 2], Box<[[u8; 4]; TILE_PIXELS]>)>()
+            + self.tiles.len() * size_of::<[[u8; 4]; TILE_PIXELS]>()
+    }
+
+    /// Free the tiles, LZ4 compressed as the tile x and y followed by the tile pixels
+    #[cfg(feature = "lz4_compression")]
+    fn compress(&mut self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.tiles.len() * COMPRESSED_TILE_BYTES);
+        for (tile_n, tile) in core::mem::take(&mut self.tiles) {
+            bytes.extend_from_slice(&tile_n[0].to_le_bytes());
+            bytes.extend_from_slice(&tile_n[1].to_le_bytes());
+            bytes.extend_from_slice(tile.as_flattened());
+        }
+        lz4_flex::compress_prepend_size(&bytes)
+    }
+
+    /// Tiles compressed by `SparseBuffer::compress()`
+    #[cfg(feature = "lz4_compression")]
+    fn decompress(pixel_alloc: PixelAlloc, compressed: &[u8]) -> Self {
+        let bytes = lz4_flex::decompress_size_prepended(compressed)
+            .expect("compressed by SparseBuffer::compress()");
+        let mut tiles = HashMap::default();
+        for chunk in bytes.chunks_exact(COMPRESSED_TILE_BYTES) {
+            let (tile_n, pixels) = chunk.split_at(4);
+            let mut tile = pixel_alloc.boxed_array([0u8; 4]);
+            tile.as_flattened_mut().copy_from_slice(pixels);
+            tiles.insert(
+                [
+                    u16::from_le_bytes([tile_n[0], tile_n[1]]),
+                    u16::from_le_bytes([tile_n[2], tile_n[3]]),
+                ],
+                tile,
+            );
+        }
+        Self { tiles }
     }
 }
 
+/// Tile x and y, then the tile pixels
+#[cfg(feature = "lz4_compression")]
+const COMPRESSED_TILE_BYTES: usize = 4 + TILE_PIXELS * 4;
+
 #[derive(Debug)]
 pub struct BufferMutRef<'a> {
     pub data: &'a mut [[u8; 4]],
@@ -1432,6 +3562,36 @@ impl<'a> BufferMutRef<'a> {
     pub fn get_mut(&mut self, x: u32, y: u32) -> &mut [u8; 4] {
         &mut self.data[as_usize(x) + as_usize(y) * as_usize(self.width)]
     }
+
+    /// View of the rows `min_y..max_y`, backed by the same pixels
+    ///
+    /// `BufferMutRef` has no row stride, so only whole rows can be borrowed: narrower regions such as quadrants
+    /// are not supported.
+    ///
+    /// # Panics
+    /// * `min_y..max_y` is empty or out of bounds
+    pub fn row_band<'b>(&'b mut self, min_y: u32, max_y: u32) -> BufferMutRef<'b> {
+        assert!(
+            min_y < max_y && max_y <= self.height,
+            "row band {min_y}..{max_y} out of the {} rows of the buffer",
+            self.height
+        );
+        let range = as_usize(min_y * self.width)..as_usize(max_y * self.width);
+        BufferMutRef::new(&mut self.data[range], self.width, max_y - min_y)
+    }
+
+    /// Split into the rows above `y` and the rows from `y`, which can then be rendered in parallel
+    ///
+    /// # Panics
+    /// * `y` is not in `1..height`
+    pub fn split_at_row(self, y: u32) -> (BufferMutRef<'a>, BufferMutRef<'a>) {
+        assert!(y > 0 && y < self.height);
+        let (top, bottom) = self.data.split_at_mut(as_usize(y * self.width));
+        (
+            BufferMutRef::new(top, self.width, y),
+            BufferMutRef::new(bottom, self.width, self.height - y),
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -1465,19 +3625,24 @@ impl<'a> BufferRef<'a> {
     }
 }
 
-#[allow(dead_code)]
 fn draw_rect_border_f32(
     buffer_ref: &mut BufferMutRef,
     rect: egui::Rect,
     border_size: f32,
     color: [u8; 4],
 ) {
-    // Convert float to integer pixel coordinates
-    let x0 = rect.min.x.floor().max(0.0) as u32;
-    let y0 = rect.min.y.floor().max(0.0) as u32;
-    let x1 = (rect.max.x.ceil() as u32).min(buffer_ref.width);
-    let y1 = (rect.max.y.ceil() as u32).min(buffer_ref.height);
-    let border = border_size.ceil().max(0.0) as u32;
+    let DirtyRect {
+        min_x: x0,
+        min_y: y0,
+        max_x: x1,
+        max_y: y1,
+    } = DirtyRect::from_egui_rect(rect, 1.0, buffer_ref.width, buffer_ref.height);
+    let border = (border_size.ceil().max(0.0) as u32)
+        .min(x1.saturating_sub(x0).div_ceil(2))
+        .min(y1.saturating_sub(y0).div_ceil(2));
+    if x0 >= x1 || y0 >= y1 {
+        return;
+    }
 
     // Helper closure: set pixel if inside buffer
     let mut set_pixel = |px: u32, py: u32| {
@@ -0,0 +1,118 @@
+//! `OffscreenRenderBatch`, rendering independent frames on a pool of threads
+
+use alloc::{format, vec::Vec};
+use std::sync::{Arc, mpsc};
+use std::thread::JoinHandle;
+
+use egui::mutex::Mutex;
+
+use crate::{ColorFieldOrder, EguiSoftwareRender, SoftwareFrame};
+
+/// Frame to render, and where to send its pixels
+type Job = (SoftwareFrame, mpsc::SyncSender<Vec<[u8; 4]>>);
+
+/// Pool of threads rendering independent egui frames to new buffers, e.g. thumbnails of many UI states
+///
+/// Each thread has its own `EguiSoftwareRender`, a frame is rendered by whichever thread is free first. Frames don't
+/// share textures, every `SoftwareFrame` must set all the textures it paints, like the first frame of an
+/// `egui::Context`. They are freed once the frame is rendered.
+pub struct OffscreenRenderBatch {
+    /// `None` once dropped, which stops the threads
+    jobs: Option<mpsc::Sender<Job>>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+/// Pixels of a frame submitted to `OffscreenRenderBatch::submit()`
+pub struct JobHandle {
+    pixels: mpsc::Receiver<Vec<[u8; 4]>>,
+}
+
+impl OffscreenRenderBatch {
+    /// `threads` rendering to `ColorFieldOrder::Rgba` buffers, 0 for `std::thread::available_parallelism()`, see
+    /// `OffscreenRenderBatch::with_renderer()`
+    pub fn new(threads: usize) -> Self {
+        Self::with_renderer(threads, || EguiSoftwareRender::new(ColorFieldOrder::Rgba))
+    }
+
+    /// `threads` rendering with the `EguiSoftwareRender` of `new_renderer`, called once per thread, 0 for
+    /// `std::thread::available_parallelism()`
+    ///
+    /// # Panics
+    ///
+    /// If a thread can't be spawned.
+    pub fn with_renderer(threads: usize, new_renderer: impl Fn() -> EguiSoftwareRender) -> Self {
+        let threads = match threads {
+            0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            threads => threads,
+        };
+        let (jobs, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let threads = (0..threads)
+            .map(|idx| {
+                let receiver = Arc::clone(&receiver);
+                let mut renderer = new_renderer();
+                let thread = std::thread::Builder::new().name(format!("offscreen_render_{idx}"));
+                thread
+                    .spawn(move || {
+                        loop {
+                            // Released before rendering, so the other threads take the next jobs meanwhile
+                            let job = receiver.lock().recv();
+                            let Ok((frame, pixels)) = job else {
+                                break;
+                            };
+                            let (width, height) = frame.size;
+                            let (buffer, _) = renderer.render_to_buffer(
+                                frame.paint_jobs,
+                                &frame.textures_delta,
+                                frame.pixels_per_point,
+                                width,
+                                height,
+                            );
+                            renderer.inner.free_textures(&egui::TexturesDelta {
+                                set: Vec::new(),
+                                free: frame.textures_delta.set.iter().map(|(id, _)| *id).collect(),
+                            });
+                            // The handle may have been dropped without waiting
+                            let _ = pixels.send(buffer);
+                        }
+                    })
+                    .expect("Failed to spawn thread")
+            })
+            .collect();
+        Self {
+            jobs: Some(jobs),
+            threads,
+        }
+    }
+
+    /// Renders `frame` on the next free thread, to a new `frame.size` buffer
+    pub fn submit(&self, frame: SoftwareFrame) -> JobHandle {
+        let (sender, pixels) = mpsc::sync_channel(1);
+        if let Some(jobs) = &self.jobs {
+            // The threads only stop once `self` is dropped, `JobHandle::wait()` panics if one panicked
+            let _ = jobs.send((frame, sender));
+        }
+        JobHandle { pixels }
+    }
+}
+
+impl Drop for OffscreenRenderBatch {
+    /// Waits for the submitted frames to be rendered
+    fn drop(&mut self) {
+        self.jobs = None;
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl JobHandle {
+    /// Blocks until the frame is rendered, and returns its pixels
+    ///
+    /// # Panics
+    ///
+    /// If the thread rendering the frame panicked.
+    pub fn wait(self) -> Vec<[u8; 4]> {
+        self.pixels.recv().expect("the render thread panicked")
+    }
+}
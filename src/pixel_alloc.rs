@@ -0,0 +1,80 @@
+use alloc::{boxed::Box, vec::Vec};
+
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+
+/// Allocator for the pixel buffers, see `EguiSoftwareRender::with_pixel_allocator()`
+#[cfg(feature = "allocator_api")]
+pub type PixelAllocator = &'static (dyn Allocator + Sync);
+
+#[cfg(feature = "allocator_api")]
+pub(crate) type PixelVec<T> = Vec<T, PixelAllocator>;
+#[cfg(not(feature = "allocator_api"))]
+pub(crate) type PixelVec<T> = Vec<T>;
+
+#[cfg(feature = "allocator_api")]
+pub(crate) type PixelBox<T> = Box<T, PixelAllocator>;
+#[cfg(not(feature = "allocator_api"))]
+pub(crate) type PixelBox<T> = Box<T>;
+
+/// Creates the pixel buffers: textures, tiled cache and canvas
+///
+/// Zero sized, using the global allocator, without the `allocator_api` feature.
+#[derive(Clone, Copy)]
+#[cfg_attr(not(feature = "allocator_api"), derive(Default))]
+pub(crate) struct PixelAlloc {
+    #[cfg(feature = "allocator_api")]
+    allocator: PixelAllocator,
+}
+
+#[cfg(feature = "allocator_api")]
+impl Default for PixelAlloc {
+    fn default() -> Self {
+        Self {
+            allocator: &alloc::alloc::Global,
+        }
+    }
+}
+
+impl PixelAlloc {
+    #[cfg(feature = "allocator_api")]
+    pub(crate) const fn new(allocator: PixelAllocator) -> Self {
+        Self { allocator }
+    }
+
+    /// Allocator of `vec`
+    #[cfg_attr(not(feature = "allocator_api"), allow(unused_variables))]
+    pub(crate) fn of<T>(vec: &PixelVec<T>) -> Self {
+        Self {
+            #[cfg(feature = "allocator_api")]
+            allocator: *vec.allocator(),
+        }
+    }
+
+    pub(crate) fn vec<T>(self) -> PixelVec<T> {
+        #[cfg(feature = "allocator_api")]
+        return Vec::new_in(self.allocator);
+        #[cfg(not(feature = "allocator_api"))]
+        return Vec::new();
+    }
+
+    pub(crate) fn vec_filled<T: Clone>(self, value: T, len: usize) -> PixelVec<T> {
+        let mut vec = self.vec();
+        vec.resize(len, value);
+        vec
+    }
+
+    /// Boxed array of `value`, built on the heap
+    pub(crate) fn boxed_array<T: Clone, const N: usize>(self, value: T) -> PixelBox<[T; N]> {
+        let boxed = self.vec_filled(value, N).into_boxed_slice();
+        #[cfg(feature = "allocator_api")]
+        // SAFETY: `boxed` holds exactly `N` elements, so it has the layout of `[T; N]`
+        #[allow(unsafe_code)]
+        return unsafe {
+            let (ptr, allocator) = Box::into_raw_with_allocator(boxed);
+            Box::from_raw_in(ptr.cast::<[T; N]>(), allocator)
+        };
+        #[cfg(not(feature = "allocator_api"))]
+        return boxed.try_into().unwrap_or_else(|_| unreachable!());
+    }
+}
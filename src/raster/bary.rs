@@ -19,7 +19,7 @@ pub fn raster_tri<const SUBPIX_BITS: i32>(
     mut raster: impl FnMut(i64, i64),
 ) {
     let Some((ss_min, ss_max, _sp_inv_area, mut stepper)) =
-        SingleStepper::from_ss_tri_backface_cull::<SUBPIX_BITS>(ss_bounds, ss_tri)
+        SingleStepper::from_ss_tri_backface_cull::<SUBPIX_BITS>(ss_bounds, ss_tri, MIN_TRI_AREA)
     else {
         return;
     };
@@ -36,6 +36,10 @@ pub fn raster_tri<const SUBPIX_BITS: i32>(
     }
 }
 
+/// Screen space area in pixels below which triangles are culled. The rects of `draw_egui_mesh_impl()` are culled below
+///   a quarter pixel, which would lose visible anti-aliasing slivers of the triangles.
+pub const MIN_TRI_AREA: f32 = 0.125;
+
 #[inline(always)]
 pub fn is_top_left(a: &I64Vec2, b: &I64Vec2) -> bool {
     let dy = b.y - a.y;
@@ -54,10 +58,12 @@ pub struct SingleStepper {
 
 impl SingleStepper {
     /// For the given subpixel resolution, calculate the screen space bounds, subpixel inverse area, and subpixel Stepper.
+    /// Back facing triangles, and the ones with a screen space area below `area_threshold`, are culled.
     /// returns: ss_min, ss_max, sp_inv_area, stepper
     pub fn from_ss_tri_backface_cull<const SUBPIX_BITS: i32>(
         ss_bounds: [I64Vec2; 2],
         ss_tri: &[Vec2; 3],
+        area_threshold: f32,
     ) -> Option<(I64Vec2, I64Vec2, f32, SingleStepper)> {
         let subpix_bits = SUBPIX_BITS as u32;
         let subpix: i64 = 1 << subpix_bits;
@@ -70,7 +76,9 @@ impl SingleStepper {
 
         let sp_area = orient2d(&sp0, &sp1, &sp2);
 
-        if sp_area <= 0 {
+        // sp_area is twice the area, in subpixels
+        let sp_area_threshold = area_threshold * (2 * subpix * subpix) as f32;
+        if sp_area <= 0 || (sp_area as f32) < sp_area_threshold {
             return None;
         }
 
@@ -13,10 +13,10 @@ pub fn draw_rect(
     #[constify] vert_uvs_vary: bool,
     #[constify] alpha_blend: bool,
 ) {
-    let const_tri_color_u8x4 = draw.const_tri_color_u8x4;
-    let clip_bounds = &draw.clip_bounds;
-    let tri_min = draw.tri_min;
-    let tri_max = draw.tri_max;
+    let const_tri_color_u8x4 = draw.const_tri_color_u8x4();
+    let clip_bounds = draw.clip_bounds();
+    let tri_min = draw.tri_min();
+    let tri_max = draw.tri_max();
     let min_x = ((tri_min.x + 0.5) as i64).max(clip_bounds[0].x);
     let min_y = ((tri_min.y + 0.5) as i64).max(clip_bounds[0].y);
     let max_x = ((tri_max.x + 0.5) as i64).min(clip_bounds[1].x);
@@ -42,6 +42,7 @@ pub fn draw_rect(
                     buffer.get_mut_span(min_x, max_x, y),
                 );
             } else {
+                // Opaque, `fill()` compiles to plain vector stores
                 buffer
                     .get_mut_span(min_x, max_x, y)
                     .fill(const_tri_color_u8x4);
@@ -49,18 +50,22 @@ pub fn draw_rect(
         }
     } else {
         // TODO could another level of constify make this cleaner (const use_nearest_sampling?)
+        let uv = draw.uv();
         let mut min_uv = vec2(
-            draw.uv[0].x.min(draw.uv[1].x).min(draw.uv[2].x),
-            draw.uv[0].y.min(draw.uv[1].y).min(draw.uv[2].y),
+            uv[0].x.min(uv[1].x).min(uv[2].x),
+            uv[0].y.min(uv[1].y).min(uv[2].y),
         );
         let max_uv = vec2(
-            draw.uv[0].x.max(draw.uv[1].x).max(draw.uv[2].x),
-            draw.uv[0].y.max(draw.uv[1].y).max(draw.uv[2].y),
+            uv[0].x.max(uv[1].x).max(uv[2].x),
+            uv[0].y.max(uv[1].y).max(uv[2].y),
         );
 
         let uv_step = (max_uv - min_uv) / (tri_max - tri_min);
-        min_uv += uv_step * (vec2(min_x as f32, min_y as f32) - tri_min).max(Vec2::ZERO); // Offset to account for clip
-        min_uv += uv_step * 0.5; // Raster at pixel centers
+        let texture = texture.level_for((uv_step * texture.fsize).abs().max_elem());
+        // Raster at pixel centers, from the first one in the clip rect. The rounded `min_x` can be before `tri_min.x`,
+        //   its center never is, so the offset can't go below `min_uv`.
+        let first_px_center = vec2(min_x as f32, min_y as f32) + Vec2::splat(0.5);
+        min_uv += uv_step * (first_px_center - tri_min).max(Vec2::ZERO);
 
         let ts_min = min_uv * texture.fsize;
         let ts_max = max_uv * texture.fsize;
@@ -77,8 +82,10 @@ pub fn draw_rect(
             steps_are_1px && start_on_texture_px_center
         };
 
-        let no_texture_wrap_or_overflow =
-            (ts_max.x as usize) < texture.width && (ts_max.y as usize) < texture.height;
+        let no_texture_wrap_or_overflow = ts_min.x >= 0.0
+            && ts_min.y >= 0.0
+            && (ts_max.x as usize) < texture.width
+            && (ts_max.y as usize) < texture.height;
 
         if use_nearest_sampling && no_texture_wrap_or_overflow {
             // Can just directly blend the texture over the dst buffer, no need to sample with uv
@@ -92,7 +99,7 @@ pub fn draw_rect(
                 let dst = &mut buffer.get_mut_span(min_x, max_x, y);
                 let src = &texture.data[as_usize(tex_start)..as_usize(tex_end)];
 
-                simd_impl.egui_blend_u8_slice_tinted(src, draw.const_vert_color_u8x4, dst);
+                simd_impl.egui_blend_u8_slice_tinted(src, draw.const_vert_color_u8x4(), dst);
                 tex_row += 1;
             }
         } else {
@@ -104,13 +111,19 @@ pub fn draw_rect(
             let mut uv = min_uv;
             for y in min_y..max_y {
                 uv.x = min_uv.x;
-                let buf_y = y * buffer.width;
-                for x in min_x..max_x {
-                    let tex_color = texture.sample_bilinear(uv);
-                    let pixel = &mut buffer.data[as_usize(x) + as_usize(buf_y)];
-                    let src = simd_impl.unorm_mult4x4(draw.const_vert_color_u8x4, tex_color);
-                    *pixel = simd_impl.egui_blend_u8(src, *pixel);
-                    uv.x += uv_step.x;
+                for pixels in buffer.get_mut_span(min_x, max_x, y).chunks_mut(4) {
+                    // The uvs past the end of the row are sampled but not used
+                    let uvs = core::array::from_fn(|_| {
+                        let pixel_uv = uv;
+                        uv.x += uv_step.x;
+                        pixel_uv
+                    });
+                    let tex_colors = texture.sample_bilinear_4x(simd_impl, uvs);
+                    simd_impl.egui_blend_u8_slice_tinted(
+                        &tex_colors[..pixels.len()],
+                        draw.const_vert_color_u8x4(),
+                        pixels,
+                    );
                 }
                 uv.y += uv_step.y;
             }
@@ -5,7 +5,7 @@ use crate::{
     color::vec4_to_u8x4,
     egui_texture::EguiTexture,
     raster::{
-        bary::SingleStepper,
+        bary::{MIN_TRI_AREA, SingleStepper},
         span::{calc_row_span, step_rcp},
     },
     render::DrawInfo,
@@ -22,7 +22,11 @@ pub fn draw_tri<const SUBPIX_BITS: i32>(
     #[constify] alpha_blend: bool,
 ) {
     let Some((ss_min, ss_max, sp_inv_area, mut stepper)) =
-        SingleStepper::from_ss_tri_backface_cull::<SUBPIX_BITS>(draw.clip_bounds, &draw.ss_tri)
+        SingleStepper::from_ss_tri_backface_cull::<SUBPIX_BITS>(
+            *draw.clip_bounds(),
+            draw.ss_tri(),
+            MIN_TRI_AREA,
+        )
     else {
         return;
     };
@@ -30,17 +34,26 @@ pub fn draw_tri<const SUBPIX_BITS: i32>(
     let step_rcp = step_rcp(&stepper);
 
     let mut vert_col_stepper = if vert_col_vary {
-        stepper.attr(&draw.colors, sp_inv_area)
+        stepper.attr(draw.colors(), sp_inv_area)
     } else {
         Default::default()
     };
 
     let mut vert_uv_stepper = if vert_uvs_vary {
-        stepper.attr(&draw.uv, sp_inv_area)
+        stepper.attr(draw.uv(), sp_inv_area)
     } else {
         Default::default()
     };
 
+    let texture = match vert_uvs_vary {
+        true => texture.level_for(
+            (vert_uv_stepper.step_x * texture.fsize)
+                .length()
+                .max((vert_uv_stepper.step_y * texture.fsize).length()),
+        ),
+        false => texture,
+    };
+
     let max_cols = ss_max.x - ss_min.x;
 
     for ss_y in ss_min.y..ss_max.y {
@@ -66,7 +79,7 @@ pub fn draw_tri<const SUBPIX_BITS: i32>(
                 let dst = buffer.get_mut_span(ss_start, ss_end, ss_y as u32);
                 if vert_col_vary {
                     simd_impl.egui_blend_u8_slice_one_src_tinted_fn(
-                        draw.const_tex_color_u8x4,
+                        draw.const_tex_color_u8x4(),
                         || {
                             let v = vec4_to_u8x4(&vert_col_stepper.attr);
                             vert_col_stepper.col_step();
@@ -75,7 +88,7 @@ pub fn draw_tri<const SUBPIX_BITS: i32>(
                         dst,
                     )
                 } else {
-                    simd_impl.egui_blend_u8_slice_one_src(draw.const_tri_color_u8x4, dst)
+                    simd_impl.egui_blend_u8_slice_one_src(draw.const_tri_color_u8x4(), dst)
                 }
             } else {
                 for ss_x in ss_start..ss_end {
@@ -83,16 +96,16 @@ pub fn draw_tri<const SUBPIX_BITS: i32>(
                         let tex_color = if vert_uvs_vary {
                             texture.sample_bilinear(vert_uv_stepper.attr)
                         } else {
-                            draw.const_tex_color_u8x4
+                            draw.const_tex_color_u8x4()
                         };
                         let vert_color = if vert_col_vary {
                             vec4_to_u8x4(&vert_col_stepper.attr)
                         } else {
-                            draw.const_vert_color_u8x4
+                            draw.const_vert_color_u8x4()
                         };
                         simd_impl.unorm_mult4x4(vert_color, tex_color)
                     } else {
-                        draw.const_tri_color_u8x4
+                        draw.const_tri_color_u8x4()
                     };
                     let pixel = buffer.get_mut(ss_x as u32, ss_y as u32);
                     *pixel = if alpha_blend {
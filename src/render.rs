@@ -11,6 +11,17 @@ use crate::{
 };
 use egui::{Pos2, Vec2, ahash::HashMap, epaint::Vertex, vec2};
 
+/// Triangles with vertices further than this (in pixels) are skipped
+const MAX_COORD: f32 = (1 << 20) as f32;
+
+/// Distance in pixels under which a vertex of a triangle pair is on a corner of the rect they cover, 0.0 to require
+/// exact matches
+///
+/// egui can put the vertices of a rect a few ULPs apart. The GPU comparison of the demo only starts to differ
+/// around 0.5, this stays well below.
+const RECT_MATCH_EPSILON: f32 = 0.01;
+
+#[allow(clippy::too_many_arguments)]
 pub fn draw_egui_mesh<const SUBPIX_BITS: i32>(
     textures: &HashMap<egui::TextureId, EguiTexture>,
     buffer: &mut BufferMutRef,
@@ -19,6 +30,7 @@ pub fn draw_egui_mesh<const SUBPIX_BITS: i32>(
     vert_offset: Vec2,
     allow_raster_opt: bool,
     convert_tris_to_rects: bool,
+    pixels_per_point: f32,
     #[cfg(all(feature = "raster_stats", not(feature = "rayon")))] stats: &crate::stats::RenderStats,
 ) {
     crate::dispatch_simd_impl!(|simd_impl| draw_egui_mesh_impl::<SUBPIX_BITS>(
@@ -30,6 +42,7 @@ pub fn draw_egui_mesh<const SUBPIX_BITS: i32>(
         vert_offset,
         allow_raster_opt,
         convert_tris_to_rects,
+        pixels_per_point,
         #[cfg(all(feature = "raster_stats", not(feature = "rayon")))]
         stats,
     ))
@@ -45,6 +58,7 @@ fn draw_egui_mesh_impl<const SUBPIX_BITS: i32>(
     vert_offset: Vec2,
     allow_raster_opt: bool,
     convert_tris_to_rects: bool,
+    pixels_per_point: f32,
     #[cfg(all(feature = "raster_stats", not(feature = "rayon")))] stats: &crate::stats::RenderStats,
 ) {
     if mesh.vertices.is_empty() || mesh.indices.is_empty() {
@@ -75,12 +89,16 @@ fn draw_egui_mesh_impl<const SUBPIX_BITS: i32>(
 
     let mut i = 0;
     // Get texture
-    while i < indices.len() {
-        let mut tri = [
-            vertices[indices[i] as usize],
-            vertices[indices[i + 1] as usize],
-            vertices[indices[i + 2] as usize],
-        ];
+    while i + 2 < indices.len() {
+        let (Some(&v0), Some(&v1), Some(&v2)) = (
+            vertices.get(indices[i] as usize),
+            vertices.get(indices[i + 1] as usize),
+            vertices.get(indices[i + 2] as usize),
+        ) else {
+            i += 3;
+            continue;
+        };
+        let mut tri = [v0, v1, v2];
         tri[0].pos += vert_offset;
         tri[1].pos += vert_offset;
         tri[2].pos += vert_offset;
@@ -94,8 +112,15 @@ fn draw_egui_mesh_impl<const SUBPIX_BITS: i32>(
             tri[0].pos.y.max(tri[1].pos.y).max(tri[2].pos.y),
         );
 
+        #[cfg(all(feature = "raster_stats", not(feature = "rayon")))]
         let fsize = tri_max - tri_min;
-        if fsize.x <= 0.0 || fsize.y <= 0.0 {
+        // Skips NaN, and coordinates that would overflow the subpixel math. Degenerate triangles are culled by
+        // `SingleStepper::from_ss_tri_backface_cull()`, along the ones below `MIN_TRI_AREA`.
+        let in_range = tri_min.x >= -MAX_COORD
+            && tri_min.y >= -MAX_COORD
+            && tri_max.x <= MAX_COORD
+            && tri_max.y <= MAX_COORD;
+        if !in_range {
             i += 3;
             continue;
         }
@@ -104,7 +129,7 @@ fn draw_egui_mesh_impl<const SUBPIX_BITS: i32>(
         let color1_u8x4 = tri[1].color.to_array();
         let color2_u8x4 = tri[2].color.to_array();
 
-        let mut draw = DrawInfo::new(
+        let mut draw = DrawInfo::builder(
             clip_bounds,
             [
                 u8x4_to_vec4(&color0_u8x4),
@@ -126,6 +151,7 @@ fn draw_egui_mesh_impl<const SUBPIX_BITS: i32>(
         );
 
         if !allow_raster_opt {
+            let draw = draw.build();
             draw_tri::<SUBPIX_BITS>(simd_impl, buffer, texture, &draw, true, true, true);
             i += 3;
             continue;
@@ -136,26 +162,22 @@ fn draw_egui_mesh_impl<const SUBPIX_BITS: i32>(
         let mut alpha_blend = true;
 
         if !vert_uvs_vary {
-            draw.const_tex_color_u8x4 = texture.sample_bilinear(draw.uv[0]);
-            draw.const_tex_color = u8x4_to_vec4(&draw.const_tex_color_u8x4);
+            let tex_color = texture.sample_bilinear(draw.uv[0]);
+            draw = draw.with_const_tex_color(tex_color);
         }
 
         if !vert_col_vary {
-            draw.const_vert_color = draw.colors[0];
-            draw.const_vert_color_u8x4 = color0_u8x4;
+            draw = draw.with_const_vert_color(color0_u8x4);
         }
 
-        if !vert_uvs_vary && !vert_col_vary {
-            let const_tri_color = draw.const_vert_color * draw.const_tex_color;
-            draw.const_tri_color_u8x4 = vec4_to_u8x4(&const_tri_color);
-            if draw.const_tri_color_u8x4[3] == 255 {
-                alpha_blend = false;
-            }
+        let draw = draw.build();
+        if !vert_uvs_vary && !vert_col_vary && draw.const_tri_color_u8x4()[3] == 255 {
+            alpha_blend = false;
         }
 
         if !vert_uvs_vary
             && vert_col_vary
-            && draw.const_tex_color_u8x4[3] == 255
+            && draw.const_tex_color_u8x4()[3] == 255
             && color0_u8x4[3] == 255
             && color1_u8x4[3] == 255
             && color2_u8x4[3] == 255
@@ -163,20 +185,25 @@ fn draw_egui_mesh_impl<const SUBPIX_BITS: i32>(
             alpha_blend = false;
         }
 
-        let find_rects = convert_tris_to_rects && !vert_col_vary && i + 6 < indices.len();
+        let find_rects = convert_tris_to_rects && !vert_col_vary && i + 6 <= indices.len();
         let mut found_rect = false;
 
-        if find_rects {
-            let mut tri2 = [
-                vertices[indices[i + 3] as usize],
-                vertices[indices[i + 4] as usize],
-                vertices[indices[i + 5] as usize],
-            ];
+        let tri2 = find_rects
+            .then(|| {
+                Some([
+                    *vertices.get(indices[i + 3] as usize)?,
+                    *vertices.get(indices[i + 4] as usize)?,
+                    *vertices.get(indices[i + 5] as usize)?,
+                ])
+            })
+            .flatten();
+
+        if let Some(mut tri2) = tri2 {
             tri2[0].pos += vert_offset;
             tri2[1].pos += vert_offset;
             tri2[2].pos += vert_offset;
 
-            found_rect = tri_verts_match_corners(tri_min, tri_max, tri, tri2);
+            found_rect = tri_verts_match_corners(tri_min, tri_max, tri, tri2, pixels_per_point);
 
             if found_rect {
                 let tri_area = egui_orient2df(&tri[0].pos, &tri[1].pos, &tri[2].pos).abs();
@@ -244,41 +271,122 @@ fn draw_egui_mesh_impl<const SUBPIX_BITS: i32>(
     }
 }
 
+/// Triangle, or rect, given to `draw_tri()` and `draw_rect()`, built by `DrawInfoBuilder`
 pub struct DrawInfo {
-    pub clip_bounds: [I64Vec2; 2],
-    pub colors: [Vec4; 3],
-    pub ss_tri: [Vec2; 3],
-    pub uv: [Vec2; 3],
-    pub tri_min: Vec2,
-    pub tri_max: Vec2,
-    pub const_tex_color: Vec4,
-    pub const_tex_color_u8x4: [u8; 4],
-    pub const_vert_color: Vec4,
-    pub const_vert_color_u8x4: [u8; 4],
-    pub const_tri_color_u8x4: [u8; 4],
+    clip_bounds: [I64Vec2; 2],
+    colors: [Vec4; 3],
+    ss_tri: [Vec2; 3],
+    uv: [Vec2; 3],
+    tri_min: Vec2,
+    tri_max: Vec2,
+    const_tex_color_u8x4: [u8; 4],
+    const_vert_color_u8x4: [u8; 4],
+    const_tri_color_u8x4: [u8; 4],
 }
 
 impl DrawInfo {
-    fn new(
+    const fn builder(
         clip_bounds: [I64Vec2; 2],
         colors: [Vec4; 3],
         ss_tri: [Vec2; 3],
         uv: [Vec2; 3],
         tri_min: Vec2,
         tri_max: Vec2,
-    ) -> Self {
-        Self {
+    ) -> DrawInfoBuilder {
+        DrawInfoBuilder {
             clip_bounds,
             colors,
             ss_tri,
             uv,
             tri_min,
             tri_max,
-            const_tex_color: Vec4::ONE,
-            const_tex_color_u8x4: [255; 4],
-            const_vert_color: Vec4::ONE,
-            const_vert_color_u8x4: [255; 4],
-            const_tri_color_u8x4: [255; 4],
+            const_tex_color_u8x4: None,
+            const_vert_color_u8x4: None,
+        }
+    }
+
+    pub const fn clip_bounds(&self) -> &[I64Vec2; 2] {
+        &self.clip_bounds
+    }
+
+    pub const fn colors(&self) -> &[Vec4; 3] {
+        &self.colors
+    }
+
+    pub const fn ss_tri(&self) -> &[Vec2; 3] {
+        &self.ss_tri
+    }
+
+    pub const fn uv(&self) -> &[Vec2; 3] {
+        &self.uv
+    }
+
+    pub const fn tri_min(&self) -> Vec2 {
+        self.tri_min
+    }
+
+    pub const fn tri_max(&self) -> Vec2 {
+        self.tri_max
+    }
+
+    /// Texture color sampled at the uv of all the vertices, white if the uvs vary
+    pub const fn const_tex_color_u8x4(&self) -> [u8; 4] {
+        self.const_tex_color_u8x4
+    }
+
+    /// Color of all the vertices, white if the colors vary
+    pub const fn const_vert_color_u8x4(&self) -> [u8; 4] {
+        self.const_vert_color_u8x4
+    }
+
+    /// Vertex color tinted texture color, white unless both the uvs and the colors are constant
+    pub const fn const_tri_color_u8x4(&self) -> [u8; 4] {
+        self.const_tri_color_u8x4
+    }
+}
+
+/// Precomputes the constant colors of a `DrawInfo`
+struct DrawInfoBuilder {
+    clip_bounds: [I64Vec2; 2],
+    colors: [Vec4; 3],
+    ss_tri: [Vec2; 3],
+    uv: [Vec2; 3],
+    tri_min: Vec2,
+    tri_max: Vec2,
+    const_tex_color_u8x4: Option<[u8; 4]>,
+    const_vert_color_u8x4: Option<[u8; 4]>,
+}
+
+impl DrawInfoBuilder {
+    /// The uvs of all the vertices sample `color`
+    const fn with_const_tex_color(mut self, color: [u8; 4]) -> Self {
+        self.const_tex_color_u8x4 = Some(color);
+        self
+    }
+
+    /// All the vertices are `color`
+    const fn with_const_vert_color(mut self, color: [u8; 4]) -> Self {
+        self.const_vert_color_u8x4 = Some(color);
+        self
+    }
+
+    fn build(self) -> DrawInfo {
+        let const_tri_color_u8x4 = match (self.const_vert_color_u8x4, self.const_tex_color_u8x4) {
+            (Some(vert_color), Some(tex_color)) => {
+                vec4_to_u8x4(&(u8x4_to_vec4(&vert_color) * u8x4_to_vec4(&tex_color)))
+            }
+            _ => [255; 4],
+        };
+        DrawInfo {
+            clip_bounds: self.clip_bounds,
+            colors: self.colors,
+            ss_tri: self.ss_tri,
+            uv: self.uv,
+            tri_min: self.tri_min,
+            tri_max: self.tri_max,
+            const_tex_color_u8x4: self.const_tex_color_u8x4.unwrap_or([255; 4]),
+            const_vert_color_u8x4: self.const_vert_color_u8x4.unwrap_or([255; 4]),
+            const_tri_color_u8x4,
         }
     }
 }
@@ -289,17 +397,26 @@ pub fn egui_orient2df(a: &Pos2, b: &Pos2, c: &Pos2) -> f32 {
     (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
 }
 
+/// Whether the vertices of `tri` and `tri2` are all corners of the `tri_min` to `tri_max` rect
+///
+/// With fractional scaling egui rounds some vertices to points and others to pixels, so they match within
+/// `pixels_per_point * 0.25` then. Half a point, the whole rounding error, turns the small quads of rounded corners
+/// into rects, and the GPU comparison of the demo at 1.5 pixels per point fails.
 fn tri_verts_match_corners(
     tri_min: Vec2,
     tri_max: Vec2,
     tri: [Vertex; 3],
     tri2: [Vertex; 3],
+    pixels_per_point: f32,
 ) -> bool {
-    #[inline(always)]
-    fn close(a: f32, b: f32) -> bool {
-        //(a - b).abs() <= 0.1
-        a == b
-    }
+    let subpixel_tolerance = match pixels_per_point == 1.0 {
+        true => 0.0,
+        false => pixels_per_point * 0.25,
+    };
+    let close = |a: f32, b: f32| {
+        let distance = (a - b).abs();
+        distance <= RECT_MATCH_EPSILON || distance < subpixel_tolerance
+    };
 
     // https://github.com/emilk/imgui_software_renderer/blob/b5ae63a9e42eccf7db3bf64696761a53424c53dd/src/imgui_sw.cpp#L577
     (close(tri[0].pos.x, tri_min.x) || close(tri[0].pos.x, tri_max.x))
@@ -315,3 +432,62 @@ fn tri_verts_match_corners(
         && (close(tri2[2].pos.x, tri_min.x) || close(tri2[2].pos.x, tri_max.x))
         && (close(tri2[2].pos.y, tri_min.y) || close(tri2[2].pos.y, tri_max.y))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use egui::{Color32, pos2};
+
+    /// The two triangles of `Mesh::add_colored_rect()`, with the min and max of the first one
+    fn rect_tris(min: Pos2, max: Pos2) -> (Vec2, Vec2, [Vertex; 3], [Vertex; 3]) {
+        let vertex = |x: f32, y: f32| Vertex {
+            pos: pos2(x, y),
+            uv: Pos2::ZERO,
+            color: Color32::WHITE,
+        };
+        let [left_top, right_top, left_bottom, right_bottom] = [
+            vertex(min.x, min.y),
+            vertex(max.x, min.y),
+            vertex(min.x, max.y),
+            vertex(max.x, max.y),
+        ];
+        let tri = [left_top, right_top, left_bottom];
+        let tri2 = [left_bottom, right_top, right_bottom];
+        let tri_min = vec2(
+            tri[0].pos.x.min(tri[1].pos.x).min(tri[2].pos.x),
+            tri[0].pos.y.min(tri[1].pos.y).min(tri[2].pos.y),
+        );
+        let tri_max = vec2(
+            tri[0].pos.x.max(tri[1].pos.x).max(tri[2].pos.x),
+            tri[0].pos.y.max(tri[1].pos.y).max(tri[2].pos.y),
+        );
+        (tri_min, tri_max, tri, tri2)
+    }
+
+    #[test]
+    fn rect_corners_match_within_epsilon() {
+        let (tri_min, tri_max, tri, tri2) = rect_tris(pos2(3.0, 5.0), pos2(30.0, 12.0));
+        assert!(tri_verts_match_corners(tri_min, tri_max, tri, tri2, 1.0));
+
+        // The last corner a few ULPs off, as egui's arithmetic can produce
+        let mut nudged = tri2;
+        nudged[2].pos.x = 30.000004;
+        nudged[2].pos.y = 11.999999;
+        assert_ne!(nudged[2].pos, tri2[2].pos);
+        assert!(tri_verts_match_corners(tri_min, tri_max, tri, nudged, 1.0));
+
+        // A quadrilateral that is not a rect
+        let mut skewed = tri2;
+        skewed[2].pos.x = 30.5;
+        assert!(!tri_verts_match_corners(tri_min, tri_max, tri, skewed, 1.0));
+        assert!(!tri_verts_match_corners(tri_min, tri_max, tri, skewed, 1.5));
+
+        // Rounded apart by fractional scaling
+        let mut rounded = tri2;
+        rounded[2].pos.x = 30.3;
+        assert!(!tri_verts_match_corners(
+            tri_min, tri_max, tri, rounded, 1.0
+        ));
+        assert!(tri_verts_match_corners(tri_min, tri_max, tri, rounded, 1.5));
+    }
+}
@@ -0,0 +1,99 @@
+//! Render time model of `EguiSoftwareRender::estimate_render_time_us()`
+
+/// Nanoseconds per mesh vertex, until calibrated
+const DEFAULT_VERTEX_COST_NS: f64 = 20.0;
+
+/// Nanoseconds per pixel covered by a mesh, until calibrated
+const DEFAULT_FILL_COST_NS: f64 = 1.0;
+
+/// Renders measured to calibrate the costs, the model is fixed afterwards
+#[cfg(feature = "std")]
+const CALIBRATION_FRAMES: u32 = 100;
+
+/// Vertices and pixels rasterized
+#[derive(Clone, Copy, Default)]
+pub(crate) struct RasterWork {
+    pub vertices: u64,
+    /// Pixels of the mesh bounds within the clip rect
+    pub pixels: u64,
+}
+
+impl RasterWork {
+    /// Work of `vertices` spanning `mesh_rect`, clipped to `clip_rect`, in pixels
+    pub fn of_mesh(vertices: usize, mesh_rect: egui::Rect, clip_rect: egui::Rect) -> Self {
+        let clipped = mesh_rect.intersect(clip_rect);
+        Self {
+            vertices: vertices as u64,
+            pixels: match clipped.is_positive() {
+                true => clipped.area() as u64,
+                false => 0,
+            },
+        }
+    }
+}
+
+/// `time = vertex_cost * vertices + fill_cost * pixels`, fitted to the first renders by least squares
+pub(crate) struct RenderCostModel {
+    vertex_cost_ns: f64,
+    fill_cost_ns: f64,
+    #[cfg(feature = "std")]
+    calibration_frames: u32,
+    /// Sums of the products of the measured vertices, pixels and times, the terms of the least squares fit
+    #[cfg(feature = "std")]
+    sums: [f64; 5],
+}
+
+impl Default for RenderCostModel {
+    fn default() -> Self {
+        Self {
+            vertex_cost_ns: DEFAULT_VERTEX_COST_NS,
+            fill_cost_ns: DEFAULT_FILL_COST_NS,
+            #[cfg(feature = "std")]
+            calibration_frames: 0,
+            #[cfg(feature = "std")]
+            sums: [0.0; 5],
+        }
+    }
+}
+
+impl RenderCostModel {
+    pub fn estimate_ns(&self, work: RasterWork) -> f64 {
+        self.vertex_cost_ns * work.vertices as f64 + self.fill_cost_ns * work.pixels as f64
+    }
+
+    /// Fits the costs to `work` rasterized in `elapsed`, for the first `CALIBRATION_FRAMES` renders
+    #[cfg(feature = "std")]
+    pub fn calibrate(&mut self, work: RasterWork, elapsed: core::time::Duration) {
+        if self.calibration_frames >= CALIBRATION_FRAMES || work.vertices == 0 {
+            return;
+        }
+        self.calibration_frames += 1;
+
+        let (v, p, t) = (
+            work.vertices as f64,
+            work.pixels as f64,
+            elapsed.as_nanos() as f64,
+        );
+        let [vv, vp, pp, vt, pt] = &mut self.sums;
+        *vv += v * v;
+        *vp += v * p;
+        *pp += p * p;
+        *vt += v * t;
+        *pt += p * t;
+        let [vv, vp, pp, vt, pt] = self.sums;
+
+        let det = vv * pp - vp * vp;
+        let vertex_cost = (vt * pp - pt * vp) / det;
+        let fill_cost = (pt * vv - vt * vp) / det;
+        if det > f64::EPSILON * vv * pp && vertex_cost >= 0.0 && fill_cost >= 0.0 {
+            (self.vertex_cost_ns, self.fill_cost_ns) = (vertex_cost, fill_cost);
+        } else {
+            // Renders too alike to separate the costs, e.g. always the same UI, scale the defaults instead
+            let default = Self::default();
+            let (dv, dp) = (default.vertex_cost_ns, default.fill_cost_ns);
+            let estimated_squares = dv * dv * vv + 2.0 * dv * dp * vp + dp * dp * pp;
+            let scale = (dv * vt + dp * pt) / estimated_squares;
+            (self.vertex_cost_ns, self.fill_cost_ns) = (dv * scale, dp * scale);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use alloc::vec::Vec;
+
+use crate::{BufferMutRef, BufferRef, ColorFieldOrder, as_usize, color::swizzle_rgba_bgra};
+
+impl BufferRef<'_> {
+    /// Copy of the buffer as an `image::RgbaImage`
+    ///
+    /// * `order` - Order of the buffer, as given to `EguiSoftwareRender::new()`. `Bgra` is swizzled to RGBA.
+    pub fn to_rgba_image(&self, order: ColorFieldOrder) -> image::RgbaImage {
+        to_rgba_image(self.data, self.width, self.height, order)
+    }
+
+    /// The buffer encoded as PNG, see [`BufferRef::to_rgba_image`]
+    ///
+    /// # Errors
+    ///
+    /// When the `image` PNG encoder fails, e.g. on an empty buffer.
+    pub fn to_png_bytes(&self, order: ColorFieldOrder) -> Result<Vec<u8>, image::ImageError> {
+        let mut png = Vec::new();
+        self.to_rgba_image(order)
+            .write_with_encoder(image::codecs::png::PngEncoder::new(&mut png))?;
+        Ok(png)
+    }
+}
+
+impl BufferMutRef<'_> {
+    /// Copy of the buffer as an `image::RgbaImage`, see [`BufferRef::to_rgba_image`]
+    pub fn to_rgba_image(&self, order: ColorFieldOrder) -> image::RgbaImage {
+        to_rgba_image(self.data, self.width, self.height, order)
+    }
+}
+
+fn to_rgba_image(
+    data: &[[u8; 4]],
+    width: u32,
+    height: u32,
+    order: ColorFieldOrder,
+) -> image::RgbaImage {
+    let pixels = &data[..as_usize(width) * as_usize(height)];
+    let bytes = match order {
+        ColorFieldOrder::Rgba => pixels.as_flattened().to_vec(),
+        ColorFieldOrder::Bgra => pixels
+            .iter()
+            .flat_map(|&pixel| swizzle_rgba_bgra(pixel))
+            .collect(),
+    };
+    image::RgbaImage::from_raw(width, height, bytes).expect("buffer holds width * height pixels")
+}
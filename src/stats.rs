@@ -1,11 +1,13 @@
 use crate::alloc::string::ToString;
 use alloc::format;
 use alloc::vec::Vec;
-use core::sync::atomic::{self};
 use egui::ahash::HashMap;
 use egui::mutex::Mutex;
 use std::time::Instant;
 
+use crate::MemoryStats;
+use crate::cache_stats::CacheStats;
+
 #[allow(unused_imports)]
 use egui::{Ui, Vec2, Vec2b};
 
@@ -16,21 +18,107 @@ pub struct Stat {
     pub sum_area: f32,
 }
 
+/// Number of measurements kept by each `DurationStat`
+pub const DURATION_HISTORY: usize = 256;
+
 #[derive(Default)]
 pub struct DurationStat {
-    elapsed_secs: atomic::AtomicU32, // f32
+    history: Mutex<DurationHistory>,
+}
+
+/// Ring buffer of the last `DURATION_HISTORY` measurements
+struct DurationHistory {
+    secs: [f32; DURATION_HISTORY],
+    /// Index of the next measurement
+    head: usize,
+    len: usize,
+}
+
+impl Default for DurationHistory {
+    fn default() -> Self {
+        DurationHistory {
+            secs: [0.0; DURATION_HISTORY],
+            head: 0,
+            len: 0,
+        }
+    }
+}
+
+impl DurationHistory {
+    fn samples(&self) -> &[f32] {
+        &self.secs[..self.len]
+    }
 }
 
 impl DurationStat {
     pub(crate) fn mark(&self, start: Instant) {
         let secs = start.elapsed().as_secs_f32();
-        let secs: u32 = secs.to_bits();
-        self.elapsed_secs.store(secs, atomic::Ordering::Relaxed);
+        let mut history = self.history.lock();
+        let head = history.head;
+        history.secs[head] = secs;
+        history.head = (head + 1) % DURATION_HISTORY;
+        history.len = (history.len + 1).min(DURATION_HISTORY);
     }
 
+    /// Most recent measurement
     pub fn elapsed_secs(&self) -> f32 {
-        let secs: u32 = self.elapsed_secs.load(atomic::Ordering::Relaxed);
-        f32::from_bits(secs)
+        let history = self.history.lock();
+        if history.len == 0 {
+            return 0.0;
+        }
+        history.secs[(history.head + DURATION_HISTORY - 1) % DURATION_HISTORY]
+    }
+
+    /// Nearest rank percentile of the recent measurements, `p` in `0.0..=100.0`, 0 without measurements
+    pub fn percentile(&self, p: f32) -> f32 {
+        let mut samples = self.history.lock().samples().to_vec();
+        if samples.is_empty() {
+            return 0.0;
+        }
+        samples.sort_by(f32::total_cmp);
+        let rank = (p.clamp(0.0, 100.0) / 100.0 * samples.len() as f32).ceil() as usize;
+        samples[rank.clamp(1, samples.len()) - 1]
+    }
+
+    /// Longest of the recent measurements, 0 without measurements
+    pub fn max(&self) -> f32 {
+        self.history
+            .lock()
+            .samples()
+            .iter()
+            .copied()
+            .fold(0.0, f32::max)
+    }
+
+    /// Count of the recent measurements in each of `buckets` equal ranges from 0 to `max()`
+    pub fn histogram(&self, buckets: usize) -> Vec<u32> {
+        let mut histogram = alloc::vec![0; buckets];
+        let max = self.max();
+        if buckets == 0 || max <= 0.0 {
+            return histogram;
+        }
+        for &secs in self.history.lock().samples() {
+            let bucket = ((secs / max * buckets as f32) as usize).min(buckets - 1);
+            histogram[bucket] += 1;
+        }
+        histogram
+    }
+}
+
+/// Bars of the `DurationStat::histogram()` drawn by `RenderStats::render()`
+const HISTOGRAM_BUCKETS: usize = 32;
+
+fn histogram_bars(ui: &mut Ui, histogram: &[u32]) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(100.0, 16.0), egui::Sense::hover());
+    let highest = histogram.iter().copied().max().unwrap_or(0).max(1) as f32;
+    let bar_width = rect.width() / histogram.len().max(1) as f32;
+    let color = ui.visuals().text_color();
+    for (i, &count) in histogram.iter().enumerate() {
+        let x = rect.min.x + i as f32 * bar_width;
+        let top = rect.max.y - rect.height() * count as f32 / highest;
+        let bar =
+            egui::Rect::from_min_max(egui::pos2(x, top), egui::pos2(x + bar_width, rect.max.y));
+        ui.painter().rect_filled(bar, 0.0, color);
     }
 }
 
@@ -60,11 +148,52 @@ pub struct RasterStats {
     pub tris: u32,
     /// Total rects drawn
     pub rects: u32,
+    /// Sum of the bounding box area of the tris drawn
+    pub total_pixels_filled_tris: u64,
+    /// Sum of the area of the rects drawn
+    pub total_pixels_filled_rects: u64,
+}
+
+impl RasterStats {
+    /// Megapixels filled per second, when rasterizing took `elapsed_secs`, see `RasterStats::raster_secs()`
+    pub fn fill_rate_mpps(&self, elapsed_secs: f32) -> f64 {
+        if elapsed_secs <= 0.0 {
+            return 0.0;
+        }
+        (self.total_pixels_filled_tris + self.total_pixels_filled_rects) as f64
+            / elapsed_secs as f64
+            / 1e6
+    }
+
+    /// Megapixels per second needed to fill a `width` x `height` buffer once per frame at `target_fps`
+    pub fn theoretical_mpps_for_resolution(width: u32, height: u32, target_fps: f32) -> f64 {
+        width as f64 * height as f64 * target_fps as f64 / 1e6
+    }
+
+    /// Time spent rasterizing the tris and rects
+    pub fn raster_secs(&self) -> f32 {
+        self.tri_width_buckets
+            .values()
+            .chain(self.rect_width_buckets.values())
+            .map(|stat| stat.time)
+            .sum()
+    }
 }
 
 #[derive(Default)]
 pub struct RenderStats {
     pub raster: Mutex<RasterStats>,
+    pub cache: CacheStats,
+    /// Bytes allocated at the end of the last render
+    pub memory: MemoryStats,
+    /// Primitives cached at the end of the last render
+    pub primitive_count: usize,
+    /// Textures loaded at the end of the last render
+    pub texture_count: usize,
+    /// Size of the buffer given to the last render
+    pub buffer_size: [u32; 2],
+    /// New primitives skipped by the render budget in the last render, see `EguiSoftwareRender::with_render_budget_ms()`
+    pub primitives_over_budget: u32,
     pub set_textures: DurationStat,
     pub render_prims_to_cache: DurationStat,
     pub update_dirty_rect: DurationStat,
@@ -76,6 +205,18 @@ pub struct RenderStats {
     pub blit_canvas_to_buffer: DurationStat,
     #[cfg(feature = "winit")]
     pub winit_present: DurationStat,
+    /// Number of non overlapping dirty rects (`MeshTiled` only)
+    pub dirty_rects_count: usize,
+    /// Sum of the area of the non overlapping dirty rects (`MeshTiled` only)
+    pub dirty_rects_area: u64,
+    /// Bytes allocated to compute the dirty rects (`MeshTiled` only)
+    pub dirty_rects_memory_bytes: usize,
+    /// Heap allocations during the last render, if `GlobalAllocStats` is the global allocator
+    #[cfg(feature = "alloc_stats")]
+    pub allocs_this_frame: u64,
+    /// Bytes of `allocs_this_frame`
+    #[cfg(feature = "alloc_stats")]
+    pub alloc_bytes_this_frame: u64,
 }
 
 #[cfg(not(feature = "rayon"))]
@@ -95,6 +236,7 @@ impl<'a> RasterStatsStarted<'a> {
     ) {
         let elapsed = self.start.elapsed().as_secs_f32();
         self.stats.rects += 1;
+        self.stats.total_pixels_filled_rects += (fsize.x * fsize.y) as u64;
         let tri_area = (fsize.x * fsize.y) * 0.5;
         Self::insert_or_increment(
             (fsize.x as u32).max(1),
@@ -122,6 +264,7 @@ impl<'a> RasterStatsStarted<'a> {
     ) {
         let elapsed = self.start.elapsed().as_secs_f32();
         self.stats.tris += 1;
+        self.stats.total_pixels_filled_tris += (fsize.x * fsize.y) as u64;
         let rect_area = fsize.x * fsize.y;
         Self::insert_or_increment(
             (fsize.x as u32).max(1),
@@ -164,8 +307,28 @@ impl<'a> RasterStatsStarted<'a> {
 }
 
 impl RenderStats {
+    /// Reset for a new frame, keeping the history of the `DurationStat`s
     pub(crate) fn clear(&mut self) {
-        *self = RenderStats::default();
+        let take = core::mem::take::<DurationStat>;
+        *self = RenderStats {
+            set_textures: take(&mut self.set_textures),
+            render_prims_to_cache: take(&mut self.render_prims_to_cache),
+            update_dirty_rect: take(&mut self.update_dirty_rect),
+            update_dirty_tiles: take(&mut self.update_dirty_tiles),
+            update_dirty_rects: take(&mut self.update_dirty_rects),
+            render_from_meshcache: take(&mut self.render_from_meshcache),
+            render_from_tiledcache: take(&mut self.render_from_tiledcache),
+            render_direct: take(&mut self.render_direct),
+            blit_canvas_to_buffer: take(&mut self.blit_canvas_to_buffer),
+            #[cfg(feature = "winit")]
+            winit_present: take(&mut self.winit_present),
+            ..RenderStats::default()
+        };
+    }
+
+    /// Ratio of primitives reused from the cache, see [`CacheStats::cache_hit_rate`]
+    pub fn cache_hit_rate(&self) -> f32 {
+        self.cache.cache_hit_rate()
     }
 
     #[cfg(not(feature = "rayon"))]
@@ -183,9 +346,20 @@ impl RenderStats {
             .show(ui, |ui| {
                 let raster = self.raster.lock();
                 egui::Grid::new("stats_grid").striped(true).show(ui, |ui| {
+                    ui.heading("");
+                    ["last", "p50", "p95", "p99", "max", ""]
+                        .iter()
+                        .for_each(|s| _ = ui.heading(*s));
+                    ui.end_row();
                     let mut stat = |label: &str, val: &DurationStat| {
                         ui.label(label);
-                        ui.label(format!("{:.3}ms", val.elapsed_secs() * 1000.0));
+                        let ms = |secs: f32| format!("{:.3}ms", secs * 1000.0);
+                        ui.label(ms(val.elapsed_secs()));
+                        ui.label(ms(val.percentile(50.0)));
+                        ui.label(ms(val.percentile(95.0)));
+                        ui.label(ms(val.percentile(99.0)));
+                        ui.label(ms(val.max()));
+                        histogram_bars(ui, &val.histogram(HISTOGRAM_BUCKETS));
                         ui.end_row();
                     };
                     stat("set_textures", &self.set_textures);
@@ -200,6 +374,63 @@ impl RenderStats {
                     #[cfg(feature = "winit")]
                     stat("winit_present", &self.winit_present);
 
+                    let mut stat = |label: &str, val: alloc::string::String| {
+                        ui.label(label);
+                        ui.label(val);
+                        ui.end_row();
+                    };
+                    stat("cache_hits", self.cache.cache_hits().to_string());
+                    stat("cache_misses", self.cache.cache_misses().to_string());
+                    stat("cache_evictions", self.cache.cache_evictions().to_string());
+                    stat(
+                        "cache_hit_rate",
+                        format!("{:.1}%", self.cache_hit_rate() * 100.0),
+                    );
+                    stat(
+                        "primitives_over_budget",
+                        self.primitives_over_budget.to_string(),
+                    );
+                    stat("dirty_rects_count", self.dirty_rects_count.to_string());
+                    stat("dirty_rects_area", self.dirty_rects_area.to_string());
+                    stat(
+                        "dirty_rects_memory",
+                        format!("{}B", self.dirty_rects_memory_bytes),
+                    );
+                    #[cfg(feature = "alloc_stats")]
+                    {
+                        stat("allocs_this_frame", self.allocs_this_frame.to_string());
+                        stat(
+                            "alloc_bytes_this_frame",
+                            format!("{}B", self.alloc_bytes_this_frame),
+                        );
+                    }
+                    stat("primitive_count", self.primitive_count.to_string());
+                    stat("texture_count", self.texture_count.to_string());
+                    stat(
+                        "fill_rate",
+                        format!("{:.1}MP/s", raster.fill_rate_mpps(raster.raster_secs())),
+                    );
+                    let [width, height] = self.buffer_size;
+                    stat(
+                        "full_redraw_60fps",
+                        format!(
+                            "{:.1}MP/s",
+                            RasterStats::theoretical_mpps_for_resolution(width, height, 60.0)
+                        ),
+                    );
+
+                    let total = self.memory.total_bytes().max(1) as f32;
+                    let mut stat = |label: &str, bytes: u64| {
+                        ui.label(label);
+                        ui.label(format!("{:.1}KiB", bytes as f32 / 1024.0));
+                        ui.add(egui::ProgressBar::new(bytes as f32 / total).desired_width(100.0));
+                        ui.end_row();
+                    };
+                    stat("texture_memory", self.memory.texture_bytes);
+                    stat("tiled_cache_memory", self.memory.tiled_cache_bytes);
+                    stat("mesh_cache_memory", self.memory.mesh_cache_bytes);
+                    stat("dirty_rect_memory", self.memory.dirty_rect_bytes);
+
                     ui.heading("");
                     ui.heading("Tri");
                     ui.heading("Rect");
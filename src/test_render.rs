@@ -1,14 +1,33 @@
-use alloc::{string::String, vec, vec::Vec};
+use alloc::string::String;
 use egui::TexturesDelta;
 use egui_kittest::TestRenderer;
-use image::ImageBuffer;
 
-use crate::{BufferMutRef, EguiSoftwareRenderCanvas};
+use crate::{
+    BufferMutRef, EguiSoftwareRender, EguiSoftwareRenderCanvas, EguiSoftwareRenderInner,
+    frame_source::output_size,
+};
+
+// The harness calls `handle_delta()` with each frame's delta, then `render()` with the last `FullOutput`, which
+// contains the last delta again. So `render()` ignores `output.textures_delta`, and the textures egui asks to free
+// are only freed after the next render or delta, since the shapes of the same output may still use them.
+
+impl EguiSoftwareRenderInner {
+    fn test_handle_delta(&mut self, delta: &TexturesDelta) {
+        self.free_pending_textures();
+        self.set_textures(delta);
+        self.pending_free_textures.extend_from_slice(&delta.free);
+    }
+
+    fn free_pending_textures(&mut self) {
+        for id in self.pending_free_textures.drain(..) {
+            self.textures.remove(&id);
+        }
+    }
+}
 
 impl TestRenderer for EguiSoftwareRenderCanvas {
     fn handle_delta(&mut self, delta: &TexturesDelta) {
-        self.renderer.inner.set_textures(delta);
-        self.renderer.inner.free_textures(delta);
+        self.renderer.inner.test_handle_delta(delta);
     }
 
     fn render(
@@ -17,26 +36,46 @@ impl TestRenderer for EguiSoftwareRenderCanvas {
         output: &egui::FullOutput,
     ) -> Result<image::RgbaImage, String> {
         let paint_jobs = ctx.tessellate(output.shapes.clone(), output.pixels_per_point);
+        let (width, height) = output_size(ctx, output.pixels_per_point);
 
-        let width = (ctx.content_rect().width() * output.pixels_per_point) as u32;
-        let height = (ctx.content_rect().height() * output.pixels_per_point) as u32;
+        let mut buffer = self.render_to_buffer(
+            paint_jobs,
+            &TexturesDelta::default(),
+            output.pixels_per_point,
+            width,
+            height,
+        );
+        self.renderer.inner.free_pending_textures();
+
+        Ok(BufferMutRef::new(&mut buffer, width, height)
+            .to_rgba_image(self.renderer.inner.output_field_order))
+    }
+}
 
-        let mut buffer = vec![[0u8; 4]; crate::as_usize(width * height)];
+/// Renders to a temporary buffer, without the persistent canvas of `EguiSoftwareRenderCanvas`
+impl TestRenderer for EguiSoftwareRender {
+    fn handle_delta(&mut self, delta: &TexturesDelta) {
+        self.inner.test_handle_delta(delta);
+    }
 
-        let mut buffer_ref = BufferMutRef::new(&mut buffer, width, height);
+    fn render(
+        &mut self,
+        ctx: &egui::Context,
+        output: &egui::FullOutput,
+    ) -> Result<image::RgbaImage, String> {
+        let paint_jobs = ctx.tessellate(output.shapes.clone(), output.pixels_per_point);
+        let (width, height) = output_size(ctx, output.pixels_per_point);
 
-        self.render(
-            &mut buffer_ref,
+        let (mut buffer, _) = self.render_to_buffer(
             paint_jobs,
-            &output.textures_delta,
+            &TexturesDelta::default(),
             output.pixels_per_point,
-        );
-
-        Ok(ImageBuffer::<image::Rgba<u8>, Vec<_>>::from_raw(
             width,
             height,
-            buffer.iter().flatten().cloned().collect::<Vec<_>>(),
-        )
-        .unwrap())
+        );
+        self.inner.free_pending_textures();
+
+        Ok(BufferMutRef::new(&mut buffer, width, height)
+            .to_rgba_image(self.inner.output_field_order))
     }
 }
@@ -0,0 +1,64 @@
+use alloc::vec::Vec;
+
+use egui::{Color32, ColorImage, pos2};
+
+/// Direction of `create_linear_gradient_texture()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GradientDirection {
+    /// `color_a` on the left, `color_b` on the right, `steps` x 1 pixels
+    Horizontal,
+    /// `color_a` on the top, `color_b` on the bottom, 1 x `steps` pixels
+    Vertical,
+    /// `color_a` on the top left, `color_b` on the bottom right, `steps` x `steps` pixels
+    Diagonal45,
+}
+
+/// Texture going from `color_a` to `color_b` in `steps` pixels, to register with `egui::Context::load_texture()`
+///
+/// Drawn stretched with `TextureOptions::LINEAR`, the texture gives a smooth gradient of any size.
+pub fn create_linear_gradient_texture(
+    color_a: Color32,
+    color_b: Color32,
+    steps: u32,
+    direction: GradientDirection,
+) -> ColorImage {
+    let steps = steps.max(1) as usize;
+    let last = (steps - 1).max(1) as f32;
+    let color = |t: f32| color_a.lerp_to_gamma(color_b, t);
+    match direction {
+        GradientDirection::Horizontal | GradientDirection::Vertical => {
+            let pixels = (0..steps).map(|i| color(i as f32 / last)).collect();
+            let size = match direction {
+                GradientDirection::Horizontal => [steps, 1],
+                _ => [1, steps],
+            };
+            ColorImage::new(size, pixels)
+        }
+        GradientDirection::Diagonal45 => {
+            let pixels = (0..steps * steps)
+                .map(|i| color((i % steps + i / steps) as f32 / (2.0 * last)))
+                .collect();
+            ColorImage::new([steps, steps], pixels)
+        }
+    }
+}
+
+/// Texture going from `center_color` in the center to `edge_color` at `radius_steps` pixels from it,
+/// `2 * radius_steps` pixels wide. The corners, further than `radius_steps`, are `edge_color`.
+pub fn create_radial_gradient_texture(
+    center_color: Color32,
+    edge_color: Color32,
+    radius_steps: u32,
+) -> ColorImage {
+    let radius = radius_steps.max(1) as f32;
+    let size = 2 * radius_steps.max(1) as usize;
+    let center = pos2(radius, radius);
+    let pixels: Vec<_> = (0..size * size)
+        .map(|i| {
+            let pixel_center = pos2((i % size) as f32 + 0.5, (i / size) as f32 + 0.5);
+            let t = (pixel_center.distance(center) / radius).min(1.0);
+            center_color.lerp_to_gamma(edge_color, t)
+        })
+        .collect();
+    ColorImage::new([size, size], pixels)
+}
@@ -0,0 +1,126 @@
+use alloc::vec::Vec;
+
+use egui::{Color32, TextureOptions};
+
+use crate::{EguiSoftwareRender, egui_texture::EguiTexture};
+
+impl EguiSoftwareRender {
+    /// Read back `texture` from the GPU and register it as an `egui::TextureId::User` texture
+    ///
+    /// Blocks until the GPU finished rendering to `texture`. Use `update_wgpu_texture()` for the next frames.
+    ///
+    /// # Panics
+    /// If `texture` is not a 2D `Rgba8Unorm(Srgb)` or `Bgra8Unorm(Srgb)` texture with the `COPY_SRC` usage.
+    pub fn import_wgpu_texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+    ) -> egui::TextureId {
+        let id = egui::TextureId::User(self.inner.next_user_texture_id);
+        self.inner.next_user_texture_id += 1;
+        self.update_wgpu_texture(id, device, queue, texture);
+        id
+    }
+
+    /// Read back `texture` from the GPU into the texture `id` returned by `import_wgpu_texture()`
+    ///
+    /// Cached primitives drawn with `id` are rasterized again on the next render.
+    ///
+    /// # Panics
+    /// See `import_wgpu_texture()`.
+    pub fn update_wgpu_texture(
+        &mut self,
+        id: egui::TextureId,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+    ) {
+        let size = [texture.width() as usize, texture.height() as usize];
+        let pixels = read_wgpu_texture(device, queue, texture);
+        let inner = &mut self.inner;
+        let generation = inner.textures.get(&id).map_or(0, |t| t.generation + 1);
+        let mut new_texture = EguiTexture::new(
+            inner.pixel_alloc,
+            inner.output_field_order,
+            TextureOptions::LINEAR,
+            size,
+            &pixels,
+        );
+        if inner.straight_alpha_input {
+            new_texture.premultiply_alpha();
+        }
+        new_texture.generation = generation;
+        inner.textures.insert(id, new_texture);
+    }
+}
+
+/// Copies `texture` to a staging buffer and maps it, returns the pixels in RGBA order
+fn read_wgpu_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> Vec<Color32> {
+    let bgra = match texture.format() {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+        format => panic!("Unsupported wgpu texture format {format:?}"),
+    };
+    assert_eq!(texture.dimension(), wgpu::TextureDimension::D2);
+    assert!(texture.usage().contains(wgpu::TextureUsages::COPY_SRC));
+
+    let (width, height) = (texture.width(), texture.height());
+    let unpadded_bytes_per_row = width * 4;
+    let bytes_per_row = unpadded_bytes_per_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+    let staging = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("egui_software_backend readback"),
+        size: bytes_per_row as u64 * height as u64,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("egui_software_backend readback"),
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &staging,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit([encoder.finish()]);
+
+    let slice = staging.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |result| {
+        result.expect("Failed to map the wgpu readback buffer")
+    });
+    device
+        .poll(wgpu::PollType::wait_indefinitely())
+        .expect("Failed to wait for the wgpu readback");
+
+    let mapped = slice.get_mapped_range();
+    let mut pixels = Vec::with_capacity((width * height) as usize);
+    for row in mapped.chunks_exact(bytes_per_row as usize) {
+        pixels.extend(
+            row[..unpadded_bytes_per_row as usize]
+                .chunks_exact(4)
+                .map(|p| match bgra {
+                    false => Color32::from_rgba_premultiplied(p[0], p[1], p[2], p[3]),
+                    true => Color32::from_rgba_premultiplied(p[2], p[1], p[0], p[3]),
+                }),
+        );
+    }
+    drop(mapped);
+    staging.unmap();
+    pixels
+}
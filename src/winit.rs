@@ -1,9 +1,16 @@
+#[cfg(feature = "cache_stats")]
+use crate::cache_stats::CacheStats;
+#[cfg(feature = "gamepad")]
+use crate::gamepad::{DEFAULT_GAMEPAD_KEY_MAP, GamepadInput};
 #[cfg(feature = "raster_stats")]
 use crate::stats::RenderStats;
-use crate::{BufferMutRef, ColorFieldOrder, EguiSoftwareRender, SoftwareRenderCaching};
+use crate::{
+    BufferMutRef, ColorBlindMode, ColorFieldOrder, DebugOverlay, DirtyRect, DitherMode,
+    EguiSoftwareRender, MemoryStats, SoftwareRenderCaching,
+};
 use egui::{
-    Context, CursorGrab, IconData, Pos2, SystemTheme, Vec2, ViewportBuilder, ViewportCommand,
-    WindowLevel, X11WindowType,
+    Context, CursorGrab, IMEPurpose, IconData, Pos2, SystemTheme, Vec2, ViewportBuilder,
+    ViewportCommand, WindowLevel, X11WindowType,
 };
 use softbuffer::SoftBufferError;
 use std::boxed::Box;
@@ -20,7 +27,9 @@ use std::vec::Vec;
 use winit::application::ApplicationHandler;
 use winit::event::{Event, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop, OwnedDisplayHandle};
-use winit::window::{CursorGrabMode, Fullscreen, Icon, Theme, Window, WindowButtons, WindowId};
+use winit::window::{
+    CursorGrabMode, Fullscreen, Icon, ImePurpose, Theme, Window, WindowButtons, WindowId,
+};
 
 /// Errors that can occur when using the egui software backend with winit.
 #[derive(Debug)]
@@ -152,6 +161,58 @@ struct RunningEguiAppState<EguiApp: App, EguiAppFactory: FnMut(Context) -> EguiA
     fullscreen: bool,
     visible: bool,
     input_events: Vec<egui::Event>,
+    /// Render target while a debug overlay is enabled
+    debug_overlay_canvas: Vec<[u8; 4]>,
+    /// Set by `SoftwareBackend::request_screenshot()`
+    screenshot_requested: bool,
+    /// Captured after the render, delivered to `App::on_screenshot()` after the present
+    pending_screenshot: Option<image::RgbaImage>,
+    /// `ViewportCommand::Screenshot` of this frame, answered by an `egui::Event::Screenshot` in the next frame
+    screenshot_commands: Vec<egui::UserData>,
+    /// Start of the last `RedrawRequested`
+    last_redraw: Option<Instant>,
+    /// Refresh interval of the monitor, or estimated from the last two `RedrawRequested`
+    frame_interval: Option<Duration>,
+    /// Redraw delayed to the next estimated VBLANK, see `RunningEguiAppState::request_paced_redraw()`
+    paced_redraw: Option<Instant>,
+    /// See `SoftwareBackendAppConfiguration::gamepad_navigation()`
+    #[cfg(feature = "gamepad")]
+    gamepad: Option<GamepadInput>,
+    /// Stylus touching the window, see `SoftwareBackendAppConfiguration::enable_stylus()`
+    stylus: Option<StylusState>,
+    /// Scrolling after the last `WindowEvent::MouseWheel`, see `RunningEguiAppState::coast_scroll()`
+    scroll_velocity: Option<ScrollVelocity>,
+    /// For `ViewportCommand::StartDrag`
+    left_mouse_down: bool,
+}
+
+/// Scroll speed in points per `SCROLL_INERTIA_FRAME`, 0 once stopped
+struct ScrollVelocity {
+    vx: f32,
+    vy: f32,
+    /// Of the last wheel event or inertia scroll
+    last_update: Instant,
+}
+
+/// Longest interval between two `RedrawRequested` taken as the refresh interval, longer ones are idle gaps
+const MAX_FRAME_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Interval the inertia scrolls at, and the friction applies to
+const SCROLL_INERTIA_FRAME: Duration = Duration::from_millis(16);
+
+/// Points per `SCROLL_INERTIA_FRAME` under which the inertia stops
+const MIN_SCROLL_INERTIA_VELOCITY: f32 = 0.5;
+
+/// Longest interval between two wheel events of the same scroll gesture
+const MAX_SCROLL_EVENT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Wake up the event loop at `when`, or earlier if it already waits for an earlier time
+fn wake_up_before(elwt: &ActiveEventLoop, when: Instant) {
+    match elwt.control_flow() {
+        ControlFlow::WaitUntil(earlier) if earlier <= when => {}
+        ControlFlow::Poll => {}
+        _ => elwt.set_control_flow(ControlFlow::WaitUntil(when)),
+    }
 }
 
 impl<EguiApp: App, EguiAppFactory: FnMut(Context) -> EguiApp> Default
@@ -224,6 +285,14 @@ impl<EguiApp: App, EguiAppFactory: FnMut(Context) -> EguiApp>
         let egui_app = (self.egui_app_factory)(self.egui_context.clone());
         let fullscreen = self.config.viewport_builder.fullscreen.unwrap_or_default();
         let visible = self.config.viewport_builder.visible.unwrap_or(true);
+        #[cfg(feature = "gamepad")]
+        let gamepad = if self.config.gamepad_navigation {
+            let key_map = (self.config.gamepad_key_map.clone())
+                .unwrap_or_else(|| DEFAULT_GAMEPAD_KEY_MAP.to_vec());
+            GamepadInput::new(key_map)
+        } else {
+            None
+        };
 
         Ok(RunningEguiAppState {
             config: self.config,
@@ -239,6 +308,18 @@ impl<EguiApp: App, EguiAppFactory: FnMut(Context) -> EguiApp>
             visible,
             input_events: Vec::new(),
             last_frame_time: None,
+            debug_overlay_canvas: Vec::new(),
+            screenshot_requested: false,
+            pending_screenshot: None,
+            screenshot_commands: Vec::new(),
+            last_redraw: None,
+            frame_interval: None,
+            paced_redraw: None,
+            #[cfg(feature = "gamepad")]
+            gamepad,
+            stylus: None,
+            scroll_velocity: None,
+            left_mouse_down: false,
         })
     }
 }
@@ -332,7 +413,7 @@ impl<EguiApp: App, InitSurface: FnMut(Context) -> EguiApp> ApplicationHandler<Us
                     if current_pass_nr == cumulative_pass_nr
                         || current_pass_nr == cumulative_pass_nr + 1
                     {
-                        state.request_redraw();
+                        state.request_paced_redraw(event_loop);
                     }
                     event_loop.set_control_flow(ControlFlow::WaitUntil(when));
                 }
@@ -429,6 +510,116 @@ impl<EguiApp: App, EguiAppFactory: FnMut(Context) -> EguiApp>
         self.window.request_redraw();
     }
 
+    /// Redraw at the next estimated VBLANK, so continuous repaints (e.g. animations) don't render faster than the
+    /// display refreshes. Redraws right away without an estimate, or if the VBLANK already passed.
+    pub(crate) fn request_paced_redraw(&mut self, elwt: &ActiveEventLoop) {
+        let next_vblank = self
+            .last_redraw
+            .zip(self.frame_interval)
+            .map(|(last_redraw, frame_interval)| last_redraw + frame_interval);
+        match next_vblank {
+            Some(when) if when > Instant::now() => {
+                let when = self.paced_redraw.map_or(when, |paced| paced.min(when));
+                self.paced_redraw = Some(when);
+                elwt.set_control_flow(ControlFlow::WaitUntil(when));
+            }
+            _ => self.request_redraw(),
+        }
+    }
+
+    /// Queue the gamepad input for the next frame, and wake up the event loop to poll the gamepads again
+    #[cfg(feature = "gamepad")]
+    fn poll_gamepad(&mut self, elwt: &ActiveEventLoop) {
+        let Some(gamepad) = &mut self.gamepad else {
+            return;
+        };
+        let pointer = self.egui_context.input(|i| i.pointer.latest_pos());
+        let size = self.window.inner_size();
+        let screen_size =
+            Vec2::new(size.width as f32, size.height as f32) / self.egui_context.pixels_per_point();
+        let len = self.input_events.len();
+        gamepad.poll(pointer, screen_size, &mut self.input_events);
+        if self.input_events.len() > len {
+            self.request_paced_redraw(elwt);
+        }
+
+        wake_up_before(elwt, Instant::now() + crate::gamepad::POLL_INTERVAL);
+    }
+
+    /// Keep scrolling after the last `WindowEvent::MouseWheel`, slowed down by the friction, see
+    /// `SoftwareBackendAppConfiguration::scroll_inertia_friction()`
+    fn coast_scroll(&mut self, elwt: &ActiveEventLoop) {
+        let Some(velocity) = &mut self.scroll_velocity else {
+            return;
+        };
+        if velocity.vx == 0.0 && velocity.vy == 0.0 {
+            return;
+        }
+        let now = Instant::now();
+        let elapsed = now - velocity.last_update;
+        if elapsed < SCROLL_INERTIA_FRAME {
+            // Still scrolling, or already coasted this frame
+            wake_up_before(elwt, velocity.last_update + SCROLL_INERTIA_FRAME);
+            return;
+        }
+        let frames = elapsed.as_secs_f32() / SCROLL_INERTIA_FRAME.as_secs_f32();
+        let decay = self.config.scroll_inertia_friction.powf(frames);
+        velocity.vx *= decay;
+        velocity.vy *= decay;
+        velocity.last_update = now;
+        if velocity.vx.hypot(velocity.vy) < MIN_SCROLL_INERTIA_VELOCITY {
+            (velocity.vx, velocity.vy) = (0.0, 0.0);
+            return;
+        }
+        self.input_events.push(egui::Event::MouseWheel {
+            unit: egui::MouseWheelUnit::Point,
+            delta: Vec2::new(velocity.vx, velocity.vy) * frames,
+            modifiers: egui::Modifiers::NONE,
+        });
+        self.request_paced_redraw(elwt);
+        wake_up_before(elwt, now + SCROLL_INERTIA_FRAME);
+    }
+
+    /// Velocity of a `WindowEvent::MouseWheel`, from the interval since the previous wheel event
+    fn update_scroll_velocity(&mut self, delta: winit::event::MouseScrollDelta) {
+        let now = Instant::now();
+        let modifiers = self.egui_winit.egui_input().modifiers;
+        if !(0.0..1.0).contains(&self.config.scroll_inertia_friction)
+            || modifiers.ctrl
+            || modifiers.command
+        {
+            // Zooms instead of scrolling
+            self.scroll_velocity = None;
+            return;
+        }
+        let delta = match delta {
+            winit::event::MouseScrollDelta::LineDelta(x, y) => {
+                let line_scroll_speed = self
+                    .egui_context
+                    .options(|options| options.input_options.line_scroll_speed);
+                Vec2::new(x, y) * line_scroll_speed
+            }
+            winit::event::MouseScrollDelta::PixelDelta(delta) => {
+                Vec2::new(delta.x as f32, delta.y as f32) / self.egui_context.pixels_per_point()
+            }
+        };
+        // A lone mouse wheel notch after a pause doesn't coast, a touchpad swipe sends an event per frame
+        let velocity = self
+            .scroll_velocity
+            .as_ref()
+            .map(|velocity| now - velocity.last_update)
+            .filter(|elapsed| *elapsed <= MAX_SCROLL_EVENT_INTERVAL)
+            .map_or(Vec2::ZERO, |elapsed| {
+                let elapsed = elapsed.max(Duration::from_millis(1));
+                delta * (SCROLL_INERTIA_FRAME.as_secs_f32() / elapsed.as_secs_f32())
+            });
+        self.scroll_velocity = Some(ScrollVelocity {
+            vx: velocity.x,
+            vy: velocity.y,
+            last_update: now,
+        });
+    }
+
     pub(crate) fn handle_event(
         &mut self,
         event: Event<()>,
@@ -436,7 +627,21 @@ impl<EguiApp: App, EguiAppFactory: FnMut(Context) -> EguiApp>
     ) -> Result<(), SoftwareBackendAppError> {
         let start = Instant::now();
 
-        elwt.set_control_flow(ControlFlow::Wait);
+        match self.paced_redraw {
+            Some(when) if when <= start => {
+                self.paced_redraw = None;
+                self.request_redraw();
+                elwt.set_control_flow(ControlFlow::Wait);
+            }
+            Some(when) => elwt.set_control_flow(ControlFlow::WaitUntil(when)),
+            None => elwt.set_control_flow(ControlFlow::Wait),
+        }
+
+        if matches!(event, Event::AboutToWait) {
+            #[cfg(feature = "gamepad")]
+            self.poll_gamepad(elwt);
+            self.coast_scroll(elwt);
+        }
 
         let Event::WindowEvent {
             window_id,
@@ -452,6 +657,24 @@ impl<EguiApp: App, EguiAppFactory: FnMut(Context) -> EguiApp>
 
         match window_event {
             WindowEvent::RedrawRequested => {
+                #[cfg(feature = "puffin")]
+                puffin::GlobalProfiler::lock().new_frame();
+
+                let measured_interval = self
+                    .last_redraw
+                    .map(|last_redraw| start - last_redraw)
+                    .filter(|interval| *interval <= MAX_FRAME_INTERVAL);
+                let refresh_interval = self
+                    .window
+                    .current_monitor()
+                    .and_then(|monitor| monitor.refresh_rate_millihertz())
+                    .map(|millihertz| Duration::from_secs_f64(1000.0 / f64::from(millihertz)));
+                self.frame_interval = refresh_interval
+                    .or(measured_interval)
+                    .or(self.frame_interval);
+                self.last_redraw = Some(start);
+                self.paced_redraw = None;
+
                 let size = self.window.inner_size();
                 let width = NonZeroU32::new(size.width).unwrap_or(ONE_PIXEL);
                 let height = NonZeroU32::new(size.height).unwrap_or(ONE_PIXEL);
@@ -473,6 +696,8 @@ impl<EguiApp: App, EguiAppFactory: FnMut(Context) -> EguiApp>
                         &mut SoftwareBackend {
                             last_frame_time: self.last_frame_time,
                             renderer: &mut self.renderer,
+                            screenshot_requested: &mut self.screenshot_requested,
+                            stylus: self.stylus,
                         },
                     );
 
@@ -652,8 +877,8 @@ impl<EguiApp: App, EguiAppFactory: FnMut(Context) -> EguiApp>
                                 ViewportCommand::MousePassthrough(_) => {
                                     //UNSUPPORTED
                                 }
-                                ViewportCommand::Screenshot(_) => {
-                                    //UNSUPPORTED (YET)
+                                ViewportCommand::Screenshot(user_data) => {
+                                    self.screenshot_commands.push(user_data.clone());
                                 }
                                 ViewportCommand::BeginResize(_) => {
                                     //UNSUPPORTED
@@ -661,11 +886,15 @@ impl<EguiApp: App, EguiAppFactory: FnMut(Context) -> EguiApp>
                                 ViewportCommand::IMERect(_) => {
                                     //UNSUPPORTED
                                 }
-                                ViewportCommand::IMEAllowed(_) => {
-                                    //UNSUPPORTED
+                                ViewportCommand::IMEAllowed(allowed) => {
+                                    self.window.set_ime_allowed(*allowed);
                                 }
-                                ViewportCommand::IMEPurpose(_) => {
-                                    //UNSUPPORTED
+                                ViewportCommand::IMEPurpose(purpose) => {
+                                    self.window.set_ime_purpose(match purpose {
+                                        IMEPurpose::Normal => ImePurpose::Normal,
+                                        IMEPurpose::Password => ImePurpose::Password,
+                                        IMEPurpose::Terminal => ImePurpose::Terminal,
+                                    });
                                 }
                                 ViewportCommand::CursorPosition(_) => {
                                     //UNSUPPORTED
@@ -674,7 +903,17 @@ impl<EguiApp: App, EguiAppFactory: FnMut(Context) -> EguiApp>
                                     //UNSUPPORTED
                                 }
                                 ViewportCommand::StartDrag => {
-                                    //UNSUPPORTED
+                                    // The compositors only start a drag while the button is held
+                                    if mem::take(&mut self.left_mouse_down) {
+                                        // The release ends the drag, the window may never receive it
+                                        match self.window.drag_window() {
+                                            Ok(()) => {}
+                                            Err(_error) => {
+                                                #[cfg(feature = "log")]
+                                                log::warn!("drag_window: {_error}");
+                                            }
+                                        }
+                                    }
                                 }
                             }
 
@@ -706,19 +945,69 @@ impl<EguiApp: App, EguiAppFactory: FnMut(Context) -> EguiApp>
                     width.get(),
                     height.get(),
                 );
-                let redraw_everything_this_frame =
-                    self.renderer.cached_size() != (buffer_ref.width, buffer_ref.height);
-                let dirty_rect = self.renderer.render(
-                    buffer_ref,
-                    redraw_everything_this_frame,
-                    clipped_primitives,
-                    &full_output.textures_delta,
-                    full_output.pixels_per_point,
-                );
+                let dirty_rect = if self.renderer.debug_overlay().is_empty() {
+                    let redraw_everything_this_frame = !self.debug_overlay_canvas.is_empty()
+                        || self.renderer.cached_size() != (buffer_ref.width, buffer_ref.height);
+                    self.debug_overlay_canvas = Vec::new();
+                    self.renderer.render(
+                        buffer_ref,
+                        redraw_everything_this_frame,
+                        clipped_primitives,
+                        &full_output.textures_delta,
+                        full_output.pixels_per_point,
+                    )
+                } else {
+                    // Render to a separate canvas so the overlays are not cached
+                    let len = buffer_ref.data.len();
+                    let redraw_everything_this_frame = self.debug_overlay_canvas.len() != len
+                        || self.renderer.cached_size() != (buffer_ref.width, buffer_ref.height);
+                    if redraw_everything_this_frame {
+                        self.debug_overlay_canvas.clear();
+                        self.debug_overlay_canvas.resize(len, [0; 4]);
+                    }
+                    let canvas = &mut BufferMutRef::new(
+                        &mut self.debug_overlay_canvas,
+                        buffer_ref.width,
+                        buffer_ref.height,
+                    );
+                    self.renderer.render(
+                        canvas,
+                        redraw_everything_this_frame,
+                        clipped_primitives,
+                        &full_output.textures_delta,
+                        full_output.pixels_per_point,
+                    );
+                    buffer_ref.data.copy_from_slice(canvas.data);
+                    self.renderer.draw_debug_overlay(buffer_ref);
+                    DirtyRect {
+                        min_x: 0,
+                        min_y: 0,
+                        max_x: buffer_ref.width,
+                        max_y: buffer_ref.height,
+                    }
+                };
+
+                if mem::take(&mut self.screenshot_requested) {
+                    self.pending_screenshot = Some(screenshot(buffer_ref));
+                }
+                if !self.screenshot_commands.is_empty() {
+                    let image = Arc::new(screenshot_color_image(buffer_ref));
+                    for user_data in self.screenshot_commands.drain(..) {
+                        self.input_events.push(egui::Event::Screenshot {
+                            viewport_id: egui::ViewportId::ROOT,
+                            user_data,
+                            image: image.clone(),
+                        });
+                    }
+                    // Delivered by the next frame
+                    self.window.request_redraw();
+                }
 
                 #[cfg(feature = "raster_stats")]
                 let present_start = std::time::Instant::now();
                 if !dirty_rect.is_empty() {
+                    #[cfg(feature = "puffin")]
+                    puffin::profile_scope!("winit_present");
                     let dirty_rect = softbuffer::Rect {
                         x: dirty_rect.min_x,
                         y: dirty_rect.min_y,
@@ -734,6 +1023,10 @@ impl<EguiApp: App, EguiAppFactory: FnMut(Context) -> EguiApp>
                     self.renderer.stats().winit_present.mark(present_start);
                 }
 
+                if let Some(image) = self.pending_screenshot.take() {
+                    self.egui_app.on_screenshot(image);
+                }
+
                 self.last_frame_time = Some(start.elapsed());
             }
 
@@ -742,13 +1035,37 @@ impl<EguiApp: App, EguiAppFactory: FnMut(Context) -> EguiApp>
                 elwt.exit();
             }
             _ => {
+                // egui_winit already turns the touches into `egui::Event::Touch`, with their force
+                if self.config.enable_stylus
+                    && let WindowEvent::Touch(touch) = &window_event
+                {
+                    self.stylus = StylusState::from_touch(touch);
+                }
+                if let WindowEvent::MouseInput {
+                    state,
+                    button: winit::event::MouseButton::Left,
+                    ..
+                } = &window_event
+                {
+                    self.left_mouse_down = state.is_pressed();
+                }
+                match &window_event {
+                    WindowEvent::MouseWheel { delta, .. } => self.update_scroll_velocity(*delta),
+                    // Grabbing the content stops it
+                    WindowEvent::MouseInput {
+                        state: winit::event::ElementState::Pressed,
+                        ..
+                    }
+                    | WindowEvent::Touch(_) => self.scroll_velocity = None,
+                    _ => {}
+                }
                 let response = self
                     .egui_winit
                     .on_window_event(self.window.deref(), &window_event);
 
                 if response.repaint {
                     // Redraw when egui says it's necessary (e.g., mouse move, key press):
-                    self.window.request_redraw();
+                    self.request_paced_redraw(elwt);
                 }
             }
         };
@@ -781,8 +1098,41 @@ impl<EguiApp: App, EguiAppFactory: FnMut(Context) -> EguiApp>
 ///
 /// ```
 pub struct SoftwareBackend<'a> {
-    last_frame_time: Option<Duration>,
-    renderer: &'a mut EguiSoftwareRender,
+    pub(crate) last_frame_time: Option<Duration>,
+    pub(crate) renderer: &'a mut EguiSoftwareRender,
+    pub(crate) screenshot_requested: &'a mut bool,
+    pub(crate) stylus: Option<StylusState>,
+}
+
+/// Pressure of the stylus touching the window, see [`SoftwareBackend::stylus`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StylusState {
+    /// From 0.0 to 1.0, the maximum force the device reports
+    pub pressure: f32,
+
+    /// Angle in radians between the stylus and the surface, from 0 (parallel) to Pi/2 (perpendicular). Only
+    ///   reported by iOS, winit has no tilt direction.
+    pub altitude_angle: Option<f32>,
+}
+
+impl StylusState {
+    /// `None` once the touch ends, or for the touches without force, e.g. of fingers on most touchscreens
+    fn from_touch(touch: &winit::event::Touch) -> Option<Self> {
+        use winit::event::{Force, TouchPhase};
+        if matches!(touch.phase, TouchPhase::Ended | TouchPhase::Cancelled) {
+            return None;
+        }
+        let force = touch.force?;
+        Some(Self {
+            pressure: force.normalized() as f32,
+            altitude_angle: match force {
+                Force::Calibrated { altitude_angle, .. } => {
+                    altitude_angle.map(|angle| angle as f32)
+                }
+                Force::Normalized(_) => None,
+            },
+        })
+    }
 }
 
 impl<'a> SoftwareBackend<'a> {
@@ -797,6 +1147,33 @@ impl<'a> SoftwareBackend<'a> {
         self.renderer.stats()
     }
 
+    /// Cache hits, misses and evictions of the last frame
+    #[cfg(feature = "cache_stats")]
+    pub fn cache_stats(&self) -> &CacheStats {
+        self.renderer.cache_stats()
+    }
+
+    /// Tris and rects drawn by the last frame, `None` without the `raster_stats` feature
+    pub fn primitives_this_frame(&self) -> Option<(u32, u32)> {
+        #[cfg(feature = "raster_stats")]
+        {
+            let raster = self.renderer.stats().raster.lock();
+            Some((raster.tris, raster.rects))
+        }
+        #[cfg(not(feature = "raster_stats"))]
+        None
+    }
+
+    /// Ratio of primitives reused from the cache by the last frame, `None` without the `cache_stats` feature
+    pub fn cache_hit_rate(&self) -> Option<f32> {
+        #[cfg(feature = "cache_stats")]
+        {
+            Some(self.renderer.cache_stats().cache_hit_rate())
+        }
+        #[cfg(not(feature = "cache_stats"))]
+        None
+    }
+
     /// Get the caching mode of the renderer
     pub fn caching(&self) -> SoftwareRenderCaching {
         self.renderer.caching()
@@ -807,18 +1184,144 @@ impl<'a> SoftwareBackend<'a> {
         self.renderer.set_caching(caching);
     }
 
+    /// Are triangle pairs converted to rects, see [`EguiSoftwareRender::with_convert_tris_to_rects`]
+    pub fn convert_tris_to_rects(&self) -> bool {
+        self.renderer.convert_tris_to_rects()
+    }
+
+    /// Change the conversion of triangle pairs to rects, everything is rasterized again
+    pub fn set_convert_tris_to_rects(&mut self, set: bool) {
+        self.renderer.set_convert_tris_to_rects(set);
+    }
+
+    /// Are the rasterization optimizations allowed, see [`EguiSoftwareRender::with_allow_raster_opt`]
+    pub fn allow_raster_opt(&self) -> bool {
+        self.renderer.allow_raster_opt()
+    }
+
+    /// Change the rasterization optimizations, everything is rasterized again
+    pub fn set_allow_raster_opt(&mut self, set: bool) {
+        self.renderer.set_allow_raster_opt(set);
+    }
+
+    /// Mode selected by the first frame with `SoftwareRenderCaching::Auto`
+    pub fn detected_caching_mode(&self) -> Option<SoftwareRenderCaching> {
+        self.renderer.detected_caching_mode()
+    }
+
+    /// Get the enabled debug overlays
+    pub fn debug_overlay(&self) -> DebugOverlay {
+        self.renderer.debug_overlay()
+    }
+
+    /// Change the enabled debug overlays
+    ///
+    /// While any overlay is enabled, frames are rendered to an intermediate canvas so the overlays don't end up in
+    /// the cached pixels of the window buffer.
+    pub fn set_debug_overlay(&mut self, debug_overlay: DebugOverlay) {
+        self.renderer.set_debug_overlay(debug_overlay);
+    }
+
+    /// Color vision deficiency simulated on the output, see [`EguiSoftwareRender::with_color_blind_mode`]
+    pub fn color_blind_mode(&self) -> ColorBlindMode {
+        self.renderer.color_blind_mode()
+    }
+
+    /// Simulate a color vision deficiency on the output, e.g. to check the contrast of a UI
+    pub fn set_color_blind_mode(&mut self, mode: ColorBlindMode) {
+        self.renderer.set_color_blind_mode(mode);
+    }
+
+    /// Dithering of the output, see [`EguiSoftwareRender::with_dithering`]
+    pub fn dithering(&self) -> DitherMode {
+        self.renderer.dithering()
+    }
+
+    /// Quantize the output, e.g. to preview a 4 bits display
+    pub fn set_dithering(&mut self, mode: DitherMode) {
+        self.renderer.set_dithering(mode);
+    }
+
+    /// Is the output desaturated, see [`EguiSoftwareRender::with_grayscale`]
+    pub fn grayscale(&self) -> bool {
+        self.renderer.grayscale()
+    }
+
+    /// Desaturate the output, e.g. for accessibility or for e-ink displays
+    pub fn set_grayscale(&mut self, grayscale: bool) {
+        self.renderer.set_grayscale(grayscale);
+    }
+
+    /// Bytes currently allocated by the textures and each cache, see [`EguiSoftwareRender::memory_stats`]
+    pub fn memory_stats(&self) -> MemoryStats {
+        self.renderer.memory_stats()
+    }
+
+    /// Estimate of the bytes allocated by the render caches, see [`EguiSoftwareRender::cache_memory_usage`]
+    pub fn cache_memory_usage(&self) -> usize {
+        self.renderer.cache_memory_usage()
+    }
+
+    /// Number of cached primitives, see [`EguiSoftwareRender::primitive_count`]
+    pub fn primitive_count(&self) -> usize {
+        self.renderer.primitive_count()
+    }
+
+    /// Number of loaded textures, see [`EguiSoftwareRender::texture_count`]
+    pub fn texture_count(&self) -> usize {
+        self.renderer.texture_count()
+    }
+
     /// Clear cache and reclaim memory
     ///
     /// This will cause the next frame to redraw everything
     pub fn clear_cache(&mut self) {
         self.renderer.clear_cache();
     }
+
+    /// Redraw everything on the next frame, keeping the cache, see [`EguiSoftwareRender::force_redraw_next_frame`]
+    pub fn force_redraw_next_frame(&mut self) {
+        self.renderer.force_redraw_next_frame();
+    }
+
+    /// Capture the window content once this frame is rendered, it is passed to [`App::on_screenshot`]
+    pub fn request_screenshot(&mut self) {
+        *self.screenshot_requested = true;
+    }
+
+    /// Stylus touching the window, `None` without [`SoftwareBackendAppConfiguration::enable_stylus`]
+    ///
+    /// egui gets the same force in the `egui::Event::Touch` events of its input.
+    pub fn stylus(&self) -> Option<StylusState> {
+        self.stylus
+    }
+}
+
+/// Copy of the window buffer, which is `Bgra` with the alpha ignored by softbuffer
+pub(crate) fn screenshot(buffer: &BufferMutRef) -> image::RgbaImage {
+    let pixels = buffer
+        .data
+        .iter()
+        .flat_map(|&[b, g, r, _]| [r, g, b, 255])
+        .collect();
+    image::RgbaImage::from_raw(buffer.width, buffer.height, pixels).expect("size matches buffer")
+}
+
+/// `screenshot()` for `egui::Event::Screenshot`
+fn screenshot_color_image(buffer: &BufferMutRef) -> egui::ColorImage {
+    let pixels = (buffer.data.iter())
+        .map(|&[b, g, r, _]| egui::Color32::from_rgb(r, g, b))
+        .collect();
+    egui::ColorImage::new([buffer.width as usize, buffer.height as usize], pixels)
 }
 
 pub trait App {
     fn update(&mut self, ctx: &Context, software_backend: &mut SoftwareBackend);
 
     fn on_exit(&mut self, _ctx: &Context) {}
+
+    /// Receives the window content after a [`SoftwareBackend::request_screenshot`]
+    fn on_screenshot(&mut self, _image: image::RgbaImage) {}
 }
 
 #[derive(Debug, Clone)]
@@ -842,6 +1345,55 @@ pub struct SoftwareBackendAppConfiguration {
     ///
     /// Default is [`SoftwareRenderCaching::BlendTiled`]!
     pub caching: SoftwareRenderCaching,
+
+    /// Color temperature in kelvin the output is adjusted to, see [`EguiSoftwareRender::with_color_temperature`]
+    ///
+    /// Default is 6500.0, the colors are unchanged!
+    pub color_temperature: f32,
+
+    /// Megabytes the cached primitives may take, see [`EguiSoftwareRender::with_max_cache_memory_mb`]
+    ///
+    /// Default is `None`, unlimited!
+    pub max_cache_memory_mb: Option<f32>,
+
+    /// Number of cached primitives kept, see [`EguiSoftwareRender::with_max_cache_entries`]
+    ///
+    /// Default is `None`, unlimited!
+    pub max_cache_entries: Option<usize>,
+
+    /// Frames a cached primitive is kept without being painted, see [`EguiSoftwareRender::with_max_idle_frames`]
+    ///
+    /// Default is `None`, for the renderer default of 0!
+    pub max_idle_frames: Option<u32>,
+
+    /// DRM device opened by `run_app_with_drm_backend()`
+    ///
+    /// Default is `None`, for [`DEFAULT_DRM_DEVICE`](crate::DEFAULT_DRM_DEVICE)!
+    #[cfg(feature = "drm")]
+    pub drm_device: Option<std::path::PathBuf>,
+
+    /// Track the pressure of the stylus, see [`SoftwareBackend::stylus`]
+    ///
+    /// Default is false!
+    pub enable_stylus: bool,
+
+    /// Factor the inertia scroll velocity is multiplied by every 16ms, see
+    /// [`SoftwareBackendAppConfiguration::scroll_inertia_friction`]
+    ///
+    /// Default is 0.92!
+    pub scroll_inertia_friction: f32,
+
+    /// Navigate the app with a gamepad, see [`SoftwareBackendAppConfiguration::gamepad_navigation`]
+    ///
+    /// Default is false!
+    #[cfg(feature = "gamepad")]
+    pub gamepad_navigation: bool,
+
+    /// Keys pressed by the gamepad buttons
+    ///
+    /// Default is `None`, for [`DEFAULT_GAMEPAD_KEY_MAP`](crate::DEFAULT_GAMEPAD_KEY_MAP)!
+    #[cfg(feature = "gamepad")]
+    pub gamepad_key_map: Option<Vec<(crate::GamepadButton, egui::Key)>>,
 }
 
 impl SoftwareBackendAppConfiguration {
@@ -887,6 +1439,18 @@ impl SoftwareBackendAppConfiguration {
             allow_raster_opt: true,
             convert_tris_to_rects: true,
             caching: SoftwareRenderCaching::BlendTiled,
+            color_temperature: 6500.0,
+            max_cache_memory_mb: None,
+            max_cache_entries: None,
+            max_idle_frames: None,
+            #[cfg(feature = "drm")]
+            drm_device: None,
+            enable_stylus: false,
+            scroll_inertia_friction: 0.92,
+            #[cfg(feature = "gamepad")]
+            gamepad_navigation: false,
+            #[cfg(feature = "gamepad")]
+            gamepad_key_map: None,
         }
     }
 
@@ -1076,6 +1640,52 @@ impl SoftwareBackendAppConfiguration {
         self
     }
 
+    /// X11: A dock or panel, usually kept on top of all other windows. Ignored on other platforms.
+    pub const fn dock(self) -> Self {
+        self.window_type(Some(X11WindowType::Dock))
+    }
+
+    /// X11: A toolbar "torn off" from the main window. Ignored on other platforms.
+    pub const fn toolbar(self) -> Self {
+        self.window_type(Some(X11WindowType::Toolbar))
+    }
+
+    /// X11: A small persistent utility window, like a palette or toolbox. Ignored on other platforms.
+    pub const fn utility(self) -> Self {
+        self.window_type(Some(X11WindowType::Utility))
+    }
+
+    /// X11: A dialog window. Ignored on other platforms.
+    pub const fn dialog(self) -> Self {
+        self.window_type(Some(X11WindowType::Dialog))
+    }
+
+    /// X11: A splash screen shown while the application starts. Ignored on other platforms.
+    pub const fn splash(self) -> Self {
+        self.window_type(Some(X11WindowType::Splash))
+    }
+
+    /// X11: A tooltip, usually combined with `decorations(Some(false))`. Ignored on other platforms.
+    pub const fn tooltip(self) -> Self {
+        self.window_type(Some(X11WindowType::Tooltip))
+    }
+
+    /// X11: A notification, usually combined with `decorations(Some(false))`. Ignored on other platforms.
+    pub const fn notification(self) -> Self {
+        self.window_type(Some(X11WindowType::Notification))
+    }
+
+    /// Hide the window from the taskbar, sets `ViewportBuilder::taskbar` to `Some(!skip)`
+    ///
+    /// Only applied on Windows, where winit removes the taskbar button of the window (`ITaskbarList::DeleteTab`),
+    /// unlike the `WS_EX_TOOLWINDOW` style which also changes its title bar. egui-winit ignores it on X11: set a
+    /// window type the window manager keeps out of the taskbar instead, like `utility()` or `dock()`, as the
+    /// `_NET_WM_STATE_SKIP_TASKBAR` state is not exposed.
+    pub const fn skip_taskbar(mut self, skip: bool) -> Self {
+        self.viewport_builder.taskbar = Some(!skip);
+        self
+    }
+
     /// If false: Rasterize everything with triangles, always calculate vertex colors, uvs, use bilinear
     ///   everywhere, etc... Things *should* look the same with this set to `true` while rendering faster.
     ///
@@ -1100,6 +1710,91 @@ impl SoftwareBackendAppConfiguration {
         self.caching = caching;
         self
     }
+
+    /// Color temperature in kelvin the output is adjusted to, e.g. 3400.0 for a night mode
+    ///
+    /// Default is 6500.0, the colors are unchanged!
+    pub const fn color_temperature(mut self, color_temperature: f32) -> Self {
+        self.color_temperature = color_temperature;
+        self
+    }
+
+    /// Megabytes the cached primitives may take before the least recently painted are evicted
+    ///
+    /// Default is `None`, unlimited!
+    pub const fn max_cache_memory_mb(mut self, max_cache_memory_mb: Option<f32>) -> Self {
+        self.max_cache_memory_mb = max_cache_memory_mb;
+        self
+    }
+
+    /// Number of cached primitives kept before the least recently painted are evicted
+    ///
+    /// Default is `None`, unlimited!
+    pub const fn max_cache_entries(mut self, max_cache_entries: Option<usize>) -> Self {
+        self.max_cache_entries = max_cache_entries;
+        self
+    }
+
+    /// Frames a cached primitive is kept without being painted, e.g. for content that is hidden and shown again
+    ///
+    /// Default is `None`, for the renderer default of 0!
+    pub const fn max_idle_frames(mut self, max_idle_frames: Option<u32>) -> Self {
+        self.max_idle_frames = max_idle_frames;
+        self
+    }
+
+    /// DRM device opened by `run_app_with_drm_backend()`
+    ///
+    /// Default is `None`, for [`DEFAULT_DRM_DEVICE`](crate::DEFAULT_DRM_DEVICE)!
+    #[cfg(feature = "drm")]
+    pub fn drm_device(mut self, drm_device: Option<std::path::PathBuf>) -> Self {
+        self.drm_device = drm_device;
+        self
+    }
+
+    /// If true: the pressure of the stylus touching the window is given to the app by [`SoftwareBackend::stylus`],
+    ///   e.g. for drawing or handwriting apps on tablets. Only read by `run_app_with_software_backend()`.
+    ///
+    /// Default is false!
+    pub const fn enable_stylus(mut self, enable_stylus: bool) -> Self {
+        self.enable_stylus = enable_stylus;
+        self
+    }
+
+    /// Scrolling continues after the last mouse wheel or touchpad scroll event, slowed down by multiplying its
+    ///   velocity by `scroll_inertia_friction` every 16ms until it falls under 0.5 points per 16ms. Clicks and
+    ///   touches stop it. Values closer to 1.0 scroll for longer, 0.0 or values outside of `0.0..1.0` disable the
+    ///   inertia. Only read by `run_app_with_software_backend()`.
+    ///
+    /// Default is 0.92!
+    pub const fn scroll_inertia_friction(mut self, scroll_inertia_friction: f32) -> Self {
+        self.scroll_inertia_friction = scroll_inertia_friction;
+        self
+    }
+
+    /// If true: the gamepad buttons press the keys of `gamepad_key_map()`, for the keyboard navigation of egui, the
+    ///   left stick moves the pointer, and the left and right triggers scroll up and down. The gamepads are polled
+    ///   every 16ms, for kiosk apps on hardware with only a gamepad for input. Only read by
+    ///   `run_app_with_software_backend()`.
+    ///
+    /// Default is false!
+    #[cfg(feature = "gamepad")]
+    pub const fn gamepad_navigation(mut self, gamepad_navigation: bool) -> Self {
+        self.gamepad_navigation = gamepad_navigation;
+        self
+    }
+
+    /// Keys pressed by the gamepad buttons, a button can press several keys
+    ///
+    /// Default is `None`, for [`DEFAULT_GAMEPAD_KEY_MAP`](crate::DEFAULT_GAMEPAD_KEY_MAP)!
+    #[cfg(feature = "gamepad")]
+    pub fn gamepad_key_map(
+        mut self,
+        gamepad_key_map: Option<Vec<(crate::GamepadButton, egui::Key)>>,
+    ) -> Self {
+        self.gamepad_key_map = gamepad_key_map;
+        self
+    }
 }
 
 impl Default for SoftwareBackendAppConfiguration {
@@ -1114,10 +1809,34 @@ pub fn run_app_with_software_backend<T: App>(
     settings: SoftwareBackendAppConfiguration,
     egui_app_factory: impl FnMut(Context) -> T,
 ) -> Result<(), SoftwareBackendAppError> {
+    run_app(settings, egui_app_factory).map(|_| ())
+}
+
+/// Like `run_app_with_software_backend()`, and returns what `extractor` takes out of the `App` once the window is
+/// closed, e.g. the settings the user edited
+///
+/// `Ok(None)` if the event loop exits while suspended, the `App` is dropped on suspend and created again on resume.
+pub fn run_app_with_software_backend_and_return<T: App, R: 'static>(
+    settings: SoftwareBackendAppConfiguration,
+    egui_app_factory: impl FnMut(Context) -> T,
+    extractor: impl FnOnce(T) -> R,
+) -> Result<Option<R>, SoftwareBackendAppError> {
+    run_app(settings, egui_app_factory).map(|egui_app| egui_app.map(extractor))
+}
+
+/// Runs the event loop until the window is closed, and returns the `App` if it was still running
+fn run_app<T: App>(
+    settings: SoftwareBackendAppConfiguration,
+    egui_app_factory: impl FnMut(Context) -> T,
+) -> Result<Option<T>, SoftwareBackendAppError> {
     let egui_software_render = EguiSoftwareRender::new(ColorFieldOrder::Bgra)
         .with_allow_raster_opt(settings.allow_raster_opt)
         .with_convert_tris_to_rects(settings.convert_tris_to_rects)
-        .with_mode(settings.caching);
+        .with_mode(settings.caching)
+        .with_color_temperature(settings.color_temperature)
+        .with_max_cache_memory_mb(settings.max_cache_memory_mb)
+        .with_max_cache_entries(settings.max_cache_entries)
+        .with_max_idle_frames(settings.max_idle_frames.unwrap_or_default());
 
     let event_loop: EventLoop<UserEvent> = EventLoop::with_user_event()
         .build()
@@ -1163,9 +1882,10 @@ pub fn run_app_with_software_backend<T: App>(
         )));
     }
 
-    if let WinitAppStateMachine::Dead(Some(app_err)) = app {
-        return Err(app_err);
+    match app {
+        WinitAppStateMachine::Dead(Some(app_err)) => Err(app_err),
+        // The other fields are dropped in their declaration order
+        WinitAppStateMachine::Running(RunningEguiAppState { egui_app, .. }) => Ok(Some(egui_app)),
+        _ => Ok(None),
     }
-
-    Ok(())
 }
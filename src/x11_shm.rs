@@ -0,0 +1,258 @@
+#![allow(unsafe_code)]
+use crate::{BufferMutRef, DirtyRect};
+use std::boxed::Box;
+use std::env;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io;
+use x11rb::connection::Connection;
+use x11rb::protocol::shm::ConnectionExt as _;
+use x11rb::protocol::xproto::{ConnectionExt as _, CreateGCAux, Gcontext, ImageFormat, ImageOrder};
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+
+/// Errors that can occur when presenting with `X11ShmSurface`.
+#[derive(Debug)]
+pub enum X11ShmError {
+    /// The `DISPLAY` environment variable is not set.
+    NoDisplay,
+
+    /// The X server doesn't support MIT-SHM, or the window isn't a 24 bits BGRX visual.
+    Unsupported(&'static str),
+
+    /// An X11 request has failed.
+    X11 {
+        x11_error: Box<dyn Error>,
+        function: &'static str,
+    },
+
+    /// Creating or attaching the System V shared memory segment has failed.
+    Io {
+        io_error: io::Error,
+        function: &'static str,
+    },
+}
+
+impl Display for X11ShmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            X11ShmError::NoDisplay => f.write_str("DISPLAY is not set"),
+            X11ShmError::Unsupported(reason) => {
+                f.write_str("MIT-SHM unsupported: ")?;
+                f.write_str(reason)
+            }
+            X11ShmError::X11 { function, .. } | X11ShmError::Io { function, .. } => {
+                f.write_str("error calling ")?;
+                f.write_str(function)
+            }
+        }
+    }
+}
+
+impl Error for X11ShmError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            X11ShmError::X11 { x11_error, .. } => Some(x11_error.as_ref()),
+            X11ShmError::Io { io_error, .. } => Some(io_error),
+            _ => None,
+        }
+    }
+}
+
+impl X11ShmError {
+    fn x11<E: Error + 'static>(function: &'static str) -> impl FnOnce(E) -> X11ShmError {
+        move |error| Self::X11 {
+            x11_error: Box::new(error),
+            function,
+        }
+    }
+
+    fn last_os_error(function: &'static str) -> X11ShmError {
+        Self::Io {
+            io_error: io::Error::last_os_error(),
+            function,
+        }
+    }
+}
+
+/// System V shared memory segment, attached by this process and the X server
+struct Segment {
+    shmseg: u32,
+    ptr: *mut [u8; 4],
+    width: u32,
+    height: u32,
+}
+
+/// Presents to an X11 window through a MIT-SHM shared memory segment
+///
+/// `buffer_mut()` is the memory the X server reads from, so `present()` only sends a `ShmPutImage` request of the
+/// dirty rect, without copying the pixels. The buffer is `ColorFieldOrder::Bgra`, and keeps its content between
+/// frames, so it can be rendered with a cached `EguiSoftwareRender` like a softbuffer buffer.
+///
+/// For apps driving X11 windows without softbuffer, which already uses MIT-SHM.
+pub struct X11ShmSurface {
+    conn: RustConnection,
+    window: u32,
+    gc: Gcontext,
+    depth: u8,
+    segment: Option<Segment>,
+}
+
+impl X11ShmSurface {
+    /// Connects to `$DISPLAY` and checks that MIT-SHM can present to `window`, an X11 window id
+    pub fn new(window: u32) -> Result<Self, X11ShmError> {
+        if env::var_os("DISPLAY").is_none() {
+            return Err(X11ShmError::NoDisplay);
+        }
+        let (conn, _screen) =
+            RustConnection::connect(None).map_err(X11ShmError::x11("RustConnection::connect"))?;
+        let shm_available = conn
+            .shm_query_version()
+            .is_ok_and(|cookie| cookie.reply().is_ok());
+        if !shm_available {
+            return Err(X11ShmError::Unsupported("no MIT-SHM extension"));
+        }
+        if conn.setup().image_byte_order != ImageOrder::LSB_FIRST {
+            return Err(X11ShmError::Unsupported("big endian image byte order"));
+        }
+        let geometry = conn
+            .get_geometry(window)
+            .map_err(X11ShmError::x11("get_geometry"))?
+            .reply()
+            .map_err(X11ShmError::x11("get_geometry"))?;
+        let zpixmap_32 = conn
+            .setup()
+            .pixmap_formats
+            .iter()
+            .any(|format| format.depth == geometry.depth && format.bits_per_pixel == 32);
+        if !matches!(geometry.depth, 24 | 32) || !zpixmap_32 {
+            return Err(X11ShmError::Unsupported("window is not 32 bits per pixel"));
+        }
+
+        let gc = conn
+            .generate_id()
+            .map_err(X11ShmError::x11("generate_id"))?;
+        conn.create_gc(gc, window, &CreateGCAux::new())
+            .map_err(X11ShmError::x11("create_gc"))?;
+        Ok(Self {
+            conn,
+            window,
+            gc,
+            depth: geometry.depth,
+            segment: None,
+        })
+    }
+
+    /// `width` x `height` shared buffer, cleared when the size changes
+    pub fn buffer_mut(&mut self, width: u32, height: u32) -> Result<BufferMutRef<'_>, X11ShmError> {
+        if self
+            .segment
+            .as_ref()
+            .is_none_or(|segment| (segment.width, segment.height) != (width, height))
+        {
+            self.detach()?;
+            self.segment = Some(self.attach(width, height)?);
+        }
+        let segment = self.segment.as_ref().expect("attached");
+        // SAFETY: The segment is `width * height` pixels, and the X server only reads it during `present()`
+        let data = unsafe {
+            core::slice::from_raw_parts_mut(segment.ptr, (segment.width * segment.height) as usize)
+        };
+        Ok(BufferMutRef::new(data, width, height))
+    }
+
+    /// Draws `dirty_rect` of the buffer on the window, and waits for the X server to have read it
+    pub fn present(&mut self, dirty_rect: DirtyRect) -> Result<(), X11ShmError> {
+        let Some(segment) = &self.segment else {
+            return Ok(());
+        };
+        if dirty_rect.is_empty() {
+            return Ok(());
+        }
+        self.conn
+            .shm_put_image(
+                self.window,
+                self.gc,
+                segment.width as u16,
+                segment.height as u16,
+                dirty_rect.min_x as u16,
+                dirty_rect.min_y as u16,
+                dirty_rect.width() as u16,
+                dirty_rect.height() as u16,
+                dirty_rect.min_x as i16,
+                dirty_rect.min_y as i16,
+                self.depth,
+                ImageFormat::Z_PIXMAP.into(),
+                false,
+                segment.shmseg,
+                0,
+            )
+            .map_err(X11ShmError::x11("shm_put_image"))?;
+        // The next frame can write to the buffer once the server processed the put
+        self.conn.sync().map_err(X11ShmError::x11("sync"))?;
+        Ok(())
+    }
+
+    fn attach(&self, width: u32, height: u32) -> Result<Segment, X11ShmError> {
+        let len = (width.max(1) * height.max(1)) as usize * 4;
+        // SAFETY: Plain System V calls, the segment is marked for deletion once the X server attached it
+        unsafe {
+            let shmid = libc::shmget(libc::IPC_PRIVATE, len, libc::IPC_CREAT | 0o600);
+            if shmid < 0 {
+                return Err(X11ShmError::last_os_error("shmget"));
+            }
+            let ptr = libc::shmat(shmid, core::ptr::null(), 0);
+            if ptr as isize == -1 {
+                let error = X11ShmError::last_os_error("shmat");
+                libc::shmctl(shmid, libc::IPC_RMID, core::ptr::null_mut());
+                return Err(error);
+            }
+            let attached = self
+                .conn
+                .generate_id()
+                .map_err(X11ShmError::x11("generate_id"))
+                .and_then(|shmseg| {
+                    self.conn
+                        .shm_attach(shmseg, shmid as u32, true)
+                        .map_err(X11ShmError::x11("shm_attach"))?;
+                    self.conn.sync().map_err(X11ShmError::x11("shm_attach"))?;
+                    Ok(shmseg)
+                });
+            libc::shmctl(shmid, libc::IPC_RMID, core::ptr::null_mut());
+            match attached {
+                Ok(shmseg) => Ok(Segment {
+                    shmseg,
+                    ptr: ptr.cast(),
+                    width,
+                    height,
+                }),
+                Err(error) => {
+                    libc::shmdt(ptr);
+                    Err(error)
+                }
+            }
+        }
+    }
+
+    fn detach(&mut self) -> Result<(), X11ShmError> {
+        let Some(segment) = self.segment.take() else {
+            return Ok(());
+        };
+        let detached = self
+            .conn
+            .shm_detach(segment.shmseg)
+            .map_err(X11ShmError::x11("shm_detach"))
+            .and_then(|_| self.conn.sync().map_err(X11ShmError::x11("shm_detach")));
+        // SAFETY: No `BufferMutRef` borrows the segment anymore, the X server keeps its own mapping until it detaches
+        unsafe { libc::shmdt(segment.ptr.cast()) };
+        detached
+    }
+}
+
+impl Drop for X11ShmSurface {
+    fn drop(&mut self) {
+        _ = self.detach();
+        _ = self.conn.free_gc(self.gc);
+        _ = self.conn.flush();
+    }
+}
@@ -1,14 +1,55 @@
+// Counts the allocations of every test, see `RenderStats::allocs_this_frame`
+#[cfg(feature = "alloc_stats")]
+#[global_allocator]
+static ALLOCATOR: egui_software_backend::GlobalAllocStats =
+    egui_software_backend::GlobalAllocStats::new();
+
 mod tests {
 
     use egui::{Vec2, vec2};
-    use egui_software_backend::{ColorFieldOrder, EguiSoftwareRender, SoftwareRenderCaching};
+    use egui_software_backend::{
+        BlendPrecision, BufferMutRef, BufferRef, ColorFieldOrder, ComputeTiledDirtyRects,
+        DebugOverlay, DirtyRect, EguiSoftwareRender, SoftwareRenderCaching, premultiply_alpha,
+        premultiply_alpha_slice, swizzle_and_premultiply_rgba_bgra_slice,
+        unpremultiply_alpha_slice,
+    };
     use image::{ImageBuffer, Rgba};
 
     use egui_kittest::HarnessBuilder;
 
     const RESOLUTION: Vec2 = vec2(1280.0, 720.0);
 
+    /// Sets the font texture to a single white texel, sampled by the meshes of `rect_paint_jobs`
+    fn white_texture_delta() -> egui::TexturesDelta {
+        egui::TexturesDelta {
+            set: vec![(
+                egui::TextureId::default(),
+                egui::epaint::ImageDelta::full(
+                    egui::ColorImage::new([1, 1], vec![egui::Color32::WHITE]),
+                    egui::TextureOptions::LINEAR,
+                ),
+            )],
+            free: vec![],
+        }
+    }
+
+    /// A separate unclipped primitive for each colored rect, painted in order
+    fn rect_paint_jobs(rects: &[(egui::Rect, egui::Color32)]) -> Vec<egui::ClippedPrimitive> {
+        rects
+            .iter()
+            .map(|&(rect, color)| {
+                let mut mesh = egui::Mesh::default();
+                mesh.add_colored_rect(rect, color);
+                egui::ClippedPrimitive {
+                    clip_rect: egui::Rect::EVERYTHING,
+                    primitive: egui::epaint::Primitive::Mesh(mesh),
+                }
+            })
+            .collect()
+    }
+
     #[test]
+    #[cfg_attr(miri, ignore)] // Needs a GPU
     // Tests many configurations of the cpu software render backend against the GPU implementation.
     // Outputs PNG files with diffs when pixels didn't match and will panic above a certain threshold:
     // (1px for 1.0 px_per_point, 7px for 1.5 px_per_point).
@@ -106,6 +147,3238 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)] // Building the egui fonts is too slow under Miri
+    // Renders a 16x16 checkerboard texture over 200x200px with uvs above 1.0 and repeat wrap mode.
+    // Each texel covers exactly one pixel so the output must tile the checkerboard perfectly.
+    pub fn texture_wrap_mode_repeat() {
+        const CHECKER_SIZE: usize = 16;
+        const SIZE: f32 = 200.0;
+
+        fn checker(x: usize, y: usize) -> egui::Color32 {
+            if (x + y) % 2 == 0 {
+                egui::Color32::WHITE
+            } else {
+                egui::Color32::BLACK
+            }
+        }
+
+        fn app() -> impl FnMut(&egui::Context) {
+            let mut texture: Option<egui::TextureHandle> = None;
+            move |ctx: &egui::Context| {
+                let texture = texture.get_or_insert_with(|| {
+                    let pixels = (0..CHECKER_SIZE * CHECKER_SIZE)
+                        .map(|i| checker(i % CHECKER_SIZE, i / CHECKER_SIZE))
+                        .collect();
+                    ctx.load_texture(
+                        "checker",
+                        egui::ColorImage::new([CHECKER_SIZE, CHECKER_SIZE], pixels),
+                        egui::TextureOptions::NEAREST_REPEAT,
+                    )
+                });
+                let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, Vec2::splat(SIZE));
+                let uv = egui::Rect::from_min_size(
+                    egui::Pos2::ZERO,
+                    Vec2::splat(SIZE / CHECKER_SIZE as f32),
+                );
+                ctx.layer_painter(egui::LayerId::background()).image(
+                    texture.id(),
+                    rect,
+                    uv,
+                    egui::Color32::WHITE,
+                );
+            }
+        }
+
+        for mode in [
+            SoftwareRenderCaching::Direct,
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            for allow_raster_opt in [false, true] {
+                let egui_software_render = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                    .with_allow_raster_opt(allow_raster_opt)
+                    .with_mode(mode)
+                    .with_canvas();
+
+                let mut harness = HarnessBuilder::default()
+                    .with_size(Vec2::splat(SIZE))
+                    .with_pixels_per_point(1.0)
+                    .renderer(egui_software_render)
+                    .build(app());
+                harness.run();
+                let image = harness.render().unwrap();
+
+                for (x, y, px) in image.enumerate_pixels() {
+                    let expected = checker(x as usize % CHECKER_SIZE, y as usize % CHECKER_SIZE);
+                    assert_eq!(
+                        px.0,
+                        expected.to_array(),
+                        "pixel ({x}, {y}), mode {mode:?}, raster_opt {allow_raster_opt}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Building the egui fonts is too slow under Miri
+    // The texture is freed by the delta of the frame that paints it, the harness hands that delta over before
+    // rendering the frame.
+    pub fn test_renderer_texture_freed_by_painted_frame() {
+        const SIZE: f32 = 16.0;
+
+        fn app() -> impl FnMut(&egui::Context) {
+            |ctx: &egui::Context| {
+                let texture = ctx.load_texture(
+                    "red",
+                    egui::ColorImage::new([1, 1], vec![egui::Color32::RED]),
+                    egui::TextureOptions::NEAREST,
+                );
+                ctx.layer_painter(egui::LayerId::background()).image(
+                    texture.id(),
+                    egui::Rect::from_min_size(egui::Pos2::ZERO, Vec2::splat(SIZE)),
+                    egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0)),
+                    egui::Color32::WHITE,
+                );
+            }
+        }
+
+        fn check(image: image::RgbaImage, name: &str) {
+            for (x, y, px) in image.enumerate_pixels() {
+                assert_eq!(px.0, [255, 0, 0, 255], "pixel ({x}, {y}), {name}");
+            }
+        }
+
+        for mode in [
+            SoftwareRenderCaching::Direct,
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            let renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_mode(mode);
+            let mut harness = HarnessBuilder::default()
+                .with_size(Vec2::splat(SIZE))
+                .with_pixels_per_point(1.0)
+                .renderer(renderer)
+                .build(app());
+            harness.run();
+            check(harness.render().unwrap(), &format!("mode {mode:?}"));
+
+            let renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .with_canvas();
+            let mut harness = HarnessBuilder::default()
+                .with_size(Vec2::splat(SIZE))
+                .with_pixels_per_point(1.0)
+                .renderer(renderer)
+                .build(app());
+            harness.run();
+            check(harness.render().unwrap(), &format!("canvas, mode {mode:?}"));
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Building the egui fonts is too slow under Miri
+    pub fn overdraw_visualization() {
+        fn render(mode: SoftwareRenderCaching, overdraw: bool) -> image::RgbaImage {
+            let egui_software_render = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .with_overdraw_visualization(overdraw)
+                .with_canvas();
+            let mut harness = HarnessBuilder::default()
+                .with_size(Vec2::splat(128.0))
+                .with_pixels_per_point(1.0)
+                .renderer(egui_software_render)
+                .build(|ctx| {
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        ui.label("Hello");
+                    });
+                });
+            harness.run();
+            harness.render().unwrap()
+        }
+
+        let plain = render(SoftwareRenderCaching::BlendTiled, false);
+        let heat_map = render(SoftwareRenderCaching::BlendTiled, true);
+        // Only the panel background is written here
+        let [r, g, ..] = heat_map.get_pixel(100, 100).0;
+        assert!(
+            g > r + 50,
+            "expected green, got {:?}",
+            heat_map.get_pixel(100, 100)
+        );
+        assert_ne!(plain.get_pixel(100, 100), heat_map.get_pixel(100, 100));
+
+        // Not supported in other modes
+        assert_eq!(
+            render(SoftwareRenderCaching::Mesh, false),
+            render(SoftwareRenderCaching::Mesh, true)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Building the egui fonts is too slow under Miri
+    pub fn debug_overlay() {
+        fn render(mode: SoftwareRenderCaching, debug_overlay: DebugOverlay) -> image::RgbaImage {
+            let egui_software_render = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .with_debug_overlay(debug_overlay)
+                .with_canvas();
+            let mut harness = HarnessBuilder::default()
+                .with_size(Vec2::splat(128.0))
+                .with_pixels_per_point(1.0)
+                .renderer(egui_software_render)
+                .build(|ctx| {
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        ui.label("Hello");
+                    });
+                });
+            harness.run();
+            harness.render().unwrap()
+        }
+
+        let plain = render(SoftwareRenderCaching::Mesh, DebugOverlay::empty());
+        let dirty_rects = render(SoftwareRenderCaching::Mesh, DebugOverlay::DIRTY_RECTS);
+        // Everything is redrawn on the first frame
+        assert_eq!(dirty_rects.get_pixel(0, 100).0, [255, 0, 0, 255]);
+        assert_eq!(dirty_rects.get_pixel(127, 100).0, [255, 0, 0, 255]);
+        assert_eq!(dirty_rects.get_pixel(64, 100), plain.get_pixel(64, 100));
+
+        let tile_grid = render(SoftwareRenderCaching::Mesh, DebugOverlay::TILE_GRID);
+        assert_ne!(tile_grid.get_pixel(64, 100), plain.get_pixel(64, 100));
+        assert_eq!(tile_grid.get_pixel(63, 100), plain.get_pixel(63, 100));
+
+        let mut all = DebugOverlay::TILE_GRID | DebugOverlay::DIRTY_RECTS;
+        all |= DebugOverlay::PRIMITIVE_BBOXES | DebugOverlay::CACHE_OCCUPANCY;
+        assert_eq!(all, DebugOverlay::all());
+        all.set(DebugOverlay::TILE_GRID, false);
+        assert!(!all.contains(DebugOverlay::TILE_GRID));
+        assert!(all.contains(DebugOverlay::DIRTY_RECTS));
+
+        for mode in [
+            SoftwareRenderCaching::Direct,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            assert_ne!(render(mode, DebugOverlay::all()), plain, "mode {mode:?}");
+        }
+    }
+
+    #[test]
+    pub fn debug_overlay_partial_redraw() {
+        use egui::{Color32, Pos2, Rect, TexturesDelta};
+        const SIZE: u32 = 128;
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = |color| {
+            rect_paint_jobs(&[
+                (
+                    Rect::from_min_max(Pos2::ZERO, Pos2::new(SIZE as f32, SIZE as f32)),
+                    Color32::GRAY,
+                ),
+                (
+                    Rect::from_min_max(Pos2::new(10.0, 10.0), Pos2::new(20.0, 20.0)),
+                    color,
+                ),
+            ])
+        };
+
+        for mode in [
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .with_debug_overlay(DebugOverlay::DIRTY_RECTS)
+                .with_canvas();
+            let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+            let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+            renderer.render(&mut buffer, paint_jobs(Color32::RED), &textures_delta, 1.0);
+            // Everything is dirty on the first frame
+            assert_eq!(data[(SIZE * 100 + SIZE - 1) as usize], [255, 0, 0, 255]);
+
+            // Only the small rect is dirty, the border of the last frame is gone
+            let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+            let no_delta = TexturesDelta::default();
+            renderer.render(&mut buffer, paint_jobs(Color32::BLUE), &no_delta, 1.0);
+            let gray = Color32::GRAY.to_array();
+            assert_eq!(
+                data[(SIZE * 100 + SIZE - 1) as usize],
+                gray,
+                "mode {mode:?}"
+            );
+            assert_eq!(data[(SIZE * 100) as usize], gray, "mode {mode:?}");
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Building the egui fonts is too slow under Miri
+    #[cfg(feature = "cache_stats")]
+    pub fn cache_stats() {
+        const SIZE: u32 = 128;
+        let ctx = egui::Context::default();
+        let frame = |show_button: bool| {
+            let input = egui::RawInput {
+                screen_rect: Some(egui::Rect::from_min_size(
+                    egui::Pos2::ZERO,
+                    Vec2::splat(SIZE as f32),
+                )),
+                ..Default::default()
+            };
+            let output = ctx.run(input, |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    ui.label("Hello");
+                    if show_button {
+                        let _ = ui.button("World");
+                    }
+                });
+            });
+            let paint_jobs = ctx.tessellate(output.shapes, output.pixels_per_point);
+            (paint_jobs, output.textures_delta, output.pixels_per_point)
+        };
+
+        let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba);
+        let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+        let mut render = |renderer: &mut EguiSoftwareRender, redraw: bool, show_button: bool| {
+            let (paint_jobs, textures_delta, pixels_per_point) = frame(show_button);
+            let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+            renderer.render(
+                &mut buffer,
+                redraw,
+                paint_jobs,
+                &textures_delta,
+                pixels_per_point,
+            );
+        };
+
+        render(&mut renderer, true, true);
+        let stats = renderer.cache_stats();
+        assert_eq!(stats.cache_hits(), 0);
+        assert!(stats.cache_misses() > 0);
+        assert_eq!(stats.cache_hit_rate(), 0.0);
+
+        render(&mut renderer, false, true);
+        let stats = renderer.cache_stats();
+        assert!(stats.cache_hits() > 0);
+        assert_eq!(stats.cache_misses(), 0);
+        assert_eq!(stats.cache_evictions(), 0);
+        assert_eq!(stats.cache_hit_rate(), 1.0);
+
+        render(&mut renderer, false, false);
+        let stats = renderer.cache_stats();
+        assert!(stats.cache_evictions() > 0);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Building the egui fonts is too slow under Miri
+    pub fn cache_memory_usage() {
+        const SIZE: u32 = 256;
+        let ctx = egui::Context::default();
+        let input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                Vec2::splat(SIZE as f32),
+            )),
+            ..Default::default()
+        };
+        let output = ctx.run(input, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.label("Hello");
+                let _ = ui.button("World");
+            });
+        });
+        let paint_jobs = ctx.tessellate(output.shapes, output.pixels_per_point);
+
+        for mode in [
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_mode(mode);
+            assert_eq!(renderer.cache_memory_usage(), 0, "mode {mode:?}");
+
+            let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+            let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+            renderer.render(
+                &mut buffer,
+                true,
+                paint_jobs.clone(),
+                &output.textures_delta,
+                output.pixels_per_point,
+            );
+            assert!(renderer.cache_memory_usage() > 0, "mode {mode:?}");
+            assert!(renderer.primitive_count() > 0, "mode {mode:?}");
+            assert_eq!(renderer.texture_count(), 1, "mode {mode:?}");
+            let memory = renderer.memory_stats();
+            assert!(memory.texture_bytes > 0, "mode {mode:?}");
+            if mode == SoftwareRenderCaching::BlendTiled {
+                assert!(memory.tiled_cache_bytes > 0);
+                assert_eq!(memory.mesh_cache_bytes, 0);
+            } else {
+                assert_eq!(memory.tiled_cache_bytes, 0, "mode {mode:?}");
+                assert!(memory.mesh_cache_bytes > 0, "mode {mode:?}");
+            }
+
+            renderer.clear_cache();
+            assert_eq!(renderer.cache_memory_usage(), 0, "mode {mode:?}");
+            assert_eq!(renderer.primitive_count(), 0, "mode {mode:?}");
+            assert_eq!(renderer.texture_count(), 1, "mode {mode:?}");
+            assert_eq!(
+                renderer.memory_stats().texture_bytes,
+                memory.texture_bytes,
+                "mode {mode:?}"
+            );
+        }
+    }
+
+    #[test]
+    // Small enough to run under Miri, covers the scalar blending in every mode.
+    pub fn render_small_meshes() {
+        use egui::{
+            Color32, ColorImage, Pos2, Rect, TextureId, TextureOptions, TexturesDelta,
+            epaint::{ClippedPrimitive, ImageDelta, Mesh, Primitive},
+        };
+        const SIZE: u32 = 32;
+
+        let image = ColorImage::new(
+            [2, 2],
+            vec![
+                Color32::WHITE,
+                Color32::from_rgba_premultiplied(0, 0, 128, 128),
+                Color32::RED,
+                Color32::TRANSPARENT,
+            ],
+        );
+        let textures_delta = TexturesDelta {
+            set: vec![(
+                TextureId::default(),
+                ImageDelta::full(image, TextureOptions::LINEAR),
+            )],
+            free: vec![],
+        };
+        let mut textured = Mesh::default();
+        textured.add_rect_with_uv(
+            Rect::from_min_max(Pos2::new(2.0, 2.0), Pos2::new(20.0, 24.0)),
+            Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+            Color32::WHITE,
+        );
+        let mut colored = Mesh::default();
+        colored.colored_vertex(
+            Pos2::new(4.0, 30.0),
+            Color32::from_rgba_premultiplied(0, 64, 0, 64),
+        );
+        colored.colored_vertex(
+            Pos2::new(30.0, 4.0),
+            Color32::from_rgba_premultiplied(100, 0, 0, 200),
+        );
+        colored.colored_vertex(Pos2::new(30.0, 30.0), Color32::BLUE);
+        colored.add_triangle(0, 1, 2);
+        let clip_rect = Rect::from_min_max(Pos2::ZERO, Pos2::new(28.5, 29.5));
+        let paint_jobs = vec![
+            ClippedPrimitive {
+                clip_rect,
+                primitive: Primitive::Mesh(textured),
+            },
+            ClippedPrimitive {
+                clip_rect,
+                primitive: Primitive::Mesh(colored),
+            },
+        ];
+
+        let render = |mode: SoftwareRenderCaching| {
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_mode(mode);
+            let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+            let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+            renderer.render(&mut buffer, true, paint_jobs.clone(), &textures_delta, 1.0);
+            data
+        };
+
+        let direct = render(SoftwareRenderCaching::Direct);
+        assert!(direct.iter().any(|px| px[3] != 0));
+        for mode in [
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            for (a, b) in direct.iter().zip(render(mode)) {
+                assert!(
+                    (0..4).all(|c| a[c].abs_diff(b[c]) <= 2),
+                    "mode {mode:?}: {a:?} != {b:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    pub fn blend_precision_bits16() {
+        use egui::{Color32, Pos2, Rect};
+        const SIZE: u32 = 16;
+        const LAYERS: u32 = 40;
+        let color = [2u8, 2, 2, 4];
+
+        let textures_delta = white_texture_delta();
+        // Full buffer rects of the same faint color, sized differently so they're cached separately
+        let rects: Vec<_> = (0..LAYERS)
+            .map(|i| {
+                (
+                    Rect::from_min_max(Pos2::ZERO, Pos2::new((SIZE + i) as f32, SIZE as f32)),
+                    Color32::from_rgba_premultiplied(color[0], color[1], color[2], color[3]),
+                )
+            })
+            .collect();
+        let paint_jobs = rect_paint_jobs(&rects);
+
+        let render = |precision: BlendPrecision| {
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_blend_precision(precision)
+                .with_canvas();
+            let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+            let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+            renderer.render(&mut buffer, paint_jobs.clone(), &textures_delta, 1.0);
+            data
+        };
+        let mean = |data: &[[u8; 4]], c: usize| {
+            data.iter().map(|px| px[c] as f32).sum::<f32>() / data.len() as f32
+        };
+
+        let mut expected = [0f32; 4];
+        for _ in 0..LAYERS {
+            let alpha = color[3] as f32 / 255.0;
+            expected = core::array::from_fn(|c| color[c] as f32 + expected[c] * (1.0 - alpha));
+        }
+
+        let bits8 = render(BlendPrecision::Bits8);
+        let bits16 = render(BlendPrecision::Bits16);
+        for (c, expected) in expected.into_iter().enumerate() {
+            let error8 = (mean(&bits8, c) - expected).abs();
+            let error16 = (mean(&bits16, c) - expected).abs();
+            assert!(error16 < 0.5, "channel {c}: {error16}");
+            assert!(error16 < error8, "channel {c}: {error16} >= {error8}");
+        }
+        // Dithered, premultiplied colors stay below alpha
+        assert!(bits16.iter().all(|px| px[..3].iter().all(|&c| c <= px[3])));
+    }
+
+    #[test]
+    pub fn straight_alpha_input() {
+        use egui::{
+            Color32, ColorImage, Pos2, Rect, TextureId, TextureOptions, TexturesDelta,
+            epaint::{ClippedPrimitive, ImageDelta, Mesh, Primitive},
+        };
+        const SIZE: u32 = 16;
+
+        let straight = [
+            [255, 0, 0, 128],
+            [0, 255, 255, 64],
+            [10, 20, 30, 255],
+            [255; 4],
+        ];
+        let paint = |premultiply: fn([u8; 4]) -> [u8; 4]| {
+            let [t0, t1, t2, t3] = straight.map(|c| {
+                let [r, g, b, a] = premultiply(c);
+                Color32::from_rgba_premultiplied(r, g, b, a)
+            });
+            let image = ColorImage::new([2, 2], vec![t0, t1, t2, t3]);
+            let textures_delta = TexturesDelta {
+                set: vec![(
+                    TextureId::default(),
+                    ImageDelta::full(image, TextureOptions::NEAREST),
+                )],
+                free: vec![],
+            };
+            let mut mesh = Mesh::default();
+            mesh.add_rect_with_uv(
+                Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(12.0, 12.0)),
+                Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+                t1,
+            );
+            mesh.add_colored_rect(
+                Rect::from_min_max(Pos2::new(4.0, 4.0), Pos2::new(16.0, 16.0)),
+                t0,
+            );
+            let paint_jobs = vec![ClippedPrimitive {
+                clip_rect: Rect::EVERYTHING,
+                primitive: Primitive::Mesh(mesh),
+            }];
+            (paint_jobs, textures_delta)
+        };
+
+        let render = |straight_alpha_input: bool, premultiply: fn([u8; 4]) -> [u8; 4]| {
+            let (paint_jobs, textures_delta) = paint(premultiply);
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Bgra)
+                .with_mode(SoftwareRenderCaching::Direct)
+                .with_straight_alpha_input(straight_alpha_input);
+            let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+            let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+            renderer.render(&mut buffer, true, paint_jobs, &textures_delta, 1.0);
+            data
+        };
+
+        let expected = render(false, premultiply_alpha);
+        assert!(expected.iter().any(|px| px[3] != 0));
+        assert_eq!(render(true, |c| c), expected);
+        assert_ne!(render(false, |c| c), expected);
+    }
+
+    #[test]
+    pub fn swizzle_egui_colors() {
+        let colors: Vec<_> = (0..=255u8)
+            .map(|a| egui::Color32::from_rgba_premultiplied(a / 2, a / 3, a, a))
+            .collect();
+        let bgra = swizzle_and_premultiply_rgba_bgra_slice(&colors);
+        let expected: Vec<_> = colors
+            .iter()
+            .map(|c| [c.b(), c.g(), c.r(), c.a()])
+            .collect();
+        assert_eq!(bgra, expected);
+    }
+
+    #[test]
+    pub fn bilinear_rect_matches_tris() {
+        use egui::{
+            Color32, ColorImage, Pos2, Rect, TextureId, TextureOptions, TexturesDelta,
+            epaint::{ClippedPrimitive, ImageDelta, Mesh, Primitive},
+        };
+        const SIZE: u32 = 61;
+
+        let pixels = (0..7 * 5u32)
+            .map(|i| {
+                let a = (i.wrapping_mul(2654435761) >> 24) as u8;
+                Color32::from_rgba_premultiplied(a / 2, a / 3, a, a)
+            })
+            .collect();
+        let textures_delta = TexturesDelta {
+            set: vec![(
+                TextureId::default(),
+                ImageDelta::full(ColorImage::new([7, 5], pixels), TextureOptions::LINEAR),
+            )],
+            free: vec![],
+        };
+        let mut mesh = Mesh::default();
+        // Odd width, so rows end in a partial group of 4 pixels
+        mesh.add_rect_with_uv(
+            Rect::from_min_max(Pos2::new(3.0, 2.0), Pos2::new(58.0, 59.0)),
+            Rect::from_min_max(Pos2::new(0.1, 0.0), Pos2::new(0.9, 1.0)),
+            Color32::from_rgba_premultiplied(200, 255, 100, 255),
+        );
+        let paint_jobs = vec![ClippedPrimitive {
+            clip_rect: Rect::EVERYTHING,
+            primitive: Primitive::Mesh(mesh),
+        }];
+
+        let render = |convert_tris_to_rects: bool| {
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(SoftwareRenderCaching::Direct)
+                .with_convert_tris_to_rects(convert_tris_to_rects);
+            let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+            let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+            renderer.render(&mut buffer, true, paint_jobs.clone(), &textures_delta, 1.0);
+            data
+        };
+
+        let rects = render(true);
+        let tris = render(false);
+        assert!(rects.iter().any(|px| px[3] != 0));
+        for (i, (rect, tri)) in rects.iter().zip(&tris).enumerate() {
+            for (r, t) in rect.iter().zip(tri) {
+                assert!(r.abs_diff(*t) <= 2, "pixel {i}: {rect:?} != {tri:?}");
+            }
+        }
+    }
+
+    #[test]
+    pub fn bilinear_rect_clipped_matches_tris() {
+        use egui::{
+            Color32, ColorImage, Pos2, Rect, TextureId, TextureOptions, TexturesDelta,
+            epaint::{ClippedPrimitive, ImageDelta, Mesh, Primitive},
+        };
+        const SIZE: u32 = 61;
+
+        let pixels = (0..7 * 5u32)
+            .map(|i| {
+                let a = (i.wrapping_mul(2654435761) >> 24) as u8;
+                Color32::from_rgba_premultiplied(a / 2, a / 3, a, a)
+            })
+            .collect();
+        let textures_delta = TexturesDelta {
+            set: vec![(
+                TextureId::default(),
+                ImageDelta::full(ColorImage::new([7, 5], pixels), TextureOptions::LINEAR),
+            )],
+            free: vec![],
+        };
+
+        // Rects starting mid pixel, rounded down and up, with clip rects starting before and inside them
+        for (rect_min, clip_min) in [
+            (Pos2::new(3.3, 2.2), Pos2::ZERO),
+            (Pos2::new(3.7, 2.6), Pos2::ZERO),
+            (Pos2::new(3.3, 2.2), Pos2::new(17.0, 11.0)),
+            (Pos2::new(3.7, 2.6), Pos2::new(17.0, 11.0)),
+        ] {
+            let mut mesh = Mesh::default();
+            mesh.add_rect_with_uv(
+                Rect::from_min_max(rect_min, Pos2::new(58.0, 59.0)),
+                Rect::from_min_max(Pos2::new(0.1, 0.0), Pos2::new(0.9, 1.0)),
+                Color32::from_rgba_premultiplied(200, 255, 100, 255),
+            );
+            let paint_jobs = vec![ClippedPrimitive {
+                clip_rect: Rect::from_min_max(clip_min, Pos2::new(SIZE as f32, SIZE as f32)),
+                primitive: Primitive::Mesh(mesh),
+            }];
+
+            let render = |convert_tris_to_rects: bool| {
+                let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                    .with_mode(SoftwareRenderCaching::Direct)
+                    .with_convert_tris_to_rects(convert_tris_to_rects);
+                let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+                let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+                renderer.render(&mut buffer, true, paint_jobs.clone(), &textures_delta, 1.0);
+                data
+            };
+
+            let rects = render(true);
+            let tris = render(false);
+            assert!(rects.iter().any(|px| px[3] != 0));
+            for (i, (rect, tri)) in rects.iter().zip(&tris).enumerate() {
+                for (r, t) in rect.iter().zip(tri) {
+                    assert!(
+                        r.abs_diff(*t) <= 2,
+                        "rect {rect_min:?}, clip {clip_min:?}, pixel {i}: {rect:?} != {tri:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn minified_texture_premip() {
+        use egui::{
+            Color32, ColorImage, Pos2, Rect, TextureId, TextureOptions, TexturesDelta,
+            epaint::{ClippedPrimitive, ImageDelta, Mesh, Primitive},
+        };
+        const SIZE: u32 = 8;
+
+        // 64 x 64 checkerboard of single texels, drawn 8 times smaller
+        let pixels = (0..64 * 64)
+            .map(|i| match (i % 64 + i / 64) % 2 {
+                0 => Color32::BLACK,
+                _ => Color32::WHITE,
+            })
+            .collect();
+        let image = ColorImage::new([64, 64], pixels);
+
+        let render = |mipmap_mode: Option<egui::TextureFilter>, convert_tris_to_rects: bool| {
+            let options = TextureOptions {
+                mipmap_mode,
+                ..TextureOptions::NEAREST
+            };
+            let textures_delta = TexturesDelta {
+                set: vec![(
+                    TextureId::default(),
+                    ImageDelta::full(image.clone(), options),
+                )],
+                free: vec![],
+            };
+            let mut mesh = Mesh::default();
+            mesh.add_rect_with_uv(
+                Rect::from_min_max(Pos2::ZERO, Pos2::new(SIZE as f32, SIZE as f32)),
+                Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+                Color32::WHITE,
+            );
+            let paint_jobs = vec![ClippedPrimitive {
+                clip_rect: Rect::from_min_max(Pos2::ZERO, Pos2::new(SIZE as f32, SIZE as f32)),
+                primitive: Primitive::Mesh(mesh),
+            }];
+
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(SoftwareRenderCaching::Direct)
+                .with_convert_tris_to_rects(convert_tris_to_rects);
+            let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+            let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+            renderer.render(&mut buffer, true, paint_jobs, &textures_delta, 1.0);
+            data
+        };
+
+        for convert_tris_to_rects in [true, false] {
+            // Every pixel samples a texel of the same color
+            let aliased = render(None, convert_tris_to_rects);
+            assert!(aliased.iter().all(|px| *px == aliased[0]));
+            assert!(aliased[0] == [0, 0, 0, 255] || aliased[0] == [255; 4]);
+
+            // The premip of the same size averages the texels
+            let premipped = render(Some(egui::TextureFilter::Nearest), convert_tris_to_rects);
+            for px in premipped {
+                assert!(px[..3].iter().all(|c| c.abs_diff(128) <= 1), "{px:?}");
+            }
+        }
+    }
+
+    #[test]
+    pub fn premultiply_alpha_slice_matches_scalar() {
+        let mut pixels: Vec<[u8; 4]> = (0..=255u8)
+            .map(|a| [a.wrapping_mul(7), 255 - a, a / 3, a])
+            .collect();
+        let expected: Vec<_> = pixels.iter().map(|&px| premultiply_alpha(px)).collect();
+        premultiply_alpha_slice(&mut pixels[..255]);
+        premultiply_alpha_slice(&mut pixels[255..]);
+        assert_eq!(pixels, expected);
+
+        unpremultiply_alpha_slice(&mut pixels);
+        assert_eq!(pixels[255], [255u8.wrapping_mul(7), 0, 85, 255]);
+        assert_eq!(pixels[0], [0; 4]);
+    }
+
+    #[test]
+    #[cfg(feature = "threading")]
+    pub fn blit_threads() {
+        use egui::{Color32, Pos2, Rect};
+        // Not a multiple of the tile size, so the last row of tiles is partial
+        const WIDTH: u32 = 150;
+        const HEIGHT: u32 = 300;
+
+        let textures_delta = white_texture_delta();
+        let rects: Vec<_> = (0..10)
+            .map(|i| {
+                let min = Pos2::new(i as f32 * 13.0, i as f32 * 29.0);
+                (
+                    Rect::from_min_max(min, min + Vec2::new(40.0, 50.0)),
+                    Color32::from_rgba_premultiplied(0, 20 * i, 100, 150),
+                )
+            })
+            .collect();
+        let paint_jobs = rect_paint_jobs(&rects);
+
+        let render = |mode: SoftwareRenderCaching, blit_threads: usize| {
+            EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .with_blit_threads(blit_threads)
+                .with_canvas()
+                .render_to_buffer(paint_jobs.clone(), &textures_delta, 1.0, WIDTH, HEIGHT)
+        };
+
+        let expected = render(SoftwareRenderCaching::Direct, 1);
+        assert!(expected.iter().any(|px| px[3] != 0));
+        for blit_threads in [0, 1, 2, 3, 16] {
+            assert_eq!(
+                render(SoftwareRenderCaching::BlendTiled, blit_threads),
+                expected,
+                "blit_threads {blit_threads}"
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "threading")]
+    pub fn offscreen_render_batch() {
+        use egui::{Color32, Pos2, Rect};
+        use egui_software_backend::{OffscreenRenderBatch, SoftwareFrame};
+
+        // Every frame sets its textures, the threads don't keep them
+        let frame = |i: u8| SoftwareFrame {
+            paint_jobs: rect_paint_jobs(&[(
+                Rect::from_min_max(Pos2::new(2.0, 3.0), Pos2::new(20.0 + f32::from(i), 30.0)),
+                Color32::from_rgba_premultiplied(20 * i, 100, 0, 200),
+            )]),
+            textures_delta: white_texture_delta(),
+            pixels_per_point: 1.0,
+            size: (30 + u32::from(i), 40),
+        };
+
+        for threads in [0, 1, 3] {
+            let batch = OffscreenRenderBatch::new(threads);
+            let handles: Vec<_> = (0..8).map(|i| batch.submit(frame(i))).collect();
+            // Dropped without waiting, still rendered
+            let _ = batch.submit(frame(9));
+            for (i, handle) in (0..8).zip(handles) {
+                let SoftwareFrame {
+                    paint_jobs,
+                    textures_delta,
+                    pixels_per_point,
+                    size: (width, height),
+                } = frame(i);
+                let (expected, _) = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                    .render_to_buffer(paint_jobs, &textures_delta, pixels_per_point, width, height);
+                assert!(expected.iter().any(|px| px[3] != 0));
+                assert_eq!(handle.wait(), expected, "threads {threads}, frame {i}");
+            }
+        }
+    }
+
+    #[test]
+    pub fn blit_texture_to_atlas() {
+        use egui::{
+            Color32, ColorImage, Pos2, Rect, TextureId, TextureOptions, TexturesDelta,
+            epaint::{ClippedPrimitive, ImageDelta, Mesh, Primitive},
+        };
+        const SIZE: u32 = 4;
+
+        let atlas = TextureId::Managed(1);
+        let sprite = TextureId::Managed(2);
+        let textures_delta = TexturesDelta {
+            set: vec![
+                (
+                    atlas,
+                    ImageDelta::full(
+                        ColorImage::new([4, 4], vec![Color32::BLACK; 16]),
+                        TextureOptions::NEAREST,
+                    ),
+                ),
+                (
+                    sprite,
+                    ImageDelta::full(
+                        ColorImage::new([2, 1], vec![Color32::RED, Color32::BLUE]),
+                        TextureOptions::NEAREST,
+                    ),
+                ),
+            ],
+            free: vec![],
+        };
+        // The atlas, one texel per pixel
+        let mut mesh = Mesh::with_texture(atlas);
+        mesh.add_rect_with_uv(
+            Rect::from_min_max(Pos2::ZERO, Pos2::new(SIZE as f32, SIZE as f32)),
+            Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+            Color32::WHITE,
+        );
+        let paint_jobs = vec![ClippedPrimitive {
+            clip_rect: Rect::EVERYTHING,
+            primitive: Primitive::Mesh(mesh),
+        }];
+
+        for field_order in [ColorFieldOrder::Rgba, ColorFieldOrder::Bgra] {
+            let mut renderer = EguiSoftwareRender::new(field_order);
+            let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+            let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+            renderer.render(&mut buffer, true, paint_jobs.clone(), &textures_delta, 1.0);
+
+            assert!(renderer.blit_texture(sprite, [0, 0, 2, 1], atlas, [1, 2]));
+            assert!(renderer.fill_texture_rect(atlas, [3, 0, 1, 2], Color32::GREEN));
+            assert!(renderer.blit_texture(atlas, [1, 2, 1, 1], atlas, [0, 0]));
+            assert!(!renderer.blit_texture(sprite, [0, 0, 2, 1], atlas, [3, 0]));
+            assert!(!renderer.blit_texture(TextureId::Managed(3), [0, 0, 1, 1], atlas, [0, 0]));
+            assert!(!renderer.fill_texture_rect(atlas, [0, 0, 5, 1], Color32::GREEN));
+
+            // The cached primitive drawn with the atlas is redrawn
+            let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+            renderer.render(
+                &mut buffer,
+                false,
+                paint_jobs.clone(),
+                &Default::default(),
+                1.0,
+            );
+            let rgba = |x: usize, y: usize| {
+                let pixel = data[x + y * SIZE as usize];
+                match field_order {
+                    ColorFieldOrder::Rgba => pixel,
+                    ColorFieldOrder::Bgra => [pixel[2], pixel[1], pixel[0], pixel[3]],
+                }
+            };
+            assert_eq!(rgba(0, 0), Color32::RED.to_array());
+            assert_eq!(rgba(1, 2), Color32::RED.to_array());
+            assert_eq!(rgba(2, 2), Color32::BLUE.to_array());
+            assert_eq!(rgba(3, 1), Color32::GREEN.to_array());
+            assert_eq!(rgba(3, 2), Color32::BLACK.to_array());
+
+            // The texels are in the output field order too
+            let texture = renderer.get_texture(atlas).unwrap();
+            assert_eq!([texture.width(), texture.height()], [4, 4]);
+            assert_eq!(texture.data().len(), 16);
+            assert_eq!(texture.data()[0], data[0]);
+            assert_eq!(texture.data()[1 + 2 * 4], data[1 + 2 * SIZE as usize]);
+            assert!(renderer.get_texture(TextureId::Managed(3)).is_none());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    pub fn prim_profiler() {
+        use egui::mutex::Mutex;
+        use egui::{Color32, Pos2, Rect};
+        use egui_software_backend::PrimProfile;
+        use std::sync::Arc;
+        const SIZE: u32 = 64;
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = rect_paint_jobs(&[(3.0, 5.0), (30.0, 40.0)].map(|(x, y)| {
+            (
+                Rect::from_min_size(Pos2::new(x, y), egui::vec2(20.0, 10.0)),
+                Color32::from_rgba_premultiplied(0, 100, 0, 200),
+            )
+        }));
+
+        for mode in [
+            SoftwareRenderCaching::BlendTiled,
+            SoftwareRenderCaching::MeshTiled,
+        ] {
+            let profiles = Arc::new(Mutex::new(Vec::<PrimProfile>::new()));
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_mode(mode);
+            let profiler_profiles = Arc::clone(&profiles);
+            renderer.set_prim_profiler(move |profile| profiler_profiles.lock().push(profile));
+            let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+            let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+
+            renderer.render(&mut buffer, true, paint_jobs.clone(), &textures_delta, 1.0);
+            let mut first = core::mem::take(&mut *profiles.lock());
+            first.sort_by_key(|profile| profile.rect.min_x);
+            assert_eq!(first.len(), 2, "{mode:?}");
+            assert!(first.iter().all(|profile| profile.is_new));
+            assert_eq!(
+                first[0].rect,
+                DirtyRect {
+                    min_x: 3,
+                    min_y: 5,
+                    max_x: 23,
+                    max_y: 15
+                }
+            );
+            assert_ne!(first[0].hash, first[1].hash);
+
+            // Reused from the cache
+            renderer.render(
+                &mut buffer,
+                false,
+                paint_jobs.clone(),
+                &Default::default(),
+                1.0,
+            );
+            let mut second = core::mem::take(&mut *profiles.lock());
+            second.sort_by_key(|profile| profile.rect.min_x);
+            assert!(second.iter().all(|profile| !profile.is_new));
+            let hashes =
+                |profiles: &[PrimProfile]| profiles.iter().map(|p| p.hash).collect::<Vec<_>>();
+            assert_eq!(hashes(&second), hashes(&first), "{mode:?}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc_stats")]
+    pub fn alloc_stats() {
+        use egui::{
+            Color32, ColorImage, Pos2, Rect, TextureId, TextureOptions, TexturesDelta,
+            epaint::ImageDelta,
+        };
+        const SIZE: u32 = 32;
+
+        // The texture is at least one allocation of its pixels
+        let textures_delta = TexturesDelta {
+            set: vec![(
+                TextureId::default(),
+                ImageDelta::full(
+                    ColorImage::new([64, 64], vec![Color32::WHITE; 64 * 64]),
+                    TextureOptions::LINEAR,
+                ),
+            )],
+            free: vec![],
+        };
+        let paint_jobs = rect_paint_jobs(&[(
+            Rect::from_min_max(Pos2::new(3.0, 5.0), Pos2::new(27.0, 20.0)),
+            Color32::from_rgba_premultiplied(0, 100, 0, 200),
+        )]);
+
+        let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba);
+        let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+        let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+        renderer.render(&mut buffer, true, paint_jobs, &textures_delta, 1.0);
+        // Other tests allocate on their threads meanwhile, the counts can only be higher
+        let stats = renderer.stats();
+        assert!(stats.allocs_this_frame >= 1);
+        assert!(stats.alloc_bytes_this_frame >= 64 * 64 * 4);
+    }
+
+    #[test]
+    #[cfg(feature = "raster_stats")]
+    pub fn duration_stat_history() {
+        use egui::{Color32, Pos2, Rect};
+        use egui_software_backend::stats::DURATION_HISTORY;
+        const SIZE: u32 = 32;
+        const FRAMES: usize = 300;
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = rect_paint_jobs(&[(
+            Rect::from_min_max(Pos2::new(3.0, 5.0), Pos2::new(27.0, 20.0)),
+            Color32::from_rgba_premultiplied(0, 100, 0, 200),
+        )]);
+
+        let mut renderer =
+            EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_mode(SoftwareRenderCaching::Direct);
+        let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+        let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+        let stat = &renderer.stats().render_direct;
+        assert_eq!(
+            (stat.elapsed_secs(), stat.percentile(50.0), stat.max()),
+            (0.0, 0.0, 0.0)
+        );
+
+        renderer.render(&mut buffer, true, paint_jobs.clone(), &textures_delta, 1.0);
+        for _ in 1..FRAMES {
+            renderer.render(
+                &mut buffer,
+                true,
+                paint_jobs.clone(),
+                &Default::default(),
+                1.0,
+            );
+        }
+
+        // The history is kept across frames, up to `DURATION_HISTORY` measurements
+        let stat = &renderer.stats().render_direct;
+        let histogram = stat.histogram(8);
+        assert_eq!(histogram.len(), 8);
+        assert_eq!(histogram.iter().sum::<u32>() as usize, DURATION_HISTORY);
+        assert!(*histogram.last().unwrap() >= 1);
+        let p50 = stat.percentile(50.0);
+        assert!(0.0 < p50 && p50 <= stat.percentile(99.0));
+        assert!(stat.percentile(99.0) <= stat.max());
+        assert_eq!(stat.percentile(100.0), stat.max());
+        assert!(stat.elapsed_secs() <= stat.max());
+    }
+
+    #[test]
+    #[cfg(all(feature = "raster_stats", not(feature = "rayon")))]
+    pub fn fill_rate_stats() {
+        use egui::{Color32, Pos2, Rect};
+        use egui_software_backend::stats::RasterStats;
+        const SIZE: u32 = 32;
+
+        let paint_jobs = rect_paint_jobs(&[
+            (
+                Rect::from_min_max(Pos2::new(3.0, 5.0), Pos2::new(27.0, 20.0)),
+                Color32::from_rgba_premultiplied(0, 100, 0, 200),
+            ),
+            (
+                Rect::from_min_max(Pos2::new(20.0, 20.0), Pos2::new(30.0, 30.0)),
+                Color32::from_rgba_premultiplied(100, 0, 0, 255),
+            ),
+        ]);
+
+        let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+            .with_mode(SoftwareRenderCaching::Direct)
+            .with_convert_tris_to_rects(false);
+        renderer.render_to_buffer(paint_jobs, &white_texture_delta(), 1.0, SIZE, SIZE);
+
+        let stats = renderer.stats();
+        assert_eq!(stats.buffer_size, [SIZE, SIZE]);
+        let raster = stats.raster.lock();
+        // Two tris per rect, counted by their bounding box
+        assert_eq!(raster.total_pixels_filled_tris, 2 * (24 * 15 + 10 * 10));
+        assert_eq!(raster.total_pixels_filled_rects, 0);
+        assert_eq!(raster.fill_rate_mpps(0.5), 920.0 / 0.5 / 1e6);
+        assert_eq!(raster.fill_rate_mpps(0.0), 0.0);
+        assert!(raster.raster_secs() > 0.0);
+        assert_eq!(
+            RasterStats::theoretical_mpps_for_resolution(1920, 1080, 60.0),
+            124.416
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "raster_stats", not(feature = "rayon")))]
+    #[cfg_attr(miri, ignore)] // Building the egui fonts is too slow under Miri
+    pub fn demo_rects_found_at_fractional_scale() {
+        use egui::{Pos2, Rect};
+
+        // Rects and tris rasterized by a frame of the demo
+        let raster_counts = |pixels_per_point: f32| {
+            let ctx = egui::Context::default();
+            ctx.set_pixels_per_point(pixels_per_point);
+            let mut demo = egui_demo_lib::DemoWindows::default();
+            let input = |frame_idx: u32| egui::RawInput {
+                screen_rect: Some(Rect::from_min_size(
+                    Pos2::ZERO,
+                    RESOLUTION / pixels_per_point,
+                )),
+                time: Some(f64::from(frame_idx) / 60.0),
+                ..Default::default()
+            };
+            // The first frame lays out the windows, the second one paints them where they stay
+            let mut textures_delta = ctx.run(input(0), |ctx| demo.ui(ctx)).textures_delta;
+            let output = ctx.run(input(1), |ctx| demo.ui(ctx));
+            textures_delta.append(output.textures_delta);
+            let paint_jobs = ctx.tessellate(output.shapes, output.pixels_per_point);
+
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(SoftwareRenderCaching::Direct);
+            let (width, height) = (RESOLUTION.x as u32, RESOLUTION.y as u32);
+            let (_, _) = renderer.render_to_buffer(
+                paint_jobs,
+                &textures_delta,
+                output.pixels_per_point,
+                width,
+                height,
+            );
+            let raster = renderer.stats().raster.lock();
+            (raster.rects, raster.tris)
+        };
+
+        let (rects, tris) = raster_counts(1.0);
+        let (fractional_rects, fractional_tris) = raster_counts(1.5);
+        assert!(rects > 0 && tris > 0);
+        // Missed rects are drawn as 2 tris each, they were about 11% of them before matching the corners loosely
+        assert!(
+            fractional_rects * 10 >= rects * 9,
+            "{fractional_rects} rects and {fractional_tris} tris at 1.5 pixels per point, {rects} and {tris} at 1"
+        );
+    }
+
+    #[test]
+    pub fn render_to_buffer() {
+        use egui::{Color32, Pos2, Rect};
+        const WIDTH: u32 = 40;
+        const HEIGHT: u32 = 24;
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = rect_paint_jobs(&[(
+            Rect::from_min_max(Pos2::new(3.0, 5.0), Pos2::new(27.0, 20.0)),
+            Color32::from_rgba_premultiplied(0, 100, 0, 200),
+        )]);
+
+        for mode in [
+            SoftwareRenderCaching::Direct,
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+            SoftwareRenderCaching::Auto,
+        ] {
+            let new_renderer = || EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_mode(mode);
+            let mut expected = vec![[0u8; 4]; (WIDTH * HEIGHT) as usize];
+            let expected_dirty_rect = new_renderer().render(
+                &mut BufferMutRef::new(&mut expected, WIDTH, HEIGHT),
+                true,
+                paint_jobs.clone(),
+                &textures_delta,
+                1.0,
+            );
+            assert!(expected.iter().any(|px| px[3] != 0), "mode {mode:?}");
+
+            let (buffer, dirty_rect) = new_renderer().render_to_buffer(
+                paint_jobs.clone(),
+                &textures_delta,
+                1.0,
+                WIDTH,
+                HEIGHT,
+            );
+            assert_eq!(buffer, expected, "mode {mode:?}");
+            assert_eq!(dirty_rect, expected_dirty_rect, "mode {mode:?}");
+
+            let buffer = new_renderer().with_canvas().render_to_buffer(
+                paint_jobs.clone(),
+                &textures_delta,
+                1.0,
+                WIDTH,
+                HEIGHT,
+            );
+            assert_eq!(buffer, expected, "mode {mode:?}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "gif_export")]
+    pub fn gif_recording() {
+        use egui::{Color32, Pos2, Rect, TexturesDelta};
+        const WIDTH: u32 = 40;
+        const HEIGHT: u32 = 24;
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = |x: f32| {
+            rect_paint_jobs(&[(
+                Rect::from_min_max(Pos2::new(x, 5.0), Pos2::new(x + 10.0, 20.0)),
+                Color32::from_rgb(0, 100, 0),
+            )])
+        };
+
+        let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Bgra).with_canvas();
+        assert_eq!(renderer.end_recording(), None);
+        renderer.begin_recording(30);
+        let mut frames = Vec::new();
+        for frame_idx in 0..3 {
+            let textures_delta = match frame_idx {
+                0 => textures_delta.clone(),
+                _ => TexturesDelta::default(),
+            };
+            let paint_jobs = paint_jobs(3.0 + 8.0 * frame_idx as f32);
+            frames.push(renderer.render_to_buffer(paint_jobs, &textures_delta, 1.0, WIDTH, HEIGHT));
+        }
+        let gif = renderer.end_recording().unwrap();
+        assert_eq!(renderer.end_recording(), None);
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(gif.as_slice()).unwrap();
+        assert_eq!(
+            (decoder.width(), decoder.height()),
+            (WIDTH as u16, HEIGHT as u16)
+        );
+        let mut delays = Vec::new();
+        for expected in &frames {
+            let frame = decoder.read_next_frame().unwrap().unwrap();
+            delays.push(frame.delay);
+            // Fewer than 256 colors, the palette is exact
+            let expected: Vec<u8> = expected
+                .iter()
+                .flat_map(|&[b, g, r, _]| [r, g, b, 255])
+                .collect();
+            assert_eq!(*frame.buffer, *expected);
+        }
+        assert!(decoder.read_next_frame().unwrap().is_none());
+        // 1/30 s rounded to hundredths, without drifting
+        assert_eq!(delays, [3, 4, 3]);
+    }
+
+    #[test]
+    pub fn warm_cache() {
+        use egui::{Color32, Pos2, Rect, TexturesDelta};
+        const WIDTH: u32 = 40;
+        const HEIGHT: u32 = 24;
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = rect_paint_jobs(&[(
+            Rect::from_min_max(Pos2::new(3.0, 5.0), Pos2::new(27.0, 20.0)),
+            Color32::from_rgba_premultiplied(0, 100, 0, 200),
+        )]);
+
+        for mode in [
+            SoftwareRenderCaching::Direct,
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+            SoftwareRenderCaching::Auto,
+        ] {
+            let new_renderer = || EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_mode(mode);
+            let (expected, expected_dirty_rect) = new_renderer().render_to_buffer(
+                paint_jobs.clone(),
+                &textures_delta,
+                1.0,
+                WIDTH,
+                HEIGHT,
+            );
+
+            let mut renderer = new_renderer();
+            let dirty_rect =
+                renderer.warm_cache(paint_jobs.clone(), &textures_delta, 1.0, WIDTH, HEIGHT);
+            assert_eq!(dirty_rect, expected_dirty_rect, "mode {mode:?}");
+            if mode != SoftwareRenderCaching::Direct {
+                assert_eq!(renderer.cached_size(), (WIDTH, HEIGHT), "mode {mode:?}");
+                assert!(renderer.primitive_count() > 0, "mode {mode:?}");
+            }
+
+            // Redraws everything from the cache, with the textures of the warm up
+            let mut buffer = vec![[0u8; 4]; (WIDTH * HEIGHT) as usize];
+            let dirty_rect = renderer.render(
+                &mut BufferMutRef::new(&mut buffer, WIDTH, HEIGHT),
+                false,
+                paint_jobs.clone(),
+                &TexturesDelta::default(),
+                1.0,
+            );
+            assert_eq!(buffer, expected, "mode {mode:?}");
+            assert_eq!(dirty_rect, expected_dirty_rect, "mode {mode:?}");
+            #[cfg(feature = "cache_stats")]
+            if mode != SoftwareRenderCaching::Direct {
+                assert_eq!(renderer.cache_stats().cache_misses(), 0, "mode {mode:?}");
+            }
+        }
+    }
+
+    #[test]
+    pub fn gradient_textures() {
+        use egui::Color32;
+        use egui_software_backend::{
+            GradientDirection, create_linear_gradient_texture, create_radial_gradient_texture,
+        };
+        let a = Color32::from_rgb(255, 0, 0);
+        let b = Color32::from_rgb(0, 0, 255);
+
+        for (direction, size) in [
+            (GradientDirection::Horizontal, [16, 1]),
+            (GradientDirection::Vertical, [1, 16]),
+            (GradientDirection::Diagonal45, [16, 16]),
+        ] {
+            let image = create_linear_gradient_texture(a, b, 16, direction);
+            assert_eq!(image.size, size, "{direction:?}");
+            assert_eq!(image.pixels[0], a, "{direction:?}");
+            assert_eq!(*image.pixels.last().unwrap(), b, "{direction:?}");
+        }
+
+        let image = create_radial_gradient_texture(a, b, 8);
+        assert_eq!(image.size, [16, 16]);
+        assert_eq!(image.pixels[0], b);
+        assert_ne!(image[(8, 8)], b);
+        assert_eq!(image[(7, 7)], image[(8, 8)]);
+    }
+
+    #[cfg(feature = "wgpu")]
+    #[test]
+    pub fn import_wgpu_texture() {
+        use egui::{
+            Color32, Pos2, Rect, TexturesDelta,
+            epaint::{ClippedPrimitive, Mesh, Primitive},
+        };
+        const SIZE: u32 = 8;
+
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let Ok(adapter) =
+            pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+        else {
+            // No GPU or software adapter available
+            return;
+        };
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default())).unwrap();
+
+        let upload = |format, pixel: [u8; 4]| {
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: None,
+                size: wgpu::Extent3d {
+                    width: SIZE,
+                    height: SIZE,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            });
+            queue.write_texture(
+                texture.as_image_copy(),
+                &pixel.repeat((SIZE * SIZE) as usize),
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(SIZE * 4),
+                    rows_per_image: Some(SIZE),
+                },
+                texture.size(),
+            );
+            texture
+        };
+
+        let draw = |renderer: &mut EguiSoftwareRender, id| {
+            let mut mesh = Mesh::with_texture(id);
+            mesh.add_rect_with_uv(
+                Rect::from_min_max(Pos2::ZERO, Pos2::new(SIZE as f32, SIZE as f32)),
+                Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+                Color32::WHITE,
+            );
+            let mut buffer = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+            renderer.render(
+                &mut BufferMutRef::new(&mut buffer, SIZE, SIZE),
+                true,
+                vec![ClippedPrimitive {
+                    clip_rect: Rect::EVERYTHING,
+                    primitive: Primitive::Mesh(mesh),
+                }],
+                &TexturesDelta::default(),
+                1.0,
+            );
+            buffer[(SIZE * SIZE / 2 + SIZE / 2) as usize]
+        };
+
+        for mode in [
+            SoftwareRenderCaching::Direct,
+            SoftwareRenderCaching::MeshTiled,
+        ] {
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_mode(mode);
+            let rgba = upload(wgpu::TextureFormat::Rgba8Unorm, [200, 100, 50, 255]);
+            let id = renderer.import_wgpu_texture(&device, &queue, &rgba);
+            assert_eq!(
+                draw(&mut renderer, id),
+                [200, 100, 50, 255],
+                "mode {mode:?}"
+            );
+
+            let bgra = upload(wgpu::TextureFormat::Bgra8Unorm, [10, 20, 30, 255]);
+            renderer.update_wgpu_texture(id, &device, &queue, &bgra);
+            assert_eq!(draw(&mut renderer, id), [30, 20, 10, 255], "mode {mode:?}");
+
+            let other = renderer.import_wgpu_texture(&device, &queue, &rgba);
+            assert_ne!(other, id);
+        }
+    }
+
+    #[test]
+    pub fn texture_partial_updates() {
+        use egui::{
+            Color32, ColorImage, Pos2, Rect, TextureId, TextureOptions, TexturesDelta,
+            epaint::{ClippedPrimitive, ImageDelta, Mesh, Primitive},
+        };
+        let id = TextureId::Managed(1);
+        let image = |size: [usize; 2], color: Color32| {
+            ColorImage::new(size, vec![color; size[0] * size[1]])
+        };
+        let red = Color32::from_rgb(255, 0, 0);
+        let green = Color32::from_rgb(0, 255, 0);
+        let blue = Color32::from_rgb(0, 0, 255);
+        let textures_delta = TexturesDelta {
+            set: vec![
+                // Overwritten by the full replacement that follows
+                (
+                    id,
+                    ImageDelta::full(image([4, 4], blue), TextureOptions::NEAREST),
+                ),
+                (
+                    id,
+                    ImageDelta::partial([0, 0], image([1, 1], green), TextureOptions::NEAREST),
+                ),
+                (
+                    id,
+                    ImageDelta::full(image([2, 2], red), TextureOptions::NEAREST),
+                ),
+                // Applied on top of the full replacement
+                (
+                    id,
+                    ImageDelta::partial([1, 1], image([1, 1], green), TextureOptions::NEAREST),
+                ),
+                (
+                    id,
+                    ImageDelta::partial([0, 0], image([1, 1], blue), TextureOptions::NEAREST),
+                ),
+            ],
+            free: vec![],
+        };
+        let mut mesh = Mesh::with_texture(id);
+        mesh.add_rect_with_uv(
+            Rect::from_min_max(Pos2::ZERO, Pos2::new(2.0, 2.0)),
+            Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+            Color32::WHITE,
+        );
+        let paint_jobs = vec![ClippedPrimitive {
+            clip_rect: Rect::EVERYTHING,
+            primitive: Primitive::Mesh(mesh),
+        }];
+
+        for (order, to_output) in [
+            (
+                ColorFieldOrder::Rgba,
+                (|c: Color32| c.to_array()) as fn(Color32) -> [u8; 4],
+            ),
+            (ColorFieldOrder::Bgra, |c: Color32| {
+                let [r, g, b, a] = c.to_array();
+                [b, g, r, a]
+            }),
+        ] {
+            let (buffer, _) = EguiSoftwareRender::new(order)
+                .with_mode(SoftwareRenderCaching::Direct)
+                .render_to_buffer(paint_jobs.clone(), &textures_delta, 1.0, 2, 2);
+            assert_eq!(
+                buffer,
+                [blue, red, red, green].map(to_output).to_vec(),
+                "order {}",
+                order as u8
+            );
+        }
+    }
+
+    #[test]
+    pub fn auto_caching_mode() {
+        use egui::{Color32, Pos2, Rect, TexturesDelta};
+        const SIZE: u32 = 32;
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = rect_paint_jobs(&[(
+            Rect::from_min_max(Pos2::new(3.0, 5.0), Pos2::new(27.0, 20.0)),
+            Color32::from_rgba_premultiplied(0, 100, 0, 200),
+        )]);
+
+        let (expected, _) = EguiSoftwareRender::new(ColorFieldOrder::Rgba).render_to_buffer(
+            paint_jobs.clone(),
+            &textures_delta,
+            1.0,
+            SIZE,
+            SIZE,
+        );
+
+        let mut renderer =
+            EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_mode(SoftwareRenderCaching::Auto);
+        assert_eq!(renderer.detected_caching_mode(), None);
+        let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+        let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+        renderer.render(&mut buffer, true, paint_jobs.clone(), &textures_delta, 1.0);
+        let detected = renderer
+            .detected_caching_mode()
+            .expect("detected on first render");
+        assert_ne!(detected, SoftwareRenderCaching::Auto);
+        assert_eq!(renderer.caching(), detected);
+        assert!(data == expected, "mode {detected:?}");
+
+        // The cache of the selected mode is up to date
+        let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+        let dirty_rect = renderer.render(
+            &mut buffer,
+            false,
+            paint_jobs,
+            &TexturesDelta::default(),
+            1.0,
+        );
+        assert!(dirty_rect.is_empty(), "mode {detected:?}");
+
+        renderer.set_caching(SoftwareRenderCaching::Auto);
+        assert_eq!(renderer.detected_caching_mode(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    pub fn render_budget() {
+        use core::sync::atomic::{AtomicU32, Ordering};
+        use egui::{Color32, Pos2, Rect};
+        use std::sync::Arc;
+        const SIZE: u32 = 32;
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = rect_paint_jobs(&[(3.0, 27.0), (10.0, 20.0)].map(|(min, max)| {
+            (
+                Rect::from_min_max(Pos2::new(min, min), Pos2::new(max, max)),
+                Color32::from_rgba_premultiplied(0, 100, 0, 200),
+            )
+        }));
+
+        for mode in [
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            let skipped = Arc::new(AtomicU32::new(0));
+            let callback_skipped = skipped.clone();
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .with_render_budget_ms(Some(0.0))
+                .with_budget_exceeded_callback(move |count| {
+                    callback_skipped.fetch_add(count, Ordering::Relaxed);
+                });
+            let (buffer, _) =
+                renderer.render_to_buffer(paint_jobs.clone(), &textures_delta, 1.0, SIZE, SIZE);
+            assert!(buffer.iter().all(|px| px[3] == 0), "mode {mode:?}");
+            assert_eq!(skipped.load(Ordering::Relaxed), 2, "mode {mode:?}");
+            assert_eq!(renderer.primitive_count(), 0, "mode {mode:?}");
+
+            let (buffer, _) = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .with_render_budget_ms(Some(1000.0))
+                .render_to_buffer(paint_jobs.clone(), &textures_delta, 1.0, SIZE, SIZE);
+            assert!(buffer.iter().any(|px| px[3] != 0), "mode {mode:?}");
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Calibrating rasterizes millions of pixels, too slow under Miri
+    pub fn estimate_render_time() {
+        use egui::{
+            Color32, Pos2, Rect,
+            epaint::{ClippedPrimitive, Mesh, Primitive},
+        };
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = |rects: u32, size: f32, clip_rect: Rect| {
+            let mut mesh = Mesh::default();
+            for i in 0..rects {
+                let min = Pos2::new(i as f32 % 50.0, 0.0);
+                mesh.add_colored_rect(
+                    Rect::from_min_max(min, min + Vec2::splat(size)),
+                    Color32::from_rgba_premultiplied(0, 100, 0, 200),
+                );
+            }
+            vec![ClippedPrimitive {
+                clip_rect,
+                primitive: Primitive::Mesh(mesh),
+            }]
+        };
+
+        let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba);
+        for calibrated in [false, true] {
+            // A calibrated cost might fit to about 0
+            let more = |a: u64, b: u64| if calibrated { a >= b } else { a > b };
+            let estimate = |rects, size, clip_rect, pixels_per_point| {
+                renderer
+                    .estimate_render_time_us(&paint_jobs(rects, size, clip_rect), pixels_per_point)
+            };
+            assert_eq!(estimate(0, 200.0, Rect::EVERYTHING, 1.0), 0);
+            let big = estimate(1000, 200.0, Rect::EVERYTHING, 1.0);
+            assert!(big > 0);
+            assert!(more(big, estimate(10, 200.0, Rect::EVERYTHING, 1.0)));
+            assert!(more(big, estimate(1000, 20.0, Rect::EVERYTHING, 1.0)));
+            let clip_rect = Rect::from_min_size(Pos2::ZERO, Vec2::splat(20.0));
+            assert!(more(big, estimate(1000, 200.0, clip_rect, 1.0)));
+            assert!(more(estimate(1000, 200.0, Rect::EVERYTHING, 2.0), big));
+
+            // Calibrates the costs
+            for rects in [10, 1000, 100] {
+                renderer.render_to_buffer(
+                    paint_jobs(rects, 200.0, Rect::EVERYTHING),
+                    &textures_delta,
+                    1.0,
+                    256,
+                    256,
+                );
+            }
+        }
+    }
+
+    #[test]
+    pub fn invalidate_cached_primitives() {
+        use egui::{Color32, Pos2, Rect, TextureId, TexturesDelta};
+        const SIZE: u32 = 32;
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = rect_paint_jobs(&[(3.0, 27.0), (10.0, 20.0)].map(|(min, max)| {
+            (
+                Rect::from_min_max(Pos2::new(min, min), Pos2::new(max, max)),
+                Color32::from_rgba_premultiplied(0, 100, 0, 200),
+            )
+        }));
+
+        for mode in [
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_mode(mode);
+            let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+            let mut render = |renderer: &mut EguiSoftwareRender,
+                              redraw_everything: bool,
+                              textures_delta: &TexturesDelta| {
+                let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+                let dirty_rect = renderer.render(
+                    &mut buffer,
+                    redraw_everything,
+                    paint_jobs.clone(),
+                    textures_delta,
+                    1.0,
+                );
+                (dirty_rect, buffer.data.to_vec())
+            };
+            let (_, expected) = render(&mut renderer, true, &textures_delta);
+            assert_eq!(renderer.primitive_count(), 2, "mode {mode:?}");
+
+            assert!(!renderer.invalidate_hash(0), "mode {mode:?}");
+            assert_eq!(
+                renderer.invalidate_all_with_texture_id(TextureId::User(7)),
+                0,
+                "mode {mode:?}"
+            );
+            let no_delta = TexturesDelta::default();
+            assert!(
+                render(&mut renderer, false, &no_delta).0.is_empty(),
+                "mode {mode:?}"
+            );
+
+            assert_eq!(
+                renderer.invalidate_all_with_texture_id(TextureId::default()),
+                2,
+                "mode {mode:?}"
+            );
+            // Kept stale until painted again
+            assert_eq!(renderer.primitive_count(), 2, "mode {mode:?}");
+            let (dirty_rect, buffer) = render(&mut renderer, false, &no_delta);
+            let prims_rect = DirtyRect {
+                min_x: 3,
+                min_y: 3,
+                max_x: 27,
+                max_y: 27,
+            };
+            assert_eq!(
+                dirty_rect.intersection(prims_rect),
+                prims_rect,
+                "mode {mode:?}"
+            );
+            assert_eq!(renderer.primitive_count(), 2, "mode {mode:?}");
+            assert!(
+                render(&mut renderer, false, &no_delta).0.is_empty(),
+                "mode {mode:?}"
+            );
+            // The mesh modes blend over the previous content, BlendTiled composites cleared tiles
+            if mode == SoftwareRenderCaching::BlendTiled {
+                assert!(buffer == expected, "mode {mode:?}");
+            }
+        }
+    }
+
+    #[test]
+    pub fn max_idle_frames() {
+        use egui::{Color32, Pos2, Rect, TexturesDelta, epaint::ClippedPrimitive};
+        const SIZE: u32 = 128;
+
+        let textures_delta = white_texture_delta();
+        let with_popup = rect_paint_jobs(&[(3.0, 27.0), (80.0, 120.0)].map(|(min, max)| {
+            (
+                Rect::from_min_max(Pos2::new(min, min), Pos2::new(max, max)),
+                Color32::from_rgba_premultiplied(0, 100, 0, 200),
+            )
+        }));
+        let without_popup = with_popup[..1].to_vec();
+        let popup_rect = DirtyRect {
+            min_x: 80,
+            min_y: 80,
+            max_x: 120,
+            max_y: 120,
+        };
+
+        for mode in [
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .with_max_idle_frames(2);
+            let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+            let mut render = |renderer: &mut EguiSoftwareRender,
+                              redraw_everything: bool,
+                              paint_jobs: &Vec<ClippedPrimitive>,
+                              textures_delta: &TexturesDelta| {
+                let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+                let dirty_rect = renderer.render(
+                    &mut buffer,
+                    redraw_everything,
+                    paint_jobs.clone(),
+                    textures_delta,
+                    1.0,
+                );
+                (dirty_rect, buffer.data.to_vec())
+            };
+            let no_delta = TexturesDelta::default();
+
+            let (_, expected) = render(&mut renderer, true, &with_popup, &textures_delta);
+            let (dirty_rect, _) = render(&mut renderer, false, &without_popup, &no_delta);
+            assert_eq!(
+                dirty_rect.intersection(popup_rect),
+                popup_rect,
+                "mode {mode:?}"
+            );
+            assert_eq!(renderer.primitive_count(), 2, "mode {mode:?}");
+
+            // Idle primitives are neither drawn nor redrawn
+            assert!(
+                render(&mut renderer, false, &without_popup, &no_delta)
+                    .0
+                    .is_empty(),
+                "mode {mode:?}"
+            );
+
+            // Reused, but the popup area is drawn again
+            let (dirty_rect, buffer) = render(&mut renderer, false, &with_popup, &no_delta);
+            assert_eq!(
+                dirty_rect.intersection(popup_rect),
+                popup_rect,
+                "mode {mode:?}"
+            );
+            if mode == SoftwareRenderCaching::BlendTiled {
+                assert!(buffer == expected, "mode {mode:?}");
+            }
+
+            // Evicted after being idle for more than 2 frames
+            for _ in 0..3 {
+                render(&mut renderer, false, &without_popup, &no_delta);
+            }
+            assert_eq!(renderer.primitive_count(), 1, "mode {mode:?}");
+        }
+
+        // By default a primitive is evicted by the first render not painting it
+        let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba);
+        renderer.render_to_buffer(with_popup, &textures_delta, 1.0, SIZE, SIZE);
+        let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+        renderer.render(
+            &mut BufferMutRef::new(&mut data, SIZE, SIZE),
+            false,
+            without_popup,
+            &TexturesDelta::default(),
+            1.0,
+        );
+        assert_eq!(renderer.primitive_count(), 1);
+    }
+
+    #[test]
+    pub fn duplicate_primitives_last_painted_wins() {
+        use egui::{Color32, Pos2, Rect, TexturesDelta};
+        const SIZE: u32 = 64;
+
+        let textures_delta = white_texture_delta();
+        let red = (
+            Rect::from_min_max(Pos2::new(8.0, 8.0), Pos2::new(40.0, 40.0)),
+            Color32::RED,
+        );
+        let blue = (
+            Rect::from_min_max(Pos2::new(24.0, 24.0), Pos2::new(56.0, 56.0)),
+            Color32::BLUE,
+        );
+        // The same red rect painted under and over the blue one, so red ends on top
+        let paint_jobs = rect_paint_jobs(&[red, blue, red]);
+
+        let render = |mode: SoftwareRenderCaching| {
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_mode(mode);
+            let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+            let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+            renderer.render(&mut buffer, true, paint_jobs.clone(), &textures_delta, 1.0);
+            let first_frame = buffer.data.to_vec();
+            // Every primitive reused from the cache
+            renderer.render(
+                &mut buffer,
+                true,
+                paint_jobs.clone(),
+                &TexturesDelta::default(),
+                1.0,
+            );
+            assert_eq!(first_frame, buffer.data, "{mode:?}");
+            first_frame
+        };
+
+        let expected = render(SoftwareRenderCaching::Direct);
+        assert_eq!(expected[(32 * SIZE + 32) as usize], [255, 0, 0, 255]);
+        for mode in [
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            assert!(render(mode) == expected, "{mode:?}");
+        }
+    }
+
+    #[test]
+    pub fn skip_meshes_overflowing_in_pixels() {
+        use egui::{Color32, Pos2, Rect};
+        const SIZE: u32 = 32;
+
+        let textures_delta = white_texture_delta();
+        // Finite in points, infinite once multiplied by `pixels_per_point`
+        let paint_jobs = rect_paint_jobs(&[(
+            Rect::from_min_max(Pos2::new(4.0, 4.0), Pos2::new(f32::MAX, 8.0)),
+            Color32::RED,
+        )]);
+
+        for mode in [
+            SoftwareRenderCaching::Direct,
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            let (data, _) = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .render_to_buffer(paint_jobs.clone(), &textures_delta, 2.0, SIZE, SIZE);
+            assert!(data.iter().all(|&px| px == [0; 4]), "{mode:?}");
+        }
+    }
+
+    #[test]
+    pub fn skip_invalid_meshes() {
+        use egui::{
+            Color32, Pos2, Rect,
+            epaint::{ClippedPrimitive, Mesh, Primitive},
+        };
+        const SIZE: u32 = 32;
+
+        let textures_delta = white_texture_delta();
+        let valid = rect_paint_jobs(&[(
+            Rect::from_min_max(Pos2::new(4.0, 4.0), Pos2::new(12.0, 12.0)),
+            Color32::RED,
+        )]);
+        // Rects over the whole buffer, broken after tessellation
+        let broken = |break_mesh: fn(&mut Mesh)| {
+            let mut mesh = Mesh::default();
+            mesh.add_colored_rect(
+                Rect::from_min_max(Pos2::ZERO, Pos2::new(SIZE as f32, SIZE as f32)),
+                Color32::BLUE,
+            );
+            break_mesh(&mut mesh);
+            ClippedPrimitive {
+                clip_rect: Rect::EVERYTHING,
+                primitive: Primitive::Mesh(mesh),
+            }
+        };
+        let invalid = vec![
+            broken(|mesh| mesh.indices[4] = 100),
+            broken(|mesh| mesh.vertices[2].pos.x = f32::NAN),
+        ];
+
+        for mode in [
+            SoftwareRenderCaching::Direct,
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            let render = |paint_jobs| {
+                EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                    .with_mode(mode)
+                    .render_to_buffer(paint_jobs, &textures_delta, 1.0, SIZE, SIZE)
+                    .0
+            };
+            let expected = render(valid.clone());
+            assert_eq!(expected[(8 * SIZE + 8) as usize], [255, 0, 0, 255]);
+            let data = render([valid.clone(), invalid.clone()].concat());
+            assert!(data == expected, "{mode:?}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    pub fn resize_fade() {
+        use egui::{Color32, Pos2, Rect, TexturesDelta};
+
+        let textures_delta = white_texture_delta();
+        let panel = |color| {
+            rect_paint_jobs(&[(
+                Rect::from_min_max(Pos2::ZERO, Pos2::new(100.0, 100.0)),
+                color,
+            )])
+        };
+
+        for (mode, fade) in [
+            (SoftwareRenderCaching::Mesh, true),
+            (SoftwareRenderCaching::BlendTiled, true),
+            (SoftwareRenderCaching::Mesh, false),
+        ] {
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .with_resize_fade(fade)
+                .with_canvas();
+            let red = renderer.render_to_buffer(panel(Color32::RED), &textures_delta, 1.0, 32, 32);
+            assert!(red.iter().all(|&px| px == [255, 0, 0, 255]), "{mode:?}");
+
+            // Nothing rasterized after the resize, the last frame is shown scaled
+            renderer.set_render_budget_ms(Some(0.0));
+            let mut data = vec![[0u8; 4]; 48 * 48];
+            let mut buffer = BufferMutRef::new(&mut data, 48, 48);
+            renderer.render(
+                &mut buffer,
+                panel(Color32::BLUE),
+                &TexturesDelta::default(),
+                1.0,
+            );
+            let expected = if fade { [255, 0, 0, 255] } else { [0; 4] };
+            assert!(
+                buffer.data.iter().all(|&px| px == expected),
+                "{mode:?} {fade}"
+            );
+
+            // Replaced by the first full frame
+            renderer.set_render_budget_ms(None);
+            renderer.render(
+                &mut buffer,
+                panel(Color32::BLUE),
+                &TexturesDelta::default(),
+                1.0,
+            );
+            assert!(
+                buffer.data.iter().all(|&px| px == [0, 0, 255, 255]),
+                "{mode:?} {fade}"
+            );
+        }
+    }
+
+    #[test]
+    pub fn blend_tiled_resize_renders_direct() {
+        use egui::{Color32, Pos2, Rect, TexturesDelta};
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = rect_paint_jobs(&[(
+            Rect::from_min_max(Pos2::new(4.0, 4.0), Pos2::new(20.0, 20.0)),
+            Color32::RED,
+        )]);
+        let expected = |size: usize| {
+            (0..size * size)
+                .map(|i| {
+                    let (x, y) = (i % size, i / size);
+                    match (4..20).contains(&x) && (4..20).contains(&y) {
+                        true => [255, 0, 0, 255],
+                        false => [0; 4],
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba);
+        let (data, _) = renderer.render_to_buffer(paint_jobs.clone(), &textures_delta, 1.0, 24, 24);
+        assert_eq!(data, expected(24));
+        assert_eq!(renderer.primitive_count(), 1);
+
+        // Drawn directly over a garbage buffer: nothing is cached
+        let mut data = vec![[7u8; 4]; 32 * 32];
+        let mut buffer = BufferMutRef::new(&mut data, 32, 32);
+        let no_delta = TexturesDelta::default();
+        renderer.render(&mut buffer, true, paint_jobs.clone(), &no_delta, 1.0);
+        assert_eq!(data, expected(32));
+        assert_eq!(renderer.primitive_count(), 0);
+        assert_eq!(renderer.cached_size(), (32, 32));
+        assert_eq!(renderer.caching(), SoftwareRenderCaching::BlendTiled);
+
+        // Back to `BlendTiled`, the cache is filled and everything is redrawn
+        data.fill([7; 4]);
+        let mut buffer = BufferMutRef::new(&mut data, 32, 32);
+        let dirty_rect = renderer.render(&mut buffer, false, paint_jobs.clone(), &no_delta, 1.0);
+        assert_eq!([dirty_rect.max_x, dirty_rect.max_y], [32, 32]);
+        assert_eq!(data, expected(32));
+        assert_eq!(renderer.primitive_count(), 1);
+    }
+
+    #[test]
+    pub fn canvas_resize_renders_direct() {
+        use egui::{Color32, Pos2, Rect};
+
+        let textures_delta = white_texture_delta();
+        let rect_at = |min: f32| {
+            rect_paint_jobs(&[(
+                Rect::from_min_max(Pos2::new(min, min), Pos2::new(min + 16.0, min + 16.0)),
+                Color32::RED,
+            )])
+        };
+        let expected = |size: usize, min: usize| {
+            (0..size * size)
+                .map(|i| {
+                    let (x, y) = (i % size, i / size);
+                    match (min..min + 16).contains(&x) && (min..min + 16).contains(&y) {
+                        true => [255, 0, 0, 255],
+                        false => [0; 4],
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut canvas = EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_canvas();
+        let buffer = canvas.render_to_buffer(rect_at(4.0), &textures_delta, 1.0, 24, 24);
+        assert!(buffer == expected(24, 4));
+
+        // Grown past the tiles of the old size, everything drawn directly must be blitted
+        let no_delta = egui::TexturesDelta::default();
+        let buffer = canvas.render_to_buffer(rect_at(150.0), &no_delta, 1.0, 200, 200);
+        assert_eq!(buffer[155 * 200 + 155], [255, 0, 0, 255]);
+        assert!(buffer == expected(200, 150));
+
+        // Back to `BlendTiled` at the new size
+        let buffer = canvas.render_to_buffer(rect_at(150.0), &no_delta, 1.0, 200, 200);
+        assert!(buffer == expected(200, 150));
+    }
+
+    #[test]
+    pub fn grayscale_output() {
+        use egui::{Color32, Pos2, Rect};
+        use egui_software_backend::grayscale_slice;
+        const SIZE: u32 = 64;
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = rect_paint_jobs(&[
+            (
+                Rect::from_min_max(Pos2::new(4.0, 4.0), Pos2::new(40.0, 40.0)),
+                Color32::from_rgb(200, 40, 10),
+            ),
+            (
+                Rect::from_min_max(Pos2::new(20.0, 20.0), Pos2::new(60.0, 60.0)),
+                Color32::from_rgba_premultiplied(0, 90, 120, 160),
+            ),
+        ]);
+
+        let mut expected = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+            .with_mode(SoftwareRenderCaching::Direct)
+            .render_to_buffer(paint_jobs.clone(), &textures_delta, 1.0, SIZE, SIZE)
+            .0;
+        grayscale_slice(&mut expected);
+        let [r, g, b, a] = expected[(10 * SIZE + 10) as usize];
+        assert!(r == g && g == b && r > 0 && a == 255);
+
+        for mode in [
+            SoftwareRenderCaching::Direct,
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .with_grayscale(true);
+            let (buffer, _) =
+                renderer.render_to_buffer(paint_jobs.clone(), &textures_delta, 1.0, SIZE, SIZE);
+            assert!(buffer == expected, "{mode:?}");
+
+            let mut canvas = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .with_grayscale(true)
+                .with_canvas();
+            let buffer =
+                canvas.render_to_buffer(paint_jobs.clone(), &textures_delta, 1.0, SIZE, SIZE);
+            assert!(buffer == expected, "{mode:?} canvas");
+
+            // BGRA output gets the same luminance
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Bgra)
+                .with_mode(mode)
+                .with_grayscale(true);
+            let (buffer, _) =
+                renderer.render_to_buffer(paint_jobs.clone(), &textures_delta, 1.0, SIZE, SIZE);
+            assert!(buffer == expected, "{mode:?} bgra");
+        }
+    }
+
+    #[test]
+    pub fn color_temperature() {
+        use egui::{Color32, Pos2, Rect};
+        const SIZE: u32 = 32;
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = rect_paint_jobs(&[(
+            Rect::from_min_max(Pos2::new(4.0, 4.0), Pos2::new(28.0, 28.0)),
+            Color32::WHITE,
+        )]);
+        let render = |mode, order, kelvin| {
+            EguiSoftwareRender::new(order)
+                .with_mode(mode)
+                .with_color_temperature(kelvin)
+                .render_to_buffer(paint_jobs.clone(), &textures_delta, 1.0, SIZE, SIZE)
+                .0
+        };
+        let center = (16 * SIZE + 16) as usize;
+
+        let unchanged = render(SoftwareRenderCaching::Direct, ColorFieldOrder::Rgba, 6500.0);
+        assert_eq!(unchanged[center], [255; 4]);
+        let warm = render(SoftwareRenderCaching::Direct, ColorFieldOrder::Rgba, 3000.0);
+        let [r, g, b, a] = warm[center];
+        assert!(
+            r == 255 && g < 255 && b < g && a == 255,
+            "{:?}",
+            warm[center]
+        );
+        // Transparent pixels stay transparent
+        assert_eq!(warm[0], [0; 4]);
+
+        for mode in [
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            assert!(
+                render(mode, ColorFieldOrder::Rgba, 3000.0) == warm,
+                "{mode:?}"
+            );
+            let bgra = render(mode, ColorFieldOrder::Bgra, 3000.0);
+            assert_eq!(bgra[center], [b, g, r, a], "{mode:?}");
+        }
+    }
+
+    #[test]
+    pub fn color_blind_mode() {
+        use egui::{Color32, Pos2, Rect};
+        use egui_software_backend::ColorBlindMode;
+        const SIZE: u32 = 64;
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = rect_paint_jobs(&[
+            (
+                Rect::from_min_max(Pos2::ZERO, Pos2::new(32.0, SIZE as f32)),
+                Color32::from_rgb(200, 40, 10),
+            ),
+            (
+                Rect::from_min_max(Pos2::new(32.0, 0.0), Pos2::new(64.0, SIZE as f32)),
+                Color32::from_rgb(40, 160, 10),
+            ),
+        ]);
+        let render = |field_order, mode| {
+            EguiSoftwareRender::new(field_order)
+                .with_mode(SoftwareRenderCaching::MeshTiled)
+                .with_color_blind_mode(mode)
+                .render_to_buffer(paint_jobs.clone(), &textures_delta, 1.0, SIZE, SIZE)
+                .0
+        };
+        let red_green = |buffer: &[[u8; 4]]| {
+            let row = (SIZE / 2 * SIZE) as usize;
+            (buffer[row + 8], buffer[row + 56])
+        };
+        let normal = render(ColorFieldOrder::Rgba, ColorBlindMode::Normal);
+        assert_eq!(red_green(&normal), ([200, 40, 10, 255], [40, 160, 10, 255]));
+        for mode in ColorBlindMode::ALL {
+            let buffer = render(ColorFieldOrder::Rgba, mode);
+            let bgra = render(ColorFieldOrder::Bgra, mode);
+            let swizzled: Vec<_> = bgra.iter().map(|&[b, g, r, a]| [r, g, b, a]).collect();
+            assert!(buffer == swizzled, "{mode:?}");
+            assert!(buffer.iter().all(|pixel| pixel[3] == 255), "{mode:?}");
+        }
+        // Without red or green cones, both halves get the same hue, they only differ by their brightness
+        for mode in [ColorBlindMode::Protanopia, ColorBlindMode::Deuteranopia] {
+            let (red, green) = red_green(&render(ColorFieldOrder::Rgba, mode));
+            assert!(
+                red[0] > red[1] && red[1] > red[2] && green[0] > green[1] && green[1] > green[2],
+                "{mode:?} {red:?} {green:?}"
+            );
+        }
+        let (red, green) = red_green(&render(
+            ColorFieldOrder::Rgba,
+            ColorBlindMode::Achromatopsia,
+        ));
+        assert!(red[0] == red[1] && red[1] == red[2]);
+        assert!(green[0] == green[1] && green[1] == green[2]);
+    }
+
+    #[test]
+    pub fn dithering_gradient() {
+        use egui::{
+            Color32, Pos2, Rect,
+            epaint::{ClippedPrimitive, Mesh, Primitive},
+        };
+        use egui_software_backend::DitherMode;
+        const WIDTH: u32 = 256;
+        const HEIGHT: u32 = 8;
+        const LEVELS: u8 = 4;
+
+        let textures_delta = white_texture_delta();
+        // Black to white horizontal gradient
+        let mut mesh = Mesh::default();
+        for (x, color) in [(0.0, Color32::BLACK), (WIDTH as f32, Color32::WHITE)] {
+            mesh.colored_vertex(Pos2::new(x, 0.0), color);
+            mesh.colored_vertex(Pos2::new(x, HEIGHT as f32), color);
+        }
+        mesh.add_triangle(0, 1, 2);
+        mesh.add_triangle(1, 2, 3);
+        let paint_jobs = vec![ClippedPrimitive {
+            clip_rect: Rect::EVERYTHING,
+            primitive: Primitive::Mesh(mesh),
+        }];
+
+        let (smooth, _) = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+            .with_mode(SoftwareRenderCaching::Direct)
+            .render_to_buffer(paint_jobs.clone(), &textures_delta, 1.0, WIDTH, HEIGHT);
+        let step = 255 / (LEVELS as u32 - 1);
+        for mode in [
+            SoftwareRenderCaching::Direct,
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            let (dithered, _) = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .with_dithering(DitherMode::BayerMatrix4x4 { levels: LEVELS })
+                .render_to_buffer(paint_jobs.clone(), &textures_delta, 1.0, WIDTH, HEIGHT);
+            assert!(
+                dithered.iter().all(
+                    |pixel| pixel[..3].iter().all(|&c| c as u32 % step == 0) && pixel[3] == 255
+                ),
+                "{mode:?}"
+            );
+            // Each 4x4 block averages to the gradient, instead of bands as wide as a level
+            let average = |buffer: &[[u8; 4]], block_x: u32| -> u32 {
+                (0..16)
+                    .map(|i| buffer[((i / 4) * WIDTH + block_x * 4 + i % 4) as usize][0] as u32)
+                    .sum::<u32>()
+                    / 16
+            };
+            for block_x in 0..WIDTH / 4 {
+                let (smooth, dithered) = (average(&smooth, block_x), average(&dithered, block_x));
+                assert!(
+                    smooth.abs_diff(dithered) <= step / 4,
+                    "{mode:?} {block_x} {smooth} {dithered}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    pub fn canvas_diff_from_prev_frame() {
+        use egui::{Color32, Pos2, Rect, TexturesDelta};
+        use egui_software_backend::DirtyRect;
+        const WIDTH: u32 = 64;
+        const HEIGHT: u32 = 32;
+
+        let textures_delta = white_texture_delta();
+        let background = (
+            Rect::from_min_max(Pos2::new(4.0, 4.0), Pos2::new(20.0, 12.0)),
+            Color32::RED,
+        );
+        let button = |color| {
+            (
+                Rect::from_min_max(Pos2::new(40.0, 16.0), Pos2::new(50.0, 20.0)),
+                color,
+            )
+        };
+
+        for mode in [
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .with_canvas();
+            let mut data = vec![[0u8; 4]; (WIDTH * HEIGHT) as usize];
+            let mut buffer = BufferMutRef::new(&mut data, WIDTH, HEIGHT);
+            renderer.render(
+                &mut buffer,
+                rect_paint_jobs(&[background, button(Color32::GREEN)]),
+                &textures_delta,
+                1.0,
+            );
+
+            // Everything drawn, compared with a transparent canvas
+            let diff: Vec<_> = renderer.diff_from_prev_frame().collect();
+            assert_eq!(diff.len(), 16 * 8 + 10 * 4, "{mode:?}");
+            assert!(diff.contains(&(4, 4, [255, 0, 0, 255])), "{mode:?}");
+            assert!(diff.contains(&(49, 19, [0, 255, 0, 255])), "{mode:?}");
+            assert_eq!(renderer.diff_from_prev_frame().count(), 0, "{mode:?}");
+
+            // Only the button changed
+            renderer.render(
+                &mut buffer,
+                rect_paint_jobs(&[background, button(Color32::BLUE)]),
+                &TexturesDelta::default(),
+                1.0,
+            );
+            let mut diff = renderer.diff_from_prev_frame();
+            assert_eq!(diff.next(), Some((40, 16, [0, 0, 255, 255])), "{mode:?}");
+            drop(diff);
+            let diff_rects = renderer.take_diff_rects();
+            assert_eq!(
+                diff_rects,
+                vec![DirtyRect {
+                    min_x: 40,
+                    min_y: 16,
+                    max_x: 50,
+                    max_y: 20,
+                }],
+                "{mode:?}"
+            );
+            let canvas = renderer.canvas_pixels();
+            assert_eq!(canvas[(19 * WIDTH + 49) as usize], [0, 0, 255, 255]);
+            assert!(renderer.take_diff_rects().is_empty(), "{mode:?}");
+        }
+    }
+
+    #[test]
+    pub fn canvas_clear_region() {
+        use egui::{Color32, Pos2, Rect, TexturesDelta};
+        const SIZE: u32 = 64;
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = rect_paint_jobs(&[(
+            Rect::from_min_max(Pos2::new(3.0, 5.0), Pos2::new(47.0, 40.0)),
+            Color32::from_rgba_premultiplied(0, 100, 0, 200),
+        )]);
+
+        for mode in [
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .with_canvas();
+            let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+            let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+            renderer.render(&mut buffer, paint_jobs.clone(), &textures_delta, 1.0);
+            let expected = renderer.canvas_pixels().to_vec();
+
+            // Clipped to the canvas
+            renderer.clear_region(DirtyRect {
+                min_x: 0,
+                min_y: 0,
+                max_x: SIZE * 2,
+                max_y: SIZE * 2,
+            });
+            assert!(
+                renderer.canvas_pixels().iter().all(|px| *px == [0; 4]),
+                "{mode:?}"
+            );
+
+            // Drawn back by the next render
+            renderer.render(
+                &mut buffer,
+                paint_jobs.clone(),
+                &TexturesDelta::default(),
+                1.0,
+            );
+            assert!(renderer.canvas_pixels() == expected, "{mode:?}");
+        }
+    }
+
+    #[test]
+    pub fn software_frame_source() {
+        use egui::{Pos2, RawInput, Rect, vec2};
+        use egui_software_backend::{SoftwareFrame, SoftwareFrameSource};
+
+        /// Windowing system integration, feeding its own input to egui
+        struct Window {
+            size: egui::Vec2,
+            frames: u32,
+        }
+
+        impl SoftwareFrameSource for Window {
+            fn next_frame(&mut self, ctx: &egui::Context) -> SoftwareFrame {
+                self.frames += 1;
+                let raw_input = RawInput {
+                    screen_rect: Some(Rect::from_min_size(Pos2::ZERO, self.size)),
+                    ..Default::default()
+                };
+                let output = ctx.run(raw_input, |ctx| {
+                    egui::CentralPanel::default().show(ctx, |ui| ui.label("Hello"));
+                });
+                SoftwareFrame::from_full_output(ctx, output)
+            }
+        }
+
+        fn render_next_frame(
+            source: &mut impl SoftwareFrameSource,
+            ctx: &egui::Context,
+            renderer: &mut EguiSoftwareRender,
+        ) -> (u32, u32, DirtyRect) {
+            let frame = source.next_frame(ctx);
+            let (width, height) = frame.size;
+            let mut data = vec![[0u8; 4]; (width * height) as usize];
+            let dirty_rect = renderer.render_frame(
+                &mut BufferMutRef::new(&mut data, width, height),
+                true,
+                frame,
+            );
+            assert!(data.iter().any(|&px| px != [0; 4]));
+            (width, height, dirty_rect)
+        }
+
+        let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba);
+        let ctx = egui::Context::default();
+        let mut window = Window {
+            size: vec2(120.0, 40.0),
+            frames: 0,
+        };
+        let (width, height, dirty_rect) = render_next_frame(&mut window, &ctx, &mut renderer);
+        assert_eq!((width, height), (120, 40));
+        assert_eq!(window.frames, 1);
+        assert!(!dirty_rect.is_empty());
+
+        // Closures running egui are frame sources too
+        let ctx = egui::Context::default();
+        let mut closure = |ctx: &egui::Context| {
+            let mut raw_input = RawInput {
+                screen_rect: Some(Rect::from_min_size(Pos2::ZERO, vec2(30.0, 20.0))),
+                ..Default::default()
+            };
+            raw_input.viewports.insert(
+                egui::ViewportId::ROOT,
+                egui::ViewportInfo {
+                    native_pixels_per_point: Some(2.0),
+                    ..Default::default()
+                },
+            );
+            ctx.run(raw_input, |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| ui.label("Hi"));
+            })
+        };
+        let (width, height, _) = render_next_frame(&mut closure, &ctx, &mut renderer);
+        assert_eq!((width, height), (60, 40));
+    }
+
+    #[test]
+    pub fn set_raster_options_clears_cache() {
+        const SIZE: u32 = 64;
+        let ctx = egui::Context::default();
+        let input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                Vec2::splat(SIZE as f32),
+            )),
+            ..Default::default()
+        };
+        let output = ctx.run(input, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.label("Hello");
+                let _ = ui.button("World");
+            });
+        });
+        let paint_jobs = ctx.tessellate(output.shapes, output.pixels_per_point);
+
+        let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba);
+        assert!(renderer.convert_tris_to_rects() && renderer.allow_raster_opt());
+        let (_, dirty_rect) = renderer.render_to_buffer(
+            paint_jobs.clone(),
+            &output.textures_delta,
+            output.pixels_per_point,
+            SIZE,
+            SIZE,
+        );
+        assert!(!dirty_rect.is_empty());
+        assert!(renderer.primitive_count() > 0);
+
+        // Unchanged settings keep the cache
+        renderer.set_convert_tris_to_rects(true);
+        renderer.set_allow_raster_opt(true);
+        assert!(renderer.primitive_count() > 0);
+
+        renderer.set_convert_tris_to_rects(false);
+        assert!(!renderer.convert_tris_to_rects());
+        assert_eq!(renderer.primitive_count(), 0);
+        renderer.set_allow_raster_opt(false);
+        assert!(!renderer.allow_raster_opt());
+
+        // Rendered like a renderer built with the same settings
+        let (buffer, _) = renderer.render_to_buffer(
+            paint_jobs.clone(),
+            &egui::TexturesDelta::default(),
+            output.pixels_per_point,
+            SIZE,
+            SIZE,
+        );
+        let (expected, _) = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+            .with_convert_tris_to_rects(false)
+            .with_allow_raster_opt(false)
+            .render_to_buffer(
+                paint_jobs,
+                &output.textures_delta,
+                output.pixels_per_point,
+                SIZE,
+                SIZE,
+            );
+        assert!(buffer == expected);
+    }
+
+    #[test]
+    pub fn max_cache_limits() {
+        use egui::{Color32, Pos2, Rect, TexturesDelta};
+        const SIZE: u32 = 128;
+
+        let textures_delta = white_texture_delta();
+        let no_delta = TexturesDelta::default();
+        let [panel, popup, tooltip] =
+            [(3.0, 27.0), (80.0, 120.0), (40.0, 70.0)].map(|(min, max)| {
+                (
+                    Rect::from_min_max(Pos2::new(min, min), Pos2::new(max, max)),
+                    Color32::from_rgba_premultiplied(0, 100, 0, 200),
+                )
+            });
+        let frames = [
+            rect_paint_jobs(&[panel, popup]),
+            rect_paint_jobs(&[panel, tooltip]),
+            rect_paint_jobs(&[panel, tooltip]),
+        ];
+
+        let render =
+            |renderer: &mut EguiSoftwareRender, data: &mut Vec<[u8; 4]>, frame_idx: usize| {
+                let mut buffer = BufferMutRef::new(data, SIZE, SIZE);
+                let textures_delta = if frame_idx == 0 {
+                    &textures_delta
+                } else {
+                    &no_delta
+                };
+                renderer.render(
+                    &mut buffer,
+                    frame_idx == 0,
+                    frames[frame_idx].clone(),
+                    textures_delta,
+                    1.0,
+                );
+            };
+
+        for mode in [
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            let mut unlimited = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .with_max_idle_frames(10);
+            let mut by_entries = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .with_max_idle_frames(10)
+                .with_max_cache_entries(Some(2));
+            let mut by_memory = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .with_max_idle_frames(10)
+                .with_max_cache_memory_mb(Some(0.0));
+            let mut expected = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+            let mut entries_data = expected.clone();
+            let mut memory_data = expected.clone();
+            for frame_idx in 0..frames.len() {
+                render(&mut unlimited, &mut expected, frame_idx);
+                render(&mut by_entries, &mut entries_data, frame_idx);
+                render(&mut by_memory, &mut memory_data, frame_idx);
+
+                // Mesh modes draw over the pixels of the last frame, only the BlendTiled output is complete
+                if mode == SoftwareRenderCaching::BlendTiled {
+                    assert!(entries_data == expected, "mode {mode:?} frame {frame_idx}");
+                    assert!(memory_data == expected, "mode {mode:?} frame {frame_idx}");
+                }
+                assert_eq!(by_entries.primitive_count(), 2, "mode {mode:?}");
+                assert_eq!(by_memory.primitive_count(), 0, "mode {mode:?}");
+            }
+            // The idle popup is kept without a limit
+            assert_eq!(unlimited.primitive_count(), 3, "mode {mode:?}");
+        }
+    }
+
+    #[test]
+    pub fn tiny_triangles_culled() {
+        use egui::{
+            Color32, Pos2, Rect,
+            epaint::{ClippedPrimitive, Mesh, Primitive, Vertex},
+        };
+        const SIZE: u32 = 32;
+
+        let textures_delta = white_texture_delta();
+        // Sliver over the center of the pixel (15, 10), with both windings
+        let render_sliver = |half_height: f32| {
+            let mut mesh = Mesh::default();
+            for pos in [
+                Pos2::new(14.9, 10.5),
+                Pos2::new(15.6, 10.5 - half_height),
+                Pos2::new(15.6, 10.5 + half_height),
+            ] {
+                mesh.vertices.push(Vertex {
+                    pos,
+                    uv: egui::epaint::WHITE_UV,
+                    color: Color32::RED,
+                });
+            }
+            mesh.indices = vec![0, 1, 2, 0, 2, 1];
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(SoftwareRenderCaching::Direct);
+            let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+            renderer.render(
+                &mut BufferMutRef::new(&mut data, SIZE, SIZE),
+                true,
+                vec![ClippedPrimitive {
+                    clip_rect: Rect::EVERYTHING,
+                    primitive: Primitive::Mesh(mesh),
+                }],
+                &textures_delta,
+                1.0,
+            );
+            data
+        };
+
+        // 0.07 pixels, below MIN_TRI_AREA
+        assert!(render_sliver(0.1).iter().all(|&px| px == [0; 4]));
+        // 0.14 pixels
+        let data = render_sliver(0.2);
+        assert_eq!(data[(10 * SIZE + 15) as usize], [255, 0, 0, 255]);
+        assert_eq!(data.iter().filter(|&&px| px != [0; 4]).count(), 1);
+    }
+
+    #[test]
+    pub fn subpixel_bits() {
+        use egui::{
+            Color32, Pos2, Rect,
+            epaint::{ClippedPrimitive, Mesh, Primitive, Vertex},
+        };
+        const SIZE: u32 = 32;
+
+        let textures_delta = white_texture_delta();
+        let render =
+            |mode: SoftwareRenderCaching,
+             configure: &dyn Fn(EguiSoftwareRender) -> EguiSoftwareRender| {
+                let mut mesh = Mesh::default();
+                for pos in [
+                    Pos2::new(2.3, 3.1),
+                    Pos2::new(29.7, 7.9),
+                    Pos2::new(5.2, 28.6),
+                ] {
+                    mesh.vertices.push(Vertex {
+                        pos,
+                        uv: egui::epaint::WHITE_UV,
+                        color: Color32::RED,
+                    });
+                }
+                mesh.indices = vec![0, 1, 2];
+                let mut renderer =
+                    configure(EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_mode(mode));
+                let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+                renderer.render(
+                    &mut BufferMutRef::new(&mut data, SIZE, SIZE),
+                    true,
+                    vec![ClippedPrimitive {
+                        clip_rect: Rect::EVERYTHING,
+                        primitive: Primitive::Mesh(mesh),
+                    }],
+                    &textures_delta,
+                    1.0,
+                );
+                data
+            };
+
+        for mode in [
+            SoftwareRenderCaching::Direct,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            let default = render(mode, &|r| r);
+            let bits8 = render(mode, &|r| r.with_subpixel_bits(Some(8)));
+            let bits2 = render(mode, &|r| r.with_subpixel_bits(Some(2)));
+            assert_eq!(default, bits8, "{mode:?}");
+            assert_ne!(default, bits2, "{mode:?}");
+            // Over the threshold, unless overridden
+            assert_eq!(
+                render(mode, &|r| r.with_subpixel_bits_threshold(16)),
+                bits2,
+                "{mode:?}"
+            );
+            assert_eq!(
+                render(mode, &|r| r
+                    .with_subpixel_bits_threshold(16)
+                    .with_subpixel_bits(Some(8))),
+                bits8,
+                "{mode:?}"
+            );
+            // Clamped to 1 to 8 bits
+            assert_eq!(
+                render(mode, &|r| r.with_subpixel_bits(Some(12))),
+                bits8,
+                "{mode:?}"
+            );
+        }
+    }
+
+    #[test]
+    pub fn pre_and_post_render_callbacks() {
+        use egui::{Color32, Pos2, Rect};
+        const SIZE: u32 = 32;
+        const BACKGROUND: [u8; 4] = [0, 0, 255, 255];
+        const WATERMARK: [u8; 4] = [255, 255, 255, 255];
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = rect_paint_jobs(&[(
+            Rect::from_min_max(Pos2::new(8.0, 8.0), Pos2::new(24.0, 24.0)),
+            Color32::RED,
+        )]);
+
+        for mode in [
+            SoftwareRenderCaching::Direct,
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+        ] {
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_mode(mode);
+            renderer.set_pre_render_callback(|buffer| buffer.data.fill(BACKGROUND));
+            renderer.set_post_render_callback(|buffer| {
+                let (width, height) = (buffer.width, buffer.height);
+                buffer.data[(width * height - 1) as usize] = WATERMARK;
+            });
+            let (data, _) =
+                renderer.render_to_buffer(paint_jobs.clone(), &textures_delta, 1.0, SIZE, SIZE);
+
+            // Drawn over the background, under the watermark
+            assert_eq!(data[0], BACKGROUND, "mode {mode:?}");
+            assert_eq!(
+                data[(16 * SIZE + 16) as usize],
+                [255, 0, 0, 255],
+                "mode {mode:?}"
+            );
+            assert_eq!(data[(SIZE * SIZE - 1) as usize], WATERMARK, "mode {mode:?}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "lz4_compression")]
+    pub fn idle_tiles_compressed() {
+        use egui::{Color32, Pos2, Rect, TexturesDelta};
+        const SIZE: u32 = 128;
+
+        let textures_delta = white_texture_delta();
+        let with_popup = rect_paint_jobs(&[(3.0, 27.0), (40.0, 120.0)].map(|(min, max)| {
+            (
+                Rect::from_min_max(Pos2::new(min, min), Pos2::new(max, max)),
+                Color32::from_rgba_premultiplied(0, 100, 0, 200),
+            )
+        }));
+        let without_popup = with_popup[..1].to_vec();
+
+        let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+            .with_mode(SoftwareRenderCaching::BlendTiled)
+            .with_max_idle_frames(10);
+        let mut expected = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+        renderer.render(
+            &mut BufferMutRef::new(&mut expected, SIZE, SIZE),
+            true,
+            with_popup.clone(),
+            &textures_delta,
+            1.0,
+        );
+        let uncompressed_bytes = renderer.memory_stats().tiled_cache_bytes;
+
+        // The idle popup is compressed
+        let mut data = expected.clone();
+        renderer.render(
+            &mut BufferMutRef::new(&mut data, SIZE, SIZE),
+            false,
+            without_popup,
+            &TexturesDelta::default(),
+            1.0,
+        );
+        assert_eq!(renderer.primitive_count(), 2);
+        assert!(renderer.memory_stats().tiled_cache_bytes < uncompressed_bytes);
+
+        // Then decompressed when it is painted again
+        renderer.render(
+            &mut BufferMutRef::new(&mut data, SIZE, SIZE),
+            false,
+            with_popup,
+            &TexturesDelta::default(),
+            1.0,
+        );
+        assert!(data == expected);
+        assert_eq!(
+            renderer.memory_stats().tiled_cache_bytes,
+            uncompressed_bytes
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(feature = "rayon"),
+        should_panic = "SoftwareRenderCaching::BlendTiledParallel requires the `rayon` feature"
+    )]
+    pub fn blend_tiled_parallel() {
+        use egui::{Color32, Pos2, Rect};
+        const SIZE: u32 = 64;
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = rect_paint_jobs(&[(
+            Rect::from_min_max(Pos2::new(3.0, 5.0), Pos2::new(47.0, 40.0)),
+            Color32::from_rgba_premultiplied(0, 100, 0, 200),
+        )]);
+
+        let [expected, parallel] = [
+            SoftwareRenderCaching::BlendTiled,
+            SoftwareRenderCaching::BlendTiledParallel,
+        ]
+        .map(|mode| {
+            EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(mode)
+                .render_to_buffer(paint_jobs.clone(), &textures_delta, 1.0, SIZE, SIZE)
+        });
+        assert!(parallel == expected);
+    }
+
+    #[test]
+    pub fn force_redraw_next_frame() {
+        use egui::{Color32, Pos2, Rect, TexturesDelta};
+        const SIZE: u32 = 32;
+
+        let textures_delta = white_texture_delta();
+        let paint_jobs = rect_paint_jobs(&[(
+            Rect::from_min_max(Pos2::new(3.0, 5.0), Pos2::new(27.0, 20.0)),
+            Color32::from_rgba_premultiplied(0, 100, 0, 200),
+        )]);
+        let full = DirtyRect {
+            min_x: 0,
+            min_y: 0,
+            max_x: SIZE,
+            max_y: SIZE,
+        };
+
+        for mode in [
+            SoftwareRenderCaching::Mesh,
+            SoftwareRenderCaching::MeshTiled,
+            SoftwareRenderCaching::BlendTiled,
+        ] {
+            let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_mode(mode);
+            let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+            let render = |data: &mut Vec<[u8; 4]>,
+                          renderer: &mut EguiSoftwareRender,
+                          redraw_everything: bool,
+                          textures_delta: &TexturesDelta| {
+                let mut buffer = BufferMutRef::new(data, SIZE, SIZE);
+                renderer.render(
+                    &mut buffer,
+                    redraw_everything,
+                    paint_jobs.clone(),
+                    textures_delta,
+                    1.0,
+                )
+            };
+
+            assert_eq!(
+                render(&mut data, &mut renderer, true, &textures_delta),
+                full,
+                "mode {mode:?}"
+            );
+            let expected = data.clone();
+            assert!(expected.iter().any(|px| px[3] != 0), "mode {mode:?}");
+            let memory_usage = renderer.cache_memory_usage();
+            let no_delta = TexturesDelta::default();
+            assert!(
+                render(&mut data, &mut renderer, false, &no_delta).is_empty(),
+                "mode {mode:?}"
+            );
+
+            // Everything is drawn again from the cache
+            data.fill([0; 4]);
+            renderer.force_redraw_next_frame();
+            assert_eq!(
+                render(&mut data, &mut renderer, false, &no_delta),
+                full,
+                "mode {mode:?}"
+            );
+            assert!(data == expected, "mode {mode:?}");
+            assert_eq!(renderer.cache_memory_usage(), memory_usage, "mode {mode:?}");
+
+            // Only for one frame
+            assert!(
+                render(&mut data, &mut renderer, false, &no_delta).is_empty(),
+                "mode {mode:?}"
+            );
+        }
+    }
+
+    #[test]
+    pub fn buffer_row_bands() {
+        use egui::{Color32, Pos2, Rect};
+        const SIZE: u32 = 64;
+        const BAND: u32 = SIZE / 4;
+
+        // Covers more than any band, so it would spill over its neighbours if it could
+        let render = |buffer: &mut BufferMutRef, color: Color32| {
+            let paint_jobs = rect_paint_jobs(&[(
+                Rect::from_min_max(Pos2::new(-10.0, -10.0), Pos2::new(74.0, 74.0)),
+                color,
+            )]);
+            EguiSoftwareRender::new(ColorFieldOrder::Rgba)
+                .with_mode(SoftwareRenderCaching::Direct)
+                .render(buffer, true, paint_jobs, &white_texture_delta(), 1.0);
+        };
+        let colors = [Color32::RED, Color32::GREEN, Color32::BLUE, Color32::YELLOW];
+
+        let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+        let buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+        let (top, bottom) = buffer.split_at_row(2 * BAND);
+        let (band0, band1) = top.split_at_row(BAND);
+        let (band2, band3) = bottom.split_at_row(BAND);
+        std::thread::scope(|scope| {
+            for (mut band, color) in [band0, band1, band2, band3].into_iter().zip(colors) {
+                assert_eq!((band.width, band.height), (SIZE, BAND));
+                scope.spawn(move || render(&mut band, color));
+            }
+        });
+        for (y, row) in data.chunks(SIZE as usize).enumerate() {
+            let expected = colors[y / BAND as usize].to_array();
+            assert!(row.iter().all(|px| *px == expected), "row {y}");
+        }
+
+        let mut data = vec![[0u8; 4]; (SIZE * SIZE) as usize];
+        let mut buffer = BufferMutRef::new(&mut data, SIZE, SIZE);
+        let mut band = buffer.row_band(20, 30);
+        assert_eq!((band.width, band.height), (SIZE, 10));
+        render(&mut band, Color32::RED);
+        for (y, row) in data.chunks(SIZE as usize).enumerate() {
+            let expected = if (20..30).contains(&y) {
+                Color32::RED.to_array()
+            } else {
+                [0; 4]
+            };
+            assert!(row.iter().all(|px| *px == expected), "row {y}");
+        }
+    }
+
+    #[test]
+    #[should_panic = "row band 8..17 out of the 16 rows of the buffer"]
+    pub fn buffer_row_band_out_of_bounds() {
+        let mut data = vec![[0u8; 4]; 16 * 16];
+        let mut buffer = BufferMutRef::new(&mut data, 16, 16);
+        buffer.row_band(8, 17);
+    }
+
+    #[test]
+    pub fn buffer_to_rgba_image() {
+        let mut data = vec![[10u8, 20, 30, 255], [40, 50, 60, 128], [0, 0, 0, 0]];
+        let buffer = BufferRef {
+            data: &data[..2],
+            width: 2,
+            height: 1,
+            width_extent: 1,
+            height_extent: 0,
+        };
+
+        let rgba = buffer.to_rgba_image(ColorFieldOrder::Rgba);
+        assert_eq!(rgba.dimensions(), (2, 1));
+        assert_eq!(rgba.as_raw(), &[10, 20, 30, 255, 40, 50, 60, 128]);
+
+        let bgra = buffer.to_rgba_image(ColorFieldOrder::Bgra);
+        assert_eq!(bgra.as_raw(), &[30, 20, 10, 255, 60, 50, 40, 128]);
+
+        let png = buffer.to_png_bytes(ColorFieldOrder::Bgra).unwrap();
+        let decoded = image::load_from_memory(&png).unwrap().into_rgba8();
+        assert_eq!(decoded, bgra);
+
+        // Only the first `width * height` pixels are part of the image
+        let buffer = BufferMutRef::new(&mut data, 1, 2);
+        assert_eq!(
+            buffer.to_rgba_image(ColorFieldOrder::Rgba).as_raw(),
+            &[10, 20, 30, 255, 40, 50, 60, 128]
+        );
+    }
+
+    const fn rect(min_x: u32, min_y: u32, max_x: u32, max_y: u32) -> DirtyRect {
+        DirtyRect {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        }
+    }
+
+    #[test]
+    pub fn dirty_rect_area() {
+        assert_eq!(DirtyRect::new_empty().area(), 0);
+        assert_eq!(rect(10, 10, 10, 20).area(), 0);
+        assert_eq!(rect(10, 10, 14, 20).area(), 40);
+        assert_eq!(rect(0, 0, u32::MAX, u32::MAX).area(), u32::MAX);
+    }
+
+    #[test]
+    pub fn dirty_rect_contains_point() {
+        assert!(!DirtyRect::new_empty().contains_point(0, 0));
+        let r = rect(10, 20, 30, 40);
+        assert!(r.contains_point(10, 20));
+        assert!(r.contains_point(29, 39));
+        assert!(!r.contains_point(30, 39));
+        assert!(!r.contains_point(29, 40));
+        assert!(!r.contains_point(9, 20));
+        assert!(!r.contains_point(10, 19));
+    }
+
+    #[test]
+    pub fn dirty_rect_shrink() {
+        assert!(DirtyRect::new_empty().shrink(0).is_empty());
+        assert!(DirtyRect::new_empty().shrink(1).is_empty());
+        assert_eq!(rect(10, 10, 20, 30).shrink(0), rect(10, 10, 20, 30));
+        assert_eq!(rect(10, 10, 20, 30).shrink(4), rect(14, 14, 16, 26));
+        assert!(rect(10, 10, 20, 30).shrink(5).is_empty());
+        assert!(rect(10, 10, 20, 30).shrink(u32::MAX).is_empty());
+    }
+
+    #[test]
+    pub fn dirty_rect_expand() {
+        assert!(DirtyRect::new_empty().expand(10, 100, 100).is_empty());
+        assert_eq!(rect(10, 10, 20, 30).expand(4, 100, 100), rect(6, 6, 24, 34));
+        assert_eq!(rect(10, 10, 20, 30).expand(64, 100, 50), rect(0, 0, 84, 50));
+        assert_eq!(
+            rect(10, 10, u32::MAX - 1, u32::MAX - 1).expand(u32::MAX, u32::MAX, u32::MAX),
+            rect(0, 0, u32::MAX, u32::MAX)
+        );
+    }
+
+    #[test]
+    pub fn dirty_rect_split() {
+        assert_eq!(
+            rect(10, 10, 20, 30).split_vertical(15),
+            Some((rect(10, 10, 15, 30), rect(15, 10, 20, 30)))
+        );
+        assert_eq!(rect(10, 10, 20, 30).split_vertical(10), None);
+        assert_eq!(rect(10, 10, 20, 30).split_vertical(25), None);
+        assert_eq!(
+            rect(10, 10, 20, 30).split_horizontal(11),
+            Some((rect(10, 10, 20, 11), rect(10, 11, 20, 30)))
+        );
+        assert_eq!(rect(10, 10, 20, 30).split_horizontal(0), None);
+        assert_eq!(rect(10, 10, 20, 30).split_horizontal(30), None);
+        assert_eq!(DirtyRect::new_empty().split_vertical(0), None);
+    }
+
+    #[test]
+    pub fn dirty_rect_subdivide_to_tiles() {
+        let tiles: Vec<_> = rect(10, 60, 140, 70).subdivide_to_tiles(64).collect();
+        assert_eq!(
+            tiles,
+            vec![
+                rect(10, 60, 64, 64),
+                rect(64, 60, 128, 64),
+                rect(128, 60, 140, 64),
+                rect(10, 64, 64, 70),
+                rect(64, 64, 128, 70),
+                rect(128, 64, 140, 70),
+            ]
+        );
+        assert_eq!(
+            rect(64, 0, 128, 64)
+                .subdivide_to_tiles(64)
+                .collect::<Vec<_>>(),
+            vec![rect(64, 0, 128, 64)]
+        );
+        assert_eq!(
+            rect(0, 0, u32::MAX, 1)
+                .subdivide_to_tiles(u32::MAX / 2 + 1)
+                .collect::<Vec<_>>(),
+            vec![
+                rect(0, 0, u32::MAX / 2 + 1, 1),
+                rect(u32::MAX / 2 + 1, 0, u32::MAX, 1)
+            ]
+        );
+        assert_eq!(DirtyRect::new_empty().subdivide_to_tiles(64).count(), 0);
+    }
+
+    #[test]
+    pub fn dirty_rect_checked_constructors() {
+        assert_eq!(DirtyRect::try_new(1, 2, 3, 4), Some(rect(1, 2, 3, 4)));
+        assert_eq!(DirtyRect::try_new(1, 2, 1, 2), Some(rect(1, 2, 1, 2)));
+        assert_eq!(DirtyRect::try_new(3, 2, 1, 4), None);
+        assert_eq!(DirtyRect::try_new(1, 4, 3, 2), None);
+
+        assert_eq!(
+            DirtyRect::clamped(1, 2, 30, 40, (100, 100)),
+            rect(1, 2, 30, 40)
+        );
+        assert_eq!(
+            DirtyRect::clamped(-5, -8, 130, 140, (100, 50)),
+            rect(0, 0, 100, 50)
+        );
+        assert_eq!(
+            DirtyRect::clamped(120, 10, 130, 20, (100, 50)),
+            rect(100, 10, 100, 20)
+        );
+        assert!(DirtyRect::clamped(30, 20, 10, 5, (100, 50)).is_empty());
+        assert!(DirtyRect::clamped(-20, 0, -10, 10, (100, 50)).is_empty());
+    }
+
+    #[test]
+    pub fn dirty_rect_from_egui_rect() {
+        use egui::{Pos2, Rect};
+        let egui_rect = |min_x, min_y, max_x, max_y| {
+            Rect::from_min_max(Pos2::new(min_x, min_y), Pos2::new(max_x, max_y))
+        };
+        assert_eq!(
+            DirtyRect::from_egui_rect(egui_rect(1.5, 2.0, 10.2, 20.0), 1.0, 100, 100),
+            rect(1, 2, 11, 20)
+        );
+        assert_eq!(
+            DirtyRect::from_egui_rect(egui_rect(1.5, 2.0, 10.2, 20.0), 2.0, 100, 100),
+            rect(3, 4, 21, 40)
+        );
+        assert_eq!(
+            DirtyRect::from_egui_rect(egui_rect(-5.0, -5.0, 150.0, 60.5), 1.0, 100, 50),
+            rect(0, 0, 100, 50)
+        );
+        assert!(DirtyRect::from_egui_rect(Rect::NOTHING, 1.0, 100, 100).is_empty());
+        assert!(
+            DirtyRect::from_egui_rect(egui_rect(120.0, 0.0, 130.0, 10.0), 1.0, 100, 100).is_empty()
+        );
+    }
+
+    #[test]
+    pub fn dirty_rects_non_overlapping() {
+        let mut dirty_rects = ComputeTiledDirtyRects::default();
+        dirty_rects.set_bboxes([rect(10, 10, 100, 30), rect(50, 20, 300, 200)].into_iter());
+
+        // Tiled to 64px: 0..128 x 0..64 and 0..320 x 0..256
+        assert_eq!(dirty_rects.total_dirty_area(), 320 * 256);
+        for (i, a) in dirty_rects.iter().enumerate() {
+            for b in &dirty_rects[i + 1..] {
+                let overlap = a.min_x < b.max_x
+                    && b.min_x < a.max_x
+                    && a.min_y < b.max_y
+                    && b.min_y < a.max_y;
+                assert!(!overlap, "{a:?} overlaps {b:?}");
+            }
+        }
+
+        let mut area = 0;
+        dirty_rects.for_each_intersection(rect(300, 0, 1000, 1000), |r| {
+            assert!(r.min_x >= 300 && r.max_x <= 320, "{r:?}");
+            area += r.area();
+        });
+        assert_eq!(area, 20 * 256);
+
+        dirty_rects.for_each_intersection(rect(320, 0, 1000, 1000), |r| {
+            panic!("unexpected intersection {r:?}");
+        });
+
+        dirty_rects.set_bboxes(core::iter::empty());
+        assert_eq!(dirty_rects.rect_count(), 0);
+        dirty_rects.for_each_intersection(rect(0, 0, 1000, 1000), |r| {
+            panic!("unexpected intersection {r:?}");
+        });
+
+        dirty_rects.set_bboxes([rect(10, 10, 100, 30)].into_iter());
+        assert_eq!(dirty_rects.rect_count(), 1);
+        dirty_rects.reset();
+        assert_eq!(dirty_rects.rect_count(), 0);
+        assert_eq!(dirty_rects.memory_usage_bytes(), 0);
+    }
+
     // Returning none indicates no diff
     fn dify(
         gpu_render_image: &ImageBuffer<Rgba<u8>, Vec<u8>>,
@@ -0,0 +1,91 @@
+//! CPU only visual regression test of the caching modes, compared with the references of `tests/reference/`
+//!
+//! Run with `UPDATE_SNAPSHOTS=1` to write the references from the current output.
+//!
+//! The references were rendered on x86_64. The Neon path has not been checked against them, so the test only runs on
+//! x86_64 until there are aarch64 references.
+#![cfg(target_arch = "x86_64")]
+
+use egui::{Pos2, Rect, vec2};
+use egui_software_backend::{
+    BufferMutRef, ColorFieldOrder, EguiSoftwareRender, SoftwareRenderCaching,
+};
+
+const WIDTH: u32 = 1280;
+const HEIGHT: u32 = 720;
+
+/// Last of 3 frames of `DemoWindows`, the first one redrawing everything and the next ones going through the cache
+fn render_demo(mode: SoftwareRenderCaching) -> image::RgbaImage {
+    let ctx = egui::Context::default();
+    let mut demo = egui_demo_lib::DemoWindows::default();
+    let mut renderer = EguiSoftwareRender::new(ColorFieldOrder::Rgba).with_mode(mode);
+    let mut data = vec![[0u8; 4]; (WIDTH * HEIGHT) as usize];
+    let mut buffer = BufferMutRef::new(&mut data, WIDTH, HEIGHT);
+    for frame_idx in 0..3 {
+        let input = egui::RawInput {
+            screen_rect: Some(Rect::from_min_size(
+                Pos2::ZERO,
+                vec2(WIDTH as f32, HEIGHT as f32),
+            )),
+            time: Some(frame_idx as f64 / 60.0),
+            // Hovers a widget, so the last frame changes part of the UI
+            events: match frame_idx {
+                2 => vec![egui::Event::PointerMoved(Pos2::new(60.0, 60.0))],
+                _ => vec![],
+            },
+            ..Default::default()
+        };
+        let output = ctx.run(input, |ctx| demo.ui(ctx));
+        let paint_jobs = ctx.tessellate(output.shapes, output.pixels_per_point);
+        renderer.render(
+            &mut buffer,
+            frame_idx == 0,
+            paint_jobs,
+            &output.textures_delta,
+            output.pixels_per_point,
+        );
+    }
+    buffer.to_rgba_image(ColorFieldOrder::Rgba)
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Building the egui fonts is too slow under Miri
+pub fn caching_modes_match_references() {
+    let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+    let _ = std::fs::create_dir("tests/reference/");
+    let _ = std::fs::create_dir("tests/tmp/");
+
+    let mut failures = Vec::new();
+    for mode in [
+        SoftwareRenderCaching::Direct,
+        SoftwareRenderCaching::Mesh,
+        SoftwareRenderCaching::MeshTiled,
+        SoftwareRenderCaching::BlendTiled,
+    ] {
+        let image = render_demo(mode);
+        let reference_path = format!("tests/reference/demo_{mode:?}.png");
+        if update {
+            image.save(&reference_path).unwrap();
+            continue;
+        }
+        let reference = image::open(&reference_path)
+            .unwrap_or_else(|_| panic!("{reference_path} is missing, run with UPDATE_SNAPSHOTS=1"))
+            .into_rgba8();
+        let pixels_failed = reference
+            .pixels()
+            .zip(image.pixels())
+            .filter(|(reference, actual)| reference != actual)
+            .count();
+        if reference.dimensions() != image.dimensions() || pixels_failed > 0 {
+            image
+                .save(format!("tests/tmp/visual_regression_{mode:?} - FAIL.png"))
+                .unwrap();
+            failures.push(format!("{mode:?}: {pixels_failed} pixels"));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "differs from tests/reference/: {}",
+        failures.join(", ")
+    );
+}